@@ -0,0 +1,4 @@
+def main(a:i12, b:i12, en:bool) -> (y:i12) {
+    y:i12 = lrega_i12(t0, en) @lut(??, ??);
+    t0:i12 = ladd_i12(a, b) @lut(??, ??);
+}
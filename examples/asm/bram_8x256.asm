@@ -0,0 +1,3 @@
+def main(addr:i8, data:i8, en:bool) -> (y:i8) {
+    y:i8 = bram_i8i8(addr, data, en) @bram(??, ??);
+}
@@ -0,0 +1,9 @@
+def main(a:i4, b:i4, en:bool, fast:clock, slow:clock) -> (y:i4, z:bool) {
+    t1:i4 = laddrega_i4(a, b, en, fast) @lut(??, ??);
+    y:i4 = id(t1);
+    z:bool = lrega_bool(t5, t3, slow) @lut(??, ??);
+    t5:bool = lrega_bool(t4, t3, slow) @lut(??, ??);
+    t3:bool = const[1];
+    t4:bool = lrega_bool(t2, t3, fast) @lut(??, ??);
+    t2:bool = leq_i4(t1, a) @lut(??, ??);
+}
@@ -0,0 +1,3 @@
+def main(addr:i3, data:i8, en:bool) -> (y:i8) {
+    y:i8 = lram_i8i3(addr, data, en) @lram(??, ??);
+}
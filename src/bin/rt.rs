@@ -1,8 +1,9 @@
 use translate::driver::Driver;
-use translate::errors::Error;
 
-fn main() -> Result<(), Error> {
+fn main() {
     let driver = Driver::default();
-    driver.run()?;
-    Ok(())
+    if let Err(err) = driver.run() {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
 }
@@ -11,10 +11,13 @@ pub type Expr = ir::Expr;
 pub type OpWire = ir::OpWire;
 pub type InstrWire = ir::InstrWire;
 pub type Sig = ir::Sig;
+pub type Span = ir::Span;
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash)]
 pub enum OpCoord {
     Add,
+    Sub,
+    Mul,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash)]
@@ -41,8 +44,11 @@ pub enum OpAsm {
 pub struct InstrAsm {
     pub op: OpAsm,
     pub dst: Expr,
+    pub attr: Expr,
     pub arg: Expr,
     pub loc: Loc,
+    #[serde(skip)]
+    pub span: Span,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash)]
@@ -0,0 +1,20 @@
+use crate::ast::*;
+use ir::dot::{dot_cluster, dot_color, dot_digraph, dot_label, DotNode};
+
+// selected patterns show their location and are colored by its prim
+pub fn dot_from_prog(prog: &Prog) -> String {
+    let mut node: Vec<DotNode> = Vec::new();
+    for instr in prog.body() {
+        let dst = instr.dst().to_string();
+        let attr = match instr {
+            Instr::Wire(wire) => dot_label(&[dst, wire.op().to_string()]),
+            Instr::Asm(asm) => {
+                let label = dot_label(&[dst, asm.op().to_string(), format!("@{}", asm.loc())]);
+                format!("{}, fillcolor = {}", label, dot_color(asm.loc().prim()))
+            }
+        };
+        node.push(DotNode::new(instr.dst(), instr.arg(), &attr));
+    }
+    let sig = prog.sig();
+    dot_digraph(&[dot_cluster(&sig.id(), sig.input(), sig.output(), &node)])
+}
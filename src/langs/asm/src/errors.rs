@@ -8,6 +8,7 @@ pub enum Error {
     Parser(pest_consume::Error<parser::Rule>),
     Conversion(String),
     Type(String),
+    Place(String),
     TryFromInt(TryFromIntError),
     ParseInt(ParseIntError),
 }
@@ -19,6 +20,9 @@ impl Error {
     pub fn new_type_error(msg: &str) -> Self {
         Error::Conversion(msg.to_string())
     }
+    pub fn new_place_error(msg: &str) -> Self {
+        Error::Place(msg.to_string())
+    }
 }
 
 impl From<pest_consume::Error<parser::Rule>> for Error {
@@ -46,6 +50,7 @@ impl fmt::Display for Error {
             Error::ParseInt(msg) => write!(f, "{}", msg),
             Error::Conversion(msg) => write!(f, "{}", msg),
             Error::Type(msg) => write!(f, "{}", msg),
+            Error::Place(msg) => write!(f, "{}", msg),
             Error::TryFromInt(msg) => write!(f, "{}", msg),
         }
     }
@@ -35,6 +35,8 @@ impl FromStr for OpCoord {
         let err = format!("Error: {} is not valid coordinate operation", input);
         match input {
             "+" => Ok(OpCoord::Add),
+            "-" => Ok(OpCoord::Sub),
+            "*" => Ok(OpCoord::Mul),
             _ => Err(Error::new_conv_error(&err)),
         }
     }
@@ -1,4 +1,48 @@
 use crate::ast::*;
+use std::rc::Rc;
+
+impl OpCoord {
+    // multiplication binds tighter than addition and subtraction
+    pub fn prec(&self) -> u8 {
+        match self {
+            OpCoord::Mul => 1,
+            _ => 0,
+        }
+    }
+}
+
+impl ExprCoord {
+    pub fn new_bin(op: OpCoord, lhs: ExprCoord, rhs: ExprCoord) -> Self {
+        ExprCoord::Bin(op, Rc::new(lhs), Rc::new(rhs))
+    }
+    // left-associative expression from a flat list of terms, respecting precedence
+    pub fn from_terms<I: IntoIterator<Item = (OpCoord, ExprCoord)>>(
+        term: ExprCoord,
+        bin: I,
+    ) -> Self {
+        let mut sum: Option<(ExprCoord, OpCoord)> = None;
+        let mut prod = term;
+        for (op, rhs) in bin {
+            if op.prec() > 0 {
+                prod = ExprCoord::new_bin(op, prod, rhs);
+            } else {
+                let lhs = match sum {
+                    Some((lhs, sop)) => ExprCoord::new_bin(sop, lhs, prod),
+                    None => prod,
+                };
+                sum = Some((lhs, op));
+                prod = rhs;
+            }
+        }
+        match sum {
+            Some((lhs, op)) => ExprCoord::new_bin(op, lhs, prod),
+            None => prod,
+        }
+    }
+    pub fn is_any(&self) -> bool {
+        matches!(self, ExprCoord::Any)
+    }
+}
 
 impl Loc {
     pub fn prim(&self) -> &Prim {
@@ -45,12 +89,18 @@ impl InstrAsm {
     pub fn op(&self) -> &OpAsm {
         &self.op
     }
+    pub fn attr(&self) -> &Expr {
+        &self.attr
+    }
     pub fn arg(&self) -> &Expr {
         &self.arg
     }
     pub fn loc(&self) -> &Loc {
         &self.loc
     }
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
     pub fn is_lut(&self) -> bool {
         self.loc.is_lut()
     }
@@ -66,6 +116,9 @@ impl InstrAsm {
     pub fn set_dst(&mut self, dst: Expr) {
         self.dst = dst;
     }
+    pub fn set_attr(&mut self, attr: Expr) {
+        self.attr = attr;
+    }
     pub fn set_arg(&mut self, arg: Expr) {
         self.arg = arg;
     }
@@ -84,6 +137,12 @@ impl Instr {
             Instr::Wire(instr) => instr.arg(),
         }
     }
+    pub fn span(&self) -> &Span {
+        match self {
+            Instr::Asm(instr) => instr.span(),
+            Instr::Wire(instr) => &instr.span,
+        }
+    }
     pub fn set_dst(&mut self, dst: Expr) {
         match self {
             Instr::Asm(instr) => instr.set_dst(dst),
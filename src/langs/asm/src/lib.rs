@@ -1,10 +1,12 @@
 pub mod ast;
 pub mod default;
 pub mod display;
+pub mod dot;
 pub mod errors;
 pub mod from;
 pub mod from_str;
 pub mod helpers;
 pub mod infer;
 pub mod parser;
+pub mod place;
 pub mod pretty_print;
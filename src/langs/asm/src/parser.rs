@@ -1,6 +1,7 @@
 use crate::ast::*;
 use crate::errors::Error;
 use crate::infer::infer_type_try_from_prog;
+use diag::path_from;
 use io::read_to_string;
 use pest_consume::match_nodes;
 use pest_consume::Error as PestError;
@@ -10,7 +11,12 @@ use std::rc::Rc;
 use std::str::FromStr;
 
 pub type ParseResult<T> = std::result::Result<T, PestError<Rule>>;
-type Node<'i> = pest_consume::Node<'i, Rule, ()>;
+// nodes carry the path of the file they come from
+type Node<'i> = pest_consume::Node<'i, Rule, Option<Rc<str>>>;
+
+fn span(input: &Node) -> Span {
+    Span::new(input.as_span(), input.user_data().clone())
+}
 
 const _GRAMMAR: &str = include_str!("syntax.pest");
 
@@ -32,7 +38,7 @@ impl Parser {
         let val = i64::from_str_radix(input.as_str(), 2);
         match val {
             Ok(v) => Ok(ExprTerm::Val(v)),
-            Err(_) => panic!("Error: parsing {} as bin i64", input.as_str()),
+            Err(_) => Err(input.error(format!("{} does not fit in i64", input.as_str()))),
         }
     }
 
@@ -40,7 +46,7 @@ impl Parser {
         let val = i64::from_str_radix(input.as_str(), 16);
         match val {
             Ok(v) => Ok(ExprTerm::Val(v)),
-            Err(_) => panic!("Error: parsing {} as hex i64", input.as_str()),
+            Err(_) => Err(input.error(format!("{} does not fit in i64", input.as_str()))),
         }
     }
 
@@ -48,7 +54,7 @@ impl Parser {
         let val = input.as_str().parse::<i64>();
         match val {
             Ok(v) => Ok(ExprTerm::Val(v)),
-            Err(_) => panic!("Error: parsing {} as dec i64", input.as_str()),
+            Err(_) => Err(input.error(format!("{} does not fit in i64", input.as_str()))),
         }
     }
 
@@ -65,7 +71,7 @@ impl Parser {
         let ty = Ty::from_str(input.as_str());
         match ty {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -73,7 +79,7 @@ impl Parser {
         let op = OpCoord::from_str(input.as_str());
         match op {
             Ok(e) => Ok(e),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -81,15 +87,29 @@ impl Parser {
         let expr = ExprCoord::from_str(input.as_str());
         match expr {
             Ok(e) => Ok(e),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
-    fn expr_coord(input: Node) -> ParseResult<ExprCoord> {
+    fn term_coord(input: Node) -> ParseResult<ExprCoord> {
         Ok(match_nodes!(
             input.into_children();
             [coord(coord)] => coord,
-            [coord(lhs), op_coord(op), coord(rhs)] => ExprCoord::Bin(op, Rc::new(lhs), Rc::new(rhs)),
+            [expr_coord(expr)] => expr,
+        ))
+    }
+
+    fn bin_coord(input: Node) -> ParseResult<(OpCoord, ExprCoord)> {
+        Ok(match_nodes!(
+            input.into_children();
+            [op_coord(op), term_coord(term)] => (op, term),
+        ))
+    }
+
+    fn expr_coord(input: Node) -> ParseResult<ExprCoord> {
+        Ok(match_nodes!(
+            input.into_children();
+            [term_coord(term), bin_coord(bin)..] => ExprCoord::from_terms(term, bin),
         ))
     }
 
@@ -97,7 +117,7 @@ impl Parser {
         let prim = Prim::from_str(input.as_str());
         match prim {
             Ok(p) => Ok(p),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -151,7 +171,7 @@ impl Parser {
         let op = OpAsm::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -159,11 +179,12 @@ impl Parser {
         let op = OpWire::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
     fn instr(input: Node) -> ParseResult<Instr> {
+        let span = span(&input);
         Ok(match_nodes!(
             input.into_children();
             [io(dst), op_wire(op), tup_val(attr)] => Instr::from(InstrWire {
@@ -171,24 +192,37 @@ impl Parser {
                 dst,
                 attr: Expr::from(attr),
                 arg: Expr::default(),
+                span: span.clone(),
             }),
             [io(dst), op_wire(op), io(arg)] => Instr::from(InstrWire {
                 op,
                 dst,
                 attr: Expr::default(),
                 arg,
+                span: span.clone(),
             }),
             [io(dst), op_wire(op), tup_val(attr), io(arg)] => Instr::from(InstrWire {
                 op,
                 dst,
                 attr: Expr::from(attr),
                 arg,
+                span: span.clone(),
             }),
             [io(dst), op_asm(op), io(arg), loc(loc)] => Instr::from(InstrAsm {
                 op,
                 dst,
+                attr: Expr::default(),
+                arg,
+                loc,
+                span: span.clone(),
+            }),
+            [io(dst), op_asm(op), tup_val(attr), io(arg), loc(loc)] => Instr::from(InstrAsm {
+                op,
+                dst,
+                attr: Expr::from(attr),
                 arg,
                 loc,
+                span: span.clone(),
             })
         ))
     }
@@ -201,22 +235,26 @@ impl Parser {
     }
 
     fn sig(input: Node) -> ParseResult<Sig> {
+        let span = span(&input);
         Ok(match_nodes!(
             input.into_children();
             [id(id)] => Sig {
                 id,
                 input: Expr::default(),
                 output: Expr::default(),
+                span: span.clone(),
             },
             [id(id), io(output)] => Sig {
                 id,
                 input: Expr::default(),
                 output,
+                span: span.clone(),
             },
             [id(id), io(input), io(output)] => Sig {
                 id,
                 input,
                 output,
+                span: span.clone(),
             },
         ))
     }
@@ -244,14 +282,21 @@ impl Parser {
 }
 
 impl Parser {
-    pub fn parse_from_str(input_str: &str) -> Result<Prog, Error> {
-        let inputs = Parser::parse(Rule::file, input_str)?;
+    fn parse_with_path(input_str: &str, path: Option<Rc<str>>) -> Result<Prog, Error> {
+        let inputs = Parser::parse_with_userdata(Rule::file, input_str, path)?;
         let input = inputs.single()?;
         let prog = Parser::file(input)?;
         Ok(infer_type_try_from_prog(&prog))
     }
+    pub fn parse_from_str(input_str: &str) -> Result<Prog, Error> {
+        Parser::parse_with_path(input_str, None)
+    }
     pub fn parse_from_file<P: AsRef<Path>>(path: P) -> Result<Prog, Error> {
-        let content = read_to_string(path);
-        Parser::parse_from_str(&content)
+        let content = read_to_string(&path);
+        let path = path_from(&path);
+        match Parser::parse_with_path(&content, Some(path.clone())) {
+            Err(Error::Parser(e)) => Err(Error::Parser(e.with_path(&path))),
+            res => res,
+        }
     }
 }
@@ -0,0 +1,357 @@
+use crate::ast::*;
+use crate::errors::Error;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
+
+pub type Binding = HashMap<Id, u64>;
+
+type Value = HashMap<Id, i64>;
+
+// linear coordinate, a constant plus a coefficient per variable
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Lin {
+    val: i64,
+    var: BTreeMap<Id, i64>,
+}
+
+// arithmetic on coordinates returns None when it overflows
+impl Lin {
+    fn scale(&self, k: i64) -> Option<Lin> {
+        let mut var = BTreeMap::new();
+        for (n, c) in &self.var {
+            let c = c.checked_mul(k)?;
+            if c != 0 {
+                var.insert(n.to_string(), c);
+            }
+        }
+        Some(Lin {
+            val: self.val.checked_mul(k)?,
+            var,
+        })
+    }
+    fn add(&self, rhs: &Lin) -> Option<Lin> {
+        let mut res = self.clone();
+        res.val = res.val.checked_add(rhs.val)?;
+        for (n, c) in &rhs.var {
+            let entry = res.var.entry(n.to_string()).or_insert(0);
+            *entry = entry.checked_add(*c)?;
+        }
+        res.var = res.var.into_iter().filter(|(_, c)| *c != 0).collect();
+        Some(res)
+    }
+    fn is_const(&self) -> bool {
+        self.var.is_empty()
+    }
+    fn eval(&self, value: &Value) -> Option<i64> {
+        self.var.iter().try_fold(self.val, |acc, (n, c)| {
+            acc.checked_add(c.checked_mul(*value.get(n).unwrap_or(&0))?)
+        })
+    }
+}
+
+fn overflow_error(expr: &ExprCoord) -> Error {
+    let err = format!("coordinate {} overflows", expr);
+    Error::new_place_error(&err)
+}
+
+fn lin_try_from_expr(expr: &ExprCoord) -> Result<Lin, Error> {
+    match expr {
+        ExprCoord::Any => {
+            let err = "?? can only be used as a whole coordinate";
+            Err(Error::new_place_error(err))
+        }
+        ExprCoord::Val(n) => Ok(Lin {
+            val: i64::try_from(*n)?,
+            var: BTreeMap::new(),
+        }),
+        ExprCoord::Var(n) => {
+            let mut var = BTreeMap::new();
+            var.insert(n.to_string(), 1);
+            Ok(Lin { val: 0, var })
+        }
+        ExprCoord::Bin(op, lhs, rhs) => {
+            let l = lin_try_from_expr(lhs)?;
+            let r = lin_try_from_expr(rhs)?;
+            let res = match op {
+                OpCoord::Add => l.add(&r),
+                OpCoord::Sub => r.scale(-1).and_then(|r| l.add(&r)),
+                OpCoord::Mul if r.is_const() => l.scale(r.val),
+                OpCoord::Mul if l.is_const() => r.scale(l.val),
+                OpCoord::Mul => {
+                    let err = format!(
+                        "{} is not linear, coordinates can only be multiplied by constants",
+                        expr
+                    );
+                    return Err(Error::new_place_error(&err));
+                }
+            };
+            res.ok_or_else(|| overflow_error(expr))
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Cell {
+    name: String,
+    loc: Loc,
+    x: Lin,
+    y: Lin,
+}
+
+impl Cell {
+    fn vars(&self) -> impl Iterator<Item = &Id> {
+        self.x.var.keys().chain(self.y.var.keys())
+    }
+    fn key(&self, value: &Value) -> Result<(Prim, i64, i64), Error> {
+        Ok((
+            self.loc.prim().clone(),
+            self.x
+                .eval(value)
+                .ok_or_else(|| overflow_error(self.loc.x()))?,
+            self.y
+                .eval(value)
+                .ok_or_else(|| overflow_error(self.loc.y()))?,
+        ))
+    }
+}
+
+fn cells_try_from_prog(prog: &Prog) -> Result<Vec<Cell>, Error> {
+    let mut cells: Vec<Cell> = Vec::new();
+    for instr in prog.body() {
+        if let Instr::Asm(asm) = instr {
+            let loc = asm.loc();
+            // unplaced instructions are left for the vendor tools
+            if loc.x().is_any() || loc.y().is_any() {
+                continue;
+            }
+            let dst: Vec<ExprTerm> = asm.dst().clone().into();
+            cells.push(Cell {
+                name: dst
+                    .iter()
+                    .filter_map(|t| t.id())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                loc: loc.clone(),
+                x: lin_try_from_expr(loc.x())?,
+                y: lin_try_from_expr(loc.y())?,
+            });
+        }
+    }
+    Ok(cells)
+}
+
+// instructions sharing a free variable move together, in order of appearance
+fn groups_from_cells(cells: &[Cell], bind: &Binding) -> Vec<Vec<usize>> {
+    let mut groups: Vec<(HashSet<Id>, Vec<usize>)> = Vec::new();
+    for (i, cell) in cells.iter().enumerate() {
+        let vars: HashSet<Id> = cell
+            .vars()
+            .filter(|n| !bind.contains_key(*n))
+            .cloned()
+            .collect();
+        let (mut merged, rest): (Vec<_>, Vec<_>) = groups
+            .into_iter()
+            .partition(|(v, _)| !vars.is_empty() && !v.is_disjoint(&vars));
+        groups = rest;
+        let mut group = (vars, vec![i]);
+        for (v, c) in merged.drain(..) {
+            group.0.extend(v);
+            group.1.extend(c);
+        }
+        group.1.sort_unstable();
+        groups.push(group);
+    }
+    groups.sort_by_key(|(_, c)| c[0]);
+    groups.into_iter().map(|(_, c)| c).collect()
+}
+
+struct Solver<'a> {
+    cells: &'a [Cell],
+    value: Value,
+    free: HashSet<Id>,
+    used: HashMap<(Prim, i64, i64), usize>,
+}
+
+impl<'a> Solver<'a> {
+    fn new(cells: &'a [Cell], bind: &Binding) -> Result<Self, Error> {
+        let mut value = value_try_from_binding(bind)?;
+        let mut free: HashSet<Id> = HashSet::new();
+        for cell in cells {
+            for n in cell.vars() {
+                if !value.contains_key(n) {
+                    value.insert(n.to_string(), 0);
+                    free.insert(n.to_string());
+                }
+            }
+        }
+        Ok(Solver {
+            cells,
+            value,
+            free,
+            used: HashMap::new(),
+        })
+    }
+    // raise free variables until every coordinate in the group is non-negative
+    fn lower(&mut self, group: &[usize]) -> Result<(), Error> {
+        let limit = group.len() * self.free.len() + 1;
+        for _ in 0..limit {
+            let mut changed = false;
+            for i in group {
+                let cell = &self.cells[*i];
+                for (lin, expr) in &[(&cell.x, cell.loc.x()), (&cell.y, cell.loc.y())] {
+                    let v = lin.eval(&self.value).ok_or_else(|| overflow_error(expr))?;
+                    if v >= 0 {
+                        continue;
+                    }
+                    let var = lin
+                        .var
+                        .iter()
+                        .find(|(n, c)| **c > 0 && self.free.contains(*n));
+                    if let Some((n, c)) = var {
+                        let value = self.value.get_mut(n).unwrap();
+                        *value = v
+                            .checked_neg()
+                            .and_then(|v| v.checked_add(c - 1))
+                            .and_then(|v| value.checked_add(v / c))
+                            .ok_or_else(|| overflow_error(expr))?;
+                        changed = true;
+                    } else {
+                        let err = format!("coordinate {} of {} is negative", expr, cell.name);
+                        return Err(Error::new_place_error(&err));
+                    }
+                }
+            }
+            if !changed {
+                return Ok(());
+            }
+        }
+        let err = format!(
+            "coordinates of {} cannot be made non-negative",
+            self.cells[group[0]].name
+        );
+        Err(Error::new_place_error(&err))
+    }
+    fn conflict(&self, group: &[usize]) -> Result<Option<(usize, usize)>, Error> {
+        let mut local: HashMap<(Prim, i64, i64), usize> = HashMap::new();
+        let mut other = None;
+        for i in group {
+            let key = self.cells[*i].key(&self.value)?;
+            if let Some(j) = local.insert(key.clone(), *i) {
+                // no offset can separate instructions of the same group
+                return Err(self.conflict_error(j, *i)?);
+            }
+            if let Some(j) = self.used.get(&key) {
+                other = other.or(Some((*j, *i)));
+            }
+        }
+        Ok(other)
+    }
+    fn conflict_error(&self, a: usize, b: usize) -> Result<Error, Error> {
+        let (prim, x, y) = self.cells[b].key(&self.value)?;
+        let err = format!(
+            "{} and {} are both placed at {}({}, {})",
+            self.cells[a].name, self.cells[b].name, prim, x, y
+        );
+        Ok(Error::new_place_error(&err))
+    }
+    // groups are stacked along y until they fit next to the ones placed before
+    fn place(&mut self, group: &[usize]) -> Result<(), Error> {
+        self.lower(group)?;
+        let step: Vec<Id> = group
+            .iter()
+            .flat_map(|i| self.cells[*i].y.var.iter())
+            .filter(|(n, c)| **c > 0 && self.free.contains(*n))
+            .map(|(n, _)| n.to_string())
+            .collect();
+        let limit = self
+            .used
+            .keys()
+            .map(|(_, _, y)| *y)
+            .max()
+            .unwrap_or(0)
+            .saturating_add(2);
+        for _ in 0..limit {
+            match self.conflict(group)? {
+                None => {
+                    for i in group {
+                        let key = self.cells[*i].key(&self.value)?;
+                        self.used.insert(key, *i);
+                    }
+                    return Ok(());
+                }
+                Some((a, b)) if step.is_empty() => return Err(self.conflict_error(a, b)?),
+                Some(_) => {
+                    for n in &step {
+                        let value = self.value.get_mut(n).unwrap();
+                        *value = value
+                            .checked_add(1)
+                            .ok_or_else(|| overflow_error(&ExprCoord::Var(n.to_string())))?;
+                    }
+                    self.lower(group)?;
+                }
+            }
+        }
+        let err = format!(
+            "could not find a free location for {}",
+            self.cells[group[0]].name
+        );
+        Err(Error::new_place_error(&err))
+    }
+}
+
+// concrete value for every coordinate variable, variables in the binding keep
+// their value and the rest are chosen so that no two instructions overlap
+pub fn solve_try_from_prog(prog: &Prog, bind: &Binding) -> Result<Binding, Error> {
+    let cells = cells_try_from_prog(prog)?;
+    let mut solver = Solver::new(&cells, bind)?;
+    let groups = groups_from_cells(&cells, bind);
+    // groups without free variables cannot move, so they go first
+    let (fixed, free): (Vec<_>, Vec<_>) = groups.into_iter().partition(|g| {
+        g.iter()
+            .all(|i| cells[*i].vars().all(|n| !solver.free.contains(n)))
+    });
+    for group in fixed.iter().chain(free.iter()) {
+        solver.place(group)?;
+    }
+    let mut res = Binding::new();
+    for (n, v) in solver.value {
+        res.insert(n, u64::try_from(v)?);
+    }
+    Ok(res)
+}
+
+fn value_try_from_binding(bind: &Binding) -> Result<Value, Error> {
+    let mut value = Value::new();
+    for (n, v) in bind {
+        value.insert(n.to_string(), i64::try_from(*v)?);
+    }
+    Ok(value)
+}
+
+fn val_try_from_expr(expr: &ExprCoord, value: &Value) -> Result<ExprCoord, Error> {
+    let v = lin_try_from_expr(expr)?
+        .eval(value)
+        .ok_or_else(|| overflow_error(expr))?;
+    Ok(ExprCoord::Val(u64::try_from(v)?))
+}
+
+// replace relative locations with concrete coordinates
+pub fn place_try_from_prog(prog: &Prog, bind: &Binding) -> Result<Prog, Error> {
+    let value = value_try_from_binding(&solve_try_from_prog(prog, bind)?)?;
+    let mut res = prog.clone();
+    let mut body: Vec<Instr> = Vec::new();
+    for instr in prog.body() {
+        let mut instr = instr.clone();
+        if let Instr::Asm(asm) = &mut instr {
+            let mut loc = asm.loc().clone();
+            if !loc.x().is_any() && !loc.y().is_any() {
+                loc.set_x(val_try_from_expr(asm.loc().x(), &value)?);
+                loc.set_y(val_try_from_expr(asm.loc().y(), &value)?);
+            }
+            asm.set_loc(loc);
+        }
+        body.push(instr);
+    }
+    res.set_body(body);
+    Ok(res)
+}
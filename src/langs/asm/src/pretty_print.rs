@@ -1,11 +1,13 @@
 use crate::ast::*;
-use ir::pretty_print::expr_names;
+use ir::pretty_print::{expr_attrs, expr_names};
 use prettyprint::{block_with_braces, intersperse, PrettyHelper, PrettyPrint, RcDoc};
 
 impl PrettyPrint for OpCoord {
     fn to_doc(&self) -> RcDoc<()> {
         match self {
             OpCoord::Add => RcDoc::text("+"),
+            OpCoord::Sub => RcDoc::text("-"),
+            OpCoord::Mul => RcDoc::text("*"),
         }
     }
 }
@@ -16,7 +18,18 @@ impl PrettyPrint for ExprCoord {
             ExprCoord::Any => RcDoc::text("??"),
             ExprCoord::Val(n) => RcDoc::as_string(n),
             ExprCoord::Var(n) => RcDoc::as_string(n),
-            ExprCoord::Bin(op, lhs, rhs) => lhs.to_doc().append(op.to_doc()).append(rhs.to_doc()),
+            ExprCoord::Bin(op, lhs, rhs) => {
+                // parens are only needed where the tree differs from the parsing order
+                let lhs = match lhs.as_ref() {
+                    ExprCoord::Bin(lop, _, _) if lop.prec() < op.prec() => lhs.to_doc().parens(),
+                    _ => lhs.to_doc(),
+                };
+                let rhs = match rhs.as_ref() {
+                    ExprCoord::Bin(rop, _, _) if rop.prec() <= op.prec() => rhs.to_doc().parens(),
+                    _ => rhs.to_doc(),
+                };
+                lhs.append(op.to_doc()).append(rhs)
+            }
         }
     }
 }
@@ -49,6 +62,7 @@ impl PrettyPrint for InstrAsm {
             .append(RcDoc::text("="))
             .append(RcDoc::space())
             .append(self.op().to_doc())
+            .append(expr_attrs(self.attr()))
             .append(expr_names(self.arg()))
             .append(RcDoc::space())
             .append(RcDoc::text("@"))
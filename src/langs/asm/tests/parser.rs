@@ -8,3 +8,12 @@ fn simple_prog() {
     exp.set_id("main");
     assert_eq!(res, exp);
 }
+
+#[test]
+fn unknown_prim_is_error() {
+    let res = Parser::parse_from_str(
+        "def main (a:i8) -> (y:i8) {\n    y:i8 = inv_i8(a) @nope(??, ??);\n}",
+    );
+    let msg = res.unwrap_err().to_string();
+    assert!(msg.contains("2:23"));
+}
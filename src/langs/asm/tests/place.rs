@@ -0,0 +1,117 @@
+use asm::ast::{ExprCoord, Instr, Prog};
+use asm::errors::Error;
+use asm::parser::Parser;
+use asm::place::{place_try_from_prog, solve_try_from_prog, Binding};
+use std::collections::HashSet;
+
+const CHAIN: &str = "def main(a:i8, b:i8, c:i8, en:bool) -> (y:i8) {
+    t0:i8 = laddrega_i8(a, b, en) @lut(x0, 2*y0-1);
+    t1:i8 = laddrega_i8(t0, c, en) @lut(x0+1, 2*y0-1);
+    y:i8 = laddrega_i8(t1, c, en) @lut(x0+1, 2*(y0+1)-1);
+}";
+
+fn bind(values: &[(&str, u64)]) -> Binding {
+    values.iter().map(|(n, v)| (n.to_string(), *v)).collect()
+}
+
+fn coords(prog: &Prog) -> Vec<(u64, u64)> {
+    prog.body()
+        .iter()
+        .filter_map(|instr| match instr {
+            Instr::Asm(asm) => match (asm.loc().x(), asm.loc().y()) {
+                (ExprCoord::Val(x), ExprCoord::Val(y)) => Some((*x, *y)),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+fn is_place_error(res: Result<Prog, Error>, msg: &str) -> bool {
+    match res {
+        Err(Error::Place(err)) => err.contains(msg),
+        _ => false,
+    }
+}
+
+#[test]
+fn coord_roundtrip() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(CHAIN)?;
+    let res = Parser::parse_from_str(&prog.to_string())?;
+    assert_eq!(res, prog);
+    let prog = Parser::parse_from_str(&CHAIN.replace("2*(y0+1)-1", "y0-(y1-3)"))?;
+    assert!(prog.to_string().contains("@lut(x0+1, y0-(y1-3))"));
+    Ok(())
+}
+
+#[test]
+fn chain_origin() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(CHAIN)?;
+    let res = place_try_from_prog(&prog, &Binding::new())?;
+    assert_eq!(coords(&res), vec![(0, 1), (1, 1), (1, 3)]);
+    Ok(())
+}
+
+#[test]
+fn chain_relocate() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(CHAIN)?;
+    let res = place_try_from_prog(&prog, &bind(&[("x0", 4), ("y0", 10)]))?;
+    assert_eq!(coords(&res), vec![(4, 19), (5, 19), (5, 21)]);
+    Ok(())
+}
+
+#[test]
+fn cascade_groups() -> Result<(), Error> {
+    let prog = Parser::parse_from_file("../../../examples/asm/tdot_5_3_opt.asm")?;
+    let sol = solve_try_from_prog(&prog, &Binding::new())?;
+    assert_eq!(sol["y0"], 0);
+    assert_eq!(sol["y1"], 3);
+    let res = place_try_from_prog(&prog, &Binding::new())?;
+    let placed = coords(&res);
+    assert_eq!(placed.len(), 15);
+    assert_eq!(placed.iter().collect::<HashSet<_>>().len(), 15);
+    Ok(())
+}
+
+#[test]
+fn conflict() -> Result<(), Error> {
+    let prog = Parser::parse_from_file("../../../examples/asm/tdot_5_3_opt.asm")?;
+    let res = place_try_from_prog(&prog, &bind(&[("x0", 0), ("y0", 0), ("x1", 0), ("y1", 1)]));
+    assert!(is_place_error(
+        res,
+        "v and t29 are both placed at dsp(0, 2)"
+    ));
+    Ok(())
+}
+
+#[test]
+fn negative() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(CHAIN)?;
+    let res = place_try_from_prog(&prog, &bind(&[("y0", 0)]));
+    assert!(is_place_error(res, "coordinate 2*y0-1 of t0 is negative"));
+    Ok(())
+}
+
+#[test]
+fn nonlinear() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(&CHAIN.replace("2*y0-1", "x0*y0"))?;
+    let res = place_try_from_prog(&prog, &Binding::new());
+    assert!(is_place_error(res, "x0*y0 is not linear"));
+    let prog = Parser::parse_from_str(&CHAIN.replace("2*y0-1", "x0*0*y0+3"))?;
+    let res = place_try_from_prog(&prog, &Binding::new())?;
+    assert_eq!(coords(&res), vec![(0, 3), (1, 3), (1, 1)]);
+    Ok(())
+}
+
+#[test]
+fn overflow() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        &CHAIN.replace("2*y0-1", "4611686018427387904*4611686018427387904*y0"),
+    )?;
+    let res = place_try_from_prog(&prog, &Binding::new());
+    assert!(is_place_error(res, "overflows"));
+    let prog = Parser::parse_from_str(&CHAIN.replace("2*y0-1", "4611686018427387904*y0"))?;
+    let res = place_try_from_prog(&prog, &bind(&[("y0", 2)]));
+    assert!(is_place_error(res, "overflows"));
+    Ok(())
+}
@@ -1,8 +1,10 @@
+use crate::elab::GenInstr;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
 pub type Id = String;
+pub type Span = diag::Span;
 pub type InstrMap = HashMap<Id, Instr>;
 pub type TermMap = HashMap<Id, ExprTerm>;
 pub type ExprSet = HashSet<Expr>;
@@ -11,9 +13,13 @@ pub type ExprSet = HashSet<Expr>;
 pub enum Ty {
     Any,
     Bool,
+    Clock,
     UInt(u64),
     SInt(u64),
     Vector(Rc<Ty>, u64),
+    UIntParam(Id),
+    SIntParam(Id),
+    VectorParam(Rc<Ty>, Id),
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash)]
@@ -79,6 +85,7 @@ pub enum OpPrim {
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash)]
 pub struct OpCall {
     pub op: Id,
+    pub param: Vec<ExprTerm>,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash)]
@@ -87,6 +94,8 @@ pub struct InstrWire {
     pub dst: Expr,
     pub attr: Expr,
     pub arg: Expr,
+    #[serde(skip)]
+    pub span: Span,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash)]
@@ -96,6 +105,8 @@ pub struct InstrPrim {
     pub attr: Expr,
     pub arg: Expr,
     pub prim: Prim,
+    #[serde(skip)]
+    pub span: Span,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash)]
@@ -103,6 +114,8 @@ pub struct InstrCall {
     pub op: OpCall,
     pub dst: Expr,
     pub arg: Expr,
+    #[serde(skip)]
+    pub span: Span,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash)]
@@ -117,12 +130,19 @@ pub struct Sig {
     pub id: Id,
     pub input: Expr,
     pub output: Expr,
+    #[serde(skip)]
+    pub span: Span,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash, Default)]
 pub struct Def {
     pub sig: Sig,
+    pub param: Vec<Id>,
     pub body: Vec<Instr>,
+    // body of parametric defs, it is elaborated for every specialization,
+    // so loops can be bounded by parameters. Other defs are elaborated when parsed
+    #[serde(skip)]
+    pub gen: Vec<GenInstr>,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Default)]
@@ -0,0 +1,476 @@
+use crate::ast::*;
+use crate::errors::Error;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// handle to a variable defined by a builder, it carries its type so
+// mismatches are caught when an instruction is added
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Value {
+    id: Id,
+    ty: Ty,
+}
+
+impl Value {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+    pub fn ty(&self) -> &Ty {
+        &self.ty
+    }
+    fn term(&self) -> ExprTerm {
+        ExprTerm::Var(self.id.to_string(), self.ty.clone())
+    }
+}
+
+fn builder_error<T>(name: &str, msg: &str) -> Result<T, Error> {
+    let err = format!("{}, in op: {}", msg, name);
+    Err(Error::new_builder_error(&err))
+}
+
+// width of a value or of each lane of a vector
+fn width(name: &str, ty: &Ty) -> Result<u64, Error> {
+    match ty.width() {
+        Some(w) if !ty.is_clock() => Ok(w),
+        _ => builder_error(name, &format!("{} does not have a width", ty)),
+    }
+}
+
+// same kind of integer as ty with a new width, bools become unsigned
+fn resize(ty: &Ty, width: u64) -> Ty {
+    match ty {
+        Ty::SInt(_) => Ty::SInt(width),
+        Ty::Vector(ty, len) => Ty::Vector(Rc::new(resize(ty, width)), *len),
+        _ => Ty::UInt(width),
+    }
+}
+
+// every type the parser accepts except the parametric ones, builders do
+// not have parameters
+fn is_valid_ty(ty: &Ty) -> bool {
+    match ty {
+        Ty::Bool | Ty::Clock => true,
+        Ty::UInt(w) | Ty::SInt(w) => *w > 0,
+        Ty::Vector(ty, len) => {
+            *len > 0 && matches!(ty.as_ref(), Ty::UInt(_) | Ty::SInt(_)) && is_valid_ty(ty)
+        }
+        _ => false,
+    }
+}
+
+// clocks can only be renamed with id or name the clock of an instruction
+fn check_no_clock(name: &str, ty: &Ty, arg: &[&Value]) -> Result<(), Error> {
+    if ty.is_clock() || arg.iter().any(|v| v.ty.is_clock()) {
+        builder_error(name, &format!("{} can not use clocks", name))
+    } else {
+        Ok(())
+    }
+}
+
+// values must be representable in the type, or in each lane of a vector
+fn check_fit(name: &str, ty: &Ty, val: i64) -> Result<(), Error> {
+    let w = match ty.width() {
+        Some(w) => w.min(64),
+        None => return builder_error(name, &format!("{} does not have a width", ty)),
+    };
+    let (min, max) = if ty.is_signed() {
+        (-(1_i128 << (w - 1)), (1_i128 << (w - 1)) - 1)
+    } else {
+        (0, (1_i128 << w) - 1)
+    };
+    if (val as i128) < min || (val as i128) > max {
+        return builder_error(name, &format!("{} does not fit in {}", val, ty));
+    }
+    Ok(())
+}
+
+fn tup(value: &[&Value]) -> Expr {
+    Expr::from(ExprTup::from(
+        value.iter().map(|v| v.term()).collect::<Vec<ExprTerm>>(),
+    ))
+}
+
+fn attr(val: &[i64]) -> Expr {
+    Expr::from(ExprTup::from(
+        val.iter()
+            .map(|v| ExprTerm::Val(*v))
+            .collect::<Vec<ExprTerm>>(),
+    ))
+}
+
+pub struct DefBuilder {
+    id: Id,
+    input: Vec<ExprTerm>,
+    output: Vec<ExprTerm>,
+    body: Vec<Instr>,
+    env: HashMap<Id, Ty>,
+    count: u64,
+}
+
+impl DefBuilder {
+    pub fn new(id: &str) -> Self {
+        DefBuilder {
+            id: id.to_string(),
+            input: Vec::new(),
+            output: Vec::new(),
+            body: Vec::new(),
+            env: HashMap::new(),
+            count: 0,
+        }
+    }
+    fn declare(&mut self, name: &str, ty: &Ty) -> Result<Value, Error> {
+        if self.env.contains_key(name) {
+            return builder_error(name, &format!("{} is already defined", name));
+        }
+        if !is_valid_ty(ty) {
+            return builder_error(name, &format!("{} is not a valid type", ty));
+        }
+        self.env.insert(name.to_string(), ty.clone());
+        Ok(Value {
+            id: name.to_string(),
+            ty: ty.clone(),
+        })
+    }
+    fn fresh(&mut self, ty: &Ty) -> Result<Value, Error> {
+        let mut id = format!("t{}", self.count);
+        while self.env.contains_key(&id) {
+            self.count += 1;
+            id = format!("t{}", self.count);
+        }
+        self.count += 1;
+        self.declare(&id, ty)
+    }
+    // arguments must come from this builder
+    fn check_arg(&self, name: &str, arg: &[&Value]) -> Result<(), Error> {
+        for value in arg {
+            if self.env.get(&value.id) != Some(&value.ty) {
+                let msg = format!("{} is not defined in def {}", value.id, self.id);
+                return builder_error(name, &msg);
+            }
+        }
+        Ok(())
+    }
+    fn check_same(&self, name: &str, exp: &Ty, arg: &[&Value]) -> Result<(), Error> {
+        for value in arg {
+            if &value.ty != exp {
+                let msg = format!("{} expects {} arguments but got {}", name, exp, value.ty);
+                return builder_error(name, &msg);
+            }
+        }
+        Ok(())
+    }
+    fn check_bool(&self, name: &str, value: &Value) -> Result<(), Error> {
+        if value.ty == Ty::Bool {
+            Ok(())
+        } else {
+            builder_error(name, &format!("{} expects bool but got {}", name, value.ty))
+        }
+    }
+    fn prim(&mut self, op: OpPrim, ty: &Ty, val: &[i64], arg: &[&Value]) -> Result<Value, Error> {
+        self.check_arg(&op.to_string(), arg)?;
+        check_no_clock(&op.to_string(), ty, arg)?;
+        let dst = self.fresh(ty)?;
+        self.body.push(Instr::from(InstrPrim {
+            op,
+            dst: Expr::from(dst.term()),
+            attr: attr(val),
+            arg: tup(arg),
+            prim: Prim::Any,
+            span: Span::default(),
+        }));
+        Ok(dst)
+    }
+    fn wire(&mut self, op: OpWire, ty: &Ty, val: &[i64], arg: &[&Value]) -> Result<Value, Error> {
+        self.check_arg(&op.to_string(), arg)?;
+        if op != OpWire::Id {
+            check_no_clock(&op.to_string(), ty, arg)?;
+        }
+        let dst = self.fresh(ty)?;
+        self.body.push(Instr::from(InstrWire {
+            op,
+            dst: Expr::from(dst.term()),
+            attr: attr(val),
+            arg: tup(arg),
+            span: Span::default(),
+        }));
+        Ok(dst)
+    }
+    fn binop(&mut self, op: OpPrim, lhs: &Value, rhs: &Value) -> Result<Value, Error> {
+        self.check_same(&op.to_string(), &lhs.ty, &[rhs])?;
+        self.prim(op, &lhs.ty.clone(), &[], &[lhs, rhs])
+    }
+    fn cmpop(&mut self, op: OpPrim, lhs: &Value, rhs: &Value) -> Result<Value, Error> {
+        self.check_same(&op.to_string(), &lhs.ty, &[rhs])?;
+        self.prim(op, &Ty::Bool, &[], &[lhs, rhs])
+    }
+    fn shift(&mut self, op: OpWire, arg: &Value, amount: i64) -> Result<Value, Error> {
+        if amount < 0 {
+            return builder_error(&op.to_string(), "shift amount must be positive");
+        }
+        self.wire(op, &arg.ty.clone(), &[amount], &[arg])
+    }
+    pub fn input(&mut self, name: &str, ty: Ty) -> Result<Value, Error> {
+        let value = self.declare(name, &ty)?;
+        self.input.push(value.term());
+        Ok(value)
+    }
+    // outputs are named in the signature, values with a different name are
+    // forwarded with an id
+    pub fn output(&mut self, name: &str, value: &Value) -> Result<(), Error> {
+        self.check_arg(name, &[value])?;
+        let res = if value.id == name {
+            value.clone()
+        } else {
+            let dst = self.declare(name, &value.ty)?;
+            self.body.push(Instr::from(InstrWire {
+                op: OpWire::Id,
+                dst: Expr::from(dst.term()),
+                attr: Expr::default(),
+                arg: tup(&[value]),
+                span: Span::default(),
+            }));
+            dst
+        };
+        if self.output.contains(&res.term()) || self.input.contains(&res.term()) {
+            return builder_error(name, &format!("{} can not be an output", name));
+        }
+        self.output.push(res.term());
+        Ok(())
+    }
+    // placement hint for the instruction that defines value
+    pub fn set_prim(&mut self, value: &Value, prim: Prim) -> Result<(), Error> {
+        for instr in self.body.iter_mut() {
+            if let Instr::Prim(p) = instr {
+                if p.dst().get_id(0)? == value.id {
+                    p.set_prim(prim);
+                    return Ok(());
+                }
+            }
+        }
+        builder_error(&value.id, &format!("{} is not defined by a prim", value.id))
+    }
+    // clock domain of the reg, ram or block rom read that defines value, the
+    // clock becomes its last argument so regs have to be connected first
+    pub fn set_clock(&mut self, value: &Value, clock: &Value) -> Result<(), Error> {
+        self.check_arg(&value.id, &[value, clock])?;
+        if !clock.ty.is_clock() {
+            let msg = format!("{} expects clock but got {}", value.id, clock.ty);
+            return builder_error(&value.id, &msg);
+        }
+        for instr in self.body.iter_mut() {
+            if let Instr::Prim(p) = instr {
+                if p.dst().get_id(0)? != value.id {
+                    continue;
+                }
+                let num = match p.op() {
+                    OpPrim::Reg => 2,
+                    OpPrim::Ram => 3,
+                    OpPrim::Rom if p.is_sync_read() => 1,
+                    _ => 0,
+                };
+                let mut arg: Vec<ExprTerm> = p.arg().clone().into();
+                if num == 0 || arg.len() != num {
+                    break;
+                }
+                arg.push(clock.term());
+                p.set_arg(Expr::from(ExprTup::from(arg)));
+                return Ok(());
+            }
+        }
+        let msg = format!(
+            "{} is not a connected reg, ram or block rom without clock",
+            value.id
+        );
+        builder_error(&value.id, &msg)
+    }
+    pub fn con(&mut self, ty: Ty, val: i64) -> Result<Value, Error> {
+        let name = OpWire::Con.to_string();
+        width(&name, &ty)?;
+        check_fit(&name, &ty, val)?;
+        self.wire(OpWire::Con, &ty, &[val], &[])
+    }
+    pub fn id(&mut self, arg: &Value) -> Result<Value, Error> {
+        self.wire(OpWire::Id, &arg.ty.clone(), &[], &[arg])
+    }
+    pub fn sll(&mut self, arg: &Value, amount: i64) -> Result<Value, Error> {
+        self.shift(OpWire::Sll, arg, amount)
+    }
+    pub fn srl(&mut self, arg: &Value, amount: i64) -> Result<Value, Error> {
+        self.shift(OpWire::Srl, arg, amount)
+    }
+    pub fn sra(&mut self, arg: &Value, amount: i64) -> Result<Value, Error> {
+        self.shift(OpWire::Sra, arg, amount)
+    }
+    // bits lo to hi inclusive
+    pub fn ext(&mut self, arg: &Value, lo: u64, hi: u64) -> Result<Value, Error> {
+        let name = OpWire::Ext.to_string();
+        let arg_width = width(&name, &arg.ty)?;
+        if hi < lo || hi >= arg_width {
+            let msg = format!("ext range [{}, {}] is out of bounds for {}", lo, hi, arg.ty);
+            return builder_error(&name, &msg);
+        }
+        let ty = resize(&arg.ty, hi - lo + 1);
+        self.wire(OpWire::Ext, &ty, &[lo as i64, hi as i64], &[arg])
+    }
+    // the first value is the least significant part
+    pub fn cat(&mut self, arg: &[&Value]) -> Result<Value, Error> {
+        let name = OpWire::Cat.to_string();
+        if arg.is_empty() {
+            return builder_error(&name, "cat expects at least one argument");
+        }
+        let mut sum = 0;
+        for value in arg {
+            if value.ty.length() != arg[0].ty.length() {
+                let msg = format!("cat of {} can not produce {}", value.ty, arg[0].ty);
+                return builder_error(&name, &msg);
+            }
+            sum += width(&name, &value.ty)?;
+        }
+        let ty = resize(&arg[0].ty, sum);
+        self.wire(OpWire::Cat, &ty, &[], arg)
+    }
+    pub fn add(&mut self, lhs: &Value, rhs: &Value) -> Result<Value, Error> {
+        self.binop(OpPrim::Add, lhs, rhs)
+    }
+    pub fn sub(&mut self, lhs: &Value, rhs: &Value) -> Result<Value, Error> {
+        self.binop(OpPrim::Sub, lhs, rhs)
+    }
+    pub fn mul(&mut self, lhs: &Value, rhs: &Value) -> Result<Value, Error> {
+        self.binop(OpPrim::Mul, lhs, rhs)
+    }
+    pub fn and(&mut self, lhs: &Value, rhs: &Value) -> Result<Value, Error> {
+        self.binop(OpPrim::And, lhs, rhs)
+    }
+    pub fn or(&mut self, lhs: &Value, rhs: &Value) -> Result<Value, Error> {
+        self.binop(OpPrim::Or, lhs, rhs)
+    }
+    pub fn xor(&mut self, lhs: &Value, rhs: &Value) -> Result<Value, Error> {
+        self.binop(OpPrim::Xor, lhs, rhs)
+    }
+    pub fn not(&mut self, arg: &Value) -> Result<Value, Error> {
+        self.prim(OpPrim::Not, &arg.ty.clone(), &[], &[arg])
+    }
+    pub fn eq(&mut self, lhs: &Value, rhs: &Value) -> Result<Value, Error> {
+        self.cmpop(OpPrim::Eql, lhs, rhs)
+    }
+    pub fn neq(&mut self, lhs: &Value, rhs: &Value) -> Result<Value, Error> {
+        self.cmpop(OpPrim::Neql, lhs, rhs)
+    }
+    pub fn gt(&mut self, lhs: &Value, rhs: &Value) -> Result<Value, Error> {
+        self.cmpop(OpPrim::Gt, lhs, rhs)
+    }
+    pub fn lt(&mut self, lhs: &Value, rhs: &Value) -> Result<Value, Error> {
+        self.cmpop(OpPrim::Lt, lhs, rhs)
+    }
+    pub fn ge(&mut self, lhs: &Value, rhs: &Value) -> Result<Value, Error> {
+        self.cmpop(OpPrim::Ge, lhs, rhs)
+    }
+    pub fn le(&mut self, lhs: &Value, rhs: &Value) -> Result<Value, Error> {
+        self.cmpop(OpPrim::Le, lhs, rhs)
+    }
+    pub fn mux(&mut self, cond: &Value, tru: &Value, fal: &Value) -> Result<Value, Error> {
+        let name = OpPrim::Mux.to_string();
+        self.check_bool(&name, cond)?;
+        self.check_same(&name, &tru.ty, &[fal])?;
+        self.prim(OpPrim::Mux, &tru.ty.clone(), &[], &[cond, tru, fal])
+    }
+    pub fn reg(&mut self, arg: &Value, en: &Value, init: i64) -> Result<Value, Error> {
+        let name = OpPrim::Reg.to_string();
+        self.check_bool(&name, en)?;
+        check_fit(&name, &arg.ty, init)?;
+        self.prim(OpPrim::Reg, &arg.ty.clone(), &[init], &[arg, en])
+    }
+    // registers that feed back into their own input are declared first and
+    // connected once the next value is known
+    pub fn reg_decl(&mut self, ty: Ty, init: i64) -> Result<Value, Error> {
+        check_fit(&OpPrim::Reg.to_string(), &ty, init)?;
+        let dst = self.fresh(&ty)?;
+        self.body.push(Instr::from(InstrPrim {
+            op: OpPrim::Reg,
+            dst: Expr::from(dst.term()),
+            attr: attr(&[init]),
+            arg: Expr::default(),
+            prim: Prim::Any,
+            span: Span::default(),
+        }));
+        Ok(dst)
+    }
+    pub fn reg_connect(&mut self, reg: &Value, arg: &Value, en: &Value) -> Result<(), Error> {
+        let name = OpPrim::Reg.to_string();
+        self.check_arg(&name, &[reg, arg, en])?;
+        self.check_same(&name, &reg.ty, &[arg])?;
+        self.check_bool(&name, en)?;
+        for instr in self.body.iter_mut() {
+            if let Instr::Prim(p) = instr {
+                if p.is_reg()
+                    && p.dst().get_id(0)? == reg.id
+                    && p.arg().tup().map_or(false, |t| t.is_empty())
+                {
+                    p.set_arg(tup(&[arg, en]));
+                    return Ok(());
+                }
+            }
+        }
+        builder_error(&name, &format!("{} is not an unconnected reg", reg.id))
+    }
+    pub fn rom(&mut self, addr: &Value, ty: Ty) -> Result<Value, Error> {
+        width(&OpPrim::Rom.to_string(), &addr.ty)?;
+        self.prim(OpPrim::Rom, &ty, &[], &[addr])
+    }
+    pub fn ram(&mut self, addr: &Value, data: &Value, en: &Value) -> Result<Value, Error> {
+        width(&OpPrim::Ram.to_string(), &addr.ty)?;
+        self.check_bool(&OpPrim::Ram.to_string(), en)?;
+        self.prim(OpPrim::Ram, &data.ty.clone(), &[], &[addr, data, en])
+    }
+    // the callee signature gives the types of the results
+    pub fn call(&mut self, def: &Def, arg: &[&Value]) -> Result<Vec<Value>, Error> {
+        let name = def.id();
+        if def.is_param() {
+            return builder_error(&name, &format!("def {} must be specialized", name));
+        }
+        self.check_arg(&name, arg)?;
+        let input: Vec<ExprTerm> = def.input().clone().into();
+        let output: Vec<ExprTerm> = def.output().clone().into();
+        if input.len() != arg.len() {
+            let msg = format!(
+                "{} expects {} arguments but got {}",
+                name,
+                input.len(),
+                arg.len()
+            );
+            return builder_error(&name, &msg);
+        }
+        for (term, value) in input.iter().zip(arg.iter()) {
+            self.check_same(&name, term.get_ty()?, &[value])?;
+        }
+        let mut dst: Vec<Value> = Vec::new();
+        for term in &output {
+            dst.push(self.fresh(term.get_ty()?)?);
+        }
+        let dst_expr = match dst.as_slice() {
+            [value] => Expr::from(value.term()),
+            _ => tup(&dst.iter().collect::<Vec<&Value>>()),
+        };
+        self.body.push(Instr::from(InstrCall {
+            op: OpCall::new(&name),
+            dst: dst_expr,
+            arg: tup(arg),
+            span: Span::default(),
+        }));
+        Ok(dst)
+    }
+    pub fn build(self) -> Result<Def, Error> {
+        for instr in &self.body {
+            if instr.is_reg() && instr.arg().tup().map_or(false, |t| t.is_empty()) {
+                let id = instr.dst().get_id(0)?;
+                return builder_error(&id, &format!("reg {} is never connected", id));
+            }
+        }
+        let mut def = Def::default();
+        def.set_id(&self.id);
+        def.sig.input = Expr::from(ExprTup::from(self.input));
+        def.sig.output = Expr::from(ExprTup::from(self.output));
+        def.set_body(self.body);
+        Ok(def)
+    }
+}
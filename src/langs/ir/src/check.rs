@@ -0,0 +1,323 @@
+use crate::ast::*;
+use crate::errors::Error;
+use std::collections::HashMap;
+use std::slice;
+
+type Env = HashMap<Id, Ty>;
+
+fn type_error<T>(instr: &Instr, msg: &str) -> Result<T, Error> {
+    let err = format!("{}, in instr: {}", msg, instr);
+    Err(Error::new_type_error(&instr.span().render(&err)))
+}
+
+fn insert_term(env: &mut Env, term: &ExprTerm, def: &str) -> Result<(), Error> {
+    let id = term.get_id()?;
+    let ty = term.get_ty()?;
+    if ty == &Ty::Any {
+        let err = format!("{} in def {} does not have a type", id, def);
+        Err(Error::new_type_error(&err))
+    } else if env.insert(id.to_string(), ty.clone()).is_some() {
+        let err = format!("{} in def {} is assigned more than once", id, def);
+        Err(Error::new_type_error(&err))
+    } else {
+        Ok(())
+    }
+}
+
+fn build_env(def: &Def) -> Result<Env, Error> {
+    let mut env = Env::new();
+    let input: Vec<ExprTerm> = def.input().clone().into();
+    for term in input {
+        insert_term(&mut env, &term, &def.id())?;
+    }
+    for instr in def.body() {
+        let dst: Vec<ExprTerm> = instr.dst().clone().into();
+        for term in dst {
+            insert_term(&mut env, &term, &def.id())?;
+        }
+    }
+    Ok(env)
+}
+
+fn arg_types(env: &Env, instr: &Instr) -> Result<Vec<Ty>, Error> {
+    let arg: Vec<ExprTerm> = instr.arg().clone().into();
+    let mut ty: Vec<Ty> = Vec::new();
+    for term in arg {
+        match term.id() {
+            Some(id) if env.contains_key(&id) => ty.push(env[&id].clone()),
+            Some(id) => return type_error(instr, &format!("{} is not defined", id)),
+            None => return type_error(instr, "arguments must be variables"),
+        }
+    }
+    Ok(ty)
+}
+
+fn attr_values(instr: &Instr, attr: &Expr) -> Result<Vec<i64>, Error> {
+    let attr: Vec<ExprTerm> = attr.clone().into();
+    let mut val: Vec<i64> = Vec::new();
+    for term in attr {
+        if let Some(v) = term.val() {
+            val.push(v);
+        } else {
+            return type_error(instr, "attributes must be values");
+        }
+    }
+    Ok(val)
+}
+
+fn check_arity(instr: &Instr, name: &str, arg: &[Ty], num: usize) -> Result<(), Error> {
+    if arg.len() == num {
+        Ok(())
+    } else {
+        let msg = format!("{} expects {} arguments but got {}", name, num, arg.len());
+        type_error(instr, &msg)
+    }
+}
+
+fn check_attr(instr: &Instr, name: &str, attr: &[i64], num: &[usize]) -> Result<(), Error> {
+    if num.contains(&attr.len()) {
+        Ok(())
+    } else {
+        let msg = format!("{} has wrong number of attributes", name);
+        type_error(instr, &msg)
+    }
+}
+
+fn check_same(instr: &Instr, name: &str, exp: &Ty, arg: &[Ty]) -> Result<(), Error> {
+    for ty in arg {
+        if ty != exp {
+            let msg = format!("{} expects {} arguments but got {}", name, exp, ty);
+            return type_error(instr, &msg);
+        }
+    }
+    Ok(())
+}
+
+fn check_bool(instr: &Instr, name: &str, ty: &Ty) -> Result<(), Error> {
+    if ty == &Ty::Bool {
+        Ok(())
+    } else {
+        let msg = format!("{} expects bool but got {}", name, ty);
+        type_error(instr, &msg)
+    }
+}
+
+// the clock of reg and ram is optional, it must be the last argument
+fn check_clock(instr: &Instr, name: &str, arg: &[Ty], num: usize) -> Result<(), Error> {
+    if arg.len() == num + 1 && arg[num].is_clock() {
+        check_arity(instr, name, &arg[..num], num)
+    } else if arg.len() == num + 1 {
+        let msg = format!("{} expects clock but got {}", name, arg[num]);
+        type_error(instr, &msg)
+    } else if let Some(ty) = arg.iter().find(|ty| ty.is_clock()) {
+        let msg = format!("{} of {} can only be used as a clock", name, ty);
+        type_error(instr, &msg)
+    } else {
+        check_arity(instr, name, arg, num)
+    }
+}
+
+fn check_length(instr: &Instr, name: &str, dst: &Ty, arg: &Ty) -> Result<(), Error> {
+    if dst.length() == arg.length() {
+        Ok(())
+    } else {
+        let msg = format!("{} of {} can not produce {}", name, arg, dst);
+        type_error(instr, &msg)
+    }
+}
+
+fn width(instr: &Instr, ty: &Ty) -> Result<u64, Error> {
+    if let Some(width) = ty.width() {
+        Ok(width)
+    } else {
+        type_error(instr, &format!("{} does not have a width", ty))
+    }
+}
+
+fn check_instr_prim(instr: &Instr, prim: &InstrPrim, env: &Env) -> Result<(), Error> {
+    let name = prim.op().to_string();
+    let dst = instr.dst().get_ty(0)?;
+    let arg = arg_types(env, instr)?;
+    let attr = attr_values(instr, prim.attr())?;
+    let clock = dst.is_clock() || arg.iter().any(|ty| ty.is_clock());
+    if clock && !matches!(prim.op(), OpPrim::Reg | OpPrim::Rom | OpPrim::Ram) {
+        return type_error(instr, &format!("{} can not use clocks", name));
+    }
+    match prim.op() {
+        OpPrim::Add | OpPrim::Sub | OpPrim::Mul | OpPrim::And | OpPrim::Or | OpPrim::Xor => {
+            check_arity(instr, &name, &arg, 2)?;
+            check_attr(instr, &name, &attr, &[0])?;
+            check_same(instr, &name, dst, &arg)
+        }
+        OpPrim::Not => {
+            check_arity(instr, &name, &arg, 1)?;
+            check_attr(instr, &name, &attr, &[0])?;
+            check_same(instr, &name, dst, &arg)
+        }
+        OpPrim::Eql | OpPrim::Neql | OpPrim::Gt | OpPrim::Lt | OpPrim::Ge | OpPrim::Le => {
+            check_arity(instr, &name, &arg, 2)?;
+            check_attr(instr, &name, &attr, &[0])?;
+            check_same(instr, &name, &arg[0], &arg[1..])?;
+            check_bool(instr, &name, dst)
+        }
+        OpPrim::Mux => {
+            check_arity(instr, &name, &arg, 3)?;
+            check_attr(instr, &name, &attr, &[0])?;
+            check_bool(instr, &name, &arg[0])?;
+            check_same(instr, &name, dst, &arg[1..])
+        }
+        OpPrim::Reg => {
+            check_clock(instr, &name, &arg, 2)?;
+            check_attr(instr, &name, &attr, &[0, 1, 2])?;
+            check_same(instr, &name, dst, &arg[..1])?;
+            check_bool(instr, &name, &arg[1])
+        }
+        OpPrim::Rom => {
+            check_clock(instr, &name, &arg, 1)?;
+            if arg.len() > 1 && !prim.is_sync_read() {
+                return type_error(instr, "rom can only use a clock when placed with @bram");
+            }
+            check_attr(instr, &name, &attr, &[0])?;
+            width(instr, &arg[0]).map(|_| ())
+        }
+        OpPrim::Ram => {
+            check_clock(instr, &name, &arg, 3)?;
+            check_attr(instr, &name, &attr, &[0])?;
+            width(instr, &arg[0])?;
+            check_same(instr, &name, dst, &arg[1..2])?;
+            check_bool(instr, &name, &arg[2])
+        }
+    }
+}
+
+fn check_instr_wire(instr: &Instr, wire: &InstrWire, env: &Env) -> Result<(), Error> {
+    let name = wire.op().to_string();
+    let dst = instr.dst().get_ty(0)?;
+    let arg = arg_types(env, instr)?;
+    let attr = attr_values(instr, wire.attr())?;
+    let clock = dst.is_clock() || arg.iter().any(|ty| ty.is_clock());
+    if clock && wire.op() != &OpWire::Id {
+        return type_error(instr, &format!("{} can not use clocks", name));
+    }
+    match wire.op() {
+        OpWire::Id => {
+            check_arity(instr, &name, &arg, 1)?;
+            check_attr(instr, &name, &attr, &[0])?;
+            check_same(instr, &name, dst, &arg)
+        }
+        OpWire::Con => {
+            check_arity(instr, &name, &arg, 0)?;
+            let length = dst.length().unwrap_or(1) as usize;
+            check_attr(instr, &name, &attr, &[1, length])
+        }
+        OpWire::Sll | OpWire::Srl | OpWire::Sra => {
+            check_arity(instr, &name, &arg, 1)?;
+            check_attr(instr, &name, &attr, &[1])?;
+            check_same(instr, &name, dst, &arg)?;
+            if attr[0] < 0 {
+                type_error(instr, "shift amount must be positive")
+            } else {
+                Ok(())
+            }
+        }
+        OpWire::Ext => {
+            check_arity(instr, &name, &arg, 1)?;
+            check_attr(instr, &name, &attr, &[1, 2])?;
+            check_length(instr, &name, dst, &arg[0])?;
+            let lo = attr[0];
+            let hi = *attr.last().unwrap();
+            let arg_width = width(instr, &arg[0])? as i64;
+            let dst_width = width(instr, dst)? as i64;
+            if lo < 0 || hi < lo || hi >= arg_width {
+                let msg = format!("ext range [{}, {}] is out of bounds for {}", lo, hi, arg[0]);
+                type_error(instr, &msg)
+            } else if dst_width != hi - lo + 1 {
+                let msg = format!("ext range [{}, {}] can not produce {}", lo, hi, dst);
+                type_error(instr, &msg)
+            } else {
+                Ok(())
+            }
+        }
+        OpWire::Cat => {
+            if arg.is_empty() {
+                return type_error(instr, "cat expects at least one argument");
+            }
+            check_attr(instr, &name, &attr, &[0])?;
+            let mut sum = 0;
+            for ty in &arg {
+                check_length(instr, &name, dst, ty)?;
+                sum += width(instr, ty)?;
+            }
+            if sum == width(instr, dst)? {
+                Ok(())
+            } else {
+                let msg = format!("cat of {} bits can not produce {}", sum, dst);
+                type_error(instr, &msg)
+            }
+        }
+    }
+}
+
+fn check_instr_call(instr: &Instr, call: &InstrCall, env: &Env, prog: &Prog) -> Result<(), Error> {
+    let arg = arg_types(env, instr)?;
+    let callee = if let Some(def) = prog.get(&call.op().op()) {
+        def
+    } else {
+        let msg = format!("def {} is not defined", call.op().op());
+        return type_error(instr, &msg);
+    };
+    if callee.is_param() {
+        let msg = format!("def {} must be specialized before checking", callee.id());
+        return type_error(instr, &msg);
+    }
+    let input: Vec<ExprTerm> = callee.input().clone().into();
+    let output: Vec<ExprTerm> = callee.output().clone().into();
+    let dst: Vec<ExprTerm> = call.dst().clone().into();
+    if input.len() != arg.len() || output.len() != dst.len() {
+        let msg = format!("call does not match signature of def {}", callee.id());
+        return type_error(instr, &msg);
+    }
+    for (term, ty) in input.iter().zip(arg.iter()) {
+        check_same(instr, &callee.id(), term.get_ty()?, slice::from_ref(ty))?;
+    }
+    for (term, d) in output.iter().zip(dst.iter()) {
+        let ty = d.get_ty()?;
+        check_same(instr, &callee.id(), term.get_ty()?, slice::from_ref(ty))?;
+    }
+    Ok(())
+}
+
+pub fn type_check_def(def: &Def, prog: &Prog) -> Result<(), Error> {
+    let env = build_env(def)?;
+    for instr in def.body() {
+        match instr {
+            Instr::Prim(prim) => check_instr_prim(instr, prim, &env)?,
+            Instr::Wire(wire) => check_instr_wire(instr, wire, &env)?,
+            Instr::Call(call) => check_instr_call(instr, call, &env, prog)?,
+        }
+    }
+    let output: Vec<ExprTerm> = def.output().clone().into();
+    for term in output {
+        let id = term.get_id()?;
+        match env.get(&id) {
+            Some(ty) if ty == term.get_ty()? => (),
+            Some(ty) => {
+                let err = format!("output {} of def {} is {}", id, def.id(), ty);
+                return Err(Error::new_type_error(&def.sig().span().render(&err)));
+            }
+            None => {
+                let err = format!("output {} of def {} is not defined", id, def.id());
+                return Err(Error::new_type_error(&def.sig().span().render(&err)));
+            }
+        }
+    }
+    Ok(())
+}
+
+// parametric defs are checked once they are specialized
+pub fn type_check_prog(prog: &Prog) -> Result<(), Error> {
+    for def in prog.def().values().filter(|d| !d.is_param()) {
+        type_check_def(def, prog)?;
+    }
+    Ok(())
+}
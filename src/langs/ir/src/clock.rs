@@ -0,0 +1,220 @@
+use crate::ast::*;
+use crate::errors::Error;
+use crate::inline::inline_try_from_prog;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+// registers and memories without a clock argument belong to this domain
+pub const DEFAULT_CLOCK: &str = "clock";
+
+pub(crate) type Domain = BTreeSet<Id>;
+
+fn is_clocked(instr: &Instr) -> Option<&InstrPrim> {
+    match instr {
+        Instr::Prim(prim) if matches!(prim.op(), OpPrim::Reg | OpPrim::Ram) => Some(prim),
+        Instr::Prim(prim) if prim.is_sync_read() => Some(prim),
+        _ => None,
+    }
+}
+
+// clocks can be renamed with id, map every name to the clock it refers to
+fn alias_map_from_def(def: &Def) -> Result<HashMap<Id, Id>, Error> {
+    let mut map: HashMap<Id, Id> = HashMap::new();
+    for instr in def.body() {
+        if let Instr::Wire(wire) = instr {
+            if wire.op() == &OpWire::Id && instr.dst().get_ty(0)?.is_clock() {
+                map.insert(instr.dst().get_id(0)?, instr.arg().get_id(0)?);
+            }
+        }
+    }
+    let mut res: HashMap<Id, Id> = HashMap::new();
+    for id in map.keys() {
+        let mut root = id;
+        let mut hops = 0;
+        while let Some(next) = map.get(root) {
+            root = next;
+            hops += 1;
+            if hops > map.len() {
+                let err = format!(
+                    "clock {} in def {} is defined in terms of itself",
+                    id,
+                    def.id()
+                );
+                return Err(Error::new_cdc_error(&err));
+            }
+        }
+        res.insert(id.to_string(), root.to_string());
+    }
+    Ok(res)
+}
+
+// map every register and memory to the clock of its domain
+pub fn domain_map_from_def(def: &Def) -> Result<HashMap<Id, Id>, Error> {
+    let alias = alias_map_from_def(def)?;
+    let mut map: HashMap<Id, Id> = HashMap::new();
+    for instr in def.body() {
+        if let Some(prim) = is_clocked(instr) {
+            let clock = match prim.clock() {
+                Some(id) => alias.get(&id).cloned().unwrap_or(id),
+                None => DEFAULT_CLOCK.to_string(),
+            };
+            map.insert(instr.dst().get_id(0)?, clock);
+        }
+    }
+    Ok(map)
+}
+
+// remove clock arguments and clock renames, for passes that assume a single clock
+pub fn strip_clocks_from_def(def: &Def) -> Result<Def, Error> {
+    let mut body: Vec<Instr> = Vec::new();
+    for instr in def.body() {
+        if instr.is_wire() && instr.dst().get_ty(0)?.is_clock() {
+            continue;
+        }
+        let mut instr = instr.clone();
+        if is_clocked(&instr).map_or(false, |prim| prim.clock().is_some()) {
+            let mut term: Vec<ExprTerm> = instr.arg().clone().into();
+            term.pop();
+            let mut arg = ExprTup::default();
+            for t in term {
+                arg.add_term(t);
+            }
+            instr.set_arg(Expr::from(arg));
+        }
+        body.push(instr);
+    }
+    let mut res = def.clone();
+    res.set_body(body);
+    Ok(res)
+}
+
+fn domain_of(dom: &HashMap<Id, Domain>, term: &ExprTerm) -> Domain {
+    match term.id() {
+        Some(id) => dom.get(&id).cloned().unwrap_or_default(),
+        None => Domain::new(),
+    }
+}
+
+// domains driving every value, inputs, constants and clocks do not belong to any domain
+pub(crate) fn domain_env_from_def(
+    def: &Def,
+    clock: &HashMap<Id, Id>,
+) -> Result<HashMap<Id, Domain>, Error> {
+    let mut dom: HashMap<Id, Domain> = HashMap::new();
+    for (id, clk) in clock {
+        dom.insert(id.to_string(), [clk.to_string()].iter().cloned().collect());
+    }
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for instr in def.body() {
+            let arg: Vec<ExprTerm> = instr.arg().clone().into();
+            let mut src = Domain::new();
+            match is_clocked(instr) {
+                // reads are addressed asynchronously, writes are part of the memory domain.
+                // Block ram reads go through a register in the memory domain
+                Some(prim) if prim.op() == &OpPrim::Ram && !prim.is_sync_read() => {
+                    src.extend(domain_of(&dom, &arg[0]))
+                }
+                Some(_) => (),
+                None => {
+                    for term in &arg {
+                        src.extend(domain_of(&dom, term));
+                    }
+                }
+            }
+            let dst: Vec<ExprTerm> = instr.dst().clone().into();
+            for term in dst {
+                let entry = dom.entry(term.get_id()?).or_default();
+                let len = entry.len();
+                entry.extend(src.iter().cloned());
+                changed |= entry.len() != len;
+            }
+        }
+    }
+    Ok(dom)
+}
+
+fn cdc_error(instr: &Instr, src: &Domain, dst: &str) -> Result<(), Error> {
+    let src: Vec<Id> = src.iter().cloned().collect();
+    let err = format!(
+        "unsynchronized crossing from {} to {} at {}, in instr: {}",
+        src.join(", "),
+        dst,
+        instr.dst().get_id(0)?,
+        instr
+    );
+    Err(Error::new_cdc_error(&instr.span().render(&err)))
+}
+
+// the first register of a synchronizer samples a register from another domain
+// and only feeds registers in its own domain
+fn is_sync_stage(
+    instr: &Instr,
+    clock: &HashMap<Id, Id>,
+    dom: &HashMap<Id, Domain>,
+    user: &HashMap<Id, Vec<&Instr>>,
+    reg: &HashSet<Id>,
+) -> Result<bool, Error> {
+    let id = instr.dst().get_id(0)?;
+    let own = &clock[&id];
+    let data = instr.arg().get_term(0)?;
+    let en = instr.arg().get_term(1)?;
+    let from_reg = match data.id() {
+        Some(d) => reg.contains(&d) && clock[&d] != *own && instr.dst().get_ty(0)? == &Ty::Bool,
+        None => false,
+    };
+    if !from_reg || domain_of(dom, en).iter().any(|c| c != own) {
+        return Ok(false);
+    }
+    for u in user.get(&id).into_iter().flatten() {
+        if !u.is_reg() || clock[&u.dst().get_id(0)?] != *own {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// registers and memories can only sample values from their own domain, except
+// through two-register synchronizers on single bits
+pub fn cdc_check_def(def: &Def) -> Result<(), Error> {
+    let clock = domain_map_from_def(def)?;
+    let dom = domain_env_from_def(def, &clock)?;
+    let mut user: HashMap<Id, Vec<&Instr>> = HashMap::new();
+    let mut reg: HashSet<Id> = HashSet::new();
+    for instr in def.body() {
+        if instr.is_reg() {
+            reg.insert(instr.dst().get_id(0)?);
+        }
+        let arg: Vec<ExprTerm> = instr.arg().clone().into();
+        for term in arg {
+            if let Some(id) = term.id() {
+                user.entry(id).or_default().push(instr);
+            }
+        }
+    }
+    for instr in def.body() {
+        if is_clocked(instr).is_some() {
+            let id = instr.dst().get_id(0)?;
+            let own = &clock[&id];
+            let arg: Vec<ExprTerm> = instr.arg().clone().into();
+            let mut src = Domain::new();
+            for term in &arg {
+                src.extend(domain_of(&dom, term));
+            }
+            src.remove(own);
+            let synced = instr.is_reg() && is_sync_stage(instr, &clock, &dom, &user, &reg)?;
+            if !src.is_empty() && !synced {
+                return cdc_error(instr, &src, own);
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn cdc_check_prog(prog: &Prog) -> Result<(), Error> {
+    let prog = inline_try_from_prog(prog)?;
+    for def in prog.def().values() {
+        cdc_check_def(def)?;
+    }
+    Ok(())
+}
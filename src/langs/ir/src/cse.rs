@@ -0,0 +1,106 @@
+use crate::ast::*;
+use crate::errors::Error;
+use std::collections::{HashMap, HashSet};
+
+fn is_commutative(op: &OpPrim) -> bool {
+    matches!(
+        op,
+        OpPrim::Add
+            | OpPrim::Mul
+            | OpPrim::And
+            | OpPrim::Or
+            | OpPrim::Xor
+            | OpPrim::Eql
+            | OpPrim::Neql
+    )
+}
+
+// memories are not merged because their content is given per destination,
+// and calls are left to inlining
+fn is_mergeable(instr: &Instr) -> bool {
+    match instr {
+        Instr::Wire(_) => true,
+        Instr::Prim(prim) => !matches!(prim.op(), OpPrim::Rom | OpPrim::Ram),
+        Instr::Call(_) => false,
+    }
+}
+
+// an instruction without its destination name, arguments of commutative
+// operations are sorted so add(a, b) and add(b, a) have the same key
+fn key_try_from_instr(instr: &Instr) -> Result<Instr, Error> {
+    let mut key = instr.clone();
+    let ty = instr.dst().get_ty(0)?.clone();
+    key.set_dst(Expr::from(ExprTerm::Var(String::new(), ty)));
+    if let Instr::Prim(prim) = &mut key {
+        if is_commutative(prim.op()) {
+            let mut arg: Vec<ExprTerm> = prim.arg().clone().into();
+            arg.sort_by_key(|t| t.id());
+            prim.set_arg(Expr::from(ExprTup::from(arg)));
+        }
+    }
+    Ok(key)
+}
+
+// one round of merging, returns the number of removed instructions
+fn merge(def: &Def, output: &HashSet<Id>) -> Result<(Def, usize), Error> {
+    let mut first: HashMap<Instr, Id> = HashMap::new();
+    let mut rename: HashMap<Id, Id> = HashMap::new();
+    for instr in def.body().iter().filter(|i| is_mergeable(i)) {
+        let id = instr.dst().get_id(0)?;
+        let key = key_try_from_instr(instr)?;
+        match first.get(&key) {
+            Some(f) if !output.contains(&id) => {
+                rename.insert(id, f.to_string());
+            }
+            Some(_) => (),
+            None => {
+                first.insert(key, id);
+            }
+        }
+    }
+    let mut body: Vec<Instr> = Vec::new();
+    for instr in def.body() {
+        if !rename.contains_key(&instr.dst().get_id(0)?) {
+            let mut instr = instr.clone();
+            instr.rename_arg(&rename);
+            body.push(instr);
+        }
+    }
+    let mut res = def.clone();
+    res.set_body(body);
+    Ok((res, rename.len()))
+}
+
+// merge instructions with the same operation, attributes, arguments and type.
+// Merged values can have more than one use, so they become tree roots in
+// instruction selection instead of being duplicated in every tree
+pub fn cse_try_from_def(def: &Def) -> Result<(Def, usize), Error> {
+    let output: Vec<ExprTerm> = def.output().clone().into();
+    let mut output_set: HashSet<Id> = HashSet::new();
+    for term in output {
+        output_set.insert(term.get_id()?);
+    }
+    let mut res = def.clone();
+    let mut removed = 0;
+    // renaming arguments can make more instructions identical
+    loop {
+        let (def, num) = merge(&res, &output_set)?;
+        res = def;
+        removed += num;
+        if num == 0 {
+            break;
+        }
+    }
+    Ok((res, removed))
+}
+
+pub fn cse_try_from_prog(prog: &Prog) -> Result<(Prog, usize), Error> {
+    let mut res = Prog::default();
+    let mut removed = 0;
+    for (name, def) in prog.def() {
+        let (def, num) = cse_try_from_def(def)?;
+        res.insert(name, def);
+        removed += num;
+    }
+    Ok((res, removed))
+}
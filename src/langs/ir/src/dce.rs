@@ -0,0 +1,53 @@
+use crate::ast::*;
+use crate::errors::Error;
+use std::collections::{HashMap, HashSet};
+
+// remove instructions that do not reach an output, registers and memories
+// are live only if their value is used
+pub fn dce_try_from_def(def: &Def) -> Result<Def, Error> {
+    let mut map: HashMap<Id, &Instr> = HashMap::new();
+    for instr in def.body() {
+        let dst: Vec<ExprTerm> = instr.dst().clone().into();
+        for term in dst {
+            map.insert(term.get_id()?, instr);
+        }
+    }
+    let mut live: HashSet<Id> = HashSet::new();
+    let mut stack: Vec<Id> = Vec::new();
+    let output: Vec<ExprTerm> = def.output().clone().into();
+    for term in output {
+        stack.push(term.get_id()?);
+    }
+    while let Some(id) = stack.pop() {
+        if !live.insert(id.to_string()) {
+            continue;
+        }
+        if let Some(instr) = map.get(&id) {
+            let dst: Vec<ExprTerm> = instr.dst().clone().into();
+            let arg: Vec<ExprTerm> = instr.arg().clone().into();
+            for term in dst.iter().chain(arg.iter()) {
+                let id = term.get_id()?;
+                if !live.contains(&id) {
+                    stack.push(id);
+                }
+            }
+        }
+    }
+    let mut body: Vec<Instr> = Vec::new();
+    for instr in def.body() {
+        if live.contains(&instr.dst().get_id(0)?) {
+            body.push(instr.clone());
+        }
+    }
+    let mut res = def.clone();
+    res.set_body(body);
+    Ok(res)
+}
+
+pub fn dce_try_from_prog(prog: &Prog) -> Result<Prog, Error> {
+    let mut res = Prog::default();
+    for (name, def) in prog.def() {
+        res.insert(name, dce_try_from_def(def)?);
+    }
+    Ok(res)
+}
@@ -6,6 +6,7 @@ impl Default for Sig {
             id: String::new(),
             input: Expr::Tup(ExprTup::default()),
             output: Expr::Tup(ExprTup::default()),
+            span: Span::default(),
         }
     }
 }
@@ -0,0 +1,135 @@
+use crate::ast::*;
+use crate::errors::Error;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Dfg {
+    // instr index where every variable is assigned
+    dst: HashMap<Id, usize>,
+    // instr indices every instr depends on combinationally
+    pred: Vec<BTreeSet<usize>>,
+    succ: Vec<BTreeSet<usize>>,
+}
+
+// registers and block ram reads break combinational paths, only the read
+// address of a ram without output register does not
+pub(crate) fn comb_arg(instr: &Instr) -> Vec<ExprTerm> {
+    let arg: Vec<ExprTerm> = instr.arg().clone().into();
+    match instr {
+        Instr::Prim(prim) if prim.is_reg() => Vec::new(),
+        Instr::Prim(prim) if prim.is_sync_read() => Vec::new(),
+        Instr::Prim(prim) if prim.op() == &OpPrim::Ram => arg.into_iter().take(1).collect(),
+        _ => arg,
+    }
+}
+
+impl Dfg {
+    pub fn pred(&self, index: usize) -> Option<&BTreeSet<usize>> {
+        self.pred.get(index)
+    }
+    pub fn succ(&self, index: usize) -> Option<&BTreeSet<usize>> {
+        self.succ.get(index)
+    }
+    pub fn get_index(&self, id: &str) -> Option<usize> {
+        self.dst.get(id).copied()
+    }
+    pub fn len(&self) -> usize {
+        self.pred.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.pred.is_empty()
+    }
+    // find a cycle among the instrs that could not be ordered
+    fn find_loop(&self, rest: &BTreeSet<usize>) -> Vec<usize> {
+        let mut path: Vec<usize> = Vec::new();
+        let mut pos: HashMap<usize, usize> = HashMap::new();
+        let mut curr = rest.iter().next().copied();
+        while let Some(index) = curr {
+            if let Some(start) = pos.get(&index) {
+                let mut cycle = path.split_off(*start);
+                cycle.reverse();
+                // start reporting from the first instr in the body
+                if let Some(first) = (0..cycle.len()).min_by_key(|i| cycle[*i]) {
+                    cycle.rotate_left(first);
+                }
+                return cycle;
+            }
+            pos.insert(index, path.len());
+            path.push(index);
+            curr = self.pred[index].iter().find(|p| rest.contains(p)).copied();
+        }
+        path
+    }
+    // Kahn's algorithm, ties are broken by body position so the order is unique
+    fn order(&self) -> Result<Vec<usize>, Vec<usize>> {
+        let mut degree: Vec<usize> = self.pred.iter().map(|p| p.len()).collect();
+        let mut ready: BTreeSet<usize> = (0..self.len()).filter(|i| degree[*i] == 0).collect();
+        let mut order: Vec<usize> = Vec::new();
+        while let Some(index) = ready.iter().next().copied() {
+            ready.remove(&index);
+            order.push(index);
+            for s in &self.succ[index] {
+                degree[*s] -= 1;
+                if degree[*s] == 0 {
+                    ready.insert(*s);
+                }
+            }
+        }
+        if order.len() == self.len() {
+            Ok(order)
+        } else {
+            let done: HashSet<usize> = order.into_iter().collect();
+            let rest: BTreeSet<usize> = (0..self.len()).filter(|i| !done.contains(i)).collect();
+            Err(self.find_loop(&rest))
+        }
+    }
+}
+
+impl From<&Def> for Dfg {
+    fn from(def: &Def) -> Self {
+        let mut dfg = Dfg::default();
+        for (index, instr) in def.body().iter().enumerate() {
+            let dst: Vec<ExprTerm> = instr.dst().clone().into();
+            for term in dst {
+                if let Some(id) = term.id() {
+                    dfg.dst.insert(id, index);
+                }
+            }
+            dfg.pred.push(BTreeSet::new());
+            dfg.succ.push(BTreeSet::new());
+        }
+        for (index, instr) in def.body().iter().enumerate() {
+            for term in comb_arg(instr) {
+                if let Some(from) = term.id().and_then(|id| dfg.get_index(&id)) {
+                    dfg.pred[index].insert(from);
+                    dfg.succ[from].insert(index);
+                }
+            }
+        }
+        dfg
+    }
+}
+
+fn loop_error(def: &Def, index: &[usize]) -> Result<Error, Error> {
+    let mut id: Vec<Id> = Vec::new();
+    for i in index {
+        id.push(def.body()[*i].dst().get_id(0)?);
+    }
+    Ok(Error::new_loop_error(&def.id(), &id))
+}
+
+pub fn topo_order_try_from_def(def: &Def) -> Result<Vec<usize>, Error> {
+    let dfg = Dfg::from(def);
+    match dfg.order() {
+        Ok(order) => Ok(order),
+        Err(index) => Err(loop_error(def, &index)?),
+    }
+}
+
+pub fn comb_loop_from_def(def: &Def) -> Result<Option<Vec<Id>>, Error> {
+    match topo_order_try_from_def(def) {
+        Ok(_) => Ok(None),
+        Err(Error::Loop(_, id)) => Ok(Some(id)),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,117 @@
+use crate::ast::*;
+use std::collections::HashMap;
+
+// a node of a dataflow graph, names of the values it defines and uses and
+// the graphviz attributes used to draw it
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DotNode {
+    pub dst: Vec<Id>,
+    pub arg: Vec<Id>,
+    pub attr: String,
+}
+
+fn names(expr: &Expr) -> Vec<Id> {
+    let term: Vec<ExprTerm> = expr.clone().into();
+    term.iter().filter_map(|t| t.id()).collect()
+}
+
+impl DotNode {
+    pub fn new(dst: &Expr, arg: &Expr, attr: &str) -> Self {
+        DotNode {
+            dst: names(dst),
+            arg: names(arg),
+            attr: attr.to_string(),
+        }
+    }
+}
+
+pub fn dot_label(lines: &[String]) -> String {
+    let text: Vec<String> = lines.iter().map(|l| l.replace('"', "\\\"")).collect();
+    format!("label = \"{}\"", text.join("\\n"))
+}
+
+pub fn dot_color(prim: &Prim) -> &'static str {
+    match prim {
+        Prim::Any => "white",
+        Prim::Lut => "lightblue",
+        Prim::Dsp => "orange",
+        Prim::Lram => "palegreen",
+        Prim::Bram => "mediumseagreen",
+        Prim::Uram => "darkseagreen",
+    }
+}
+
+// every def is a cluster and node names are prefixed with the def name, so
+// clusters of a program can share one digraph
+pub fn dot_cluster(id: &str, input: &Expr, output: &Expr, node: &[DotNode]) -> String {
+    let mut dot = format!("subgraph \"cluster_{}\" {{\nlabel = \"{}\"\n", id, id);
+    let mut src: HashMap<Id, String> = HashMap::new();
+    for name in names(input) {
+        let key = format!("\"{}/in/{}\"", id, name);
+        dot.push_str(&format!(
+            "{} [ label = \"{}\", shape = ellipse ]\n",
+            key, name
+        ));
+        src.insert(name, key);
+    }
+    for (i, n) in node.iter().enumerate() {
+        let key = format!("\"{}/{}\"", id, i);
+        dot.push_str(&format!("{} [ {} ]\n", key, n.attr));
+        for name in &n.dst {
+            src.insert(name.to_string(), key.to_string());
+        }
+    }
+    for (i, n) in node.iter().enumerate() {
+        for name in &n.arg {
+            if let Some(from) = src.get(name) {
+                dot.push_str(&format!("{} -> \"{}/{}\" [ ]\n", from, id, i));
+            }
+        }
+    }
+    for name in names(output) {
+        let key = format!("\"{}/out/{}\"", id, name);
+        dot.push_str(&format!(
+            "{} [ label = \"{}\", shape = ellipse ]\n",
+            key, name
+        ));
+        if let Some(from) = src.get(&name) {
+            dot.push_str(&format!("{} -> {} [ ]\n", from, key));
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+pub fn dot_digraph(cluster: &[String]) -> String {
+    format!(
+        "digraph {{\nnode [ shape = box, style = filled, fillcolor = white ]\n{}}}",
+        cluster.concat()
+    )
+}
+
+// instructions are colored by their prim hint and registers get a double border
+pub fn dot_from_def(def: &Def) -> String {
+    let mut node: Vec<DotNode> = Vec::new();
+    for instr in def.body() {
+        let dst = instr.dst().to_string();
+        let (op, prim, reg) = match instr {
+            Instr::Wire(wire) => (wire.op().to_string(), Prim::Any, false),
+            Instr::Prim(prim) => (prim.op().to_string(), prim.prim().clone(), prim.is_reg()),
+            Instr::Call(call) => (format!("call {}", call.op()), Prim::Any, false),
+        };
+        let mut attr = dot_label(&[dst, op]);
+        attr.push_str(&format!(", fillcolor = {}", dot_color(&prim)));
+        if reg {
+            attr.push_str(", peripheries = 2");
+        }
+        node.push(DotNode::new(instr.dst(), instr.arg(), &attr));
+    }
+    dot_cluster(&def.id(), def.input(), def.output(), &node)
+}
+
+pub fn dot_from_prog(prog: &Prog) -> String {
+    let mut name: Vec<&Id> = prog.def().keys().collect();
+    name.sort();
+    let cluster: Vec<String> = name.iter().map(|n| dot_from_def(&prog.def()[*n])).collect();
+    dot_digraph(&cluster)
+}
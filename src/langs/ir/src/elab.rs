@@ -0,0 +1,338 @@
+use crate::ast::*;
+use crate::errors::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+pub type IndexEnv = HashMap<Id, i64>;
+
+// generate constructs as they are parsed, before elaboration
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Range {
+    pub var: Id,
+    pub lo: ExprTerm,
+    pub hi: ExprTerm,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GenTerm {
+    Term(ExprTerm),
+    For(Range, Vec<GenTerm>),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GenExpr {
+    Term(ExprTerm),
+    Tup(Vec<GenTerm>),
+}
+
+// instr dst and arg are kept apart, because they can be generated
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GenInstr {
+    Instr(Instr, GenExpr, GenExpr),
+    For(Range, Vec<GenInstr>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct GenDef {
+    pub id: Id,
+    pub param: Vec<Id>,
+    pub input: GenExpr,
+    pub output: GenExpr,
+    pub body: Vec<GenInstr>,
+    pub span: Span,
+}
+
+impl Default for GenExpr {
+    fn default() -> Self {
+        GenExpr::Tup(Vec::new())
+    }
+}
+
+fn index_error<T>(text: &str, msg: &str) -> Result<T, Error> {
+    let err = format!("{} in index expression {{{}}}", msg, text);
+    Err(Error::new_elab_error(&err))
+}
+
+// recursive descent over + - * / % and parentheses
+struct Index<'a> {
+    text: &'a str,
+    chars: Peekable<Chars<'a>>,
+    env: &'a IndexEnv,
+}
+
+impl<'a> Index<'a> {
+    fn new(text: &'a str, env: &'a IndexEnv) -> Self {
+        Index {
+            text,
+            chars: text.chars().peekable(),
+            env,
+        }
+    }
+    fn skip(&mut self) {
+        while let Some(c) = self.chars.peek() {
+            if c.is_whitespace() {
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+    }
+    fn peek(&mut self) -> Option<char> {
+        self.skip();
+        self.chars.peek().copied()
+    }
+    fn checked(&self, val: Option<i64>) -> Result<i64, Error> {
+        match val {
+            Some(val) => Ok(val),
+            None => index_error(self.text, "overflow"),
+        }
+    }
+    fn eval(mut self) -> Result<i64, Error> {
+        let val = self.sum()?;
+        match self.peek() {
+            None => Ok(val),
+            Some(c) => index_error(self.text, &format!("unexpected {}", c)),
+        }
+    }
+    fn sum(&mut self) -> Result<i64, Error> {
+        let mut val = self.product()?;
+        while let Some(op) = self.peek() {
+            if op != '+' && op != '-' {
+                break;
+            }
+            self.chars.next();
+            let rhs = self.product()?;
+            let res = if op == '+' {
+                val.checked_add(rhs)
+            } else {
+                val.checked_sub(rhs)
+            };
+            val = self.checked(res)?;
+        }
+        Ok(val)
+    }
+    fn product(&mut self) -> Result<i64, Error> {
+        let mut val = self.atom()?;
+        while let Some(op) = self.peek() {
+            if op != '*' && op != '/' && op != '%' {
+                break;
+            }
+            self.chars.next();
+            let rhs = self.atom()?;
+            let res = match op {
+                '*' => val.checked_mul(rhs),
+                _ if rhs == 0 => return index_error(self.text, "division by zero"),
+                '/' => val.checked_div(rhs),
+                _ => val.checked_rem(rhs),
+            };
+            val = self.checked(res)?;
+        }
+        Ok(val)
+    }
+    fn atom(&mut self) -> Result<i64, Error> {
+        match self.peek() {
+            Some('(') => {
+                self.chars.next();
+                let val = self.sum()?;
+                if self.peek() == Some(')') {
+                    self.chars.next();
+                    Ok(val)
+                } else {
+                    index_error(self.text, "missing )")
+                }
+            }
+            Some('-') => {
+                self.chars.next();
+                let val = self.atom()?;
+                self.checked(val.checked_neg())
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let mut num = String::new();
+                while let Some(c) = self.chars.peek().copied().filter(|c| c.is_ascii_digit()) {
+                    num.push(c);
+                    self.chars.next();
+                }
+                Ok(num.parse::<i64>()?)
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                let mut name = String::new();
+                while let Some(c) = self
+                    .chars
+                    .peek()
+                    .copied()
+                    .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+                {
+                    name.push(c);
+                    self.chars.next();
+                }
+                if let Some(val) = self.env.get(&name) {
+                    Ok(*val)
+                } else {
+                    index_error(self.text, &format!("unknown index variable {}", name))
+                }
+            }
+            Some(c) => index_error(self.text, &format!("unexpected {}", c)),
+            None => index_error(self.text, "missing operand"),
+        }
+    }
+}
+
+pub fn eval_index(text: &str, env: &IndexEnv) -> Result<i64, Error> {
+    Index::new(text, env).eval()
+}
+
+// replace every {expr} in a name with its value, i.e. t{3*i+1} is t4 when i is 1
+fn elab_name(name: &str, env: &IndexEnv) -> Result<Id, Error> {
+    let mut res = String::new();
+    let mut rest = name;
+    while let Some(start) = rest.find('{') {
+        res.push_str(&rest[..start]);
+        if let Some(len) = rest[start..].find('}') {
+            let val = eval_index(&rest[start + 1..start + len], env)?;
+            res.push_str(&val.to_string());
+            rest = &rest[start + len + 1..];
+        } else {
+            return index_error(rest, "missing }");
+        }
+    }
+    res.push_str(rest);
+    Ok(res)
+}
+
+fn elab_term(term: &ExprTerm, env: &IndexEnv) -> Result<ExprTerm, Error> {
+    match term {
+        ExprTerm::Var(id, ty) => Ok(ExprTerm::Var(elab_name(id, env)?, ty.clone())),
+        _ => Ok(term.clone()),
+    }
+}
+
+// attrs can be loop variables or index expressions, other names are def parameters
+fn elab_val(term: &ExprTerm, env: &IndexEnv) -> Result<ExprTerm, Error> {
+    match term {
+        ExprTerm::Var(id, _) if id.starts_with('{') && id.ends_with('}') => {
+            Ok(ExprTerm::Val(eval_index(&id[1..id.len() - 1], env)?))
+        }
+        ExprTerm::Var(id, _) if env.contains_key(id) => Ok(ExprTerm::Val(env[id])),
+        _ => elab_term(term, env),
+    }
+}
+
+pub fn elab_vals(expr: &Expr, env: &IndexEnv) -> Result<Expr, Error> {
+    let term: Vec<ExprTerm> = expr.clone().into();
+    let mut res: Vec<ExprTerm> = Vec::new();
+    for t in term {
+        res.push(elab_val(&t, env)?);
+    }
+    match expr {
+        Expr::Term(_) if res.len() == 1 => Ok(Expr::from(res[0].clone())),
+        _ => Ok(Expr::from(ExprTup::from(res))),
+    }
+}
+
+fn bound(term: &ExprTerm, env: &IndexEnv) -> Result<i64, Error> {
+    match elab_val(term, env)? {
+        ExprTerm::Val(val) => Ok(val),
+        _ => {
+            let err = format!("loop bound {} must be a constant", term);
+            Err(Error::new_elab_error(&err))
+        }
+    }
+}
+
+// run f for every value of the loop variable, inner loops can shadow outer ones
+pub fn each<F, E>(range: &Range, env: &IndexEnv, mut f: F) -> Result<(), E>
+where
+    F: FnMut(&IndexEnv) -> Result<(), E>,
+    E: From<Error>,
+{
+    let lo = bound(&range.lo, env)?;
+    let hi = bound(&range.hi, env)?;
+    let mut env = env.clone();
+    for i in lo..hi {
+        env.insert(range.var.to_string(), i);
+        f(&env)?;
+    }
+    Ok(())
+}
+
+fn elab_terms(term: &[GenTerm], env: &IndexEnv, res: &mut Vec<ExprTerm>) -> Result<(), Error> {
+    for t in term {
+        match t {
+            GenTerm::Term(t) => res.push(elab_term(t, env)?),
+            GenTerm::For(range, body) => each(range, env, |env| elab_terms(body, env, res))?,
+        }
+    }
+    Ok(())
+}
+
+pub fn elab_expr(expr: &GenExpr, env: &IndexEnv) -> Result<Expr, Error> {
+    match expr {
+        GenExpr::Term(term) => Ok(Expr::from(elab_term(term, env)?)),
+        GenExpr::Tup(tup) => {
+            let mut term: Vec<ExprTerm> = Vec::new();
+            elab_terms(tup, env, &mut term)?;
+            Ok(Expr::from(ExprTup::from(term)))
+        }
+    }
+}
+
+fn elab_instrs(instr: &[GenInstr], env: &IndexEnv, res: &mut Vec<Instr>) -> Result<(), Error> {
+    for i in instr {
+        match i {
+            GenInstr::Instr(instr, dst, arg) => {
+                let mut instr = instr.clone();
+                instr.set_dst(elab_expr(dst, env)?);
+                instr.set_arg(elab_expr(arg, env)?);
+                match &mut instr {
+                    Instr::Prim(prim) => prim.attr = elab_vals(prim.attr(), env)?,
+                    Instr::Wire(wire) => wire.attr = elab_vals(wire.attr(), env)?,
+                    Instr::Call(call) => {
+                        let param = Expr::from(ExprTup::from(call.op().param().clone()));
+                        call.op.set_param(elab_vals(&param, env)?.into());
+                    }
+                }
+                res.push(instr);
+            }
+            GenInstr::For(range, body) => each(range, env, |env| elab_instrs(body, env, res))?,
+        }
+    }
+    Ok(())
+}
+
+// loops and index expressions are evaluated, def parameters are looked up in env
+pub fn body_try_from_gen(gen: &[GenInstr], env: &IndexEnv) -> Result<Vec<Instr>, Error> {
+    let mut body: Vec<Instr> = Vec::new();
+    elab_instrs(gen, env, &mut body)?;
+    Ok(body)
+}
+
+// parametric defs keep their body as it is, until they are specialized
+pub fn elab_try_from_gen_def(gen: &GenDef) -> Result<Def, Error> {
+    let env = IndexEnv::new();
+    let sig = Sig {
+        id: gen.id.to_string(),
+        input: elab_expr(&gen.input, &env)?,
+        output: elab_expr(&gen.output, &env)?,
+        span: gen.span.clone(),
+    };
+    let mut def = Def::default();
+    def.set_sig(sig);
+    def.set_param(gen.param.clone());
+    if gen.param.is_empty() {
+        def.set_body(body_try_from_gen(&gen.body, &env)?);
+    } else {
+        def.set_gen(gen.body.clone());
+    }
+    Ok(def)
+}
+
+pub fn elab_try_from_gen_defs(gen: &[GenDef]) -> Result<Prog, Error> {
+    let mut prog = Prog::default();
+    for g in gen {
+        let def = elab_try_from_gen_def(g)?;
+        prog.insert(&def.id(), def);
+    }
+    Ok(prog)
+}
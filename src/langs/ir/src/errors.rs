@@ -1,3 +1,4 @@
+use crate::ast::Id;
 use crate::parser;
 use std::fmt;
 use std::num::ParseIntError;
@@ -8,6 +9,15 @@ pub enum Error {
     Parser(pest_consume::Error<parser::Rule>),
     Conversion(String),
     Type(String),
+    Interp(String),
+    Inline(String),
+    Mono(String),
+    Elab(String),
+    Pipe(String),
+    Builder(String),
+    Cdc(String),
+    Yosys(String),
+    Loop(Id, Vec<Id>),
     TryFromInt(TryFromIntError),
     ParseInt(ParseIntError),
 }
@@ -17,7 +27,34 @@ impl Error {
         Error::Conversion(msg.to_string())
     }
     pub fn new_type_error(msg: &str) -> Self {
-        Error::Conversion(msg.to_string())
+        Error::Type(msg.to_string())
+    }
+    pub fn new_interp_error(msg: &str) -> Self {
+        Error::Interp(msg.to_string())
+    }
+    pub fn new_inline_error(msg: &str) -> Self {
+        Error::Inline(msg.to_string())
+    }
+    pub fn new_mono_error(msg: &str) -> Self {
+        Error::Mono(msg.to_string())
+    }
+    pub fn new_elab_error(msg: &str) -> Self {
+        Error::Elab(msg.to_string())
+    }
+    pub fn new_pipe_error(msg: &str) -> Self {
+        Error::Pipe(msg.to_string())
+    }
+    pub fn new_builder_error(msg: &str) -> Self {
+        Error::Builder(msg.to_string())
+    }
+    pub fn new_cdc_error(msg: &str) -> Self {
+        Error::Cdc(msg.to_string())
+    }
+    pub fn new_yosys_error(msg: &str) -> Self {
+        Error::Yosys(msg.to_string())
+    }
+    pub fn new_loop_error(def: &str, id: &[Id]) -> Self {
+        Error::Loop(def.to_string(), id.to_vec())
     }
 }
 
@@ -46,6 +83,24 @@ impl fmt::Display for Error {
             Error::ParseInt(msg) => write!(f, "{}", msg),
             Error::Conversion(msg) => write!(f, "{}", msg),
             Error::Type(msg) => write!(f, "{}", msg),
+            Error::Interp(msg) => write!(f, "{}", msg),
+            Error::Inline(msg) => write!(f, "{}", msg),
+            Error::Mono(msg) => write!(f, "{}", msg),
+            Error::Elab(msg) => write!(f, "{}", msg),
+            Error::Pipe(msg) => write!(f, "{}", msg),
+            Error::Builder(msg) => write!(f, "{}", msg),
+            Error::Cdc(msg) => write!(f, "{}", msg),
+            Error::Yosys(msg) => write!(f, "{}", msg),
+            Error::Loop(def, id) => {
+                let mut path = id.clone();
+                path.extend(id.first().cloned());
+                write!(
+                    f,
+                    "combinational loop in def {}: {}",
+                    def,
+                    path.join(" -> ")
+                )
+            }
             Error::TryFromInt(msg) => write!(f, "{}", msg),
         }
     }
@@ -0,0 +1,144 @@
+use crate::ast::*;
+use crate::errors::Error;
+use crate::interp::{eval_comb_try_from_instr, Env, Value};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+
+// constants are merged when they have the same type and value
+type ConstMap = HashMap<(Ty, Vec<i128>), Id>;
+
+fn is_const(instr: &Instr) -> bool {
+    matches!(instr, Instr::Wire(wire) if wire.op() == &OpWire::Con)
+}
+
+fn is_comb(instr: &Instr) -> bool {
+    match instr {
+        Instr::Wire(_) => true,
+        Instr::Prim(prim) => !matches!(prim.op(), OpPrim::Reg | OpPrim::Rom | OpPrim::Ram),
+        Instr::Call(_) => false,
+    }
+}
+
+// values are folded into const attributes, so they have to fit in i64
+fn is_foldable(instr: &Instr) -> Result<bool, Error> {
+    let ty = instr.dst().get_ty(0)?;
+    Ok(ty.width().map_or(false, |w| w <= 64))
+}
+
+fn const_try_from_value(instr: &Instr, value: &Value) -> Option<Instr> {
+    let mut lanes: Vec<i64> = Vec::new();
+    for v in value.lanes() {
+        lanes.push(i64::try_from(v).ok()?);
+    }
+    if lanes.iter().all(|v| *v == lanes[0]) {
+        lanes.truncate(1);
+    }
+    let attr: Vec<ExprTerm> = lanes.into_iter().map(ExprTerm::Val).collect();
+    Some(Instr::from(InstrWire {
+        op: OpWire::Con,
+        dst: instr.dst().clone(),
+        attr: Expr::from(ExprTup::from(attr)),
+        arg: Expr::default(),
+        span: instr.span().clone(),
+    }))
+}
+
+// mux with a constant select becomes an id of the selected argument
+fn mux_try_from_instr(instr: &Instr, env: &Env) -> Result<Option<Instr>, Error> {
+    if let Instr::Prim(prim) = instr {
+        if prim.op() == &OpPrim::Mux {
+            if let Some(Value::Scalar(sel)) = env.get(&prim.arg().get_id(0)?) {
+                let index = if *sel != 0 { 1 } else { 2 };
+                let arg = prim.arg().get_term(index)?.clone();
+                return Ok(Some(Instr::from(InstrWire {
+                    op: OpWire::Id,
+                    dst: instr.dst().clone(),
+                    attr: Expr::default(),
+                    arg: Expr::from(ExprTup::from(vec![arg])),
+                    span: instr.span().clone(),
+                })));
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn fold_try_from_instr(instr: &Instr, env: &Env) -> Result<Option<Instr>, Error> {
+    if is_const(instr) || !is_comb(instr) {
+        return Ok(None);
+    }
+    let mux = mux_try_from_instr(instr, env)?;
+    let curr = mux.as_ref().unwrap_or(instr);
+    if !is_foldable(curr)? {
+        return Ok(mux);
+    }
+    let arg: Vec<ExprTerm> = curr.arg().clone().into();
+    for a in arg {
+        if !env.contains_key(&a.get_id()?) {
+            return Ok(mux);
+        }
+    }
+    let value = eval_comb_try_from_instr(curr, env)?;
+    Ok(const_try_from_value(curr, &value).or(mux))
+}
+
+// fold combinational instructions with constant arguments into constants and
+// merge constants with the same type and value
+pub fn fold_try_from_def(def: &Def) -> Result<Def, Error> {
+    let mut sorted = def.clone();
+    sorted.sort_body()?;
+    let output: Vec<ExprTerm> = def.output().clone().into();
+    let mut output_set: HashSet<Id> = HashSet::new();
+    for term in output {
+        output_set.insert(term.get_id()?);
+    }
+    let mut env = Env::new();
+    let mut folded: HashMap<Id, Instr> = HashMap::new();
+    let mut rename: HashMap<Id, Id> = HashMap::new();
+    let mut consts = ConstMap::new();
+    for instr in sorted.body() {
+        let instr = match fold_try_from_instr(instr, &env)? {
+            Some(new) => {
+                folded.insert(instr.dst().get_id(0)?, new.clone());
+                new
+            }
+            None => instr.clone(),
+        };
+        if is_const(&instr) && is_foldable(&instr)? {
+            let id = instr.dst().get_id(0)?;
+            let ty = instr.dst().get_ty(0)?.clone();
+            let value = eval_comb_try_from_instr(&instr, &env)?;
+            let key = (ty, value.lanes());
+            match consts.get(&key) {
+                Some(first) if !output_set.contains(&id) => {
+                    rename.insert(id.to_string(), first.to_string());
+                }
+                _ => {
+                    consts.insert(key, id.to_string());
+                }
+            }
+            env.insert(id, value);
+        }
+    }
+    let mut body: Vec<Instr> = Vec::new();
+    for instr in def.body() {
+        let id = instr.dst().get_id(0)?;
+        if rename.contains_key(&id) {
+            continue;
+        }
+        let mut instr = folded.remove(&id).unwrap_or_else(|| instr.clone());
+        instr.rename_arg(&rename);
+        body.push(instr);
+    }
+    let mut res = def.clone();
+    res.set_body(body);
+    Ok(res)
+}
+
+pub fn fold_try_from_prog(prog: &Prog) -> Result<Prog, Error> {
+    let mut res = Prog::default();
+    for (name, def) in prog.def() {
+        res.insert(name, fold_try_from_def(def)?);
+    }
+    Ok(res)
+}
@@ -11,6 +11,7 @@ const RE_UVEC: &str = r"^[\s\t]*u[[:digit:]]+<[[:digit:]]+>[\s\t]*$";
 const RE_SVEC: &str = r"^[\s\t]*i[[:digit:]]+<[[:digit:]]+>[\s\t]*$";
 const RE_LENGTH: &str = r"^[\s\t]*[ui][[:digit:]]+<([[:digit:]]+)>[\s\t]*$";
 const RE_WIDTH: &str = r"^[\s\t]*[ui]([[:digit:]]+)[<[[:digit:]]+>]*[\s\t]*$";
+const RE_PARAM: &str = r"^[\s\t]*([ui])(?:([[:digit:]]+)|<([[:alpha:]][[:alnum:]_-]*)>)(?:<(?:([[:digit:]]+)|([[:alpha:]][[:alnum:]_-]*))>)?[\s\t]*$";
 
 fn is_bool(input: &str) -> bool {
     lazy_static::lazy_static! {
@@ -81,12 +82,35 @@ fn length(input: &str) -> Result<u64, Error> {
     }
 }
 
+// types where width or length are def parameters, i.e. i<W>, u8<N> or i<W><N>
+fn param_ty(input: &str) -> Option<Ty> {
+    lazy_static::lazy_static! {
+        static ref RE: Regex = Regex::new(RE_PARAM).unwrap();
+    }
+    let caps = RE.captures(input)?;
+    let signed = caps.get(1)?.as_str() == "i";
+    let ty = match (caps.get(2), caps.get(3)) {
+        (Some(w), _) if signed => Ty::SInt(w.as_str().parse::<u64>().ok()?),
+        (Some(w), _) => Ty::UInt(w.as_str().parse::<u64>().ok()?),
+        (_, Some(p)) if signed => Ty::SIntParam(p.as_str().to_string()),
+        (_, Some(p)) => Ty::UIntParam(p.as_str().to_string()),
+        _ => return None,
+    };
+    match (caps.get(4), caps.get(5)) {
+        (Some(l), _) => Some(Ty::Vector(Rc::new(ty), l.as_str().parse::<u64>().ok()?)),
+        (_, Some(p)) => Some(Ty::VectorParam(Rc::new(ty), p.as_str().to_string())),
+        _ => Some(ty),
+    }
+}
+
 impl FromStr for Ty {
     type Err = Error;
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         let err = format!("Error: {} is not valid type", input);
         if is_bool(input) {
             Ok(Ty::Bool)
+        } else if input.trim() == "clock" {
+            Ok(Ty::Clock)
         } else if is_uint(input) {
             Ok(Ty::UInt(width(input)?))
         } else if is_sint(input) {
@@ -95,6 +119,8 @@ impl FromStr for Ty {
             Ok(Ty::Vector(Rc::new(Ty::UInt(width(input)?)), length(input)?))
         } else if is_svec(input) {
             Ok(Ty::Vector(Rc::new(Ty::SInt(width(input)?)), length(input)?))
+        } else if let Some(ty) = param_ty(input) {
+            Ok(ty)
         } else {
             Err(Error::new_conv_error(&err))
         }
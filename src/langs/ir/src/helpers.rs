@@ -1,9 +1,10 @@
 use crate::ast::*;
+use crate::dfg::topo_order_try_from_def;
+use crate::elab::GenInstr;
 use crate::errors::Error;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
-use std::collections::{HashMap, HashSet};
-use std::convert::TryInto;
+use std::collections::HashMap;
 
 impl Prim {
     pub fn is_any(&self) -> bool {
@@ -14,7 +15,7 @@ impl Prim {
 impl Ty {
     pub fn width(&self) -> Option<u64> {
         match self {
-            Ty::Bool => Some(1),
+            Ty::Bool | Ty::Clock => Some(1),
             Ty::UInt(w) => Some(*w),
             Ty::SInt(w) => Some(*w),
             Ty::Vector(ty, _) => ty.width(),
@@ -29,23 +30,45 @@ impl Ty {
     }
     pub fn is_signed(&self) -> bool {
         match self {
-            Ty::SInt(_) => true,
+            Ty::SInt(_) | Ty::SIntParam(_) => true,
             Ty::Vector(ty, _) => ty.is_signed(),
             _ => false,
         }
     }
     pub fn is_vector(&self) -> bool {
-        matches!(self, Ty::Vector(_, _))
+        matches!(self, Ty::Vector(_, _) | Ty::VectorParam(_, _))
+    }
+    pub fn is_clock(&self) -> bool {
+        matches!(self, Ty::Clock)
+    }
+    pub fn is_param(&self) -> bool {
+        match self {
+            Ty::UIntParam(_) | Ty::SIntParam(_) | Ty::VectorParam(_, _) => true,
+            Ty::Vector(ty, _) => ty.is_param(),
+            _ => false,
+        }
     }
 }
 
 impl OpCall {
     pub fn new(op: &str) -> OpCall {
-        OpCall { op: op.to_string() }
+        OpCall {
+            op: op.to_string(),
+            param: Vec::new(),
+        }
     }
     pub fn op(&self) -> String {
         self.op.to_string()
     }
+    pub fn param(&self) -> &Vec<ExprTerm> {
+        &self.param
+    }
+    pub fn set_op(&mut self, op: &str) {
+        self.op = op.to_string();
+    }
+    pub fn set_param(&mut self, param: Vec<ExprTerm>) {
+        self.param = param;
+    }
 }
 
 impl ExprTerm {
@@ -235,12 +258,29 @@ impl InstrPrim {
     pub fn is_reg(&self) -> bool {
         matches!(self.op(), OpPrim::Reg)
     }
+    // block rams latch the read address on the clock edge, their output is a register
+    pub fn is_sync_read(&self) -> bool {
+        matches!(self.op(), OpPrim::Rom | OpPrim::Ram) && self.prim() == &Prim::Bram
+    }
+    // registers, rams and block rom reads can name the clock of their domain as last argument
+    pub fn clock(&self) -> Option<Id> {
+        let index = match self.op() {
+            OpPrim::Reg => 2,
+            OpPrim::Rom => 1,
+            OpPrim::Ram => 3,
+            _ => return None,
+        };
+        self.arg().idx(index).and_then(|t| t.id())
+    }
     pub fn set_prim(&mut self, prim: Prim) {
         self.prim = prim;
     }
     pub fn set_dst(&mut self, dst: Expr) {
         self.dst = dst;
     }
+    pub fn set_attr(&mut self, attr: Expr) {
+        self.attr = attr;
+    }
     pub fn set_arg(&mut self, arg: Expr) {
         self.arg = arg;
     }
@@ -311,6 +351,37 @@ impl Instr {
             Instr::Call(instr) => instr.set_arg(arg),
         }
     }
+    pub fn span(&self) -> &Span {
+        match self {
+            Instr::Prim(instr) => &instr.span,
+            Instr::Wire(instr) => &instr.span,
+            Instr::Call(instr) => &instr.span,
+        }
+    }
+    pub fn set_span(&mut self, span: Span) {
+        match self {
+            Instr::Prim(instr) => instr.span = span,
+            Instr::Wire(instr) => instr.span = span,
+            Instr::Call(instr) => instr.span = span,
+        }
+    }
+    pub fn rename_arg(&mut self, map: &HashMap<Id, Id>) {
+        let term: Vec<ExprTerm> = self.arg().clone().into();
+        let term: Vec<ExprTerm> = term
+            .into_iter()
+            .map(|t| match t {
+                ExprTerm::Var(id, ty) if map.contains_key(&id) => {
+                    ExprTerm::Var(map[&id].to_string(), ty)
+                }
+                _ => t,
+            })
+            .collect();
+        let arg = match self.arg() {
+            Expr::Term(_) if term.len() == 1 => Expr::from(term[0].clone()),
+            _ => Expr::from(ExprTup::from(term)),
+        };
+        self.set_arg(arg);
+    }
 }
 
 impl Sig {
@@ -326,34 +397,9 @@ impl Sig {
     pub fn set_id(&mut self, id: &str) {
         self.id = id.to_string();
     }
-}
-
-fn term_is_ready(env: &HashSet<Id>, term: &ExprTerm) -> bool {
-    if let Some(id) = term.id() {
-        env.contains(&id)
-    } else {
-        false
-    }
-}
-
-fn instr_is_ready(env: &HashSet<Id>, instr: &Instr) -> bool {
-    let mut is_ready = true;
-    if !instr.is_reg() {
-        match instr.arg() {
-            Expr::Tup(tup) => {
-                for term in tup.term() {
-                    is_ready = term_is_ready(env, term);
-                    if !is_ready {
-                        break;
-                    }
-                }
-            }
-            Expr::Term(term) => {
-                is_ready = term_is_ready(env, term);
-            }
-        }
+    pub fn span(&self) -> &Span {
+        &self.span
     }
-    is_ready
 }
 
 impl Def {
@@ -372,15 +418,30 @@ impl Def {
     pub fn body(&self) -> &Vec<Instr> {
         &self.body
     }
+    pub fn param(&self) -> &Vec<Id> {
+        &self.param
+    }
+    pub fn is_param(&self) -> bool {
+        !self.param.is_empty()
+    }
+    pub fn gen(&self) -> &Vec<GenInstr> {
+        &self.gen
+    }
     pub fn set_id(&mut self, id: &str) {
         self.sig.set_id(id);
     }
+    pub fn set_param(&mut self, param: Vec<Id>) {
+        self.param = param;
+    }
     pub fn set_sig(&mut self, sig: Sig) {
         self.sig = sig;
     }
     pub fn set_body(&mut self, body: Vec<Instr>) {
         self.body = body;
     }
+    pub fn set_gen(&mut self, gen: Vec<GenInstr>) {
+        self.gen = gen;
+    }
     pub fn body_mut(&mut self) -> &mut Vec<Instr> {
         &mut self.body
     }
@@ -388,39 +449,10 @@ impl Def {
         self.body.shuffle(&mut thread_rng());
     }
     pub fn sort_body(&mut self) -> Result<(), Error> {
-        let mut env: HashSet<Id> = HashSet::new();
-        let input: Vec<Id> = self.sig.input().clone().try_into()?;
-        for id in input {
-            env.insert(id);
-        }
-        let mut p: Vec<Instr> = Vec::new();
-        let mut q: Vec<Instr> = self.body.clone();
-        let mut pass = true;
-        while !q.is_empty() {
-            let mut t: Vec<Instr> = Vec::new();
-            for instr in &q {
-                if instr_is_ready(&env, instr) {
-                    p.push(instr.clone());
-                    let dst: Vec<Id> = instr.dst().clone().try_into()?;
-                    for id in dst {
-                        env.insert(id);
-                    }
-                } else {
-                    t.push(instr.clone());
-                }
-            }
-            if q.len() == t.len() {
-                pass = false;
-                break;
-            }
-            q = t;
-        }
-        if pass {
-            self.body = p;
-            Ok(())
-        } else {
-            Err(Error::new_conv_error("Sorting"))
-        }
+        let order = topo_order_try_from_def(self)?;
+        let body: Vec<Instr> = order.iter().map(|i| self.body[*i].clone()).collect();
+        self.body = body;
+        Ok(())
     }
 }
 
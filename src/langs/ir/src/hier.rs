@@ -0,0 +1,110 @@
+use crate::ast::*;
+use crate::errors::Error;
+use std::collections::HashSet;
+
+fn visit(
+    prog: &Prog,
+    name: &str,
+    stack: &mut Vec<Id>,
+    done: &mut HashSet<Id>,
+    order: &mut Vec<Id>,
+) -> Result<(), Error> {
+    if done.contains(name) {
+        return Ok(());
+    }
+    if stack.iter().any(|n| n == name) {
+        let err = format!("recursive call: {} -> {}", stack.join(" -> "), name);
+        return Err(Error::new_inline_error(&err));
+    }
+    let def = if let Some(def) = prog.get(name) {
+        def
+    } else {
+        let err = format!("def {} not found", name);
+        return Err(Error::new_inline_error(&err));
+    };
+    stack.push(name.to_string());
+    for instr in def.body() {
+        if let Instr::Call(call) = instr {
+            visit(prog, &call.op().op(), stack, done, order)?;
+        }
+    }
+    stack.pop();
+    done.insert(name.to_string());
+    order.push(name.to_string());
+    Ok(())
+}
+
+// defs reachable from name, every callee comes before its callers
+pub fn def_order_try_from_def(prog: &Prog, name: &str) -> Result<Vec<Id>, Error> {
+    let mut stack: Vec<Id> = Vec::new();
+    let mut done: HashSet<Id> = HashSet::new();
+    let mut order: Vec<Id> = Vec::new();
+    visit(prog, name, &mut stack, &mut done, &mut order)?;
+    Ok(order)
+}
+
+// all defs, every callee comes before its callers
+pub fn def_order_try_from_prog(prog: &Prog) -> Result<Vec<Id>, Error> {
+    let mut name: Vec<&Id> = prog.def().keys().collect();
+    name.sort();
+    let mut stack: Vec<Id> = Vec::new();
+    let mut done: HashSet<Id> = HashSet::new();
+    let mut order: Vec<Id> = Vec::new();
+    for n in name {
+        visit(prog, n, &mut stack, &mut done, &mut order)?;
+    }
+    Ok(order)
+}
+
+// remove calls from a def, so it can be compiled on its own. Call destinations
+// become inputs and call arguments computed in the def become outputs
+pub fn split_calls_from_def(def: &Def) -> Result<(Def, Vec<InstrCall>), Error> {
+    let mut call: Vec<InstrCall> = Vec::new();
+    let mut body: Vec<Instr> = Vec::new();
+    for instr in def.body() {
+        match instr {
+            Instr::Call(c) => call.push(c.clone()),
+            _ => body.push(instr.clone()),
+        }
+    }
+    let mut local: HashSet<Id> = HashSet::new();
+    for instr in &body {
+        let dst: Vec<ExprTerm> = instr.dst().clone().into();
+        for term in dst {
+            local.insert(term.get_id()?);
+        }
+    }
+    let mut input: Vec<ExprTerm> = def.input().clone().into();
+    let mut output: Vec<ExprTerm> = Vec::new();
+    let mut used: HashSet<Id> = HashSet::new();
+    for c in &call {
+        let dst: Vec<ExprTerm> = c.dst().clone().into();
+        input.extend(dst);
+    }
+    let def_output: Vec<ExprTerm> = def.output().clone().into();
+    for term in def_output {
+        let id = term.get_id()?;
+        if local.contains(&id) && used.insert(id) {
+            output.push(term);
+        }
+    }
+    for c in &call {
+        let arg: Vec<ExprTerm> = c.arg().clone().into();
+        for term in arg {
+            let id = term.get_id()?;
+            if local.contains(&id) && used.insert(id) {
+                output.push(term);
+            }
+        }
+    }
+    let sig = Sig {
+        id: def.id(),
+        input: Expr::from(ExprTup::from(input)),
+        output: Expr::from(ExprTup::from(output)),
+        span: def.sig().span().clone(),
+    };
+    let mut res = Def::default();
+    res.set_sig(sig);
+    res.set_body(body);
+    Ok((res, call))
+}
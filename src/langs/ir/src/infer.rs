@@ -32,14 +32,15 @@ fn infer_type_try_from_def(def: &Def) -> Def {
     // solve instr arg types with environment
     for instr in def.body_mut() {
         let mut arg = ExprTup::default();
-        if let Some(tup) = instr.arg().tup() {
-            for e in tup.term() {
-                if let Some(id) = e.id() {
-                    if let Some(ty) = env.get(&id) {
-                        let term = ExprTerm::Var(id.to_string(), ty.clone());
-                        arg.add_term(term);
-                    }
+        let term: Vec<ExprTerm> = instr.arg().clone().into();
+        for e in term {
+            match e.id() {
+                Some(id) if env.contains_key(&id) => {
+                    let term = ExprTerm::Var(id.to_string(), env[&id].clone());
+                    arg.add_term(term);
                 }
+                // unresolved terms are kept, so the checker can report them
+                _ => arg.add_term(e),
             }
         }
         let e = Expr::from(arg);
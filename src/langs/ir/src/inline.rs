@@ -0,0 +1,162 @@
+use crate::ast::*;
+use crate::errors::Error;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Namer {
+    used: HashSet<Id>,
+}
+
+impl Namer {
+    pub(crate) fn new(def: &Def) -> Self {
+        let mut used = HashSet::new();
+        let input: Vec<ExprTerm> = def.input().clone().into();
+        for term in input {
+            if let Some(id) = term.id() {
+                used.insert(id);
+            }
+        }
+        for instr in def.body() {
+            let dst: Vec<ExprTerm> = instr.dst().clone().into();
+            for term in dst {
+                if let Some(id) = term.id() {
+                    used.insert(id);
+                }
+            }
+        }
+        Namer { used }
+    }
+    pub(crate) fn fresh(&mut self, name: &str) -> Id {
+        let mut id = name.to_string();
+        let mut count = 0;
+        while self.used.contains(&id) {
+            id = format!("{}_{}", name, count);
+            count += 1;
+        }
+        self.used.insert(id.clone());
+        id
+    }
+}
+
+fn rename_expr(expr: &Expr, map: &HashMap<Id, Id>) -> Expr {
+    let term: Vec<ExprTerm> = expr.clone().into();
+    let term: Vec<ExprTerm> = term
+        .into_iter()
+        .map(|t| match t {
+            ExprTerm::Var(id, ty) if map.contains_key(&id) => ExprTerm::Var(map[&id].clone(), ty),
+            _ => t,
+        })
+        .collect();
+    match expr {
+        Expr::Term(_) if term.len() == 1 => Expr::from(term[0].clone()),
+        _ => Expr::from(ExprTup::from(term)),
+    }
+}
+
+fn inline_call(call: &InstrCall, callee: &Def, namer: &mut Namer) -> Result<Vec<Instr>, Error> {
+    let arg: Vec<ExprTerm> = call.arg().clone().into();
+    let dst: Vec<ExprTerm> = call.dst().clone().into();
+    let input: Vec<ExprTerm> = callee.input().clone().into();
+    let output: Vec<ExprTerm> = callee.output().clone().into();
+    if arg.len() != input.len() || dst.len() != output.len() {
+        let err = format!("call does not match signature of def {}", callee.id());
+        return Err(Error::new_inline_error(&err));
+    }
+    // instance variables are prefixed with the first destination of the call
+    let prefix = if let Some(term) = dst.first() {
+        term.get_id()?
+    } else {
+        callee.id()
+    };
+    let mut map: HashMap<Id, Id> = HashMap::new();
+    let mut body: Vec<Instr> = Vec::new();
+    for (i, a) in input.iter().zip(arg.iter()) {
+        map.insert(i.get_id()?, a.get_id()?);
+    }
+    for (o, d) in output.iter().zip(dst.iter()) {
+        let id = o.get_id()?;
+        if let Some(from) = map.get(&id) {
+            // outputs that are inputs or repeated outputs need a wire
+            let term = ExprTerm::Var(from.to_string(), o.get_ty()?.clone());
+            let wire = InstrWire {
+                op: OpWire::Id,
+                dst: Expr::from(d.clone()),
+                attr: Expr::default(),
+                arg: Expr::from(ExprTup::from(vec![term])),
+                span: call.span.clone(),
+            };
+            body.push(Instr::from(wire));
+        } else {
+            map.insert(id, d.get_id()?);
+        }
+    }
+    for instr in callee.body() {
+        let dst: Vec<ExprTerm> = instr.dst().clone().into();
+        for term in dst {
+            let id = term.get_id()?;
+            let name = format!("{}_{}", prefix, id);
+            map.entry(id).or_insert_with(|| namer.fresh(&name));
+        }
+    }
+    for instr in callee.body() {
+        let mut instr = instr.clone();
+        instr.set_dst(rename_expr(instr.dst(), &map));
+        instr.set_arg(rename_expr(instr.arg(), &map));
+        body.push(instr);
+    }
+    Ok(body)
+}
+
+fn inline_def(
+    prog: &Prog,
+    name: &str,
+    stack: &mut Vec<Id>,
+    done: &mut HashMap<Id, Def>,
+) -> Result<Def, Error> {
+    if let Some(def) = done.get(name) {
+        return Ok(def.clone());
+    }
+    if stack.iter().any(|n| n == name) {
+        let err = format!("recursive call: {} -> {}", stack.join(" -> "), name);
+        return Err(Error::new_inline_error(&err));
+    }
+    let def = if let Some(def) = prog.get(name) {
+        def
+    } else {
+        let err = format!("def {} not found", name);
+        return Err(Error::new_inline_error(&err));
+    };
+    stack.push(name.to_string());
+    let mut namer = Namer::new(def);
+    let mut body: Vec<Instr> = Vec::new();
+    for instr in def.body() {
+        if let Instr::Call(call) = instr {
+            let callee = inline_def(prog, &call.op().op(), stack, done)?;
+            body.extend(inline_call(call, &callee, &mut namer)?);
+        } else {
+            body.push(instr.clone());
+        }
+    }
+    stack.pop();
+    let mut res = def.clone();
+    res.set_body(body);
+    done.insert(name.to_string(), res.clone());
+    Ok(res)
+}
+
+pub fn inline_try_from_def(prog: &Prog, name: &str) -> Result<Def, Error> {
+    let mut stack: Vec<Id> = Vec::new();
+    let mut done: HashMap<Id, Def> = HashMap::new();
+    inline_def(prog, name, &mut stack, &mut done)
+}
+
+pub fn inline_try_from_prog(prog: &Prog) -> Result<Prog, Error> {
+    let mut stack: Vec<Id> = Vec::new();
+    let mut done: HashMap<Id, Def> = HashMap::new();
+    let mut res = Prog::default();
+    for name in prog.def().keys() {
+        let def = inline_def(prog, name, &mut stack, &mut done)?;
+        res.insert(name, def);
+    }
+    Ok(res)
+}
@@ -0,0 +1,652 @@
+use crate::ast::*;
+use crate::errors::Error;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+
+pub type Env = HashMap<Id, Value>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Scalar(i128),
+    Vector(Vec<i128>),
+}
+
+// values computed in one cycle, including the ones of every call instance
+#[derive(Clone, Debug, Default)]
+struct Trace {
+    env: Env,
+    call: HashMap<Id, Trace>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Interp {
+    def: Def,
+    reg: Env,
+    mem: HashMap<Id, Vec<i128>>,
+    // output registers of block rams, these read synchronously
+    read: Env,
+    call: HashMap<Id, Interp>,
+    cycle: u64,
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Scalar(i128::from(value))
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Scalar(i128::from(value))
+    }
+}
+
+impl From<Vec<i64>> for Value {
+    fn from(value: Vec<i64>) -> Self {
+        Value::Vector(value.into_iter().map(i128::from).collect())
+    }
+}
+
+fn mask(width: u64) -> u128 {
+    if width >= 128 {
+        u128::MAX
+    } else {
+        (1_u128 << width) - 1
+    }
+}
+
+fn bits(width: u64, value: i128) -> u128 {
+    (value as u128) & mask(width)
+}
+
+fn sign_extend(width: u64, value: i128) -> i128 {
+    if width == 0 {
+        0
+    } else if width >= 128 {
+        value
+    } else {
+        let b = bits(width, value);
+        if (b >> (width - 1)) & 1 == 1 {
+            (b | !mask(width)) as i128
+        } else {
+            b as i128
+        }
+    }
+}
+
+fn elem_ty(ty: &Ty) -> &Ty {
+    match ty {
+        Ty::Vector(ty, _) => ty,
+        _ => ty,
+    }
+}
+
+fn norm(ty: &Ty, value: i128) -> i128 {
+    let ty = elem_ty(ty);
+    match ty.width() {
+        Some(width) if ty.is_signed() => sign_extend(width, value),
+        Some(width) => bits(width, value) as i128,
+        None => value,
+    }
+}
+
+fn width_try_from_term(term: &ExprTerm) -> Result<u64, Error> {
+    if let Some(width) = term.width() {
+        Ok(width)
+    } else {
+        let err = format!("{} has unknown width", term.get_id()?);
+        Err(Error::new_interp_error(&err))
+    }
+}
+
+fn check_arity(instr: &Instr, arg: &[ExprTerm], num: usize) -> Result<(), Error> {
+    if arg.len() == num {
+        Ok(())
+    } else {
+        let err = format!(
+            "{} expects {} arguments but got {}",
+            instr.dst().get_id(0)?,
+            num,
+            arg.len()
+        );
+        Err(Error::new_interp_error(&err))
+    }
+}
+
+fn shift_try_from_attr(attr: &Expr) -> Result<u32, Error> {
+    let shift = attr.get_val(0)?;
+    let shift = u32::try_from(shift)?;
+    Ok(shift.min(127))
+}
+
+impl Value {
+    pub fn new(ty: &Ty, lanes: &[i128]) -> Self {
+        if let Some(length) = ty.length() {
+            let lanes = (0..length as usize)
+                .map(|i| norm(ty, lanes.get(i).copied().unwrap_or(0)))
+                .collect();
+            Value::Vector(lanes)
+        } else {
+            Value::Scalar(norm(ty, lanes.first().copied().unwrap_or(0)))
+        }
+    }
+    pub fn is_vector(&self) -> bool {
+        matches!(self, Value::Vector(_))
+    }
+    pub fn lanes(&self) -> Vec<i128> {
+        match self {
+            Value::Scalar(v) => vec![*v],
+            Value::Vector(v) => v.clone(),
+        }
+    }
+    // scalars are broadcasted to every lane
+    pub fn lane(&self, index: usize) -> i128 {
+        match self {
+            Value::Scalar(v) => *v,
+            Value::Vector(v) => v.get(index).copied().unwrap_or(0),
+        }
+    }
+    pub fn len(&self) -> usize {
+        match self {
+            Value::Scalar(_) => 1,
+            Value::Vector(v) => v.len(),
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn get_scalar(&self) -> Result<i128, Error> {
+        match self {
+            Value::Scalar(v) => Ok(*v),
+            _ => Err(Error::new_interp_error("not a scalar value")),
+        }
+    }
+}
+
+fn value_try_from_input(ty: &Ty, value: &Value) -> Result<Value, Error> {
+    let length = ty.length().unwrap_or(1) as usize;
+    if ty.is_vector() == value.is_vector() && value.len() == length {
+        Ok(Value::new(ty, &value.lanes()))
+    } else {
+        Err(Error::new_interp_error("input value does not match type"))
+    }
+}
+
+fn ty_try_from_dst(instr: &Instr) -> Result<&Ty, Error> {
+    let ty = instr.dst().get_ty(0)?;
+    match elem_ty(ty).width() {
+        Some(width) if width <= 128 => Ok(ty),
+        Some(_) => {
+            let err = format!("{} is wider than 128 bits", instr.dst().get_id(0)?);
+            Err(Error::new_interp_error(&err))
+        }
+        None => {
+            let err = format!("{} has unknown type", instr.dst().get_id(0)?);
+            Err(Error::new_interp_error(&err))
+        }
+    }
+}
+
+fn value_try_from_term<'a>(env: &'a Env, term: &ExprTerm) -> Result<&'a Value, Error> {
+    let id = term.get_id()?;
+    if let Some(value) = env.get(&id) {
+        Ok(value)
+    } else {
+        let err = format!("{} is not defined", id);
+        Err(Error::new_interp_error(&err))
+    }
+}
+
+fn eval_lanes<F>(ty: &Ty, arg: &[&Value], f: F) -> Value
+where
+    F: Fn(&[i128]) -> i128,
+{
+    let length = ty.length().unwrap_or(1) as usize;
+    let lanes: Vec<i128> = (0..length)
+        .map(|i| {
+            let lane: Vec<i128> = arg.iter().map(|a| a.lane(i)).collect();
+            f(&lane)
+        })
+        .collect();
+    Value::new(ty, &lanes)
+}
+
+fn eval_comb_prim(instr: &Instr, prim: &InstrPrim, env: &Env) -> Result<Value, Error> {
+    let ty = ty_try_from_dst(instr)?;
+    let term: Vec<ExprTerm> = prim.arg().clone().into();
+    let mut arg: Vec<&Value> = Vec::new();
+    for t in &term {
+        arg.push(value_try_from_term(env, t)?);
+    }
+    let value = match prim.op() {
+        OpPrim::Add => {
+            check_arity(instr, &term, 2)?;
+            eval_lanes(ty, &arg, |a| a[0].wrapping_add(a[1]))
+        }
+        OpPrim::Sub => {
+            check_arity(instr, &term, 2)?;
+            eval_lanes(ty, &arg, |a| a[0].wrapping_sub(a[1]))
+        }
+        OpPrim::Mul => {
+            check_arity(instr, &term, 2)?;
+            eval_lanes(ty, &arg, |a| a[0].wrapping_mul(a[1]))
+        }
+        OpPrim::Not => {
+            check_arity(instr, &term, 1)?;
+            eval_lanes(ty, &arg, |a| !a[0])
+        }
+        OpPrim::And => {
+            check_arity(instr, &term, 2)?;
+            eval_lanes(ty, &arg, |a| a[0] & a[1])
+        }
+        OpPrim::Or => {
+            check_arity(instr, &term, 2)?;
+            eval_lanes(ty, &arg, |a| a[0] | a[1])
+        }
+        OpPrim::Xor => {
+            check_arity(instr, &term, 2)?;
+            eval_lanes(ty, &arg, |a| a[0] ^ a[1])
+        }
+        OpPrim::Mux => {
+            check_arity(instr, &term, 3)?;
+            eval_lanes(ty, &arg, |a| if a[0] != 0 { a[1] } else { a[2] })
+        }
+        OpPrim::Eql => {
+            check_arity(instr, &term, 2)?;
+            eval_lanes(ty, &arg, |a| i128::from(a[0] == a[1]))
+        }
+        OpPrim::Neql => {
+            check_arity(instr, &term, 2)?;
+            eval_lanes(ty, &arg, |a| i128::from(a[0] != a[1]))
+        }
+        OpPrim::Gt => {
+            check_arity(instr, &term, 2)?;
+            eval_lanes(ty, &arg, |a| i128::from(a[0] > a[1]))
+        }
+        OpPrim::Lt => {
+            check_arity(instr, &term, 2)?;
+            eval_lanes(ty, &arg, |a| i128::from(a[0] < a[1]))
+        }
+        OpPrim::Ge => {
+            check_arity(instr, &term, 2)?;
+            eval_lanes(ty, &arg, |a| i128::from(a[0] >= a[1]))
+        }
+        OpPrim::Le => {
+            check_arity(instr, &term, 2)?;
+            eval_lanes(ty, &arg, |a| i128::from(a[0] <= a[1]))
+        }
+        OpPrim::Reg | OpPrim::Rom | OpPrim::Ram => {
+            let err = format!("{} is not combinational", instr.dst().get_id(0)?);
+            return Err(Error::new_interp_error(&err));
+        }
+    };
+    Ok(value)
+}
+
+fn eval_wire(instr: &Instr, wire: &InstrWire, env: &Env) -> Result<Value, Error> {
+    let ty = ty_try_from_dst(instr)?;
+    let term: Vec<ExprTerm> = wire.arg().clone().into();
+    let mut arg: Vec<&Value> = Vec::new();
+    for t in &term {
+        arg.push(value_try_from_term(env, t)?);
+    }
+    let value = match wire.op() {
+        OpWire::Id => {
+            check_arity(instr, &term, 1)?;
+            eval_lanes(ty, &arg, |a| a[0])
+        }
+        OpWire::Con => {
+            check_arity(instr, &term, 0)?;
+            let attr: Vec<ExprTerm> = wire.attr().clone().into();
+            let mut lanes: Vec<i128> = Vec::new();
+            for t in attr {
+                lanes.push(i128::from(t.get_val()?));
+            }
+            if lanes.len() == 1 {
+                eval_lanes(ty, &[&Value::Scalar(lanes[0])], |a| a[0])
+            } else {
+                Value::new(ty, &lanes)
+            }
+        }
+        OpWire::Sll => {
+            check_arity(instr, &term, 1)?;
+            let shift = shift_try_from_attr(wire.attr())?;
+            eval_lanes(ty, &arg, |a| ((a[0] as u128) << shift) as i128)
+        }
+        OpWire::Srl => {
+            check_arity(instr, &term, 1)?;
+            let shift = shift_try_from_attr(wire.attr())?;
+            let width = width_try_from_term(&term[0])?;
+            eval_lanes(ty, &arg, |a| (bits(width, a[0]) >> shift) as i128)
+        }
+        OpWire::Sra => {
+            check_arity(instr, &term, 1)?;
+            let shift = shift_try_from_attr(wire.attr())?;
+            let width = width_try_from_term(&term[0])?;
+            eval_lanes(ty, &arg, |a| sign_extend(width, a[0]) >> shift)
+        }
+        OpWire::Ext => {
+            check_arity(instr, &term, 1)?;
+            let lo = u64::try_from(wire.attr().get_val(0)?)?;
+            let hi = if let Some(t) = wire.attr().idx(1) {
+                u64::try_from(t.get_val()?)?
+            } else {
+                lo
+            };
+            let width = width_try_from_term(&term[0])?;
+            if hi < lo || hi >= width {
+                let err = format!("invalid ext range for {}", instr.dst().get_id(0)?);
+                return Err(Error::new_interp_error(&err));
+            }
+            eval_lanes(ty, &arg, |a| {
+                ((bits(width, a[0]) >> lo) & mask(hi - lo + 1)) as i128
+            })
+        }
+        OpWire::Cat => {
+            let mut width: Vec<u64> = Vec::new();
+            for t in &term {
+                width.push(width_try_from_term(t)?);
+            }
+            if width.iter().sum::<u64>() > 128 {
+                let err = format!("{} is wider than 128 bits", instr.dst().get_id(0)?);
+                return Err(Error::new_interp_error(&err));
+            }
+            // first argument is the least significant one
+            eval_lanes(ty, &arg, |a| {
+                let mut res: u128 = 0;
+                let mut offset: u64 = 0;
+                for (v, w) in a.iter().zip(width.iter()) {
+                    if offset < 128 {
+                        res |= bits(*w, *v) << offset;
+                    }
+                    offset += w;
+                }
+                res as i128
+            })
+        }
+    };
+    Ok(value)
+}
+
+// value of a combinational instr, every argument must be defined in env
+pub fn eval_comb_try_from_instr(instr: &Instr, env: &Env) -> Result<Value, Error> {
+    match instr {
+        Instr::Prim(prim) => eval_comb_prim(instr, prim, env),
+        Instr::Wire(wire) => eval_wire(instr, wire, env),
+        Instr::Call(_) => {
+            let err = format!("{} is not combinational", instr.dst().get_id(0)?);
+            Err(Error::new_interp_error(&err))
+        }
+    }
+}
+
+impl Interp {
+    pub fn try_from_prog(prog: &Prog) -> Result<Self, Error> {
+        Interp::try_from_def(prog, "main")
+    }
+    pub fn try_from_def(prog: &Prog, name: &str) -> Result<Self, Error> {
+        let mut stack = HashSet::new();
+        Interp::new_with_stack(prog, name, &mut stack)
+    }
+    fn new_with_stack(prog: &Prog, name: &str, stack: &mut HashSet<Id>) -> Result<Self, Error> {
+        let mut def = if let Some(def) = prog.get(name) {
+            def.clone()
+        } else {
+            let err = format!("def {} not found", name);
+            return Err(Error::new_interp_error(&err));
+        };
+        def.sort_body()?;
+        if !stack.insert(name.to_string()) {
+            let err = format!("def {} is recursive", name);
+            return Err(Error::new_interp_error(&err));
+        }
+        let mut reg = Env::new();
+        let mut read = Env::new();
+        let mut call = HashMap::new();
+        for instr in def.body() {
+            match instr {
+                Instr::Prim(prim) if prim.is_reg() => {
+                    let ty = ty_try_from_dst(instr)?;
+                    let init = if let Some(term) = prim.attr().idx(0) {
+                        term.get_val()?
+                    } else {
+                        0
+                    };
+                    let init = Value::Scalar(i128::from(init));
+                    let value = eval_lanes(ty, &[&init], |a| a[0]);
+                    reg.insert(instr.dst().get_id(0)?, value);
+                }
+                Instr::Prim(prim) if prim.is_sync_read() => {
+                    let ty = ty_try_from_dst(instr)?;
+                    let value = eval_lanes(ty, &[&Value::Scalar(0)], |a| a[0]);
+                    read.insert(instr.dst().get_id(0)?, value);
+                }
+                Instr::Call(c) => {
+                    let inst = Interp::new_with_stack(prog, &c.op().op(), stack)?;
+                    call.insert(instr.dst().get_id(0)?, inst);
+                }
+                _ => (),
+            }
+        }
+        stack.remove(name);
+        Ok(Interp {
+            def,
+            reg,
+            mem: HashMap::new(),
+            read,
+            call,
+            cycle: 0,
+        })
+    }
+    pub fn def(&self) -> &Def {
+        &self.def
+    }
+    pub fn cycle(&self) -> u64 {
+        self.cycle
+    }
+    pub fn reg(&self) -> &Env {
+        &self.reg
+    }
+    // initial content of rom/ram instr with destination id
+    pub fn set_mem(&mut self, id: &str, values: &[i64]) {
+        let values = values.iter().map(|v| i128::from(*v)).collect();
+        self.mem.insert(id.to_string(), values);
+    }
+    fn read_mem(&self, id: &str, addr: u128) -> i128 {
+        if let Some(mem) = self.mem.get(id) {
+            usize::try_from(addr)
+                .ok()
+                .and_then(|a| mem.get(a).copied())
+                .unwrap_or(0)
+        } else {
+            0
+        }
+    }
+    // memory content at the address of rom/ram instr
+    fn eval_read(&self, instr: &Instr, prim: &InstrPrim, env: &Env) -> Result<Value, Error> {
+        let ty = ty_try_from_dst(instr)?;
+        let term: Vec<ExprTerm> = prim.arg().clone().into();
+        let num = if prim.op() == &OpPrim::Rom { 1 } else { 3 };
+        let num = if prim.clock().is_some() { num + 1 } else { num };
+        check_arity(instr, &term, num)?;
+        let addr = value_try_from_term(env, &term[0])?;
+        let id = instr.dst().get_id(0)?;
+        let width = width_try_from_term(&term[0])?;
+        let length = ty.length().unwrap_or(1) as usize;
+        let lanes: Vec<i128> = (0..length)
+            .map(|i| self.read_mem(&id, bits(width, addr.lane(i))))
+            .collect();
+        Ok(Value::new(ty, &lanes))
+    }
+    fn eval_prim(&self, instr: &Instr, prim: &InstrPrim, env: &Env) -> Result<Value, Error> {
+        match prim.op() {
+            _ if prim.is_sync_read() => Ok(self.read[&instr.dst().get_id(0)?].clone()),
+            OpPrim::Rom | OpPrim::Ram => self.eval_read(instr, prim, env),
+            OpPrim::Reg => Ok(self.reg[&instr.dst().get_id(0)?].clone()),
+            _ => eval_comb_prim(instr, prim, env),
+        }
+    }
+    fn eval_call(&self, instr: &Instr, call: &InstrCall, env: &Env) -> Result<Trace, Error> {
+        let id = instr.dst().get_id(0)?;
+        let inst = &self.call[&id];
+        let arg: Vec<ExprTerm> = call.arg().clone().into();
+        let input: Vec<ExprTerm> = inst.def.input().clone().into();
+        if arg.len() != input.len() {
+            let err = format!("call {} has wrong number of arguments", id);
+            return Err(Error::new_interp_error(&err));
+        }
+        let mut child = Env::new();
+        for (a, i) in arg.iter().zip(input.iter()) {
+            let value = value_try_from_term(env, a)?;
+            child.insert(i.get_id()?, value.clone());
+        }
+        inst.eval(&child)
+    }
+    fn eval(&self, input: &Env) -> Result<Trace, Error> {
+        let mut trace = Trace::default();
+        let inp: Vec<ExprTerm> = self.def.input().clone().into();
+        for term in inp {
+            let id = term.get_id()?;
+            if let Some(value) = input.get(&id) {
+                let value = value_try_from_input(term.get_ty()?, value)?;
+                trace.env.insert(id, value);
+            } else {
+                let err = format!("missing value for input {}", id);
+                return Err(Error::new_interp_error(&err));
+            }
+        }
+        for (id, value) in &self.reg {
+            trace.env.insert(id.to_string(), value.clone());
+        }
+        // body is in topological order, registers were already read from state
+        for instr in self.def.body().iter().filter(|i| !i.is_reg()) {
+            match instr {
+                Instr::Prim(prim) => {
+                    let value = self.eval_prim(instr, prim, &trace.env)?;
+                    trace.env.insert(instr.dst().get_id(0)?, value);
+                }
+                Instr::Wire(wire) => {
+                    let value = eval_wire(instr, wire, &trace.env)?;
+                    trace.env.insert(instr.dst().get_id(0)?, value);
+                }
+                Instr::Call(call) => {
+                    let child = self.eval_call(instr, call, &trace.env)?;
+                    let inst = &self.call[&instr.dst().get_id(0)?];
+                    let dst: Vec<ExprTerm> = call.dst().clone().into();
+                    let output: Vec<ExprTerm> = inst.def.output().clone().into();
+                    if dst.len() != output.len() {
+                        let err = format!("call {} has wrong number of outputs", inst.def.id());
+                        return Err(Error::new_interp_error(&err));
+                    }
+                    for (d, o) in dst.iter().zip(output.iter()) {
+                        let value = value_try_from_term(&child.env, o)?;
+                        trace.env.insert(d.get_id()?, value.clone());
+                    }
+                    trace.call.insert(instr.dst().get_id(0)?, child);
+                }
+            }
+        }
+        Ok(trace)
+    }
+    // clock edge, update registers and memories with values computed in the current cycle.
+    // Registers and memories with a clock argument only update when their clock is high.
+    // Block rams read the memory before the write and, as they are configured with
+    // WRITE_MODE NO_CHANGE, keep their output in write cycles
+    fn update(&mut self, trace: &Trace) -> Result<(), Error> {
+        let mut reg: Vec<(Id, Value)> = Vec::new();
+        let mut read: Vec<(Id, Value)> = Vec::new();
+        let mut mem: Vec<(Id, u128, i128)> = Vec::new();
+        for instr in self.def.body() {
+            if let Instr::Prim(prim) = instr {
+                let mut term: Vec<ExprTerm> = prim.arg().clone().into();
+                if prim.clock().is_some() {
+                    if let Some(clock) = term.pop() {
+                        if value_try_from_term(&trace.env, &clock)?.get_scalar()? == 0 {
+                            continue;
+                        }
+                    }
+                }
+                match prim.op() {
+                    OpPrim::Reg => {
+                        check_arity(instr, &term, 2)?;
+                        let id = instr.dst().get_id(0)?;
+                        let ty = ty_try_from_dst(instr)?;
+                        let data = value_try_from_term(&trace.env, &term[0])?;
+                        let en = value_try_from_term(&trace.env, &term[1])?;
+                        let old = &self.reg[&id];
+                        let arg = [en, data, old];
+                        let value = eval_lanes(ty, &arg, |a| if a[0] != 0 { a[1] } else { a[2] });
+                        reg.push((id, value));
+                    }
+                    OpPrim::Ram => {
+                        check_arity(instr, &term, 3)?;
+                        let id = instr.dst().get_id(0)?;
+                        let ty = ty_try_from_dst(instr)?;
+                        let width = width_try_from_term(&term[0])?;
+                        let addr = value_try_from_term(&trace.env, &term[0])?;
+                        let data = value_try_from_term(&trace.env, &term[1])?;
+                        let en = value_try_from_term(&trace.env, &term[2])?;
+                        if en.get_scalar()? != 0 {
+                            let addr = bits(width, addr.get_scalar()?);
+                            let data = norm(ty, data.get_scalar()?);
+                            mem.push((id, addr, data));
+                        } else if prim.is_sync_read() {
+                            let value = self.eval_read(instr, prim, &trace.env)?;
+                            read.push((instr.dst().get_id(0)?, value));
+                        }
+                    }
+                    OpPrim::Rom if prim.is_sync_read() => {
+                        let value = self.eval_read(instr, prim, &trace.env)?;
+                        read.push((instr.dst().get_id(0)?, value));
+                    }
+                    _ => (),
+                }
+            }
+        }
+        for (id, value) in reg {
+            self.reg.insert(id, value);
+        }
+        for (id, value) in read {
+            self.read.insert(id, value);
+        }
+        for (id, addr, data) in mem {
+            let addr = usize::try_from(addr)?;
+            let values = self.mem.entry(id).or_default();
+            if values.len() <= addr {
+                values.resize(addr + 1, 0);
+            }
+            values[addr] = data;
+        }
+        for (id, child) in self.call.iter_mut() {
+            if let Some(t) = trace.call.get(id) {
+                child.update(t)?;
+            }
+        }
+        Ok(())
+    }
+    // evaluate one cycle, returns outputs before the clock edge
+    pub fn step(&mut self, input: &Env) -> Result<Env, Error> {
+        let trace = self.eval(input)?;
+        let mut output = Env::new();
+        let out: Vec<ExprTerm> = self.def.output().clone().into();
+        for term in out {
+            let value = value_try_from_term(&trace.env, &term)?;
+            output.insert(term.get_id()?, value.clone());
+        }
+        self.update(&trace)?;
+        self.cycle += 1;
+        Ok(output)
+    }
+    pub fn run(&mut self, input: &[Env]) -> Result<Vec<Env>, Error> {
+        let mut output = Vec::new();
+        for i in input {
+            output.push(self.step(i)?);
+        }
+        Ok(output)
+    }
+}
+
+pub fn interp_try_from_prog(prog: &Prog, input: &[Env]) -> Result<Vec<Env>, Error> {
+    let mut interp = Interp::try_from_prog(prog)?;
+    interp.run(input)
+}
@@ -1,11 +1,27 @@
 pub mod ast;
+pub mod builder;
+pub mod check;
+pub mod clock;
+pub mod cse;
+pub mod dce;
 pub mod default;
+pub mod dfg;
 pub mod display;
+pub mod dot;
+pub mod elab;
 pub mod errors;
+pub mod fold;
 pub mod from;
 pub mod from_str;
 pub mod helpers;
+pub mod hier;
 pub mod infer;
+pub mod inline;
+pub mod interp;
+pub mod mono;
+pub mod narrow;
 pub mod parser;
+pub mod pipe;
 pub mod pretty_print;
 pub mod try_from;
+pub mod yosys;
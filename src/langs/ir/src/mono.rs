@@ -0,0 +1,265 @@
+use crate::ast::*;
+use crate::elab::body_try_from_gen;
+use crate::errors::Error;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+pub type Subst = HashMap<Id, i64>;
+
+// parametric defs calling themselves with new parameters never stop specializing
+const MAX_DEPTH: usize = 64;
+
+fn param_value(id: &str, map: &Subst) -> Result<i64, Error> {
+    if let Some(val) = map.get(id) {
+        Ok(*val)
+    } else {
+        let err = format!("parameter {} is not defined", id);
+        Err(Error::new_mono_error(&err))
+    }
+}
+
+fn param_size(id: &str, map: &Subst) -> Result<u64, Error> {
+    let val = param_value(id, map)?;
+    match u64::try_from(val) {
+        Ok(size) if size > 0 => Ok(size),
+        _ => {
+            let err = format!("parameter {} is {} but must be positive", id, val);
+            Err(Error::new_mono_error(&err))
+        }
+    }
+}
+
+pub fn subst_ty(ty: &Ty, map: &Subst) -> Result<Ty, Error> {
+    match ty {
+        Ty::UIntParam(id) => Ok(Ty::UInt(param_size(id, map)?)),
+        Ty::SIntParam(id) => Ok(Ty::SInt(param_size(id, map)?)),
+        Ty::Vector(ty, len) => Ok(Ty::Vector(Rc::new(subst_ty(ty, map)?), *len)),
+        Ty::VectorParam(ty, id) => Ok(Ty::Vector(
+            Rc::new(subst_ty(ty, map)?),
+            param_size(id, map)?,
+        )),
+        _ => Ok(ty.clone()),
+    }
+}
+
+pub fn subst_expr<F>(expr: &Expr, map: &Subst, f: F) -> Result<Expr, Error>
+where
+    F: Fn(&ExprTerm, &Subst) -> Result<ExprTerm, Error>,
+{
+    match expr {
+        Expr::Term(term) => Ok(Expr::from(f(term, map)?)),
+        Expr::Tup(tup) => {
+            let mut term: Vec<ExprTerm> = Vec::new();
+            for t in tup.term() {
+                term.push(f(t, map)?);
+            }
+            Ok(Expr::from(ExprTup::from(term)))
+        }
+    }
+}
+
+pub fn subst_var(term: &ExprTerm, map: &Subst) -> Result<ExprTerm, Error> {
+    match term {
+        ExprTerm::Var(id, ty) => Ok(ExprTerm::Var(id.to_string(), subst_ty(ty, map)?)),
+        _ => Ok(term.clone()),
+    }
+}
+
+// attributes and call parameters refer to def parameters by name
+pub fn subst_val(term: &ExprTerm, map: &Subst) -> Result<ExprTerm, Error> {
+    match term {
+        ExprTerm::Var(id, _) => Ok(ExprTerm::Val(param_value(id, map)?)),
+        _ => Ok(term.clone()),
+    }
+}
+
+fn bind(map: &mut Subst, id: &str, val: u64, def: &str) -> Result<(), Error> {
+    let val = i64::try_from(val)?;
+    match map.insert(id.to_string(), val) {
+        Some(old) if old != val => {
+            let err = format!(
+                "parameter {} of def {} can not be both {} and {}",
+                id, def, old, val
+            );
+            Err(Error::new_mono_error(&err))
+        }
+        _ => Ok(()),
+    }
+}
+
+// mismatches that are not related to parameters are left to the type checker
+pub fn unify(param: &Ty, ty: &Ty, map: &mut Subst, def: &str) -> Result<(), Error> {
+    match (param, ty) {
+        (Ty::UIntParam(id), Ty::UInt(width)) | (Ty::SIntParam(id), Ty::SInt(width)) => {
+            bind(map, id, *width, def)
+        }
+        (Ty::Vector(p, _), Ty::Vector(t, _)) => unify(p, t, map, def),
+        (Ty::VectorParam(p, id), Ty::Vector(t, len)) => {
+            bind(map, id, *len, def)?;
+            unify(p, t, map, def)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn unify_expr(param: &Expr, expr: &Expr, map: &mut Subst, def: &str) -> Result<(), Error> {
+    let param: Vec<ExprTerm> = param.clone().into();
+    let expr: Vec<ExprTerm> = expr.clone().into();
+    for (p, e) in param.iter().zip(expr.iter()) {
+        if let (Some(pty), Some(ety)) = (p.ty(), e.ty()) {
+            unify(pty, ety, map, def)?;
+        }
+    }
+    Ok(())
+}
+
+fn callee_subst(call: &InstrCall, callee: &Def) -> Result<Subst, Error> {
+    let mut map = Subst::new();
+    if call.op().param().is_empty() {
+        unify_expr(callee.input(), call.arg(), &mut map, &callee.id())?;
+        unify_expr(callee.output(), call.dst(), &mut map, &callee.id())?;
+        for id in callee.param() {
+            if !map.contains_key(id) {
+                let err = format!("can not infer parameter {} of def {}", id, callee.id());
+                return Err(Error::new_mono_error(&err));
+            }
+        }
+    } else if call.op().param().len() == callee.param().len() {
+        for (id, term) in callee.param().iter().zip(call.op().param()) {
+            map.insert(id.to_string(), term.get_val()?);
+        }
+    } else {
+        let err = format!(
+            "def {} expects {} parameters but got {}",
+            callee.id(),
+            callee.param().len(),
+            call.op().param().len()
+        );
+        return Err(Error::new_mono_error(&err));
+    }
+    Ok(map)
+}
+
+fn spec_name(callee: &Def, map: &Subst) -> Result<Id, Error> {
+    let mut name = callee.id();
+    for id in callee.param() {
+        name = format!("{}_{}", name, param_value(id, map)?);
+    }
+    Ok(name)
+}
+
+fn mono_call(
+    prog: &Prog,
+    call: &InstrCall,
+    res: &mut Prog,
+    depth: usize,
+) -> Result<InstrCall, Error> {
+    let callee = if let Some(def) = prog.get(&call.op().op()) {
+        def
+    } else {
+        let err = format!("def {} not found", call.op().op());
+        return Err(Error::new_mono_error(&err));
+    };
+    let mut call = call.clone();
+    if callee.is_param() {
+        let map = callee_subst(&call, callee)?;
+        let name = spec_name(callee, &map)?;
+        if prog.get(&name).is_some() {
+            let err = format!(
+                "specialization of def {} clashes with def {}",
+                callee.id(),
+                name
+            );
+            return Err(Error::new_mono_error(&err));
+        }
+        if depth >= MAX_DEPTH && res.get(&name).is_none() {
+            let err = format!(
+                "specialization of def {} is nested more than {} levels deep",
+                callee.id(),
+                MAX_DEPTH
+            );
+            return Err(Error::new_mono_error(&err));
+        }
+        mono_def(prog, callee, &map, &name, res, depth + 1)?;
+        call.op.set_op(&name);
+        call.op.set_param(Vec::new());
+        Ok(call)
+    } else if call.op().param().is_empty() {
+        Ok(call)
+    } else {
+        let err = format!("def {} does not take parameters", callee.id());
+        Err(Error::new_mono_error(&err))
+    }
+}
+
+fn mono_def(
+    prog: &Prog,
+    def: &Def,
+    map: &Subst,
+    name: &str,
+    res: &mut Prog,
+    depth: usize,
+) -> Result<(), Error> {
+    if res.get(name).is_some() {
+        return Ok(());
+    }
+    let mut sig = def.sig().clone();
+    sig.set_id(name);
+    sig.input = subst_expr(sig.input(), map, subst_var)?;
+    sig.output = subst_expr(sig.output(), map, subst_var)?;
+    let mut spec = Def::default();
+    spec.set_sig(sig);
+    // recursive calls stop here, they are reported when inlining
+    res.insert(name, spec.clone());
+    // loops bounded by parameters can only be elaborated now
+    let gen = if def.is_param() {
+        body_try_from_gen(def.gen(), map)?
+    } else {
+        def.body().clone()
+    };
+    let mut body: Vec<Instr> = Vec::new();
+    for mut instr in gen {
+        instr.set_dst(subst_expr(instr.dst(), map, subst_var)?);
+        instr.set_arg(subst_expr(instr.arg(), map, subst_var)?);
+        let instr = match instr {
+            Instr::Prim(mut prim) => {
+                prim.attr = subst_expr(prim.attr(), map, subst_val)?;
+                Instr::from(prim)
+            }
+            Instr::Wire(mut wire) => {
+                wire.attr = subst_expr(wire.attr(), map, subst_val)?;
+                Instr::from(wire)
+            }
+            Instr::Call(mut call) => {
+                let param = subst_expr(
+                    &Expr::from(ExprTup::from(call.op().param().clone())),
+                    map,
+                    subst_val,
+                )?;
+                call.op.set_param(param.into());
+                Instr::from(mono_call(prog, &call, res, depth)?)
+            }
+        };
+        body.push(instr);
+    }
+    spec.set_body(body);
+    res.insert(name, spec);
+    Ok(())
+}
+
+// replace parametric defs with a specialized def for every set of parameters
+// they are called with, parametric defs that are never called are dropped
+pub fn mono_try_from_prog(prog: &Prog) -> Result<Prog, Error> {
+    let mut name: Vec<&Id> = prog.def().keys().collect();
+    name.sort();
+    let mut res = Prog::default();
+    for n in name {
+        if let Some(def) = prog.get(n) {
+            if !def.is_param() {
+                mono_def(prog, def, &Subst::new(), n, &mut res, 0)?;
+            }
+        }
+    }
+    Ok(res)
+}
@@ -0,0 +1,438 @@
+use crate::ast::*;
+use crate::errors::Error;
+use crate::inline::Namer;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+
+// number of bits needed by every value, k < width means the value is
+// non-negative and smaller than 2^k, k == width means nothing is known
+pub type Bits = HashMap<Id, u64>;
+
+fn is_scalar_int(ty: &Ty) -> bool {
+    matches!(ty, Ty::UInt(_) | Ty::SInt(_))
+}
+
+fn resize(ty: &Ty, width: u64) -> Ty {
+    if ty.is_signed() {
+        Ty::SInt(width)
+    } else {
+        Ty::UInt(width)
+    }
+}
+
+fn bit_len(val: i64) -> Option<u64> {
+    if val < 0 {
+        None
+    } else {
+        Some(64 - u64::from(val.leading_zeros()))
+    }
+}
+
+fn attr_u64(attr: &Expr, index: usize) -> Result<u64, Error> {
+    Ok(u64::try_from(attr.get_val(index)?)?)
+}
+
+fn bits_from_term(term: &ExprTerm, bits: &Bits) -> u64 {
+    match term {
+        ExprTerm::Var(id, ty) => bits
+            .get(id)
+            .copied()
+            .unwrap_or_else(|| ty.width().unwrap_or(u64::MAX)),
+        _ => u64::MAX,
+    }
+}
+
+fn bits_try_from_instr(instr: &Instr, bits: &Bits) -> Result<u64, Error> {
+    let width = instr.dst().get_ty(0)?.width().unwrap_or(0);
+    let arg: Vec<ExprTerm> = instr.arg().clone().into();
+    let k: Vec<u64> = arg.iter().map(|t| bits_from_term(t, bits)).collect();
+    let res = match instr {
+        Instr::Wire(wire) => match wire.op() {
+            OpWire::Con => bit_len(wire.attr().get_val(0)?).unwrap_or(width),
+            OpWire::Id => k[0],
+            OpWire::Sll => k[0].saturating_add(attr_u64(wire.attr(), 0)?),
+            OpWire::Srl => {
+                let shift = attr_u64(wire.attr(), 0)?;
+                let arg_width = arg[0].width().unwrap_or(0);
+                if k[0] < arg_width {
+                    k[0].saturating_sub(shift)
+                } else if shift > 0 {
+                    arg_width.saturating_sub(shift)
+                } else {
+                    width
+                }
+            }
+            OpWire::Sra => {
+                let arg_width = arg[0].width().unwrap_or(0);
+                if k[0] < arg_width {
+                    k[0].saturating_sub(attr_u64(wire.attr(), 0)?)
+                } else {
+                    width
+                }
+            }
+            OpWire::Ext => k[0].saturating_sub(attr_u64(wire.attr(), 0)?),
+            // first argument is the least significant one
+            OpWire::Cat => {
+                let low: u64 = arg[..arg.len() - 1]
+                    .iter()
+                    .map(|t| t.width().unwrap_or(0))
+                    .sum();
+                low.saturating_add(k[arg.len() - 1])
+            }
+        },
+        Instr::Prim(prim) => match prim.op() {
+            OpPrim::Add => k[0].max(k[1]).saturating_add(1),
+            OpPrim::Mul => k[0].saturating_add(k[1]),
+            OpPrim::And => k[0].min(k[1]),
+            OpPrim::Or | OpPrim::Xor => k[0].max(k[1]),
+            OpPrim::Mux => k[1].max(k[2]),
+            // both the power-on and the reset value must fit
+            OpPrim::Reg => {
+                let mut bits = Some(k[0]);
+                let attr: Vec<ExprTerm> = prim.attr().clone().into();
+                for term in attr {
+                    bits = match (bits, bit_len(term.get_val()?)) {
+                        (Some(b), Some(v)) => Some(b.max(v)),
+                        _ => None,
+                    };
+                }
+                bits.unwrap_or(width)
+            }
+            _ => width,
+        },
+        Instr::Call(_) => width,
+    };
+    Ok(res.min(width))
+}
+
+// compute the number of bits needed by every scalar integer value in the body,
+// registers make this a fixpoint that starts from zero bits and only grows
+pub fn bits_try_from_def(def: &Def) -> Result<Bits, Error> {
+    let mut bits = Bits::new();
+    let mut instr: Vec<&Instr> = Vec::new();
+    for i in def.body() {
+        let term = i.dst().get_term(0)?;
+        if i.dst().term().is_some() && is_scalar_int(term.get_ty()?) {
+            bits.insert(term.get_id()?, 0);
+            instr.push(i);
+        }
+    }
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for i in &instr {
+            let id = i.dst().get_id(0)?;
+            let k = bits_try_from_instr(i, &bits)?;
+            if k > bits[&id] {
+                bits.insert(id, k);
+                changed = true;
+            }
+        }
+    }
+    Ok(bits)
+}
+
+fn is_narrowable(instr: &Instr) -> bool {
+    match instr {
+        Instr::Wire(wire) => matches!(
+            wire.op(),
+            OpWire::Con | OpWire::Id | OpWire::Sll | OpWire::Ext
+        ),
+        Instr::Prim(prim) => matches!(
+            prim.op(),
+            OpPrim::Add
+                | OpPrim::Mul
+                | OpPrim::And
+                | OpPrim::Or
+                | OpPrim::Xor
+                | OpPrim::Mux
+                | OpPrim::Reg
+        ),
+        Instr::Call(_) => false,
+    }
+}
+
+fn is_const(instr: &Instr) -> bool {
+    matches!(instr, Instr::Wire(wire) if wire.op() == &OpWire::Con)
+}
+
+fn is_eq(instr: &Instr) -> bool {
+    matches!(instr, Instr::Prim(prim) if matches!(prim.op(), OpPrim::Eql | OpPrim::Neql))
+}
+
+struct Narrow<'a> {
+    // original type of every value
+    ty: HashMap<Id, Ty>,
+    // narrowed width of values that changed type
+    width: HashMap<Id, u64>,
+    instr: HashMap<Id, &'a Instr>,
+    namer: Namer,
+    // coerced values and zero constants, so they are created only once
+    coerced: HashMap<(Id, u64), Id>,
+    zero: HashMap<Ty, Id>,
+    body: Vec<Instr>,
+}
+
+impl<'a> Narrow<'a> {
+    fn new(def: &'a Def) -> Result<Self, Error> {
+        let mut ty: HashMap<Id, Ty> = HashMap::new();
+        let mut instr: HashMap<Id, &Instr> = HashMap::new();
+        let input: Vec<ExprTerm> = def.input().clone().into();
+        for term in input {
+            ty.insert(term.get_id()?, term.get_ty()?.clone());
+        }
+        for i in def.body() {
+            let dst: Vec<ExprTerm> = i.dst().clone().into();
+            for term in dst {
+                ty.insert(term.get_id()?, term.get_ty()?.clone());
+                instr.insert(term.get_id()?, i);
+            }
+        }
+        Ok(Narrow {
+            ty,
+            width: HashMap::new(),
+            instr,
+            namer: Namer::new(def),
+            coerced: HashMap::new(),
+            zero: HashMap::new(),
+            body: Vec::new(),
+        })
+    }
+    fn orig(&self, id: &str) -> u64 {
+        self.ty.get(id).and_then(|t| t.width()).unwrap_or(0)
+    }
+    fn curr(&self, id: &str) -> u64 {
+        self.width.get(id).copied().unwrap_or_else(|| self.orig(id))
+    }
+    fn term(&self, id: &str, width: u64) -> ExprTerm {
+        self.term_as(id, id, width)
+    }
+    // a value named res with the type of id resized to width
+    fn term_as(&self, res: &str, id: &str, width: u64) -> ExprTerm {
+        ExprTerm::Var(res.to_string(), resize(&self.ty[id], width))
+    }
+    // width the arguments of eq and neq are compared at
+    fn eq_width(&self, instr: &Instr) -> Result<u64, Error> {
+        let arg: Vec<ExprTerm> = instr.arg().clone().into();
+        let mut width = 0;
+        for t in arg {
+            let id = t.get_id()?;
+            if !self.ty.get(&id).map_or(false, is_scalar_int) {
+                return Ok(self.orig(&id));
+            }
+            width = width.max(self.curr(&id));
+        }
+        Ok(width)
+    }
+    // widths the arguments of instr are used at
+    fn arg_width(&self, instr: &Instr) -> Result<Vec<u64>, Error> {
+        let arg: Vec<ExprTerm> = instr.arg().clone().into();
+        let mut res: Vec<u64> = Vec::new();
+        for t in &arg {
+            res.push(self.orig(&t.get_id()?));
+        }
+        let dst = instr.dst().get_id(0)?;
+        if let Instr::Wire(wire) = instr {
+            // ext can read a narrowed value if it still has the extracted bits
+            if wire.op() == &OpWire::Ext {
+                let id = arg[0].get_id()?;
+                if self.curr(&id) >= attr_u64(wire.attr(), 0)? + self.curr(&dst) {
+                    res[0] = self.curr(&id);
+                }
+                return Ok(res);
+            }
+        }
+        if let Some(width) = self.width.get(&dst).copied() {
+            match instr {
+                Instr::Prim(prim) if prim.op() == &OpPrim::Mux => {
+                    res[0] = self.curr(&arg[0].get_id()?);
+                    res[1] = width;
+                    res[2] = width;
+                }
+                Instr::Prim(prim) if prim.op() == &OpPrim::Reg => {
+                    res[0] = width;
+                    res[1] = self.curr(&arg[1].get_id()?);
+                }
+                _ => {
+                    for r in res.iter_mut() {
+                        *r = width;
+                    }
+                }
+            }
+        } else if is_eq(instr) {
+            let width = self.eq_width(instr)?;
+            for r in res.iter_mut() {
+                *r = width;
+            }
+        }
+        Ok(res)
+    }
+    // constants are free, so they take the widest width they are used at
+    fn widen_const(&mut self, def: &Def) -> Result<(), Error> {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for instr in def.body() {
+                let arg: Vec<ExprTerm> = instr.arg().clone().into();
+                let width = self.arg_width(instr)?;
+                for (t, w) in arg.iter().zip(width) {
+                    let id = t.get_id()?;
+                    let is_const = self.instr.get(&id).map_or(false, |i| is_const(i));
+                    if is_const && self.width.contains_key(&id) && self.curr(&id) < w {
+                        if w < self.orig(&id) {
+                            self.width.insert(id, w);
+                        } else {
+                            self.width.remove(&id);
+                        }
+                        changed = true;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+    fn zero(&mut self, ty: Ty) -> Id {
+        if let Some(id) = self.zero.get(&ty) {
+            return id.to_string();
+        }
+        let id = self.namer.fresh("zero");
+        self.body.push(Instr::from(InstrWire {
+            op: OpWire::Con,
+            dst: Expr::from(ExprTerm::Var(id.to_string(), ty.clone())),
+            attr: Expr::from(ExprTup::from(vec![ExprTerm::Val(0)])),
+            arg: Expr::default(),
+            span: Span::default(),
+        }));
+        self.zero.insert(ty, id.to_string());
+        id
+    }
+    // constants are rebuilt, other values are zero extended with cat or
+    // truncated with ext
+    fn coerce(&mut self, id: &str, width: u64) -> Result<ExprTerm, Error> {
+        let curr = self.curr(id);
+        if curr == width {
+            return Ok(self.term(id, width));
+        }
+        let key = (id.to_string(), width);
+        if let Some(res) = self.coerced.get(&key) {
+            return Ok(self.term_as(res, id, width));
+        }
+        let res = self.namer.fresh(&format!("{}_{}", id, width));
+        let dst = Expr::from(self.term_as(&res, id, width));
+        let instr = match self.instr.get(id) {
+            Some(Instr::Wire(wire)) if wire.op() == &OpWire::Con => {
+                let val = wire.attr().get_val(0)?;
+                let val = if width < 64 {
+                    val & ((1_u64 << width) - 1) as i64
+                } else {
+                    val
+                };
+                InstrWire {
+                    op: OpWire::Con,
+                    dst,
+                    attr: Expr::from(ExprTup::from(vec![ExprTerm::Val(val)])),
+                    arg: Expr::default(),
+                    span: Span::default(),
+                }
+            }
+            _ if curr < width => {
+                let zero = self.zero(resize(&self.ty[id], width - curr));
+                let arg = vec![
+                    self.term(id, curr),
+                    ExprTerm::Var(zero, resize(&self.ty[id], width - curr)),
+                ];
+                InstrWire {
+                    op: OpWire::Cat,
+                    dst,
+                    attr: Expr::default(),
+                    arg: Expr::from(ExprTup::from(arg)),
+                    span: Span::default(),
+                }
+            }
+            _ => {
+                let hi = i64::try_from(width - 1)?;
+                InstrWire {
+                    op: OpWire::Ext,
+                    dst,
+                    attr: Expr::from(ExprTup::from(vec![ExprTerm::Val(0), ExprTerm::Val(hi)])),
+                    arg: Expr::from(ExprTup::from(vec![self.term(id, curr)])),
+                    span: Span::default(),
+                }
+            }
+        };
+        self.body.push(Instr::from(instr));
+        self.coerced.insert(key, res.to_string());
+        Ok(self.term_as(&res, id, width))
+    }
+    fn rewrite(&mut self, instr: &Instr) -> Result<(), Error> {
+        let arg: Vec<ExprTerm> = instr.arg().clone().into();
+        let width = self.arg_width(instr)?;
+        let mut new_arg: Vec<ExprTerm> = Vec::new();
+        for (t, w) in arg.iter().zip(width) {
+            let id = t.get_id()?;
+            if self.ty.get(&id).map_or(false, is_scalar_int) {
+                new_arg.push(self.coerce(&id, w)?);
+            } else {
+                new_arg.push(t.clone());
+            }
+        }
+        let mut res = instr.clone();
+        let arg = match instr.arg() {
+            Expr::Term(_) if new_arg.len() == 1 => Expr::from(new_arg[0].clone()),
+            _ => Expr::from(ExprTup::from(new_arg)),
+        };
+        res.set_arg(arg);
+        let dst = instr.dst().get_id(0)?;
+        if let Some(width) = self.width.get(&dst).copied() {
+            res.set_dst(Expr::from(self.term(&dst, width)));
+            if let Instr::Wire(wire) = &mut res {
+                if wire.op() == &OpWire::Ext {
+                    let lo = wire.attr().get_val(0)?;
+                    let hi = lo + i64::try_from(width)? - 1;
+                    wire.attr =
+                        Expr::from(ExprTup::from(vec![ExprTerm::Val(lo), ExprTerm::Val(hi)]));
+                }
+            }
+        }
+        self.body.push(res);
+        Ok(())
+    }
+}
+
+// narrow scalar integer values to the bits they need, rounded up to a power of
+// two so instruction selection can use smaller patterns. Values used at their
+// original width are zero extended, and outputs keep their type, so the def
+// stays bit-exact
+pub fn narrow_try_from_def(def: &Def) -> Result<Def, Error> {
+    let bits = bits_try_from_def(def)?;
+    let output: Vec<ExprTerm> = def.output().clone().into();
+    let mut output_set: HashSet<Id> = HashSet::new();
+    for term in output {
+        output_set.insert(term.get_id()?);
+    }
+    let mut narrow = Narrow::new(def)?;
+    for instr in def.body() {
+        let id = instr.dst().get_id(0)?;
+        if let Some(k) = bits.get(&id) {
+            let width = (*k).max(2).next_power_of_two();
+            if is_narrowable(instr) && !output_set.contains(&id) && width < narrow.orig(&id) {
+                narrow.width.insert(id, width);
+            }
+        }
+    }
+    narrow.widen_const(def)?;
+    for instr in def.body() {
+        narrow.rewrite(instr)?;
+    }
+    let mut res = def.clone();
+    res.set_body(narrow.body);
+    Ok(res)
+}
+
+pub fn narrow_try_from_prog(prog: &Prog) -> Result<Prog, Error> {
+    let mut res = Prog::default();
+    for (name, def) in prog.def() {
+        res.insert(name, narrow_try_from_def(def)?);
+    }
+    Ok(res)
+}
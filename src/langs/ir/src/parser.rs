@@ -1,15 +1,23 @@
 use crate::ast::*;
+use crate::elab::{elab_try_from_gen_defs, GenDef, GenExpr, GenInstr, GenTerm, Range};
 use crate::errors::Error;
 use crate::infer;
+use diag::path_from;
 use io::read_to_string;
 use pest_consume::match_nodes;
 use pest_consume::Error as PestError;
 use pest_consume::Parser as PestParser;
 use std::path::Path;
+use std::rc::Rc;
 use std::str::FromStr;
 
 pub type ParseResult<T> = std::result::Result<T, PestError<Rule>>;
-type Node<'i> = pest_consume::Node<'i, Rule, ()>;
+// nodes carry the path of the file they come from
+type Node<'i> = pest_consume::Node<'i, Rule, Option<Rc<str>>>;
+
+fn span(input: &Node) -> Span {
+    Span::new(input.as_span(), input.user_data().clone())
+}
 
 const _GRAMMAR: &str = include_str!("syntax.pest");
 
@@ -31,7 +39,7 @@ impl Parser {
         let val = i64::from_str_radix(input.as_str(), 2);
         match val {
             Ok(v) => Ok(ExprTerm::Val(v)),
-            Err(_) => panic!("Error: parsing {} as bin i64", input.as_str()),
+            Err(_) => Err(input.error(format!("{} does not fit in i64", input.as_str()))),
         }
     }
 
@@ -39,7 +47,7 @@ impl Parser {
         let val = i64::from_str_radix(input.as_str(), 16);
         match val {
             Ok(v) => Ok(ExprTerm::Val(v)),
-            Err(_) => panic!("Error: parsing {} as hex i64", input.as_str()),
+            Err(_) => Err(input.error(format!("{} does not fit in i64", input.as_str()))),
         }
     }
 
@@ -47,7 +55,7 @@ impl Parser {
         let val = input.as_str().parse::<i64>();
         match val {
             Ok(v) => Ok(ExprTerm::Val(v)),
-            Err(_) => panic!("Error: parsing {} as dec i64", input.as_str()),
+            Err(_) => Err(input.error(format!("{} does not fit in i64", input.as_str()))),
         }
     }
 
@@ -64,7 +72,7 @@ impl Parser {
         let ty = Ty::from_str(input.as_str());
         match ty {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -72,7 +80,7 @@ impl Parser {
         let prim = Prim::from_str(input.as_str());
         match prim {
             Ok(p) => Ok(p),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -84,25 +92,60 @@ impl Parser {
         ))
     }
 
-    fn tup_var(input: Node) -> ParseResult<ExprTup> {
+    fn index_val(input: Node) -> ParseResult<Id> {
+        Ok(input.as_str().to_string())
+    }
+
+    fn range(input: Node) -> ParseResult<Range> {
+        Ok(match_nodes!(
+            input.into_children();
+            [id(var), param_val(lo), param_val(hi)] => Range { var, lo, hi },
+        ))
+    }
+
+    fn gen_var(input: Node) -> ParseResult<GenTerm> {
+        Ok(match_nodes!(
+            input.into_children();
+            [range(range), tup_var(tup)] => GenTerm::For(range, tup),
+        ))
+    }
+
+    fn var_item(input: Node) -> ParseResult<GenTerm> {
+        Ok(match_nodes!(
+            input.into_children();
+            [var(var)] => GenTerm::Term(var),
+            [gen_var(gen)] => gen,
+        ))
+    }
+
+    fn tup_var(input: Node) -> ParseResult<Vec<GenTerm>> {
         Ok(match_nodes!(
             input.into_children();
-            [var(vars)..] => ExprTup{ term: vars.collect() },
+            [var_item(items)..] => items.collect(),
+        ))
+    }
+
+    fn param_val(input: Node) -> ParseResult<ExprTerm> {
+        Ok(match_nodes!(
+            input.into_children();
+            [val(val)] => val,
+            [id(id)] => ExprTerm::Var(id, Ty::Any),
+            [index_val(id)] => ExprTerm::Var(id, Ty::Any),
         ))
     }
 
     fn tup_val(input: Node) -> ParseResult<ExprTup> {
         Ok(match_nodes!(
             input.into_children();
-            [val(vals)..] => ExprTup{ term: vals.collect() },
+            [param_val(vals)..] => ExprTup{ term: vals.collect() },
         ))
     }
 
-    fn io(input: Node) -> ParseResult<Expr> {
+    fn io(input: Node) -> ParseResult<GenExpr> {
         Ok(match_nodes!(
             input.into_children();
-            [var(var)] => Expr::from(var),
-            [tup_var(tup)] => Expr::from(tup),
+            [var(var)] => GenExpr::Term(var),
+            [tup_var(tup)] => GenExpr::Tup(tup),
         ))
     }
 
@@ -110,7 +153,7 @@ impl Parser {
         let op = OpPrim::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -118,137 +161,193 @@ impl Parser {
         let op = OpWire::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
     fn op_call(input: Node) -> ParseResult<OpCall> {
-        let op = OpCall::from_str(input.as_str());
+        let node = input.clone();
+        let (id, param) = match_nodes!(
+            input.into_children();
+            [id(id)] => (id, ExprTup::default()),
+            [id(id), tup_val(param)] => (id, param),
+        );
+        let op = OpCall::from_str(&id);
         match op {
-            Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Ok(mut t) => {
+                t.set_param(param.term().clone());
+                Ok(t)
+            }
+            Err(m) => Err(node.error(m)),
         }
     }
 
-    fn instr(input: Node) -> ParseResult<Instr> {
-        Ok(match_nodes!(
+    fn instr(input: Node) -> ParseResult<GenInstr> {
+        let span = span(&input);
+        let mut instr = match_nodes!(
             input.into_children();
-            [io(dst), op_prim(op), io(arg)] => Instr::from(InstrPrim {
+            [io(dst), op_prim(op), io(arg)] => GenInstr::Instr(Instr::from(InstrPrim {
                 op,
-                dst,
+                dst: Expr::default(),
                 attr: Expr::default(),
-                arg,
+                arg: Expr::default(),
                 prim: Prim::Any,
-            }),
-            [io(dst), op_prim(op), tup_val(attr), io(arg)] => Instr::from(InstrPrim {
+                span: Span::default(),
+            }), dst, arg),
+            [io(dst), op_prim(op), tup_val(attr), io(arg)] => GenInstr::Instr(Instr::from(InstrPrim {
                 op,
-                dst,
+                dst: Expr::default(),
                 attr: Expr::from(attr),
-                arg,
+                arg: Expr::default(),
                 prim: Prim::Any,
-            }),
-            [io(dst), op_prim(op), io(arg), prim(prim)] => Instr::from(InstrPrim {
+                span: Span::default(),
+            }), dst, arg),
+            [io(dst), op_prim(op), io(arg), prim(prim)] => GenInstr::Instr(Instr::from(InstrPrim {
                 op,
-                dst,
+                dst: Expr::default(),
                 attr: Expr::default(),
-                arg,
+                arg: Expr::default(),
                 prim,
-            }),
-            [io(dst), op_prim(op), tup_val(attr), io(arg), prim(prim)] => Instr::from(InstrPrim {
+                span: Span::default(),
+            }), dst, arg),
+            [io(dst), op_prim(op), tup_val(attr), io(arg), prim(prim)] => GenInstr::Instr(Instr::from(InstrPrim {
                 op,
-                dst,
+                dst: Expr::default(),
                 attr: Expr::from(attr),
-                arg,
+                arg: Expr::default(),
                 prim,
-            }),
-            [io(dst), op_wire(op), tup_val(attr)] => Instr::from(InstrWire {
+                span: Span::default(),
+            }), dst, arg),
+            [io(dst), op_wire(op), tup_val(attr)] => GenInstr::Instr(Instr::from(InstrWire {
                 op,
-                dst,
+                dst: Expr::default(),
                 attr: Expr::from(attr),
                 arg: Expr::default(),
-            }),
-            [io(dst), op_wire(op), io(arg)] => Instr::from(InstrWire {
+                span: Span::default(),
+            }), dst, GenExpr::default()),
+            [io(dst), op_wire(op), io(arg)] => GenInstr::Instr(Instr::from(InstrWire {
                 op,
-                dst,
+                dst: Expr::default(),
                 attr: Expr::default(),
-                arg,
-            }),
-            [io(dst), op_wire(op), tup_val(attr), io(arg)] => Instr::from(InstrWire {
+                arg: Expr::default(),
+                span: Span::default(),
+            }), dst, arg),
+            [io(dst), op_wire(op), tup_val(attr), io(arg)] => GenInstr::Instr(Instr::from(InstrWire {
                 op,
-                dst,
+                dst: Expr::default(),
                 attr: Expr::from(attr),
-                arg,
-            }),
-            [io(dst), op_call(op)] => Instr::from(InstrCall {
+                arg: Expr::default(),
+                span: Span::default(),
+            }), dst, arg),
+            [io(dst), op_call(op)] => GenInstr::Instr(Instr::from(InstrCall {
                 op,
-                dst,
+                dst: Expr::default(),
                 arg: Expr::default(),
-            }),
-            [io(dst), op_call(op), io(arg)] => Instr::from(InstrCall {
+                span: Span::default(),
+            }), dst, GenExpr::default()),
+            [io(dst), op_call(op), io(arg)] => GenInstr::Instr(Instr::from(InstrCall {
                 op,
-                dst,
-                arg,
-            }),
+                dst: Expr::default(),
+                arg: Expr::default(),
+                span: Span::default(),
+            }), dst, arg),
+        );
+        if let GenInstr::Instr(instr, _, _) = &mut instr {
+            instr.set_span(span);
+        }
+        Ok(instr)
+    }
+
+    fn gen_instr(input: Node) -> ParseResult<GenInstr> {
+        Ok(match_nodes!(
+            input.into_children();
+            [range(range)] => GenInstr::For(range, Vec::new()),
+            [range(range), body(body)] => GenInstr::For(range, body),
         ))
     }
 
-    fn body(input: Node) -> ParseResult<Vec<Instr>> {
+    fn body_item(input: Node) -> ParseResult<GenInstr> {
         Ok(match_nodes!(
             input.into_children();
-            [instr(instr)..] => instr.collect(),
+            [instr(instr)] => instr,
+            [gen_instr(gen)] => gen,
         ))
     }
 
-    fn sig(input: Node) -> ParseResult<Sig> {
+    fn body(input: Node) -> ParseResult<Vec<GenInstr>> {
         Ok(match_nodes!(
             input.into_children();
-            [id(id)] => Sig {
+            [body_item(items)..] => items.collect(),
+        ))
+    }
+
+    fn param(input: Node) -> ParseResult<Vec<Id>> {
+        Ok(match_nodes!(
+            input.into_children();
+            [id(ids)..] => ids.collect(),
+        ))
+    }
+
+    fn sig(input: Node) -> ParseResult<GenDef> {
+        let span = span(&input);
+        let def = match_nodes!(
+            input.into_children();
+            [id(id)] => GenDef {
                 id,
-                input: Expr::default(),
-                output: Expr::default(),
+                ..GenDef::default()
             },
-            [id(id), io(output)] => Sig {
+            [id(id), io(output)] => GenDef {
                 id,
-                input: Expr::default(),
                 output,
+                ..GenDef::default()
             },
-            [id(id), io(input), io(output)] => Sig {
+            [id(id), io(input), io(output)] => GenDef {
                 id,
                 input,
                 output,
+                ..GenDef::default()
             },
-        ))
+            [id(id), param(param)] => GenDef {
+                id,
+                param,
+                ..GenDef::default()
+            },
+            [id(id), param(param), io(output)] => GenDef {
+                id,
+                param,
+                output,
+                ..GenDef::default()
+            },
+            [id(id), param(param), io(input), io(output)] => GenDef {
+                id,
+                param,
+                input,
+                output,
+                ..GenDef::default()
+            },
+        );
+        Ok(GenDef { span, ..def })
     }
 
-    fn def(input: Node) -> ParseResult<Def> {
+    fn def(input: Node) -> ParseResult<GenDef> {
         Ok(match_nodes!(
             input.into_children();
-            [sig(sig)] => Def {
-                sig,
-                body: Vec::new(),
-            },
-            [sig(sig), body(body)] => Def {
-                sig,
+            [sig(sig)] => sig,
+            [sig(sig), body(body)] => GenDef {
                 body,
+                ..sig
             },
         ))
     }
 
-    fn prog(input: Node) -> ParseResult<Prog> {
+    fn prog(input: Node) -> ParseResult<Vec<GenDef>> {
         Ok(match_nodes!(
             input.into_children();
-            [def(def)..] => {
-                let mut prog = Prog::default();
-                let defs: Vec<Def> = def.collect();
-                for d in defs {
-                    prog.insert(&d.id(), d.clone());
-                }
-                prog
-            }
+            [def(def)..] => def.collect(),
         ))
     }
 
-    fn file(input: Node) -> ParseResult<Prog> {
+    fn file(input: Node) -> ParseResult<Vec<GenDef>> {
         Ok(match_nodes!(
             input.into_children();
             [prog(prog), _] => prog,
@@ -257,14 +356,22 @@ impl Parser {
 }
 
 impl Parser {
-    pub fn parse_from_str(input_str: &str) -> Result<Prog, Error> {
-        let inputs = Parser::parse(Rule::file, input_str)?;
+    fn parse_with_path(input_str: &str, path: Option<Rc<str>>) -> Result<Prog, Error> {
+        let inputs = Parser::parse_with_userdata(Rule::file, input_str, path)?;
         let input = inputs.single()?;
-        let prog = Parser::file(input)?;
+        let gen = Parser::file(input)?;
+        let prog = elab_try_from_gen_defs(&gen)?;
         Ok(infer::type_try_from_prog(&prog))
     }
+    pub fn parse_from_str(input_str: &str) -> Result<Prog, Error> {
+        Parser::parse_with_path(input_str, None)
+    }
     pub fn parse_from_file<P: AsRef<Path>>(path: P) -> Result<Prog, Error> {
-        let content = read_to_string(path);
-        Parser::parse_from_str(&content)
+        let content = read_to_string(&path);
+        let path = path_from(&path);
+        match Parser::parse_with_path(&content, Some(path.clone())) {
+            Err(Error::Parser(e)) => Err(Error::Parser(e.with_path(&path))),
+            res => res,
+        }
     }
 }
@@ -0,0 +1,653 @@
+use crate::ast::*;
+use crate::clock::{domain_env_from_def, domain_map_from_def, Domain, DEFAULT_CLOCK};
+use crate::dfg::{comb_arg, topo_order_try_from_def};
+use crate::errors::Error;
+use crate::inline::Namer;
+use crate::interp::{eval_comb_try_from_instr, Env, Value};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Pipe {
+    // max number of combinational operations between registers
+    Depth(u64),
+    // number of register stages added to the critical path
+    Stages(u64),
+}
+
+// wires and registers do not add logic
+fn cost(instr: &Instr) -> u64 {
+    match instr {
+        Instr::Wire(_) => 0,
+        Instr::Prim(prim) if prim.is_reg() => 0,
+        _ => 1,
+    }
+}
+
+fn arg_id(instr: &Instr) -> Vec<Id> {
+    let arg: Vec<ExprTerm> = instr.arg().clone().into();
+    arg.iter().filter_map(|t| t.id()).collect()
+}
+
+struct Graph<'a> {
+    def: &'a Def,
+    index: HashMap<Id, usize>,
+    succ: Vec<Vec<usize>>,
+    pred: Vec<Vec<usize>>,
+    // strongly connected components in topological order
+    comp: Vec<Vec<usize>>,
+    // position of every instr in combinational order
+    rank: Vec<usize>,
+}
+
+impl<'a> Graph<'a> {
+    fn new(def: &'a Def) -> Result<Self, Error> {
+        let mut index: HashMap<Id, usize> = HashMap::new();
+        for (i, instr) in def.body().iter().enumerate() {
+            let dst: Vec<ExprTerm> = instr.dst().clone().into();
+            for term in dst {
+                index.insert(term.get_id()?, i);
+            }
+        }
+        let len = def.body().len();
+        let mut succ: Vec<Vec<usize>> = vec![Vec::new(); len];
+        let mut pred: Vec<Vec<usize>> = vec![Vec::new(); len];
+        for (i, instr) in def.body().iter().enumerate() {
+            for id in arg_id(instr) {
+                if let Some(from) = index.get(&id) {
+                    succ[*from].push(i);
+                    pred[i].push(*from);
+                }
+            }
+        }
+        let mut rank = vec![0; len];
+        for (r, i) in topo_order_try_from_def(def)?.into_iter().enumerate() {
+            rank[i] = r;
+        }
+        let mut graph = Graph {
+            def,
+            index,
+            succ,
+            pred,
+            comp: Vec::new(),
+            rank,
+        };
+        graph.comp = graph.components();
+        Ok(graph)
+    }
+    // Kosaraju, the second pass over predecessors visits components in
+    // topological order
+    fn components(&self) -> Vec<Vec<usize>> {
+        let len = self.succ.len();
+        let mut done = vec![false; len];
+        let mut finish: Vec<usize> = Vec::new();
+        for start in 0..len {
+            if done[start] {
+                continue;
+            }
+            done[start] = true;
+            let mut stack: Vec<(usize, usize)> = vec![(start, 0)];
+            while let Some((node, next)) = stack.pop() {
+                if let Some(s) = self.succ[node].get(next).copied() {
+                    stack.push((node, next + 1));
+                    if !done[s] {
+                        done[s] = true;
+                        stack.push((s, 0));
+                    }
+                } else {
+                    finish.push(node);
+                }
+            }
+        }
+        let mut comp_of: Vec<Option<usize>> = vec![None; len];
+        let mut comp: Vec<Vec<usize>> = Vec::new();
+        for start in finish.into_iter().rev() {
+            if comp_of[start].is_some() {
+                continue;
+            }
+            let mut member: Vec<usize> = Vec::new();
+            let mut stack = vec![start];
+            comp_of[start] = Some(comp.len());
+            while let Some(node) = stack.pop() {
+                member.push(node);
+                for p in &self.pred[node] {
+                    if comp_of[*p].is_none() {
+                        comp_of[*p] = Some(comp.len());
+                        stack.push(*p);
+                    }
+                }
+            }
+            member.sort_by_key(|m| self.rank[*m]);
+            comp.push(member);
+        }
+        comp
+    }
+    fn is_cyclic(&self, comp: &[usize]) -> bool {
+        comp.len() > 1 || self.pred[comp[0]].contains(&comp[0])
+    }
+    // logic depth from the output of every instr to the next register or output
+    fn after(&self) -> Vec<u64> {
+        let len = self.succ.len();
+        let mut order: Vec<usize> = (0..len).collect();
+        order.sort_by_key(|i| std::cmp::Reverse(self.rank[*i]));
+        let mut after = vec![0; len];
+        for i in order {
+            for s in &self.succ[i] {
+                let next = &self.def.body()[*s];
+                let is_comb = comb_arg(next)
+                    .iter()
+                    .filter_map(|t| t.id())
+                    .any(|id| self.index.get(&id) == Some(&i));
+                if is_comb {
+                    after[i] = after[i].max(after[*s] + cost(next));
+                }
+            }
+        }
+        after
+    }
+    // stage and logic depth of every instr, values in a cycle share a stage
+    // because adding registers to a loop changes its behavior
+    fn schedule(&self, max: Option<u64>) -> (Vec<u64>, Vec<u64>) {
+        let len = self.succ.len();
+        let mut stage = vec![0; len];
+        let mut depth = vec![0; len];
+        for comp in &self.comp {
+            let member: HashSet<usize> = comp.iter().copied().collect();
+            let mut curr = 0;
+            for m in comp {
+                for p in &self.pred[*m] {
+                    if !member.contains(p) {
+                        curr = curr.max(stage[*p]);
+                    }
+                }
+            }
+            for m in comp {
+                let instr = &self.def.body()[*m];
+                let mut arrive = 0;
+                for term in comb_arg(instr) {
+                    if let Some(p) = term.id().and_then(|id| self.index.get(&id)) {
+                        if member.contains(p) || stage[*p] == curr {
+                            arrive = arrive.max(depth[*p]);
+                        }
+                    }
+                }
+                stage[*m] = curr;
+                depth[*m] = arrive + cost(instr);
+                let split = max.map_or(false, |max| arrive > 0 && depth[*m] > max);
+                if split && !self.is_cyclic(comp) {
+                    stage[*m] = curr + 1;
+                    depth[*m] = cost(instr);
+                }
+            }
+        }
+        (stage, depth)
+    }
+}
+
+// registers in front of the instr at index are replaced by one register after
+// it, which starts and resets at the values the instr computes from theirs
+struct Move {
+    index: usize,
+    reg: Vec<usize>,
+    attr: Vec<i64>,
+}
+
+fn reg_value(prim: &InstrPrim, index: usize) -> Result<i64, Error> {
+    match prim.attr().idx(index).or_else(|| prim.attr().idx(0)) {
+        Some(term) => term.get_val(),
+        None => Ok(0),
+    }
+}
+
+// register values are single attributes, so every lane must be the same
+fn attr_try_from_value(value: &Value) -> Option<i64> {
+    let lanes = value.lanes();
+    if lanes.iter().all(|v| *v == lanes[0]) {
+        i64::try_from(lanes[0]).ok()
+    } else {
+        None
+    }
+}
+
+// registers can move forward across logic whose arguments are all registers
+// or constants, when the registers share enable and clock and feed nothing
+// else. Moving pays off when the logic after the registers is deeper than the
+// target and than the logic in front of them
+fn move_try_from_instr(
+    graph: &Graph,
+    index: usize,
+    output: &HashSet<Id>,
+    max: u64,
+    depth: &[u64],
+    after: &[u64],
+) -> Result<Option<Move>, Error> {
+    let instr = &graph.def.body()[index];
+    let is_logic = match instr {
+        Instr::Prim(prim) => !matches!(prim.op(), OpPrim::Reg | OpPrim::Rom | OpPrim::Ram),
+        _ => false,
+    };
+    if !is_logic {
+        return Ok(None);
+    }
+    let mut reg: Vec<usize> = Vec::new();
+    let mut ctrl: Option<Vec<ExprTerm>> = None;
+    let mut before = 0;
+    let mut init = Env::new();
+    let mut value = Env::new();
+    for id in arg_id(instr) {
+        let p = match graph.index.get(&id) {
+            Some(p) => *p,
+            None => return Ok(None),
+        };
+        let from = &graph.def.body()[p];
+        match from {
+            Instr::Wire(wire) if wire.op() == &OpWire::Con => {
+                let con = eval_comb_try_from_instr(from, &Env::new())?;
+                init.insert(id.to_string(), con.clone());
+                value.insert(id, con);
+            }
+            Instr::Prim(prim) if prim.is_reg() => {
+                let arg: Vec<ExprTerm> = prim.arg().clone().into();
+                let rest = arg.get(1..).map(|r| r.to_vec());
+                if output.contains(&id)
+                    || graph.succ[p].iter().any(|s| *s != index)
+                    || rest.is_none()
+                    || (ctrl.is_some() && ctrl != rest)
+                {
+                    return Ok(None);
+                }
+                ctrl = rest;
+                if let Some(d) = arg[0].id().and_then(|a| graph.index.get(&a)) {
+                    before = before.max(depth[*d]);
+                }
+                let ty = from.dst().get_ty(0)?;
+                let length = ty.length().unwrap_or(1) as usize;
+                let lanes = |v: i64| vec![i128::from(v); length];
+                init.insert(id.to_string(), Value::new(ty, &lanes(reg_value(prim, 0)?)));
+                value.insert(id, Value::new(ty, &lanes(reg_value(prim, 1)?)));
+                if !reg.contains(&p) {
+                    reg.push(p);
+                }
+            }
+            _ => return Ok(None),
+        }
+    }
+    if reg.is_empty() || after[index] + cost(instr) <= max || after[index] <= before {
+        return Ok(None);
+    }
+    let init = attr_try_from_value(&eval_comb_try_from_instr(instr, &init)?);
+    let value = attr_try_from_value(&eval_comb_try_from_instr(instr, &value)?);
+    let attr = match (init, value) {
+        (Some(init), Some(value)) if init == value => vec![init],
+        (Some(init), Some(value)) => vec![init, value],
+        _ => return Ok(None),
+    };
+    Ok(Some(Move { index, reg, attr }))
+}
+
+fn body_try_from_move(graph: &Graph, m: &Move, namer: &mut Namer) -> Result<Vec<Instr>, Error> {
+    let body = graph.def.body();
+    let mut data: HashMap<Id, ExprTerm> = HashMap::new();
+    for r in &m.reg {
+        data.insert(
+            body[*r].dst().get_id(0)?,
+            body[*r].arg().get_term(0)?.clone(),
+        );
+    }
+    let first = match &body[m.reg[0]] {
+        Instr::Prim(prim) => prim.clone(),
+        _ => return Err(Error::new_pipe_error("retimed value is not a register")),
+    };
+    let mut res: Vec<Instr> = Vec::new();
+    for (i, instr) in body.iter().enumerate() {
+        if m.reg.contains(&i) {
+            continue;
+        }
+        if i != m.index {
+            res.push(instr.clone());
+            continue;
+        }
+        let dst = instr.dst().get_term(0)?.clone();
+        let ty = dst.get_ty()?.clone();
+        let new = namer.fresh(&dst.get_id()?);
+        let arg: Vec<ExprTerm> = instr.arg().clone().into();
+        let arg: Vec<ExprTerm> = arg
+            .into_iter()
+            .map(|t| t.id().and_then(|id| data.get(&id).cloned()).unwrap_or(t))
+            .collect();
+        let mut logic = instr.clone();
+        let arg = match instr.arg() {
+            Expr::Term(_) if arg.len() == 1 => Expr::from(arg[0].clone()),
+            _ => Expr::from(ExprTup::from(arg)),
+        };
+        logic.set_arg(arg);
+        logic.set_dst(Expr::from(ExprTerm::Var(new.to_string(), ty.clone())));
+        res.push(logic);
+        let mut term = vec![ExprTerm::Var(new, ty)];
+        let ctrl: Vec<ExprTerm> = first.arg().clone().into();
+        term.extend(ctrl.into_iter().skip(1));
+        let attr: Vec<ExprTerm> = m.attr.iter().map(|v| ExprTerm::Val(*v)).collect();
+        res.push(Instr::from(InstrPrim {
+            op: OpPrim::Reg,
+            dst: Expr::from(dst),
+            attr: Expr::from(ExprTup::from(attr)),
+            arg: Expr::from(ExprTup::from(term)),
+            prim: first.prim().clone(),
+            span: instr.span().clone(),
+        }));
+    }
+    Ok(res)
+}
+
+// move registers forward one instr at a time, outside of loops, until no
+// stage gets shallower. Cycle behavior does not change
+fn retime_try_from_def(def: &Def, max: u64) -> Result<Def, Error> {
+    let output: Vec<ExprTerm> = def.output().clone().into();
+    let mut output_set: HashSet<Id> = HashSet::new();
+    for term in output {
+        output_set.insert(term.get_id()?);
+    }
+    let mut namer = Namer::new(def);
+    let mut res = def.clone();
+    loop {
+        let body = {
+            let graph = Graph::new(&res)?;
+            let (_, depth) = graph.schedule(None);
+            let after = graph.after();
+            let mut found = None;
+            for comp in graph.comp.iter().filter(|c| !graph.is_cyclic(c)) {
+                found = move_try_from_instr(&graph, comp[0], &output_set, max, &depth, &after)?;
+                if found.is_some() {
+                    break;
+                }
+            }
+            match found {
+                Some(m) => body_try_from_move(&graph, &m, &mut namer)?,
+                None => break,
+            }
+        };
+        res.set_body(body);
+    }
+    Ok(res)
+}
+
+// clock of the values computed by every instr, registers and memories belong
+// to the domain of their clock and logic to the domain of its operands. Logic
+// driven only by inputs and constants belongs to the registers it feeds
+fn clock_map_from_def(def: &Def) -> Result<HashMap<Id, Domain>, Error> {
+    let clock = domain_map_from_def(def)?;
+    let mut res = domain_env_from_def(def, &clock)?;
+    for (id, clk) in &clock {
+        res.insert(id.to_string(), [clk.to_string()].iter().cloned().collect());
+    }
+    let mut down: HashMap<Id, Domain> = HashMap::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for instr in def.body() {
+            let dst = instr.dst().get_id(0)?;
+            let src = match clock.get(&dst) {
+                Some(clk) => [clk.to_string()].iter().cloned().collect(),
+                None => down.get(&dst).cloned().unwrap_or_default(),
+            };
+            for id in arg_id(instr) {
+                let entry = down.entry(id).or_default();
+                let len = entry.len();
+                entry.extend(src.iter().cloned());
+                changed |= entry.len() != len;
+            }
+        }
+    }
+    for (id, domain) in down {
+        let entry = res.entry(id).or_default();
+        if entry.is_empty() {
+            *entry = domain;
+        }
+    }
+    Ok(res)
+}
+
+// registers inserted in front of an instr run on its clock, the default clock
+// when the instr is not tied to any domain
+fn clock_term(def: &Def, id: &str, domain: Option<&Domain>) -> Result<Option<ExprTerm>, Error> {
+    let domain: Vec<&Id> = domain.into_iter().flatten().collect();
+    match domain.as_slice() {
+        [] => Ok(None),
+        [clock] if clock.as_str() == DEFAULT_CLOCK => Ok(None),
+        [clock] => Ok(Some(ExprTerm::Var(clock.to_string(), Ty::Clock))),
+        _ => {
+            let err = format!(
+                "{} in def {} is driven by more than one clock domain and can not be pipelined",
+                id,
+                def.id()
+            );
+            Err(Error::new_pipe_error(&err))
+        }
+    }
+}
+
+struct Delay {
+    namer: Namer,
+    en: Option<Id>,
+    delayed: HashMap<(Id, u64, Option<ExprTerm>), Id>,
+    body: Vec<Instr>,
+}
+
+impl Delay {
+    fn en(&mut self) -> Id {
+        if let Some(en) = &self.en {
+            en.to_string()
+        } else {
+            let en = self.namer.fresh("pipe_en");
+            self.body.push(Instr::from(InstrWire {
+                op: OpWire::Con,
+                dst: Expr::from(ExprTerm::Var(en.to_string(), Ty::Bool)),
+                attr: Expr::from(ExprTup::from(vec![ExprTerm::Val(1)])),
+                arg: Expr::default(),
+                span: Span::default(),
+            }));
+            self.en = Some(en.to_string());
+            en
+        }
+    }
+    fn reg(&mut self, dst: &str, arg: &ExprTerm, clock: Option<&ExprTerm>) -> Result<(), Error> {
+        let en = self.en();
+        let ty = arg.get_ty()?.clone();
+        let mut term = vec![arg.clone(), ExprTerm::Var(en, Ty::Bool)];
+        term.extend(clock.cloned());
+        self.body.push(Instr::from(InstrPrim {
+            op: OpPrim::Reg,
+            dst: Expr::from(ExprTerm::Var(dst.to_string(), ty.clone())),
+            attr: Expr::from(ExprTup::from(vec![ExprTerm::Val(0)])),
+            arg: Expr::from(ExprTup::from(term)),
+            prim: Prim::Any,
+            span: Span::default(),
+        }));
+        Ok(())
+    }
+    // chains of registers are shared by every use with the same delay and clock
+    fn delay(
+        &mut self,
+        term: &ExprTerm,
+        cycles: u64,
+        clock: Option<&ExprTerm>,
+    ) -> Result<ExprTerm, Error> {
+        if cycles == 0 {
+            return Ok(term.clone());
+        }
+        let id = term.get_id()?;
+        let key = (id.to_string(), cycles, clock.cloned());
+        if let Some(res) = self.delayed.get(&key) {
+            return Ok(ExprTerm::Var(res.to_string(), term.get_ty()?.clone()));
+        }
+        let prev = self.delay(term, cycles - 1, clock)?;
+        let res = self.namer.fresh(&format!("{}_d{}", id, cycles));
+        self.reg(&res, &prev, clock)?;
+        self.delayed.insert(key, res.to_string());
+        Ok(ExprTerm::Var(res, term.get_ty()?.clone()))
+    }
+    // delayed registers start at zero, so state in later stages only updates
+    // once the values reaching it come from real inputs. The valid bit of a
+    // stage is the constant enable delayed by the stage, it turns on after
+    // as many cycles and is shared by every use with the same clock
+    fn valid(
+        &mut self,
+        en: &ExprTerm,
+        stage: u64,
+        clock: Option<&ExprTerm>,
+    ) -> Result<ExprTerm, Error> {
+        let one = ExprTerm::Var(self.en(), Ty::Bool);
+        let valid = self.delay(&one, stage, clock)?;
+        let dst = ExprTerm::Var(self.namer.fresh("pipe_valid_en"), Ty::Bool);
+        self.body.push(Instr::from(InstrPrim {
+            op: OpPrim::And,
+            dst: Expr::from(dst.clone()),
+            attr: Expr::default(),
+            arg: Expr::from(ExprTup::from(vec![en.clone(), valid])),
+            prim: Prim::Any,
+            span: Span::default(),
+        }));
+        Ok(dst)
+    }
+}
+
+// add registers so that no stage has more logic than the target, every path
+// to the outputs gets the same number of registers so outputs are delayed by
+// the returned latency. Registers already in the def are first moved forward
+// into deep logic (see retime_try_from_def). Inserted registers run on the
+// clock of the instr they feed, clock arguments are never delayed. Registers
+// and memories in later stages are only enabled once that stage is valid
+pub fn pipe_try_from_def(def: &Def, pipe: &Pipe) -> Result<(Def, u64), Error> {
+    if def.body().iter().any(|i| i.is_call()) {
+        let err = format!(
+            "calls in def {} must be inlined before pipelining",
+            def.id()
+        );
+        return Err(Error::new_pipe_error(&err));
+    }
+    if matches!(pipe, Pipe::Depth(0) | Pipe::Stages(0)) {
+        let err = format!(
+            "pipelining def {} needs a depth or stages above 0",
+            def.id()
+        );
+        return Err(Error::new_pipe_error(&err));
+    }
+    let max = match pipe {
+        Pipe::Depth(max) => *max,
+        Pipe::Stages(num) => {
+            let (_, depth) = Graph::new(def)?.schedule(None);
+            let critical = depth.iter().copied().max().unwrap_or(0);
+            ((critical + num) / (num + 1)).max(1)
+        }
+    };
+    let retimed = retime_try_from_def(def, max)?;
+    let def = &retimed;
+    let graph = Graph::new(def)?;
+    let (stage, _) = graph.schedule(Some(max));
+    let clock = clock_map_from_def(def)?;
+    let stage_of = |id: &str| graph.index.get(id).map_or(0, |i| stage[*i]);
+    let output: Vec<ExprTerm> = def.output().clone().into();
+    let mut latency = 0;
+    for term in &output {
+        latency = latency.max(stage_of(&term.get_id()?));
+    }
+    // inputs can not be renamed, so outputs passing them through can not be delayed
+    for term in &output {
+        let id = term.get_id()?;
+        if latency > 0 && !graph.index.contains_key(&id) {
+            let err = format!(
+                "output {} of def {} is an input and can not be delayed by the latency of {}",
+                id,
+                def.id(),
+                latency
+            );
+            return Err(Error::new_pipe_error(&err));
+        }
+    }
+    let mut delay = Delay {
+        namer: Namer::new(def),
+        en: None,
+        delayed: HashMap::new(),
+        body: Vec::new(),
+    };
+    // outputs in earlier stages are renamed and delayed to the last one
+    let mut rename: HashMap<Id, Id> = HashMap::new();
+    for term in &output {
+        let id = term.get_id()?;
+        if graph.index.contains_key(&id) && stage_of(&id) < latency {
+            rename.insert(id.to_string(), delay.namer.fresh(&id));
+        }
+    }
+    for (i, instr) in def.body().iter().enumerate() {
+        let arg: Vec<ExprTerm> = instr.arg().clone().into();
+        let dst = instr.dst().get_term(0)?.clone();
+        let mut new_arg: Vec<ExprTerm> = Vec::new();
+        for term in arg {
+            match &term {
+                ExprTerm::Var(id, ty) if !ty.is_clock() => {
+                    let cycles = stage[i] - stage_of(id);
+                    let id = rename.get(id).unwrap_or(id);
+                    let term = ExprTerm::Var(id.to_string(), ty.clone());
+                    let clk = if cycles > 0 {
+                        let dst = dst.get_id()?;
+                        clock_term(def, &dst, clock.get(&dst))?
+                    } else {
+                        None
+                    };
+                    new_arg.push(delay.delay(&term, cycles, clk.as_ref())?);
+                }
+                _ => new_arg.push(term),
+            }
+        }
+        if let Instr::Prim(prim) = instr {
+            let en = match prim.op() {
+                OpPrim::Reg => 1,
+                OpPrim::Ram => 2,
+                _ => 0,
+            };
+            if en > 0 && stage[i] > 0 && en < new_arg.len() {
+                let clk = prim.clock().map(|c| ExprTerm::Var(c, Ty::Clock));
+                new_arg[en] = delay.valid(&new_arg[en], stage[i], clk.as_ref())?;
+            }
+        }
+        let mut instr = instr.clone();
+        let arg = match instr.arg() {
+            Expr::Term(_) if new_arg.len() == 1 => Expr::from(new_arg[0].clone()),
+            _ => Expr::from(ExprTup::from(new_arg)),
+        };
+        instr.set_arg(arg);
+        if let Some(new) = rename.get(&dst.get_id()?) {
+            instr.set_dst(Expr::from(ExprTerm::Var(
+                new.to_string(),
+                dst.get_ty()?.clone(),
+            )));
+        }
+        delay.body.push(instr);
+    }
+    for term in &output {
+        let id = term.get_id()?;
+        if let Some(new) = rename.get(&id) {
+            let cycles = latency - stage_of(&id);
+            let ty = graph.def.body()[graph.index[&id]].dst().get_ty(0)?.clone();
+            let clk = clock_term(def, &id, clock.get(&id))?;
+            let term = ExprTerm::Var(new.to_string(), ty);
+            let prev = delay.delay(&term, cycles - 1, clk.as_ref())?;
+            delay.reg(&id, &prev, clk.as_ref())?;
+        }
+    }
+    let mut res = def.clone();
+    res.set_body(delay.body);
+    Ok((res, latency))
+}
+
+// every def is pipelined on its own, so calls have to be inlined first
+pub fn pipe_try_from_prog(prog: &Prog, pipe: &Pipe) -> Result<(Prog, HashMap<Id, u64>), Error> {
+    let mut res = Prog::default();
+    let mut latency: HashMap<Id, u64> = HashMap::new();
+    for (name, def) in prog.def() {
+        let (def, num) = pipe_try_from_def(def, pipe)?;
+        res.insert(name, def);
+        latency.insert(name.to_string(), num);
+    }
+    Ok((res, latency))
+}
@@ -1,4 +1,5 @@
 use crate::ast::*;
+use crate::elab::{GenExpr, GenInstr, GenTerm, Range};
 use itertools::Itertools;
 use prettyprint::{block_with_braces, intersperse, PrettyHelper, PrettyPrint, RcDoc};
 
@@ -33,7 +34,37 @@ pub fn expr_attrs(expr: &Expr) -> RcDoc<()> {
     match expr {
         Expr::Term(_) => RcDoc::nil(),
         Expr::Tup(tup) if tup.is_empty() => RcDoc::nil(),
-        Expr::Tup(tup) => tup.to_doc().brackets(),
+        Expr::Tup(tup) => intersperse(
+            tup.term().iter().map(|n| term_names(n)),
+            RcDoc::text(",").append(RcDoc::space()),
+        )
+        .brackets(),
+    }
+}
+
+fn gen_term_doc(term: &GenTerm, names: bool) -> RcDoc<()> {
+    match term {
+        GenTerm::Term(t) if names => term_names(t),
+        GenTerm::Term(t) => t.to_doc(),
+        GenTerm::For(range, body) => range.to_doc().append(RcDoc::space()).append(
+            intersperse(
+                body.iter().map(|t| gen_term_doc(t, names)),
+                RcDoc::text(",").append(RcDoc::space()),
+            )
+            .braces(),
+        ),
+    }
+}
+
+pub fn gen_expr_names(expr: &GenExpr) -> RcDoc<()> {
+    match expr {
+        GenExpr::Term(term) => term_names(term),
+        GenExpr::Tup(tup) if tup.is_empty() => RcDoc::nil(),
+        GenExpr::Tup(tup) => intersperse(
+            tup.iter().map(|t| gen_term_doc(t, true)),
+            RcDoc::text(",").append(RcDoc::space()),
+        )
+        .parens(),
     }
 }
 
@@ -55,9 +86,13 @@ impl PrettyPrint for Ty {
         match self {
             Ty::Any => RcDoc::text("??"),
             Ty::Bool => RcDoc::text("bool"),
+            Ty::Clock => RcDoc::text("clock"),
             Ty::UInt(width) => RcDoc::text("u").append(RcDoc::as_string(width)),
             Ty::SInt(width) => RcDoc::text("i").append(RcDoc::as_string(width)),
             Ty::Vector(dtype, len) => dtype.to_doc().append(RcDoc::as_string(len).comps()),
+            Ty::UIntParam(width) => RcDoc::text("u").append(RcDoc::as_string(width).comps()),
+            Ty::SIntParam(width) => RcDoc::text("i").append(RcDoc::as_string(width).comps()),
+            Ty::VectorParam(dtype, len) => dtype.to_doc().append(RcDoc::as_string(len).comps()),
         }
     }
 }
@@ -92,9 +127,46 @@ impl PrettyPrint for Expr {
     }
 }
 
+impl PrettyPrint for Range {
+    fn to_doc(&self) -> RcDoc<()> {
+        RcDoc::text("for")
+            .append(RcDoc::space())
+            .append(RcDoc::as_string(&self.var))
+            .append(RcDoc::space())
+            .append(RcDoc::text("in"))
+            .append(RcDoc::space())
+            .append(term_names(&self.lo))
+            .append(RcDoc::text(".."))
+            .append(term_names(&self.hi))
+    }
+}
+
+impl PrettyPrint for GenExpr {
+    fn to_doc(&self) -> RcDoc<()> {
+        match self {
+            GenExpr::Term(term) => term.to_doc(),
+            GenExpr::Tup(tup) => intersperse(
+                tup.iter().map(|t| gen_term_doc(t, false)),
+                RcDoc::text(",").append(RcDoc::space()),
+            )
+            .parens(),
+        }
+    }
+}
+
 impl PrettyPrint for OpCall {
     fn to_doc(&self) -> RcDoc<()> {
-        RcDoc::as_string(self.op())
+        if self.param().is_empty() {
+            RcDoc::as_string(self.op())
+        } else {
+            RcDoc::as_string(self.op()).append(
+                intersperse(
+                    self.param().iter().map(|n| term_names(n)),
+                    RcDoc::text(",").append(RcDoc::space()),
+                )
+                .comps(),
+            )
+        }
     }
 }
 
@@ -187,6 +259,41 @@ impl PrettyPrint for Instr {
     }
 }
 
+impl PrettyPrint for GenInstr {
+    fn to_doc(&self) -> RcDoc<()> {
+        match self {
+            GenInstr::Instr(instr, dst, arg) => {
+                let (op, attr, prim) = match instr {
+                    Instr::Call(call) => (call.op().to_doc(), RcDoc::nil(), RcDoc::nil()),
+                    Instr::Wire(wire) => {
+                        (wire.op().to_doc(), expr_attrs(wire.attr()), RcDoc::nil())
+                    }
+                    Instr::Prim(prim) => (
+                        prim.op().to_doc(),
+                        expr_attrs(prim.attr()),
+                        RcDoc::space()
+                            .append(RcDoc::text("@"))
+                            .append(prim.prim().to_doc()),
+                    ),
+                };
+                dst.to_doc()
+                    .append(RcDoc::space())
+                    .append(RcDoc::text("="))
+                    .append(RcDoc::space())
+                    .append(op)
+                    .append(attr)
+                    .append(gen_expr_names(arg))
+                    .append(prim)
+                    .append(RcDoc::text(";"))
+            }
+            GenInstr::For(range, body) => block_with_braces(
+                range.to_doc(),
+                intersperse(body.iter().map(|i| i.to_doc()), RcDoc::hardline()),
+            ),
+        }
+    }
+}
+
 impl PrettyPrint for Sig {
     fn to_doc(&self) -> RcDoc<()> {
         RcDoc::text("def")
@@ -202,13 +309,36 @@ impl PrettyPrint for Sig {
 
 impl PrettyPrint for Def {
     fn to_doc(&self) -> RcDoc<()> {
-        let sig = self.sig().to_doc();
-        let body = intersperse(
-            self.body()
-                .iter()
-                .map(|i| i.to_doc().append(RcDoc::text(";"))),
-            RcDoc::hardline(),
-        );
+        let sig = if self.is_param() {
+            RcDoc::text("def")
+                .append(RcDoc::space())
+                .append(RcDoc::as_string(self.id()))
+                .append(
+                    intersperse(
+                        self.param().iter().map(RcDoc::as_string),
+                        RcDoc::text(",").append(RcDoc::space()),
+                    )
+                    .comps(),
+                )
+                .append(self.input().to_doc())
+                .append(RcDoc::space())
+                .append(RcDoc::text("->"))
+                .append(RcDoc::space())
+                .append(self.output().to_doc())
+        } else {
+            self.sig().to_doc()
+        };
+        // parametric defs print their body template
+        let body = if self.is_param() {
+            intersperse(self.gen().iter().map(|i| i.to_doc()), RcDoc::hardline())
+        } else {
+            intersperse(
+                self.body()
+                    .iter()
+                    .map(|i| i.to_doc().append(RcDoc::text(";"))),
+                RcDoc::hardline(),
+            )
+        };
         block_with_braces(sig, body)
     }
 }
@@ -0,0 +1,998 @@
+use crate::ast::*;
+use crate::errors::Error;
+use io::read_to_string;
+use serde::Deserialize;
+use serde_json::Value as Json;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+// netlists written by yosys with write_json after proc and opt, only the
+// parts used by the importer are deserialized
+#[derive(Deserialize, Debug)]
+struct Netlist {
+    #[serde(default)]
+    modules: BTreeMap<String, Module>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Module {
+    #[serde(default)]
+    attributes: BTreeMap<String, Json>,
+    #[serde(default)]
+    ports: BTreeMap<String, Port>,
+    #[serde(default)]
+    cells: BTreeMap<String, Cell>,
+    #[serde(default)]
+    netnames: BTreeMap<String, Net>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Port {
+    direction: String,
+    bits: Vec<Json>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Cell {
+    #[serde(rename = "type")]
+    ty: String,
+    #[serde(default)]
+    parameters: BTreeMap<String, Json>,
+    #[serde(default)]
+    port_directions: BTreeMap<String, String>,
+    #[serde(default)]
+    connections: BTreeMap<String, Vec<Json>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Net {
+    #[serde(default)]
+    hide_name: u64,
+    bits: Vec<Json>,
+    #[serde(default)]
+    attributes: BTreeMap<String, Json>,
+}
+
+// bits are either nets or constants, x and z are read as zero
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Bit {
+    Net(u64),
+    Const(bool),
+}
+
+// where the value of a net comes from
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Src {
+    Var(Id, u64),
+    Const(bool),
+}
+
+// contiguous bits of a value, used to build words out of nets
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Piece {
+    Var(Id, u64, u64),
+    Const(Vec<bool>),
+}
+
+// ports of a module as seen by its callers, with their yosys names
+#[derive(Clone, Debug, Default)]
+struct Iface {
+    input: Vec<(String, Ty)>,
+    output: Vec<(String, Ty)>,
+}
+
+fn yosys_error<T>(msg: &str) -> Result<T, Error> {
+    Err(Error::new_yosys_error(msg))
+}
+
+// yosys names start with a backslash when they come from the source and with
+// a dollar sign when they are generated
+fn sanitize(name: &str) -> Id {
+    let name = name.trim_start_matches('\\');
+    let mut res: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !res.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        res = format!("n{}", res);
+    }
+    res
+}
+
+fn bits_ty(width: u64) -> Ty {
+    if width == 1 {
+        Ty::Bool
+    } else {
+        Ty::SInt(width)
+    }
+}
+
+fn int_ty(width: u64, signed: bool) -> Ty {
+    if signed {
+        Ty::SInt(width)
+    } else {
+        Ty::UInt(width)
+    }
+}
+
+fn bit_try_from_json(json: &Json) -> Result<Bit, Error> {
+    match json {
+        Json::Number(n) => match n.as_u64() {
+            Some(n) => Ok(Bit::Net(n)),
+            None => yosys_error(&format!("{} is not a valid net", n)),
+        },
+        Json::String(s) if s == "1" => Ok(Bit::Const(true)),
+        Json::String(s) if s == "0" || s == "x" || s == "z" => Ok(Bit::Const(false)),
+        _ => yosys_error(&format!("{} is not a valid bit", json)),
+    }
+}
+
+fn bits_try_from_json(json: &[Json]) -> Result<Vec<Bit>, Error> {
+    json.iter().map(bit_try_from_json).collect()
+}
+
+// parameters and attributes are binary strings, most significant bit first,
+// older versions of yosys write small values as numbers
+fn value_bits_try_from_json(json: &Json) -> Result<Vec<bool>, Error> {
+    match json {
+        Json::Number(n) => match n.as_u64() {
+            Some(n) => Ok((0..64).map(|i| (n >> i) & 1 == 1).collect()),
+            None => yosys_error(&format!("{} is not a valid value", n)),
+        },
+        Json::String(s) if !s.is_empty() && s.chars().all(|c| "01xz".contains(c)) => {
+            Ok(s.chars().rev().map(|c| c == '1').collect())
+        }
+        _ => yosys_error(&format!("{} is not a valid value", json)),
+    }
+}
+
+fn value_try_from_json(json: &Json) -> Result<u64, Error> {
+    let bits = value_bits_try_from_json(json)?;
+    if bits.iter().skip(64).any(|b| *b) {
+        return yosys_error(&format!("{} does not fit in 64 bits", json));
+    }
+    Ok(bits
+        .iter()
+        .take(64)
+        .enumerate()
+        .fold(0, |acc, (i, b)| acc | (u64::from(*b) << i)))
+}
+
+// value of bits interpreted with the signedness of ty, bits from 63 up
+// have to repeat the sign for the value to fit in an attribute
+fn const_try_from_bits(bits: &[bool], ty: &Ty) -> Result<i64, Error> {
+    let sign = ty.is_signed() && bits.last().copied().unwrap_or(false);
+    if bits.iter().skip(63).any(|b| *b != sign) {
+        return yosys_error(&format!("constant of {} does not fit in 64 bits", ty));
+    }
+    let mut val: i64 = 0;
+    for (i, b) in bits.iter().enumerate().take(63) {
+        if *b != sign {
+            val |= 1 << i;
+        }
+    }
+    Ok(if sign { !val } else { val })
+}
+
+fn extend(bits: &[Bit], width: usize, signed: bool) -> Vec<Bit> {
+    let mut res: Vec<Bit> = bits.iter().take(width).cloned().collect();
+    let pad = if signed {
+        bits.last().cloned().unwrap_or(Bit::Const(false))
+    } else {
+        Bit::Const(false)
+    };
+    while res.len() < width {
+        res.push(pad);
+    }
+    res
+}
+
+impl Cell {
+    fn conn(&self, port: &str) -> Result<Vec<Bit>, Error> {
+        match self.connections.get(port) {
+            Some(bits) => bits_try_from_json(bits),
+            None => yosys_error(&format!("{} cell does not connect port {}", self.ty, port)),
+        }
+    }
+    fn param(&self, name: &str) -> Result<u64, Error> {
+        match self.parameters.get(name) {
+            Some(json) => value_try_from_json(json),
+            None => yosys_error(&format!(
+                "{} cell does not have parameter {}",
+                self.ty, name
+            )),
+        }
+    }
+    fn param_bits(&self, name: &str) -> Result<Vec<bool>, Error> {
+        match self.parameters.get(name) {
+            Some(json) => value_bits_try_from_json(json),
+            None => yosys_error(&format!(
+                "{} cell does not have parameter {}",
+                self.ty, name
+            )),
+        }
+    }
+    fn is_signed(&self, name: &str) -> Result<bool, Error> {
+        Ok(self.parameters.contains_key(name) && self.param(name)? != 0)
+    }
+    fn is_output(&self, port: &str) -> bool {
+        self.port_directions
+            .get(port)
+            .map_or(false, |d| d == "output")
+    }
+}
+
+impl Module {
+    fn is_top(&self) -> bool {
+        self.attributes
+            .get("top")
+            .map_or(false, |v| value_try_from_json(v).map_or(false, |v| v != 0))
+    }
+    fn is_blackbox(&self) -> bool {
+        self.attributes.contains_key("blackbox")
+    }
+}
+
+// output ports of cells, calls use the ports of the callee
+fn cell_outputs(netlist: &Netlist, cell: &Cell) -> Vec<String> {
+    match netlist.modules.get(&cell.ty) {
+        Some(module) => module
+            .ports
+            .iter()
+            .filter(|(_, p)| p.direction == "output")
+            .map(|(name, _)| name.to_string())
+            .collect(),
+        None => cell
+            .connections
+            .keys()
+            .filter(|port| cell.is_output(port))
+            .cloned()
+            .collect(),
+    }
+}
+
+// nets used as clocks of registers, memories and callees
+fn clock_nets(
+    clock: &HashMap<String, HashSet<String>>,
+    module: &Module,
+) -> Result<HashSet<u64>, Error> {
+    let mut nets: HashSet<u64> = HashSet::new();
+    for cell in module.cells.values() {
+        let mut ports: Vec<String> = Vec::new();
+        if cell.connections.contains_key("CLK") && cell.ty.starts_with('$') {
+            ports.push("CLK".to_string());
+        }
+        if cell.ty == "$mem" || cell.ty == "$mem_v2" {
+            ports.push("WR_CLK".to_string());
+        }
+        if let Some(callee) = clock.get(&cell.ty) {
+            ports.extend(callee.iter().cloned());
+        }
+        for port in ports {
+            for bit in cell.conn(&port)? {
+                if let Bit::Net(n) = bit {
+                    nets.insert(n);
+                }
+            }
+        }
+    }
+    Ok(nets)
+}
+
+// clocks must be module inputs, the ports carrying them are typed as clocks
+fn clock_ports(netlist: &Netlist) -> Result<HashMap<String, HashSet<String>>, Error> {
+    let mut clock: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (name, module) in &netlist.modules {
+            let nets = clock_nets(&clock, module)?;
+            let mut ports: HashSet<String> = HashSet::new();
+            for (port, p) in &module.ports {
+                let bits = bits_try_from_json(&p.bits)?;
+                if bits
+                    .iter()
+                    .any(|b| matches!(b, Bit::Net(n) if nets.contains(n)))
+                {
+                    ports.insert(port.to_string());
+                }
+            }
+            let entry = clock.entry(name.to_string()).or_default();
+            if *entry != ports {
+                *entry = ports;
+                changed = true;
+            }
+        }
+    }
+    Ok(clock)
+}
+
+fn iface_try_from_module(module: &Module, clock: &HashSet<String>) -> Result<Iface, Error> {
+    let mut iface = Iface::default();
+    for (name, port) in &module.ports {
+        let width = port.bits.len() as u64;
+        if width == 0 {
+            return yosys_error(&format!("port {} does not have any bits", name));
+        }
+        let ty = if clock.contains(name) {
+            if width != 1 || port.direction != "input" {
+                let err = format!("clock {} must be a single bit input", name);
+                return yosys_error(&err);
+            }
+            Ty::Clock
+        } else {
+            bits_ty(width)
+        };
+        match port.direction.as_str() {
+            "input" => iface.input.push((name.to_string(), ty)),
+            "output" => iface.output.push((name.to_string(), ty)),
+            dir => return yosys_error(&format!("{} ports are not supported", dir)),
+        }
+    }
+    Ok(iface)
+}
+
+struct Importer<'a> {
+    netlist: &'a Netlist,
+    iface: &'a HashMap<String, Iface>,
+    defs: &'a HashMap<String, Id>,
+    def: Id,
+    src: HashMap<u64, Src>,
+    env: HashMap<Id, Ty>,
+    name: HashMap<Vec<Bit>, Id>,
+    reserved: HashSet<Id>,
+    init: HashMap<Vec<Bit>, Vec<bool>>,
+    out: HashMap<(String, String), ExprTerm>,
+    body: Vec<Instr>,
+    count: u64,
+}
+
+impl<'a> Importer<'a> {
+    fn new(
+        netlist: &'a Netlist,
+        iface: &'a HashMap<String, Iface>,
+        defs: &'a HashMap<String, Id>,
+        def: &str,
+        module: &Module,
+    ) -> Result<Self, Error> {
+        // source names of nets, ports take precedence over other aliases
+        let port: HashSet<Id> = module.ports.keys().map(|id| sanitize(id)).collect();
+        let mut name: HashMap<Vec<Bit>, Id> = HashMap::new();
+        let mut init: HashMap<Vec<Bit>, Vec<bool>> = HashMap::new();
+        for (id, net) in &module.netnames {
+            let bits = bits_try_from_json(&net.bits)?;
+            if let Some(val) = net.attributes.get("init") {
+                init.insert(bits.clone(), value_bits_try_from_json(val)?);
+            }
+            if net.hide_name == 0 && !port.contains(&sanitize(id)) {
+                name.entry(bits).or_insert_with(|| sanitize(id));
+            }
+        }
+        for (id, port) in &module.ports {
+            name.insert(bits_try_from_json(&port.bits)?, sanitize(id));
+        }
+        let reserved = name.values().cloned().collect();
+        Ok(Importer {
+            netlist,
+            iface,
+            defs,
+            def: def.to_string(),
+            src: HashMap::new(),
+            env: HashMap::new(),
+            name,
+            reserved,
+            init,
+            out: HashMap::new(),
+            body: Vec::new(),
+            count: 0,
+        })
+    }
+    fn error<T>(&self, cell: &str, msg: &str) -> Result<T, Error> {
+        let err = format!("{}, in cell {} of module {}", msg, cell, self.def);
+        yosys_error(&err)
+    }
+    fn declare(&mut self, name: &str, ty: &Ty) -> Result<ExprTerm, Error> {
+        if self.env.insert(name.to_string(), ty.clone()).is_some() {
+            let err = format!("{} is defined more than once in module {}", name, self.def);
+            return yosys_error(&err);
+        }
+        Ok(ExprTerm::Var(name.to_string(), ty.clone()))
+    }
+    fn fresh(&mut self, ty: &Ty) -> ExprTerm {
+        let mut id = format!("t{}", self.count);
+        while self.env.contains_key(&id) || self.reserved.contains(&id) {
+            self.count += 1;
+            id = format!("t{}", self.count);
+        }
+        self.count += 1;
+        self.env.insert(id.to_string(), ty.clone());
+        ExprTerm::Var(id, ty.clone())
+    }
+    // values named after their nets when yosys kept the name
+    fn named(&mut self, bits: &[Bit], ty: &Ty) -> Result<ExprTerm, Error> {
+        match self.name.get(bits).cloned() {
+            Some(id) if !self.env.contains_key(&id) => self.declare(&id, ty),
+            _ => Ok(self.fresh(ty)),
+        }
+    }
+    fn drive(&mut self, bits: &[Bit], term: &ExprTerm) -> Result<(), Error> {
+        let id = term.get_id()?;
+        for (i, bit) in bits.iter().enumerate() {
+            if let Bit::Net(n) = bit {
+                if self
+                    .src
+                    .insert(*n, Src::Var(id.to_string(), i as u64))
+                    .is_some()
+                {
+                    let err = format!("net {} has more than one driver in module {}", n, self.def);
+                    return yosys_error(&err);
+                }
+            }
+        }
+        Ok(())
+    }
+    fn src(&self, bit: &Bit) -> Result<Src, Error> {
+        match bit {
+            Bit::Const(b) => Ok(Src::Const(*b)),
+            Bit::Net(n) => match self.src.get(n) {
+                Some(src) => Ok(src.clone()),
+                None => yosys_error(&format!("net {} has no driver in module {}", n, self.def)),
+            },
+        }
+    }
+    fn pieces(&self, bits: &[Bit]) -> Result<Vec<Piece>, Error> {
+        let mut res: Vec<Piece> = Vec::new();
+        for bit in bits {
+            let src = self.src(bit)?;
+            match (res.last_mut(), src) {
+                (Some(Piece::Var(id, _, hi)), Src::Var(v, i)) if *id == v && *hi + 1 == i => {
+                    *hi = i;
+                }
+                // constants are split to fit in attributes
+                (Some(Piece::Const(val)), Src::Const(b)) if val.len() < 32 => val.push(b),
+                (_, Src::Var(v, i)) => res.push(Piece::Var(v, i, i)),
+                (_, Src::Const(b)) => res.push(Piece::Const(vec![b])),
+            }
+        }
+        Ok(res)
+    }
+    fn push_wire(&mut self, op: OpWire, dst: &ExprTerm, attr: Vec<i64>, arg: Vec<ExprTerm>) {
+        self.body.push(Instr::from(InstrWire {
+            op,
+            dst: Expr::from(dst.clone()),
+            attr: Expr::from(ExprTup::from(
+                attr.into_iter()
+                    .map(ExprTerm::Val)
+                    .collect::<Vec<ExprTerm>>(),
+            )),
+            arg: Expr::from(ExprTup::from(arg)),
+            span: Span::default(),
+        }));
+    }
+    fn push_prim(&mut self, op: OpPrim, dst: &ExprTerm, attr: Vec<i64>, arg: Vec<ExprTerm>) {
+        self.body.push(Instr::from(InstrPrim {
+            op,
+            dst: Expr::from(dst.clone()),
+            attr: Expr::from(ExprTup::from(
+                attr.into_iter()
+                    .map(ExprTerm::Val)
+                    .collect::<Vec<ExprTerm>>(),
+            )),
+            arg: Expr::from(ExprTup::from(arg)),
+            prim: Prim::Any,
+            span: Span::default(),
+        }));
+    }
+    fn con(&mut self, dst: &ExprTerm, val: &[bool]) -> Result<(), Error> {
+        let val = const_try_from_bits(val, dst.get_ty()?)?;
+        self.push_wire(OpWire::Con, dst, vec![val], vec![]);
+        Ok(())
+    }
+    fn var_width(&self, id: &str) -> u64 {
+        self.env.get(id).and_then(|ty| ty.width()).unwrap_or(0)
+    }
+    // value of a piece, whole values are used as they are
+    fn piece_term(&mut self, piece: &Piece) -> Result<ExprTerm, Error> {
+        match piece {
+            Piece::Var(id, 0, hi) if *hi + 1 == self.var_width(id) => {
+                Ok(ExprTerm::Var(id.to_string(), self.env[id].clone()))
+            }
+            Piece::Var(id, lo, hi) => {
+                let dst = self.fresh(&bits_ty(hi - lo + 1));
+                let arg = ExprTerm::Var(id.to_string(), self.env[id].clone());
+                self.push_wire(OpWire::Ext, &dst, vec![*lo as i64, *hi as i64], vec![arg]);
+                Ok(dst)
+            }
+            Piece::Const(val) => {
+                let dst = self.fresh(&bits_ty(val.len() as u64));
+                self.con(&dst, val)?;
+                Ok(dst)
+            }
+        }
+    }
+    // define dst from the values driving bits
+    fn assign(&mut self, dst: &ExprTerm, bits: &[Bit]) -> Result<(), Error> {
+        let pieces = self.pieces(bits)?;
+        let ty = dst.get_ty()?.clone();
+        match pieces.as_slice() {
+            [Piece::Var(id, 0, hi)] if *hi + 1 == self.var_width(id) && self.env[id] == ty => {
+                let arg = ExprTerm::Var(id.to_string(), ty.clone());
+                self.push_wire(OpWire::Id, dst, vec![], vec![arg]);
+            }
+            _ if ty.is_clock() => {
+                let err = format!("clock {} must be a module input", dst.get_id()?);
+                return yosys_error(&err);
+            }
+            [Piece::Var(id, lo, hi)] if *lo != 0 || *hi + 1 != self.var_width(id) => {
+                let arg = ExprTerm::Var(id.to_string(), self.env[id].clone());
+                self.push_wire(OpWire::Ext, dst, vec![*lo as i64, *hi as i64], vec![arg]);
+            }
+            [Piece::Const(val)] => self.con(dst, val)?,
+            _ => {
+                let mut arg: Vec<ExprTerm> = Vec::new();
+                for piece in &pieces {
+                    arg.push(self.piece_term(piece)?);
+                }
+                self.push_wire(OpWire::Cat, dst, vec![], arg);
+            }
+        }
+        Ok(())
+    }
+    // bits as a value of type ty, existing values are reused when possible
+    fn word(&mut self, bits: &[Bit], ty: &Ty) -> Result<ExprTerm, Error> {
+        if let [Piece::Var(id, 0, hi)] = self.pieces(bits)?.as_slice() {
+            if *hi + 1 == self.var_width(id) && &self.env[id] == ty {
+                return Ok(ExprTerm::Var(id.to_string(), ty.clone()));
+            }
+        }
+        if ty.is_clock() {
+            return yosys_error(&format!(
+                "clocks in module {} must be module inputs",
+                self.def
+            ));
+        }
+        let dst = self.fresh(ty);
+        self.assign(&dst, bits)?;
+        Ok(dst)
+    }
+    // results that do not have the type of the cell output are resized into it
+    fn result(&mut self, out: &ExprTerm, ty: &Ty) -> ExprTerm {
+        if out.get_ty().map_or(false, |t| t == ty) {
+            out.clone()
+        } else {
+            self.fresh(ty)
+        }
+    }
+    // truncate or zero-extend res into out
+    fn resize(&mut self, out: &ExprTerm, res: &ExprTerm) -> Result<(), Error> {
+        if out == res {
+            return Ok(());
+        }
+        let id = res.get_id()?;
+        let width = self.var_width(&id);
+        let out_width = self.var_width(&out.get_id()?);
+        let mut pieces = vec![Piece::Var(id, 0, width.min(out_width) - 1)];
+        if out_width > width {
+            pieces.push(Piece::Const(vec![false; (out_width - width) as usize]));
+        }
+        let mut arg: Vec<ExprTerm> = Vec::new();
+        for piece in &pieces {
+            arg.push(self.piece_term(piece)?);
+        }
+        self.push_wire(OpWire::Cat, out, vec![], arg);
+        Ok(())
+    }
+    fn output(&self, cell: &str, port: &str) -> Result<ExprTerm, Error> {
+        match self.out.get(&(cell.to_string(), port.to_string())) {
+            Some(term) => Ok(term.clone()),
+            None => self.error(cell, &format!("port {} is not connected", port)),
+        }
+    }
+    fn operand(
+        &mut self,
+        cell: &Cell,
+        port: &str,
+        width: u64,
+        signed: bool,
+        ty: &Ty,
+    ) -> Result<ExprTerm, Error> {
+        let bits = extend(&cell.conn(port)?, width as usize, signed);
+        self.word(&bits, ty)
+    }
+    fn constant(&mut self, ty: &Ty, val: &[bool]) -> Result<ExprTerm, Error> {
+        let dst = self.fresh(ty);
+        self.con(&dst, val)?;
+        Ok(dst)
+    }
+    fn prim(&mut self, op: OpPrim, ty: &Ty, arg: Vec<ExprTerm>) -> ExprTerm {
+        let dst = self.fresh(ty);
+        self.push_prim(op, &dst, vec![], arg);
+        dst
+    }
+    // single bit controls, inverted when they are active-low
+    fn control(&mut self, cell: &Cell, port: &str, polarity: &str) -> Result<ExprTerm, Error> {
+        let sig = self.word(&cell.conn(port)?, &Ty::Bool)?;
+        if cell.parameters.contains_key(polarity) && cell.param(polarity)? == 0 {
+            Ok(self.prim(OpPrim::Not, &Ty::Bool, vec![sig]))
+        } else {
+            Ok(sig)
+        }
+    }
+    // compare a value against all zeros or all ones
+    fn reduce(
+        &mut self,
+        cell: &Cell,
+        port: &str,
+        op: OpPrim,
+        ones: bool,
+        dst: &ExprTerm,
+    ) -> Result<(), Error> {
+        let bits = cell.conn(port)?;
+        let ty = bits_ty(bits.len() as u64);
+        let val = self.word(&bits, &ty)?;
+        let mask = self.constant(&ty, &vec![ones; bits.len()])?;
+        self.push_prim(op, dst, vec![], vec![val, mask]);
+        Ok(())
+    }
+    fn declare_cell(&mut self, name: &str, cell: &Cell) -> Result<(), Error> {
+        let callee = self
+            .iface
+            .get(&cell.ty)
+            .filter(|_| self.defs.contains_key(&cell.ty));
+        let memid = cell.parameters.get("MEMID").and_then(|id| id.as_str());
+        for port in cell_outputs(self.netlist, cell) {
+            let bits = match cell.connections.get(&port) {
+                Some(bits) => bits_try_from_json(bits)?,
+                None => Vec::new(),
+            };
+            let ty = match callee.and_then(|iface| iface.output.iter().find(|(p, _)| *p == port)) {
+                Some((_, ty)) => ty.clone(),
+                None if bits.is_empty() => continue,
+                None => bits_ty(bits.len() as u64),
+            };
+            // memories are named after the memory when the output is not named
+            let term = match memid.map(sanitize) {
+                Some(id) if !self.name.contains_key(&bits) && !self.env.contains_key(&id) => {
+                    self.declare(&id, &ty)?
+                }
+                _ => self.named(&bits, &ty)?,
+            };
+            self.drive(&bits, &term)?;
+            self.out.insert((name.to_string(), port), term);
+        }
+        Ok(())
+    }
+    fn import_call(&mut self, name: &str, cell: &Cell, def: &str) -> Result<(), Error> {
+        let iface = self.iface[&cell.ty].clone();
+        let mut arg: Vec<ExprTerm> = Vec::new();
+        for (port, ty) in &iface.input {
+            let bits = cell.conn(port)?;
+            arg.push(self.word(&bits, ty)?);
+        }
+        let mut dst: Vec<ExprTerm> = Vec::new();
+        for (port, _) in &iface.output {
+            dst.push(self.output(name, port)?);
+        }
+        let dst = match dst.as_slice() {
+            [term] => Expr::from(term.clone()),
+            _ => Expr::from(ExprTup::from(dst)),
+        };
+        self.body.push(Instr::from(InstrCall {
+            op: OpCall::new(def),
+            dst,
+            arg: Expr::from(ExprTup::from(arg)),
+            span: Span::default(),
+        }));
+        Ok(())
+    }
+    fn import_reg(&mut self, name: &str, cell: &Cell) -> Result<(), Error> {
+        if cell.param("CLK_POLARITY")? == 0 {
+            return self.error(name, "negative edge clocks are not supported");
+        }
+        let q = self.output(name, "Q")?;
+        let ty = q.get_ty()?.clone();
+        let width = ty.width().unwrap_or(1) as usize;
+        let clk = self.word(&cell.conn("CLK")?, &Ty::Clock)?;
+        let mut data = self.word(&cell.conn("D")?, &ty)?;
+        let mut en = if cell.connections.contains_key("EN") {
+            self.control(cell, "EN", "EN_POLARITY")?
+        } else {
+            self.constant(&Ty::Bool, &[true])?
+        };
+        // synchronous resets select the reset value on the data path
+        if cell.connections.contains_key("SRST") {
+            let srst = self.control(cell, "SRST", "SRST_POLARITY")?;
+            let mut val = cell.param_bits("SRST_VALUE")?;
+            val.resize(width, false);
+            let val = self.constant(&ty, &val)?;
+            data = self.prim(OpPrim::Mux, &ty, vec![srst.clone(), val, data]);
+            if cell.ty == "$sdffe" {
+                en = self.prim(OpPrim::Or, &Ty::Bool, vec![en, srst]);
+            }
+        }
+        let mut init = self.init.get(&cell.conn("Q")?).cloned().unwrap_or_default();
+        init.resize(width, false);
+        let init = const_try_from_bits(&init, &ty)?;
+        self.push_prim(OpPrim::Reg, &q, vec![init], vec![data, en, clk]);
+        Ok(())
+    }
+    // memories with one asynchronous read port and at most one write port
+    // sharing its address
+    fn import_mem(&mut self, name: &str, cell: &Cell) -> Result<(), Error> {
+        if cell.param("RD_PORTS")? != 1 {
+            return self.error(name, "memories must have a single read port");
+        }
+        if cell.param_bits("RD_CLK_ENABLE")?.iter().any(|b| *b) {
+            return self.error(name, "synchronous read ports are not supported");
+        }
+        if cell.parameters.contains_key("INIT") && cell.param_bits("INIT")?.iter().any(|b| *b) {
+            return self.error(name, "initial memory values are not supported, use --mmap");
+        }
+        let y = self.output(name, "RD_DATA")?;
+        let ty = y.get_ty()?.clone();
+        let addr_bits = cell.conn("RD_ADDR")?;
+        let addr = self.word(&addr_bits, &bits_ty(addr_bits.len() as u64))?;
+        match cell.param("WR_PORTS")? {
+            0 => self.push_prim(OpPrim::Rom, &y, vec![], vec![addr]),
+            1 => {
+                if !cell.param_bits("WR_CLK_ENABLE")?.iter().any(|b| *b)
+                    || cell.param("WR_CLK_POLARITY")? == 0
+                {
+                    return self.error(name, "write ports must be clocked on the rising edge");
+                }
+                if extend(&cell.conn("WR_ADDR")?, addr_bits.len(), false) != addr_bits {
+                    return self.error(name, "read and write ports must share the address");
+                }
+                let en_bits = cell.conn("WR_EN")?;
+                if en_bits.windows(2).any(|w| w[0] != w[1]) {
+                    return self.error(name, "write enables must be the same for all bits");
+                }
+                let data = self.word(&cell.conn("WR_DATA")?, &ty)?;
+                let en = self.word(&en_bits[..1], &Ty::Bool)?;
+                let clk = self.word(&cell.conn("WR_CLK")?, &Ty::Clock)?;
+                self.push_prim(OpPrim::Ram, &y, vec![], vec![addr, data, en, clk]);
+            }
+            _ => return self.error(name, "memories must have at most one write port"),
+        }
+        Ok(())
+    }
+    fn import_cell(&mut self, name: &str, cell: &Cell) -> Result<(), Error> {
+        if let Some(def) = self.defs.get(&cell.ty) {
+            return self.import_call(name, cell, def);
+        }
+        match cell.ty.as_str() {
+            "$add" | "$sub" | "$mul" | "$and" | "$or" | "$xor" => {
+                let y = self.output(name, "Y")?;
+                let ty = y.get_ty()?.clone();
+                let width = ty.width().unwrap_or(1);
+                let signed = cell.is_signed("A_SIGNED")? && cell.is_signed("B_SIGNED")?;
+                let a = self.operand(cell, "A", width, signed, &ty)?;
+                let b = self.operand(cell, "B", width, signed, &ty)?;
+                let op = match cell.ty.as_str() {
+                    "$add" => OpPrim::Add,
+                    "$sub" => OpPrim::Sub,
+                    "$mul" => OpPrim::Mul,
+                    "$and" => OpPrim::And,
+                    "$or" => OpPrim::Or,
+                    _ => OpPrim::Xor,
+                };
+                self.push_prim(op, &y, vec![], vec![a, b]);
+            }
+            "$not" | "$neg" | "$pos" => {
+                let y = self.output(name, "Y")?;
+                let ty = y.get_ty()?.clone();
+                let width = ty.width().unwrap_or(1);
+                let signed = cell.is_signed("A_SIGNED")?;
+                if cell.ty == "$pos" {
+                    let bits = extend(&cell.conn("A")?, width as usize, signed);
+                    return self.assign(&y, &bits);
+                }
+                let a = self.operand(cell, "A", width, signed, &ty)?;
+                if cell.ty == "$not" {
+                    self.push_prim(OpPrim::Not, &y, vec![], vec![a]);
+                } else {
+                    let zero = self.constant(&ty, &[false])?;
+                    self.push_prim(OpPrim::Sub, &y, vec![], vec![zero, a]);
+                }
+            }
+            "$mux" | "$pmux" => {
+                let y = self.output(name, "Y")?;
+                let ty = y.get_ty()?.clone();
+                let width = ty.width().unwrap_or(1) as usize;
+                let sel = cell.conn("S")?;
+                let b = cell.conn("B")?;
+                let mut acc = self.word(&cell.conn("A")?, &ty)?;
+                // parallel muxes are chained, select bits are one-hot
+                for (i, bit) in sel.iter().enumerate() {
+                    let s = self.word(&[*bit], &Ty::Bool)?;
+                    let val = self.word(&b[i * width..(i + 1) * width], &ty)?;
+                    let dst = if i + 1 == sel.len() {
+                        y.clone()
+                    } else {
+                        self.fresh(&ty)
+                    };
+                    self.push_prim(OpPrim::Mux, &dst, vec![], vec![s, val, acc]);
+                    acc = dst;
+                }
+                if sel.is_empty() {
+                    self.push_wire(OpWire::Id, &y, vec![], vec![acc]);
+                }
+            }
+            "$eq" | "$eqx" | "$ne" | "$nex" | "$lt" | "$le" | "$gt" | "$ge" => {
+                let y = self.output(name, "Y")?;
+                let width = cell.conn("A")?.len().max(cell.conn("B")?.len()) as u64;
+                let signed = cell.is_signed("A_SIGNED")? && cell.is_signed("B_SIGNED")?;
+                let (op, ty) = match cell.ty.as_str() {
+                    "$eq" | "$eqx" => (OpPrim::Eql, bits_ty(width)),
+                    "$ne" | "$nex" => (OpPrim::Neql, bits_ty(width)),
+                    "$lt" => (OpPrim::Lt, int_ty(width, signed)),
+                    "$le" => (OpPrim::Le, int_ty(width, signed)),
+                    "$gt" => (OpPrim::Gt, int_ty(width, signed)),
+                    _ => (OpPrim::Ge, int_ty(width, signed)),
+                };
+                let a = self.operand(cell, "A", width, signed, &ty)?;
+                let b = self.operand(cell, "B", width, signed, &ty)?;
+                let res = self.result(&y, &Ty::Bool);
+                self.push_prim(op, &res, vec![], vec![a, b]);
+                self.resize(&y, &res)?;
+            }
+            "$logic_not" | "$reduce_or" | "$reduce_bool" | "$reduce_and" => {
+                let y = self.output(name, "Y")?;
+                let res = self.result(&y, &Ty::Bool);
+                match cell.ty.as_str() {
+                    "$logic_not" => self.reduce(cell, "A", OpPrim::Eql, false, &res)?,
+                    "$reduce_and" => self.reduce(cell, "A", OpPrim::Eql, true, &res)?,
+                    _ => self.reduce(cell, "A", OpPrim::Neql, false, &res)?,
+                }
+                self.resize(&y, &res)?;
+            }
+            "$logic_and" | "$logic_or" => {
+                let y = self.output(name, "Y")?;
+                let a = self.fresh(&Ty::Bool);
+                self.reduce(cell, "A", OpPrim::Neql, false, &a)?;
+                let b = self.fresh(&Ty::Bool);
+                self.reduce(cell, "B", OpPrim::Neql, false, &b)?;
+                let op = if cell.ty == "$logic_and" {
+                    OpPrim::And
+                } else {
+                    OpPrim::Or
+                };
+                let res = self.result(&y, &Ty::Bool);
+                self.push_prim(op, &res, vec![], vec![a, b]);
+                self.resize(&y, &res)?;
+            }
+            "$shl" | "$sshl" | "$shr" | "$sshr" => {
+                let amount: Vec<bool> = cell
+                    .conn("B")?
+                    .iter()
+                    .map(|b| match b {
+                        Bit::Const(b) => Ok(*b),
+                        Bit::Net(_) => {
+                            self.error(name, "shifts by a variable amount are not supported")
+                        }
+                    })
+                    .collect::<Result<Vec<bool>, Error>>()?;
+                let amount = const_try_from_bits(&amount, &Ty::UInt(amount.len() as u64))?;
+                let y = self.output(name, "Y")?;
+                let signed = cell.is_signed("A_SIGNED")?;
+                let width = cell.conn("A")?.len().max(cell.conn("Y")?.len()) as u64;
+                let ty = bits_ty(width);
+                let a = self.operand(cell, "A", width, signed, &ty)?;
+                let op = match cell.ty.as_str() {
+                    "$shl" | "$sshl" => OpWire::Sll,
+                    "$sshr" if signed => OpWire::Sra,
+                    _ => OpWire::Srl,
+                };
+                let res = self.result(&y, &ty);
+                self.push_wire(op, &res, vec![amount], vec![a]);
+                self.resize(&y, &res)?;
+            }
+            "$dff" | "$dffe" | "$sdff" | "$sdffe" | "$sdffce" => self.import_reg(name, cell)?,
+            "$mem" | "$mem_v2" => self.import_mem(name, cell)?,
+            "$memrd" | "$memrd_v2" | "$memwr" | "$memwr_v2" | "$meminit" | "$meminit_v2" => {
+                return self.error(name, "memory ports must be merged with memory_collect");
+            }
+            _ => return self.error(name, &format!("unsupported yosys cell {}", cell.ty)),
+        }
+        Ok(())
+    }
+}
+
+fn def_try_from_module(
+    netlist: &Netlist,
+    iface: &HashMap<String, Iface>,
+    defs: &HashMap<String, Id>,
+    name: &str,
+    module: &Module,
+) -> Result<Def, Error> {
+    let id = &defs[name];
+    let port = &iface[name];
+    let mut imp = Importer::new(netlist, iface, defs, id, module)?;
+    let mut input: Vec<ExprTerm> = Vec::new();
+    for (p, ty) in &port.input {
+        let term = imp.declare(&sanitize(p), ty)?;
+        imp.drive(&bits_try_from_json(&module.ports[p].bits)?, &term)?;
+        input.push(term);
+    }
+    for (cell_name, cell) in &module.cells {
+        imp.declare_cell(cell_name, cell)?;
+    }
+    for (cell_name, cell) in &module.cells {
+        imp.import_cell(cell_name, cell)?;
+    }
+    // outputs driven by a value with the same name are used as they are
+    let mut output: Vec<ExprTerm> = Vec::new();
+    for (p, ty) in &port.output {
+        let bits = bits_try_from_json(&module.ports[p].bits)?;
+        let out = sanitize(p);
+        let whole = vec![Piece::Var(out.clone(), 0, bits.len() as u64 - 1)];
+        if imp.env.get(&out) == Some(ty) && imp.pieces(&bits)? == whole {
+            output.push(ExprTerm::Var(out, ty.clone()));
+        } else {
+            let term = imp.declare(&out, ty)?;
+            imp.assign(&term, &bits)?;
+            output.push(term);
+        }
+    }
+    let mut def = Def::default();
+    def.set_id(id);
+    def.sig.input = Expr::from(ExprTup::from(input));
+    def.sig.output = Expr::from(ExprTup::from(output));
+    def.set_body(imp.body);
+    def.sort_body()?;
+    Ok(def)
+}
+
+// the top module becomes main, it is either marked as top or the only module
+fn top_from_netlist(netlist: &Netlist) -> Result<String, Error> {
+    let module: Vec<(&String, &Module)> = netlist
+        .modules
+        .iter()
+        .filter(|(_, m)| !m.is_blackbox())
+        .collect();
+    if let Some((name, _)) = module.iter().find(|(_, m)| m.is_top()) {
+        Ok(name.to_string())
+    } else if module.len() == 1 {
+        Ok(module[0].0.to_string())
+    } else {
+        yosys_error("could not find the top module, run hierarchy -top <module> in yosys")
+    }
+}
+
+pub fn prog_try_from_str(input: &str) -> Result<Prog, Error> {
+    let netlist: Netlist =
+        serde_json::from_str(input).map_err(|e| Error::new_yosys_error(&e.to_string()))?;
+    let top = top_from_netlist(&netlist)?;
+    let clock = clock_ports(&netlist)?;
+    let mut iface: HashMap<String, Iface> = HashMap::new();
+    let mut defs: HashMap<String, Id> = HashMap::new();
+    for (name, module) in &netlist.modules {
+        if !module.is_blackbox() {
+            iface.insert(
+                name.to_string(),
+                iface_try_from_module(module, &clock[name])?,
+            );
+            let id = if *name == top {
+                "main".to_string()
+            } else {
+                sanitize(name)
+            };
+            defs.insert(name.to_string(), id);
+        }
+    }
+    let mut prog = Prog::default();
+    for (name, module) in &netlist.modules {
+        if !module.is_blackbox() {
+            let def = def_try_from_module(&netlist, &iface, &defs, name, module)?;
+            let id = def.id();
+            if prog.insert(&id, def).is_some() {
+                return yosys_error(&format!("more than one module is named {}", id));
+            }
+        }
+    }
+    Ok(prog)
+}
+
+pub fn prog_try_from_file<P: AsRef<Path>>(path: P) -> Result<Prog, Error> {
+    prog_try_from_str(&read_to_string(path))
+}
@@ -0,0 +1,112 @@
+use ir::ast::{Prog, Ty};
+use ir::builder::DefBuilder;
+use ir::check::type_check_prog;
+use ir::errors::Error;
+use ir::parser::Parser;
+use std::rc::Rc;
+
+#[test]
+fn counter() -> Result<(), Error> {
+    let mut b = DefBuilder::new("main");
+    let en = b.input("en", Ty::Bool)?;
+    let one = b.con(Ty::SInt(8), 1)?;
+    let cnt = b.reg_decl(Ty::SInt(8), 0)?;
+    let next = b.add(&cnt, &one)?;
+    b.reg_connect(&cnt, &next, &en)?;
+    b.output("y", &cnt)?;
+    let def = b.build()?;
+    let exp = Parser::parse_from_str(
+        r#"def main(en:bool) -> (y:i8) {
+            t0:i8 = const[1];
+            t1:i8 = reg[0](t2, en);
+            t2:i8 = add(t1, t0);
+            y:i8 = id(t1);
+        }"#,
+    )?;
+    assert_eq!(exp.get("main"), Some(&def));
+    Ok(())
+}
+
+#[test]
+fn round_trip() -> Result<(), Error> {
+    let mut b = DefBuilder::new("inc");
+    let a = b.input("a", Ty::UInt(4))?;
+    let t0 = b.con(Ty::UInt(4), 1)?;
+    let y = b.add(&a, &t0)?;
+    b.output("y", &y)?;
+    let inc = b.build()?;
+    let mut b = DefBuilder::new("main");
+    let a = b.input("a", Ty::UInt(4))?;
+    let c = b.input("c", Ty::Bool)?;
+    let res = b.call(&inc, &[&a])?;
+    let lo = b.ext(&res[0], 0, 1)?;
+    let hi = b.ext(&res[0], 2, 3)?;
+    let cat = b.cat(&[&hi, &lo])?;
+    let y = b.mux(&c, &cat, &a)?;
+    b.output("y", &y)?;
+    let main = b.build()?;
+    let mut prog = Prog::default();
+    prog.insert("inc", inc);
+    prog.insert("main", main);
+    type_check_prog(&prog)?;
+    let res = Parser::parse_from_str(&prog.to_string())?;
+    assert_eq!(res, prog);
+    Ok(())
+}
+
+#[test]
+fn width_mismatch() -> Result<(), Error> {
+    let mut b = DefBuilder::new("main");
+    let a = b.input("a", Ty::SInt(8))?;
+    let c = b.input("c", Ty::SInt(4))?;
+    let en = b.input("en", Ty::Bool)?;
+    assert!(matches!(b.add(&a, &c), Err(Error::Builder(_))));
+    assert!(matches!(b.ext(&a, 4, 8), Err(Error::Builder(_))));
+    assert!(matches!(b.con(Ty::SInt(4), 8), Err(Error::Builder(_))));
+    assert!(matches!(b.reg(&a, &c, 0), Err(Error::Builder(_))));
+    assert!(matches!(b.reg(&c, &en, 100), Err(Error::Builder(_))));
+    assert!(matches!(b.reg_decl(Ty::Bool, 2), Err(Error::Builder(_))));
+    assert!(matches!(b.input("a", Ty::Bool), Err(Error::Builder(_))));
+    Ok(())
+}
+
+#[test]
+fn vector_and_clock() -> Result<(), Error> {
+    let vec = Ty::Vector(Rc::new(Ty::SInt(8)), 4);
+    let mut b = DefBuilder::new("main");
+    let a = b.input("a", vec.clone())?;
+    let en = b.input("en", Ty::Bool)?;
+    let fast = b.input("fast", Ty::Clock)?;
+    let clk = b.id(&fast)?;
+    let one = b.con(vec.clone(), 1)?;
+    let sum = b.add(&a, &one)?;
+    let lo = b.ext(&sum, 0, 3)?;
+    let hi = b.ext(&sum, 4, 7)?;
+    let cat = b.cat(&[&hi, &lo])?;
+    let y = b.reg(&cat, &en, 0)?;
+    b.set_clock(&y, &clk)?;
+    b.output("y", &y)?;
+    assert!(matches!(b.add(&fast, &clk), Err(Error::Builder(_))));
+    assert!(matches!(b.set_clock(&sum, &clk), Err(Error::Builder(_))));
+    assert!(matches!(b.set_clock(&y, &en), Err(Error::Builder(_))));
+    let def = b.build()?;
+    let exp = Parser::parse_from_str(
+        r#"def main(a:i8<4>, en:bool, fast:clock) -> (y:i8<4>) {
+            t0:clock = id(fast);
+            t1:i8<4> = const[1];
+            t2:i8<4> = add(a, t1);
+            t3:i4<4> = ext[0, 3](t2);
+            t4:i4<4> = ext[4, 7](t2);
+            t5:i8<4> = cat(t4, t3);
+            t6:i8<4> = reg[0](t5, en, t0);
+            y:i8<4> = id(t6);
+        }"#,
+    )?;
+    type_check_prog(&exp)?;
+    assert_eq!(exp.get("main"), Some(&def));
+    let mut prog = Prog::default();
+    prog.insert("main", def);
+    let res = Parser::parse_from_str(&prog.to_string())?;
+    assert_eq!(res, prog);
+    Ok(())
+}
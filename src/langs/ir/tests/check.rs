@@ -0,0 +1,100 @@
+use ir::check::type_check_prog;
+use ir::errors::Error;
+use ir::parser::Parser;
+use std::fs;
+
+fn check(input: &str) -> Result<(), Error> {
+    let prog = Parser::parse_from_str(input)?;
+    type_check_prog(&prog)
+}
+
+fn is_type_error(res: Result<(), Error>) -> bool {
+    matches!(res, Err(Error::Type(_)))
+}
+
+#[test]
+fn examples() -> Result<(), Error> {
+    for entry in fs::read_dir("../../../examples/ir").unwrap() {
+        let path = entry.unwrap().path();
+        let prog = Parser::parse_from_file(&path)?;
+        type_check_prog(&prog)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn undefined_var() {
+    let res = check("def main(a:i8) -> (y:i8) { y:i8 = add(a, b); }");
+    assert!(is_type_error(res));
+}
+
+#[test]
+fn width_mismatch() {
+    let res = check("def main(a:i8, b:i4) -> (y:i8) { y:i8 = add(a, b); }");
+    assert!(is_type_error(res));
+}
+
+#[test]
+fn mux_arity() {
+    let res = check("def main(c:bool, a:i8) -> (y:i8) { y:i8 = mux(c, a); }");
+    assert!(is_type_error(res));
+}
+
+#[test]
+fn reg_arity() {
+    let res = check("def main(a:i8) -> (y:i8) { y:i8 = reg[0](a); }");
+    assert!(is_type_error(res));
+}
+
+#[test]
+fn reg_reset_value() {
+    let res = check("def main(a:i8, en:bool) -> (y:i8) { y:i8 = reg[0, 3](a, en); }");
+    assert!(res.is_ok());
+    let res = check("def main(a:i8, en:bool) -> (y:i8) { y:i8 = reg[0, 3, 1](a, en); }");
+    assert!(is_type_error(res));
+}
+
+#[test]
+fn reg_clock() {
+    let res = check("def main(a:i8, en:bool, c:clock) -> (y:i8) { y:i8 = reg[0](a, en, c); }");
+    assert!(res.is_ok());
+    let res = check("def main(a:i8, en:bool, c:bool) -> (y:i8) { y:i8 = reg[0](a, en, c); }");
+    assert!(is_type_error(res));
+    let res = check("def main(a:i8, c:clock) -> (y:i8) { y:i8 = reg[0](a, c); }");
+    assert!(is_type_error(res));
+}
+
+#[test]
+fn rom_clock() {
+    let res = check("def main(a:i8, c:clock) -> (y:i8) { y:i8 = rom(a, c) @bram; }");
+    assert!(res.is_ok());
+    let res = check("def main(a:i8, c:clock) -> (y:i8) { y:i8 = rom(a, c); }");
+    assert!(is_type_error(res));
+}
+
+#[test]
+fn clock_as_data() {
+    let res = check("def main(a:bool, c:clock) -> (y:bool) { y:bool = and(a, c); }");
+    assert!(is_type_error(res));
+}
+
+#[test]
+fn multiple_assign() {
+    let res = check("def main(a:i8, b:i8) -> (y:i8) { y:i8 = id(a); y:i8 = id(b); }");
+    assert!(is_type_error(res));
+}
+
+#[test]
+fn ext_range() {
+    let res = check("def main(a:i8) -> (y:i4) { y:i4 = ext[6, 9](a); }");
+    assert!(is_type_error(res));
+}
+
+#[test]
+fn error_points_to_instr() {
+    let res = check("def main(a:i8) -> (y:i8) {\n    y:i8 = add(a, b);\n}");
+    let msg = res.unwrap_err().to_string();
+    assert!(msg.contains("--> 2:5"));
+    assert!(msg.contains("2 |     y:i8 = add(a, b);"));
+    assert!(msg.contains("  |     ^^^^^^^^^^^^^^^^^"));
+}
@@ -0,0 +1,120 @@
+use ir::clock::{cdc_check_prog, domain_map_from_def, strip_clocks_from_def};
+use ir::errors::Error;
+use ir::parser::Parser;
+use std::path::Path;
+
+fn check(input: &str) -> Result<(), Error> {
+    let prog = Parser::parse_from_str(input)?;
+    cdc_check_prog(&prog)
+}
+
+fn is_cdc_error(res: Result<(), Error>) -> bool {
+    matches!(res, Err(Error::Cdc(_)))
+}
+
+#[test]
+fn synchronizer() -> Result<(), Error> {
+    let path = Path::new("../../../examples/ir/cdc_sync.ir");
+    let prog = Parser::parse_from_file(path)?;
+    cdc_check_prog(&prog)
+}
+
+#[test]
+fn domain_map() -> Result<(), Error> {
+    let path = Path::new("../../../examples/ir/cdc_sync.ir");
+    let prog = Parser::parse_from_file(path)?;
+    let map = domain_map_from_def(prog.get("main").unwrap())?;
+    assert_eq!(map["t1"], "fast");
+    assert_eq!(map["z"], "slow");
+    Ok(())
+}
+
+#[test]
+fn strip_clocks() -> Result<(), Error> {
+    let path = Path::new("../../../examples/ir/cdc_sync.ir");
+    let prog = Parser::parse_from_file(path)?;
+    let def = strip_clocks_from_def(prog.get("main").unwrap())?;
+    assert!(def
+        .body()
+        .iter()
+        .filter(|i| i.is_reg())
+        .all(|i| i.arg().tup().unwrap().term().len() == 2));
+    Ok(())
+}
+
+#[test]
+fn unsynchronized_data() {
+    let res = check(
+        "def main(a:i8, en:bool, f:clock, s:clock) -> (y:i8) {
+            t0:i8 = reg[0](a, en, f);
+            y:i8 = reg[0](t0, en, s);
+        }",
+    );
+    assert!(is_cdc_error(res));
+}
+
+#[test]
+fn crossing_through_logic() {
+    let res = check(
+        "def main(a:bool, en:bool, f:clock, s:clock) -> (y:bool) {
+            t0:bool = reg[0](a, en, f);
+            t1:bool = and(t0, a);
+            t2:bool = reg[0](t1, en, s);
+            y:bool = reg[0](t2, en, s);
+        }",
+    );
+    assert!(is_cdc_error(res));
+}
+
+#[test]
+fn synchronizer_fanout() {
+    let res = check(
+        "def main(a:bool, en:bool, f:clock, s:clock) -> (y:bool, z:bool) {
+            t0:bool = reg[0](a, en, f);
+            t1:bool = reg[0](t0, en, s);
+            y:bool = reg[0](t1, en, s);
+            z:bool = id(t1);
+        }",
+    );
+    assert!(is_cdc_error(res));
+}
+
+#[test]
+fn default_clock() {
+    let res = check(
+        "def main(a:i8, en:bool, s:clock) -> (y:i8) {
+            t0:i8 = reg[0](a, en);
+            y:i8 = reg[0](t0, en, s);
+        }",
+    );
+    assert!(is_cdc_error(res));
+}
+
+#[test]
+fn ram_crossing() {
+    let res = check(
+        "def main(a:i3, d:i8, en:bool, f:clock, s:clock) -> (y:i8) {
+            t0:i8 = reg[0](d, en, f);
+            y:i8 = ram(a, t0, en, s);
+        }",
+    );
+    assert!(is_cdc_error(res));
+}
+
+#[test]
+fn bram_read_crossing() {
+    let res = check(
+        "def main(a:i8, en:bool, f:clock, s:clock) -> (y:i8) {
+            t0:i8 = reg[0](a, en, f);
+            y:i8 = rom(t0) @bram;
+        }",
+    );
+    assert!(is_cdc_error(res));
+    let res = check(
+        "def main(a:i8, en:bool, f:clock, s:clock) -> (y:i8) {
+            t0:i8 = reg[0](a, en, f);
+            y:i8 = rom(t0, f) @bram;
+        }",
+    );
+    assert!(res.is_ok());
+}
@@ -0,0 +1,71 @@
+use ir::check::type_check_prog;
+use ir::cse::cse_try_from_prog;
+use ir::errors::Error;
+use ir::interp::{Env, Interp, Value};
+use ir::parser::Parser;
+
+const PROG: &str = r#"
+def main(a:i8, b:i8, en:bool) -> (y:i8, z:i8) {
+    t0:i8 = add(a, b);
+    t1:i8 = add(b, a);
+    t2:i8 = reg[0](t0, en);
+    t3:i8 = reg[0](t1, en);
+    t4:i8 = sub(t2, a);
+    t5:i8 = sub(t3, a);
+    t6:i8 = rom(a);
+    t7:i8 = rom(a);
+    y:i8 = add(t4, t6);
+    z:i8 = add(t5, t7);
+}
+"#;
+
+#[test]
+fn merge() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(PROG)?;
+    let (res, removed) = cse_try_from_prog(&prog)?;
+    type_check_prog(&res)?;
+    let exp = Parser::parse_from_str(
+        r#"def main(a:i8, b:i8, en:bool) -> (y:i8, z:i8) {
+            t0:i8 = add(a, b);
+            t2:i8 = reg[0](t0, en);
+            t4:i8 = sub(t2, a);
+            t6:i8 = rom(a);
+            t7:i8 = rom(a);
+            y:i8 = add(t4, t6);
+            z:i8 = add(t4, t7);
+        }"#,
+    )?;
+    assert_eq!(removed, 3);
+    assert_eq!(res.get("main"), exp.get("main"));
+    Ok(())
+}
+
+#[test]
+fn keep_output() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        r#"def main(a:i8, b:i8) -> (y:i8, z:i8) {
+            y:i8 = add(a, b);
+            z:i8 = add(a, b);
+        }"#,
+    )?;
+    let (res, removed) = cse_try_from_prog(&prog)?;
+    assert_eq!(removed, 0);
+    assert_eq!(res, prog);
+    Ok(())
+}
+
+#[test]
+fn same_behavior() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(PROG)?;
+    let (res, _) = cse_try_from_prog(&prog)?;
+    let mut exp = Interp::try_from_prog(&prog)?;
+    let mut res = Interp::try_from_prog(&res)?;
+    for i in 0..8 {
+        let mut input = Env::new();
+        input.insert("a".to_string(), Value::from(i));
+        input.insert("b".to_string(), Value::from(3 * i));
+        input.insert("en".to_string(), Value::from(i % 3 != 0));
+        assert_eq!(res.step(&input)?, exp.step(&input)?);
+    }
+    Ok(())
+}
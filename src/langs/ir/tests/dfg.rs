@@ -0,0 +1,85 @@
+use ir::check::type_check_prog;
+use ir::dfg::{comb_loop_from_def, topo_order_try_from_def};
+use ir::errors::Error;
+use ir::interp::{interp_try_from_prog, Env, Value};
+use ir::parser::Parser;
+use std::path::Path;
+
+#[test]
+fn fsm_3_order() -> Result<(), Error> {
+    let path = Path::new("../../../examples/ir/fsm_3.ir");
+    let prog = Parser::parse_from_file(path)?;
+    let main = prog.get("main").unwrap();
+    let order = topo_order_try_from_def(main)?;
+    assert_eq!(
+        order,
+        vec![0, 1, 2, 3, 13, 4, 5, 6, 7, 8, 9, 10, 11, 12, 14]
+    );
+    Ok(())
+}
+
+#[test]
+fn canonical_order() -> Result<(), Error> {
+    let path = Path::new("../../../examples/ir/tdot.ir");
+    let prog = Parser::parse_from_file(path)?;
+    let mut main = prog.get("main").unwrap().clone();
+    main.sort_body()?;
+    let exp = main.clone();
+    main.sort_body()?;
+    assert_eq!(main, exp);
+    Ok(())
+}
+
+#[test]
+fn comb_loop() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        r#"def main(a:i8, en:bool) -> (y:i8) {
+            t0:i8 = add(a, t2);
+            t1:i8 = reg[0](t0, en);
+            t2:i8 = add(t3, t1);
+            t3:i8 = id(t0);
+            y:i8 = id(t1);
+        }"#,
+    )?;
+    let main = prog.get("main").unwrap();
+    let res = comb_loop_from_def(main)?;
+    assert_eq!(
+        res,
+        Some(vec!["t0".to_string(), "t3".to_string(), "t2".to_string()])
+    );
+    Ok(())
+}
+
+#[test]
+fn reg_loop() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        r#"def main(a:i8, en:bool) -> (y:i8) {
+            t0:i8 = add(a, y);
+            y:i8 = reg[0](t0, en);
+        }"#,
+    )?;
+    let main = prog.get("main").unwrap();
+    assert_eq!(comb_loop_from_def(main)?, None);
+    Ok(())
+}
+
+#[test]
+fn bram_loop() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        r#"def main(a:i8) -> (y:i8) {
+            y:i8 = rom(t) @bram;
+            t:i8 = add(y, a);
+        }"#,
+    )?;
+    let main = prog.get("main").unwrap();
+    assert_eq!(comb_loop_from_def(main)?, None);
+    assert_eq!(topo_order_try_from_def(main)?, vec![0, 1]);
+    type_check_prog(&prog)?;
+    let input: Env = vec![("a".to_string(), Value::from(1))]
+        .into_iter()
+        .collect();
+    let res = interp_try_from_prog(&prog, &[input.clone(), input.clone(), input])?;
+    let y: Vec<Value> = res.iter().map(|o| o["y"].clone()).collect();
+    assert_eq!(y, vec![Value::from(0), Value::from(0), Value::from(0)]);
+    Ok(())
+}
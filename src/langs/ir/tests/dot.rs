@@ -0,0 +1,24 @@
+use ir::dot::dot_from_prog;
+use ir::errors::Error;
+use ir::parser::Parser;
+
+#[test]
+fn prim_and_edges() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        r#"def main(a:i8, b:i8, en:bool) -> (y:i8) {
+            t0:i8 = mul(a, b) @dsp;
+            y:i8 = reg[0](t0, en);
+        }"#,
+    )?;
+    let dot = dot_from_prog(&prog);
+    assert!(dot.starts_with("digraph {"));
+    assert!(dot.contains("subgraph \"cluster_main\""));
+    assert!(dot.contains("\"main/0\" [ label = \"t0:i8\\nmul\", fillcolor = orange ]"));
+    assert!(
+        dot.contains("\"main/1\" [ label = \"y:i8\\nreg\", fillcolor = white, peripheries = 2 ]")
+    );
+    assert!(dot.contains("\"main/in/a\" -> \"main/0\""));
+    assert!(dot.contains("\"main/0\" -> \"main/1\""));
+    assert!(dot.contains("\"main/1\" -> \"main/out/y\""));
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use ir::elab::{eval_index, IndexEnv};
+use ir::errors::Error;
+use ir::mono::mono_try_from_prog;
+use ir::parser::Parser;
+use std::path::Path;
+
+#[test]
+fn tadd_512() -> Result<(), Error> {
+    let res = Parser::parse_from_str(
+        r#"def main(en:bool, for i in 0..128 { a{i}:i8<4>, b{i}:i8<4> }) -> (for i in 0..128 { y{i}:i8<4> }) {
+            for i in 0..128 {
+                t{3*i}:i8<4> = reg[0](a{i}, en);
+                t{3*i+1}:i8<4> = reg[0](b{i}, en);
+                t{3*i+2}:i8<4> = add(t{3*i}, t{3*i+1});
+                y{i}:i8<4> = reg[0](t{3*i+2}, en);
+            }
+        }"#,
+    )?;
+    let exp = Parser::parse_from_file(Path::new("../../../examples/ir/tadd_512.ir"))?;
+    assert_eq!(res, exp);
+    Ok(())
+}
+
+#[test]
+fn ext_and_cat() -> Result<(), Error> {
+    let res = Parser::parse_from_str(
+        r#"def main(a:i128) -> (y:i128) {
+            for i in 0..16 {
+                t{i}:i8 = ext[{8*i}, {8*i+7}](a);
+            }
+            y:i128 = cat(for i in 0..16 { t{15-i} });
+        }"#,
+    )?;
+    let mut exp = String::from("def main(a:i128) -> (y:i128) {\n");
+    for i in 0..16 {
+        exp.push_str(&format!("t{}:i8 = ext[{}, {}](a);\n", i, 8 * i, 8 * i + 7));
+    }
+    let arg: Vec<String> = (0..16).map(|i| format!("t{}", 15 - i)).collect();
+    exp.push_str(&format!("y:i128 = cat({});\n}}", arg.join(", ")));
+    assert_eq!(res, Parser::parse_from_str(&exp)?);
+    Ok(())
+}
+
+#[test]
+fn nested() -> Result<(), Error> {
+    let res = Parser::parse_from_str(
+        r#"def main() -> (y:i8) {
+            for i in 0..2 {
+                for j in 0..{i+1} {
+                    c{i}_{j}:i8 = const[{(i + 1) * 10 + j}];
+                }
+                k{i}:i8 = const[i];
+            }
+            y:i8 = add(c1_0, c1_1);
+        }"#,
+    )?;
+    let exp = Parser::parse_from_str(
+        r#"def main() -> (y:i8) {
+            c0_0:i8 = const[10];
+            k0:i8 = const[0];
+            c1_0:i8 = const[20];
+            c1_1:i8 = const[21];
+            k1:i8 = const[1];
+            y:i8 = add(c1_0, c1_1);
+        }"#,
+    )?;
+    assert_eq!(res, exp);
+    Ok(())
+}
+
+#[test]
+fn index_errors() {
+    let res = Parser::parse_from_str("def main(for i in 0..2 { a{j}:i8 }) -> () {}");
+    assert!(matches!(res, Err(Error::Elab(_))));
+    let res = Parser::parse_from_str("def main(for i in 0..2 { a{i/0}:i8 }) -> () {}");
+    assert!(matches!(res, Err(Error::Elab(_))));
+}
+
+#[test]
+fn index_overflow() {
+    let env = IndexEnv::new();
+    for text in &[
+        "9223372036854775807+1",
+        "-9223372036854775807-2",
+        "4611686018427387904*2",
+        "(-9223372036854775807-1)/-1",
+        "(-9223372036854775807-1)%-1",
+        "-(-9223372036854775807-1)",
+    ] {
+        let res = eval_index(text, &env);
+        assert!(matches!(res, Err(Error::Elab(e)) if e.contains("overflow")));
+    }
+    let res = Parser::parse_from_str("def main(a{9223372036854775807+1}:i8) -> () {}");
+    assert!(matches!(res, Err(Error::Elab(_))));
+}
+
+#[test]
+fn param_bound() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        r#"def acc<N>(a:i8, b:i8) -> (y:i8) {
+            t0:i8 = id(a);
+            for i in 0..N {
+                t{i+1}:i8 = add(t{i}, b);
+            }
+            y:i8 = id(t{N});
+        }
+        def main(a:i8, b:i8) -> (y:i8) {
+            y:i8 = acc<3>(a, b);
+        }"#,
+    )?;
+    // parametric bodies are elaborated when specialized
+    assert_eq!(Parser::parse_from_str(&prog.to_string())?, prog);
+    let res = mono_try_from_prog(&prog)?;
+    let acc = res.get("acc_3").unwrap();
+    assert_eq!(acc.body().len(), 5);
+    assert_eq!(acc.body()[4].to_string(), "y:i8 = id(t3)");
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use ir::ast::ExprTerm;
+use ir::check::type_check_prog;
+use ir::dce::dce_try_from_prog;
+use ir::errors::Error;
+use ir::fold::fold_try_from_prog;
+use ir::interp::{Env, Interp, Value};
+use ir::parser::Parser;
+
+#[test]
+fn fold() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        r#"def main(a:i8, en:bool) -> (y:i8, z:i8) {
+            t0:i8 = const[3];
+            t1:i8 = const[4];
+            t2:i8 = add(t0, t1);
+            t3:i16 = cat(t2, t1);
+            t4:bool = eq(t2, t0);
+            t5:i8 = mux(t4, a, t2);
+            t6:i8 = sll[1](t5);
+            y:i8 = add(a, t6);
+            z:i8 = id(t2);
+        }"#,
+    )?;
+    let res = fold_try_from_prog(&prog)?;
+    let exp = Parser::parse_from_str(
+        r#"def main(a:i8, en:bool) -> (y:i8, z:i8) {
+            t0:i8 = const[3];
+            t1:i8 = const[4];
+            t2:i8 = const[7];
+            t3:i16 = const[1031];
+            t4:bool = const[0];
+            t6:i8 = const[14];
+            y:i8 = add(a, t6);
+            z:i8 = const[7];
+        }"#,
+    )?;
+    assert_eq!(res.get("main"), exp.get("main"));
+    Ok(())
+}
+
+#[test]
+fn merge_const() -> Result<(), Error> {
+    let prog = Parser::parse_from_file("../../../examples/ir/aes_encrypt.ir")?;
+    let res = fold_try_from_prog(&prog)?;
+    type_check_prog(&res)?;
+    let main = res.get("main").unwrap();
+    let count = |id: &str| {
+        main.body()
+            .iter()
+            .filter(|i| {
+                let arg: Vec<ExprTerm> = i.arg().clone().into();
+                arg.iter().any(|t| t.id().map_or(false, |a| a == id))
+            })
+            .count()
+    };
+    assert!(main
+        .body()
+        .iter()
+        .all(|i| i.dst().get_id(0).unwrap() != "t5"));
+    assert_eq!(count("t5"), 0);
+    assert!(count("t3") > 0);
+    Ok(())
+}
+
+#[test]
+fn dce() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        r#"def main(a:i8, b:i8, en:bool) -> (y:i8) {
+            t0:i8 = add(a, b);
+            t1:i8 = reg[0](t0, en);
+            t2:i8 = mul(a, b);
+            t3:i8 = reg[0](t4, en);
+            t4:i8 = add(t3, a);
+            y:i8 = id(t1);
+        }"#,
+    )?;
+    let res = dce_try_from_prog(&prog)?;
+    let exp = Parser::parse_from_str(
+        r#"def main(a:i8, b:i8, en:bool) -> (y:i8) {
+            t0:i8 = add(a, b);
+            t1:i8 = reg[0](t0, en);
+            y:i8 = id(t1);
+        }"#,
+    )?;
+    assert_eq!(res.get("main"), exp.get("main"));
+    Ok(())
+}
+
+#[test]
+fn same_behavior() -> Result<(), Error> {
+    let prog = Parser::parse_from_file("../../../examples/ir/fsm_3.ir")?;
+    let opt = dce_try_from_prog(&fold_try_from_prog(&prog)?)?;
+    type_check_prog(&opt)?;
+    let mut exp = Interp::try_from_prog(&prog)?;
+    let mut res = Interp::try_from_prog(&opt)?;
+    for i in 0..16 {
+        let mut input = Env::new();
+        input.insert("i0".to_string(), Value::from(i % 2 == 0));
+        input.insert("i1".to_string(), Value::from(i % 3 == 0));
+        input.insert("i2".to_string(), Value::from(i % 5 == 0));
+        assert_eq!(res.step(&input)?, exp.step(&input)?);
+    }
+    Ok(())
+}
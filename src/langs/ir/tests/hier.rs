@@ -0,0 +1,47 @@
+use ir::check::type_check_def;
+use ir::errors::Error;
+use ir::hier::{def_order_try_from_def, def_order_try_from_prog, split_calls_from_def};
+use ir::parser::Parser;
+
+const PROG: &str = r#"
+def acc(a:i8, en:bool) -> (y:i8) {
+    t0:i8 = add(a, y);
+    y:i8 = reg[0](t0, en);
+}
+def unused(a:i8) -> (y:i8) {
+    y:i8 = id(a);
+}
+def main(a:i8, b:i8, en:bool) -> (y:i8, z:i8) {
+    t0:i8 = add(a, b);
+    t1:i8 = acc(t0, en);
+    y:i8 = acc(t1, en);
+    z:i8 = add(t1, t0);
+}
+"#;
+
+#[test]
+fn order() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(PROG)?;
+    let res = def_order_try_from_def(&prog, "main")?;
+    assert_eq!(res, vec!["acc".to_string(), "main".to_string()]);
+    let res = def_order_try_from_prog(&prog)?;
+    assert_eq!(res.len(), 3);
+    assert_eq!(res.last(), Some(&"unused".to_string()));
+    Ok(())
+}
+
+#[test]
+fn split() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(PROG)?;
+    let main = prog.get("main").unwrap();
+    let (def, call) = split_calls_from_def(main)?;
+    type_check_def(&def, &prog)?;
+    assert_eq!(call.len(), 2);
+    assert!(def.body().iter().all(|i| !i.is_call()));
+    assert_eq!(
+        def.input().to_string(),
+        "(a:i8, b:i8, en:bool, t1:i8, y:i8)"
+    );
+    assert_eq!(def.output().to_string(), "(z:i8, t0:i8)");
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use ir::check::type_check_prog;
+use ir::errors::Error;
+use ir::inline::inline_try_from_prog;
+use ir::interp::{Env, Interp, Value};
+use ir::parser::Parser;
+
+const PROG: &str = r#"
+def acc(a:i8, en:bool) -> (y:i8) {
+    t0:i8 = add(a, y);
+    y:i8 = reg[0](t0, en);
+}
+def twice(a:i8, en:bool) -> (y:i8, z:i8) {
+    t0:i8 = acc(a, en);
+    y:i8 = acc(t0, en);
+    z:i8 = id(t0);
+}
+def main(a:i8, en:bool) -> (y:i8, z:i8) {
+    t0:i8 = acc(a, en);
+    (y:i8, z:i8) = twice(t0, en);
+}
+"#;
+
+#[test]
+fn flatten() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(PROG)?;
+    let res = inline_try_from_prog(&prog)?;
+    type_check_prog(&res)?;
+    for def in res.def().values() {
+        assert!(def.body().iter().all(|i| !i.is_call()));
+    }
+    let main = res.get("main").unwrap();
+    assert_eq!(main.body().len(), 7);
+    Ok(())
+}
+
+#[test]
+fn same_behavior() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(PROG)?;
+    let flat = inline_try_from_prog(&prog)?;
+    let mut exp = Interp::try_from_prog(&prog)?;
+    let mut res = Interp::try_from_prog(&flat)?;
+    for i in 0..8 {
+        let mut input = Env::new();
+        input.insert("a".to_string(), Value::from(i));
+        input.insert("en".to_string(), Value::from(i % 3 != 0));
+        assert_eq!(res.step(&input)?, exp.step(&input)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn recursive() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        r#"def foo(a:i8) -> (y:i8) { y:i8 = bar(a); }
+        def bar(a:i8) -> (y:i8) { y:i8 = foo(a); }
+        def main(a:i8) -> (y:i8) { y:i8 = foo(a); }"#,
+    )?;
+    let res = inline_try_from_prog(&prog);
+    assert!(matches!(res, Err(Error::Inline(_))));
+    Ok(())
+}
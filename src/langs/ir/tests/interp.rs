@@ -0,0 +1,217 @@
+use ir::errors::Error;
+use ir::interp::{interp_try_from_prog, Env, Value};
+use ir::parser::Parser;
+use std::path::Path;
+
+fn env(values: &[(&str, Value)]) -> Env {
+    values
+        .iter()
+        .map(|(id, value)| (id.to_string(), value.clone()))
+        .collect()
+}
+
+#[test]
+fn fsm_3() -> Result<(), Error> {
+    let path = Path::new("../../../examples/ir/fsm_3.ir");
+    let prog = Parser::parse_from_file(path)?;
+    let t = Value::from(true);
+    let f = Value::from(false);
+    let input = vec![
+        env(&[("i0", t.clone()), ("i1", f.clone()), ("i2", f.clone())]),
+        env(&[("i0", f.clone()), ("i1", t.clone()), ("i2", f.clone())]),
+        env(&[("i0", f.clone()), ("i1", f.clone()), ("i2", f.clone())]),
+        env(&[("i0", f.clone()), ("i1", f.clone()), ("i2", t.clone())]),
+        env(&[("i0", f.clone()), ("i1", f.clone()), ("i2", f)]),
+    ];
+    let res: Vec<Value> = interp_try_from_prog(&prog, &input)?
+        .iter()
+        .map(|o| o["y"].clone())
+        .collect();
+    let exp: Vec<Value> = vec![0, 1, 2, 2, 0].into_iter().map(Value::from).collect();
+    assert_eq!(res, exp);
+    Ok(())
+}
+
+#[test]
+fn tdot() -> Result<(), Error> {
+    let path = Path::new("../../../examples/ir/tdot.ir");
+    let prog = Parser::parse_from_file(path)?;
+    let cycle = env(&[
+        ("a0", Value::from(2)),
+        ("a1", Value::from(3)),
+        ("a2", Value::from(4)),
+        ("b0", Value::from(5)),
+        ("b1", Value::from(6)),
+        ("b2", Value::from(7)),
+        ("c", Value::from(100)),
+        ("en", Value::from(true)),
+    ]);
+    let input = vec![cycle; 6];
+    let res = interp_try_from_prog(&prog, &input)?;
+    // 10 + 18 + 28 + 100 wraps around in i8
+    assert_eq!(res[5]["y"], Value::from(-100));
+    Ok(())
+}
+
+#[test]
+fn signed_unsigned() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        r#"def main(a:i8, b:i8, c:u8, d:u8) -> (x:bool, y:bool, z:i8, w:u8, v:i4) {
+            x:bool = lt(a, b);
+            y:bool = lt(c, d);
+            z:i8 = sra[2](a);
+            w:u8 = srl[2](c);
+            t0:i4 = ext[4, 7](c);
+            v:i4 = add(t0, t0);
+        }"#,
+    )?;
+    let input = vec![env(&[
+        ("a", Value::from(-8)),
+        ("b", Value::from(1)),
+        ("c", Value::from(248)),
+        ("d", Value::from(1)),
+    ])];
+    let res = interp_try_from_prog(&prog, &input)?;
+    assert_eq!(res[0]["x"], Value::from(true));
+    assert_eq!(res[0]["y"], Value::from(false));
+    assert_eq!(res[0]["z"], Value::from(-2));
+    assert_eq!(res[0]["w"], Value::from(62));
+    assert_eq!(res[0]["v"], Value::from(-2));
+    Ok(())
+}
+
+#[test]
+fn vector_lanes() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        r#"def main(a:i8<4>, b:i8<4>, c:bool) -> (y:i8<4>, z:i16) {
+            t0:i8<4> = add(a, b);
+            y:i8<4> = mux(c, t0, a);
+            t1:i8 = const[1];
+            t2:i8 = const[-1];
+            z:i16 = cat(t1, t2);
+        }"#,
+    )?;
+    let input = vec![env(&[
+        ("a", Value::from(vec![127, 1, -128, 0])),
+        ("b", Value::from(vec![1, 1, -1, 0])),
+        ("c", Value::from(true)),
+    ])];
+    let res = interp_try_from_prog(&prog, &input)?;
+    assert_eq!(res[0]["y"], Value::from(vec![-128, 2, 127, 0]));
+    assert_eq!(res[0]["z"], Value::from(-255));
+    Ok(())
+}
+
+#[test]
+fn clock_domains() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        r#"def main(a:i8, en:bool, slow:clock) -> (y:i8, z:i8) {
+            y:i8 = reg[0](a, en);
+            z:i8 = reg[0](a, en, slow);
+        }"#,
+    )?;
+    let input: Vec<Env> = (1..5)
+        .map(|i| {
+            env(&[
+                ("a", Value::from(i)),
+                ("en", Value::from(true)),
+                ("slow", Value::from(i % 2 == 1)),
+            ])
+        })
+        .collect();
+    let res = interp_try_from_prog(&prog, &input)?;
+    let y: Vec<Value> = res.iter().map(|o| o["y"].clone()).collect();
+    let z: Vec<Value> = res.iter().map(|o| o["z"].clone()).collect();
+    assert_eq!(
+        y,
+        vec![0, 1, 2, 3]
+            .into_iter()
+            .map(Value::from)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        z,
+        vec![0, 1, 1, 3]
+            .into_iter()
+            .map(Value::from)
+            .collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+#[test]
+fn ram_clock() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        r#"def main(addr:i2, a:i8, en:bool, slow:clock) -> (y:i8) {
+            y:i8 = ram(addr, a, en, slow);
+        }"#,
+    )?;
+    let input: Vec<Env> = (1..5)
+        .map(|i| {
+            env(&[
+                ("addr", Value::from(0)),
+                ("a", Value::from(i)),
+                ("en", Value::from(true)),
+                ("slow", Value::from(i % 2 == 1)),
+            ])
+        })
+        .collect();
+    let res = interp_try_from_prog(&prog, &input)?;
+    let y: Vec<Value> = res.iter().map(|o| o["y"].clone()).collect();
+    assert_eq!(
+        y,
+        vec![0, 1, 1, 3]
+            .into_iter()
+            .map(Value::from)
+            .collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+#[test]
+fn bram_read() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        r#"def main(addr:i2, a:i8, en:bool) -> (y:i8, z:i8) {
+            y:i8 = ram(addr, a, en) @bram;
+            z:i8 = ram(addr, a, en) @lram;
+        }"#,
+    )?;
+    let cycles = [
+        (0, 5, true),
+        (0, 7, false),
+        (1, 0, false),
+        (0, 9, true),
+        (1, 0, false),
+        (0, 0, false),
+        (0, 0, false),
+    ];
+    let input: Vec<Env> = cycles
+        .iter()
+        .map(|(addr, a, en)| {
+            env(&[
+                ("addr", Value::from(*addr)),
+                ("a", Value::from(*a)),
+                ("en", Value::from(*en)),
+            ])
+        })
+        .collect();
+    let res = interp_try_from_prog(&prog, &input)?;
+    let y: Vec<Value> = res.iter().map(|o| o["y"].clone()).collect();
+    let z: Vec<Value> = res.iter().map(|o| o["z"].clone()).collect();
+    // block ram reads show up a cycle later and hold in write cycles
+    assert_eq!(
+        y,
+        vec![0, 0, 5, 0, 0, 0, 9]
+            .into_iter()
+            .map(Value::from)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        z,
+        vec![0, 5, 0, 5, 0, 9, 9]
+            .into_iter()
+            .map(Value::from)
+            .collect::<Vec<_>>()
+    );
+    Ok(())
+}
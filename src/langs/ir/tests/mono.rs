@@ -0,0 +1,97 @@
+use ir::check::type_check_prog;
+use ir::errors::Error;
+use ir::interp::{Env, Interp, Value};
+use ir::mono::mono_try_from_prog;
+use ir::parser::Parser;
+
+const PROG: &str = r#"
+def adder<W>(a:i<W>, b:i<W>) -> (y:i<W>) {
+    y:i<W> = add(a, b);
+}
+def pipe<W, N>(a:i<W><N>, en:bool) -> (y:i<W><N>) {
+    t0:i<W><N> = reg[0](a, en);
+    y:i<W><N> = id(t0);
+}
+def shift<W, S>(a:u<W>) -> (y:u<W>) {
+    y:u<W> = sll[S](a);
+}
+def main(a:i8, b:i8, c:i16, d:i16, v:i8<4>, e:u8, en:bool) -> (y:i8, z:i16, w:i8<4>, s:u8) {
+    y:i8 = adder<8>(a, b);
+    z:i16 = adder(c, d);
+    w:i8<4> = pipe(v, en);
+    s:u8 = shift<8, 2>(e);
+}
+"#;
+
+#[test]
+fn specialize() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(PROG)?;
+    let res = mono_try_from_prog(&prog)?;
+    type_check_prog(&res)?;
+    let mut name: Vec<String> = res.def().keys().cloned().collect();
+    name.sort();
+    assert_eq!(
+        name,
+        vec!["adder_16", "adder_8", "main", "pipe_8_4", "shift_8_2"]
+    );
+    assert!(res.def().values().all(|d| !d.is_param()));
+    let shift = res.get("shift_8_2").unwrap();
+    assert_eq!(shift.body()[0].to_string(), "y:u8 = sll[2](a)");
+    Ok(())
+}
+
+#[test]
+fn same_behavior() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(PROG)?;
+    let res = mono_try_from_prog(&prog)?;
+    let mut interp = Interp::try_from_prog(&res)?;
+    let mut input = Env::new();
+    input.insert("a".to_string(), Value::from(3));
+    input.insert("b".to_string(), Value::from(4));
+    input.insert("c".to_string(), Value::from(300));
+    input.insert("d".to_string(), Value::from(400));
+    input.insert("v".to_string(), Value::from(vec![1, 2, 3, 4]));
+    input.insert("e".to_string(), Value::from(5));
+    input.insert("en".to_string(), Value::from(true));
+    let out = interp.step(&input)?;
+    assert_eq!(out["y"], Value::from(7));
+    assert_eq!(out["z"], Value::from(700));
+    assert_eq!(out["s"], Value::from(20));
+    let out = interp.step(&input)?;
+    assert_eq!(out["w"], Value::from(vec![1, 2, 3, 4]));
+    Ok(())
+}
+
+#[test]
+fn print_and_parse() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(PROG)?;
+    let res = Parser::parse_from_str(&prog.to_string())?;
+    assert_eq!(res, prog);
+    Ok(())
+}
+
+#[test]
+fn param_errors() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        r#"def adder<W>(a:i<W>, b:i<W>) -> (y:i<W>) { y:i<W> = add(a, b); }
+        def main(a:i8, b:i16) -> (y:i8) { y:i8 = adder(a, b); }"#,
+    )?;
+    assert!(matches!(mono_try_from_prog(&prog), Err(Error::Mono(_))));
+    let prog = Parser::parse_from_str(
+        r#"def adder<W>(a:i<W>, b:i<W>) -> (y:i<W>) { y:i<W> = add(a, b); }
+        def main(a:i8, b:i8) -> (y:i8) { y:i8 = adder<8, 2>(a, b); }"#,
+    )?;
+    assert!(matches!(mono_try_from_prog(&prog), Err(Error::Mono(_))));
+    Ok(())
+}
+
+#[test]
+fn unbounded_specialization() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        r#"def f<N>(a:i8) -> (y:i8) { y:i8 = f<{N+1}>(a); }
+        def main(a:i8) -> (y:i8) { y:i8 = f<0>(a); }"#,
+    )?;
+    let err = mono_try_from_prog(&prog).unwrap_err().to_string();
+    assert!(err.contains("nested more than"));
+    Ok(())
+}
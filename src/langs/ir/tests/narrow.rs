@@ -0,0 +1,95 @@
+use ir::check::type_check_prog;
+use ir::errors::Error;
+use ir::interp::{Env, Interp, Value};
+use ir::narrow::{bits_try_from_def, narrow_try_from_prog};
+use ir::parser::Parser;
+
+#[test]
+fn bits() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        r#"def main(a:i8, b:i8, en:bool) -> (y:i8) {
+            t0:i8 = const[5];
+            t1:i8 = and(a, t0);
+            t2:i8 = add(t1, t0);
+            t3:i4 = ext[2, 5](t2);
+            t4:i8 = mux(en, t1, t2);
+            t5:i8 = reg[0](t6, en);
+            t6:i8 = add(t5, a);
+            t7:i8 = sub(t1, t0);
+            y:i8 = id(t4);
+        }"#,
+    )?;
+    let bits = bits_try_from_def(prog.get("main").unwrap())?;
+    assert_eq!(bits["t0"], 3);
+    assert_eq!(bits["t1"], 3);
+    assert_eq!(bits["t2"], 4);
+    assert_eq!(bits["t3"], 2);
+    assert_eq!(bits["t4"], 4);
+    assert_eq!(bits["t5"], 8);
+    assert_eq!(bits["t7"], 8);
+    Ok(())
+}
+
+#[test]
+fn fsm_3() -> Result<(), Error> {
+    let prog = Parser::parse_from_file("../../../examples/ir/fsm_3.ir")?;
+    let res = narrow_try_from_prog(&prog)?;
+    type_check_prog(&res)?;
+    let exp = Parser::parse_from_str(
+        r#"def main(i0:bool, i1:bool, i2:bool) -> (y:i4) {
+            t0:i2 = const[0];
+            t1:i2 = const[1];
+            t2:i2 = const[2];
+            t3:bool = const[1];
+            t4:bool = eq(t13, t0);
+            t5:bool = eq(t13, t1);
+            t6:bool = eq(t13, t2);
+            t7:bool = and(t4, i0);
+            t8:bool = and(t5, i1);
+            t9:bool = and(t6, i2);
+            t10:i2 = mux(t7, t1, t13);
+            t11:i2 = mux(t8, t2, t10);
+            t12:i2 = mux(t9, t0, t11);
+            t13:i2 = reg[0](t12, t3);
+            zero:i2 = const[0];
+            t13_4:i4 = cat(t13, zero);
+            y:i4 = id(t13_4);
+        }"#,
+    )?;
+    assert_eq!(res.get("main"), exp.get("main"));
+    Ok(())
+}
+
+#[test]
+fn same_behavior() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        r#"def main(a:i8, b:i8, en:bool) -> (y:i8, z:bool, w:i8) {
+            t0:i8 = const[3];
+            t1:i8 = and(a, t0);
+            t2:i8 = and(b, t0);
+            t3:i8 = add(t1, t2);
+            t4:i8 = reg[0](t3, en);
+            t5:bool = eq(t4, t0);
+            t6:i8 = mul(t4, t1);
+            t7:i8 = sll[2](t6);
+            t8:i8 = ext[0, 7](t7);
+            t9:i8 = sub(t8, a);
+            y:i8 = mux(t5, t9, t3);
+            z:bool = neq(t1, t2);
+            w:i8 = id(t8);
+        }"#,
+    )?;
+    let res = narrow_try_from_prog(&prog)?;
+    type_check_prog(&res)?;
+    assert_ne!(res, prog);
+    let mut exp = Interp::try_from_prog(&prog)?;
+    let mut res = Interp::try_from_prog(&res)?;
+    for i in -16..16 {
+        let mut input = Env::new();
+        input.insert("a".to_string(), Value::from(i * 7));
+        input.insert("b".to_string(), Value::from(i * 13));
+        input.insert("en".to_string(), Value::from(i % 3 != 0));
+        assert_eq!(res.step(&input)?, exp.step(&input)?);
+    }
+    Ok(())
+}
@@ -10,3 +10,28 @@ fn simple_prog() {
     exp.insert(&def.id(), def);
     assert_eq!(res, exp);
 }
+
+#[test]
+fn overflow_is_error() {
+    let res = Parser::parse_from_str(
+        "def main () -> (y:i8) {\n    y:i8 = const[99999999999999999999];\n}",
+    );
+    let msg = res.unwrap_err().to_string();
+    assert!(msg.contains("2:18"));
+    assert!(msg.contains("does not fit in i64"));
+}
+
+#[test]
+fn unknown_type_is_error() {
+    let res = Parser::parse_from_str("def main (a:k8) -> () {}");
+    assert!(res.is_err());
+}
+
+#[test]
+fn instr_span() {
+    let res = Parser::parse_from_str("def main (a:i8) -> (y:i8) {\n    y:i8 = id(a);\n}").unwrap();
+    let def = res.get("main").unwrap();
+    let span = def.body()[0].span();
+    assert_eq!((span.line(), span.col()), (2, 5));
+    assert_eq!((def.sig().span().line(), def.sig().span().col()), (1, 1));
+}
@@ -0,0 +1,204 @@
+use ir::ast::ExprTerm;
+use ir::check::type_check_prog;
+use ir::clock::{cdc_check_prog, domain_map_from_def};
+use ir::errors::Error;
+use ir::interp::{Env, Interp, Value};
+use ir::parser::Parser;
+use ir::pipe::{pipe_try_from_prog, Pipe};
+
+const CHAIN: &str = r#"
+def main(a:i8, b:i8, c:i8, d:i8, e:i8, en:bool) -> (y:i8, z:i8) {
+    t0:i8 = add(a, b);
+    t1:i8 = mul(t0, c);
+    t2:i8 = sub(t1, d);
+    t3:i8 = add(t2, e);
+    y:i8 = reg[0](t3, en);
+    z:i8 = xor(a, e);
+}
+"#;
+
+fn check(src: &str, pipe: &Pipe, exp_latency: u64) -> Result<(), Error> {
+    let prog = Parser::parse_from_str(src)?;
+    let (res, latency) = pipe_try_from_prog(&prog, pipe)?;
+    type_check_prog(&res)?;
+    let latency = latency["main"];
+    assert_eq!(latency, exp_latency);
+    let mut exp = Interp::try_from_prog(&prog)?;
+    let mut res = Interp::try_from_prog(&res)?;
+    let mut trace: Vec<Env> = Vec::new();
+    for i in 0..24_i64 {
+        let mut input = Env::new();
+        for (j, name) in ["a", "b", "c", "d", "e"].iter().enumerate() {
+            input.insert(name.to_string(), Value::from(i * (j as i64 + 3) % 17));
+        }
+        input.insert("en".to_string(), Value::from(i % 4 != 1));
+        let sig: Vec<ExprTerm> = prog.get("main").unwrap().input().clone().into();
+        for term in sig
+            .iter()
+            .filter(|t| t.get_ty().map_or(false, |ty| ty.is_clock()))
+        {
+            input.insert(term.get_id()?, Value::from(true));
+        }
+        trace.push(exp.step(&input)?);
+        let out = res.step(&input)?;
+        if i >= latency as i64 {
+            assert_eq!(out, trace[(i - latency as i64) as usize]);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn depth() -> Result<(), Error> {
+    check(CHAIN, &Pipe::Depth(2), 1)?;
+    check(CHAIN, &Pipe::Depth(1), 3)?;
+    check(CHAIN, &Pipe::Depth(4), 0)
+}
+
+#[test]
+fn stages() -> Result<(), Error> {
+    check(CHAIN, &Pipe::Stages(1), 1)?;
+    check(CHAIN, &Pipe::Stages(3), 3)
+}
+
+#[test]
+fn keep_loop() -> Result<(), Error> {
+    check(
+        r#"def main(a:i8, b:i8, c:i8, d:i8, e:i8, en:bool) -> (y:i8) {
+            t0:i8 = add(a, b);
+            t1:i8 = mul(t0, c);
+            t2:i8 = add(t1, y);
+            t3:i8 = sub(t2, d);
+            y:i8 = reg[0](t3, en);
+        }"#,
+        &Pipe::Depth(1),
+        1,
+    )
+}
+
+#[test]
+fn late_loop() -> Result<(), Error> {
+    // the loop is two stages deep and has to ignore the cycles before its
+    // inputs reach it
+    check(
+        r#"def main(a:i8, b:i8, c:i8, d:i8, e:i8) -> (y:i8) {
+            t0:i8 = add(a, b);
+            t1:i8 = mul(t0, c);
+            en:bool = eq(t1, t1);
+            m:i8 = not(t1);
+            t3:i8 = sub(y, m);
+            y:i8 = reg[0](t3, en);
+        }"#,
+        &Pipe::Depth(1),
+        2,
+    )
+}
+
+#[test]
+fn clock_domains() -> Result<(), Error> {
+    let src = r#"def main(a:i8, b:i8, c:i8, en:bool, f:clock, s:clock) -> (y:i8, z:i8) {
+        t0:i8 = add(a, b);
+        t1:i8 = mul(t0, c);
+        y:i8 = reg[0](t1, en, f);
+        t2:i8 = reg[0](a, en, s);
+        t3:i8 = add(t2, c);
+        t4:i8 = mul(t3, b);
+        z:i8 = reg[0](t4, en, s);
+    }"#;
+    check(src, &Pipe::Depth(1), 1)?;
+    let prog = Parser::parse_from_str(src)?;
+    let (res, _) = pipe_try_from_prog(&prog, &Pipe::Depth(1))?;
+    cdc_check_prog(&res)?;
+    // clocks are not delayed and the registers in front of mul take its clock
+    let def = res.get("main").unwrap();
+    for instr in def.body().iter().filter(|i| i.is_reg()) {
+        assert!(!instr.dst().get_ty(0)?.is_clock());
+    }
+    let clock = domain_map_from_def(def)?;
+    assert_eq!(clock["t3_d1"], "s");
+    assert_eq!(clock["t0_d1"], "f");
+    Ok(())
+}
+
+#[test]
+fn fsm_3() -> Result<(), Error> {
+    let prog = Parser::parse_from_file("../../../examples/ir/fsm_3.ir")?;
+    let (res, latency) = pipe_try_from_prog(&prog, &Pipe::Depth(1))?;
+    assert_eq!(latency["main"], 0);
+    assert_eq!(res, prog);
+    Ok(())
+}
+
+#[test]
+fn calls() {
+    let prog = Parser::parse_from_str(
+        r#"def f(a:i8) -> (y:i8) {
+            y:i8 = add(a, a);
+        }
+        def main(a:i8) -> (y:i8) {
+            y:i8 = f(a);
+        }"#,
+    )
+    .unwrap();
+    let res = pipe_try_from_prog(&prog, &Pipe::Depth(1));
+    assert!(matches!(res, Err(Error::Pipe(_))));
+}
+
+#[test]
+fn zero() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(CHAIN)?;
+    let res = pipe_try_from_prog(&prog, &Pipe::Depth(0));
+    assert!(matches!(res, Err(Error::Pipe(_))));
+    let res = pipe_try_from_prog(&prog, &Pipe::Stages(0));
+    assert!(matches!(res, Err(Error::Pipe(_))));
+    Ok(())
+}
+
+const RETIME: &str = r#"
+def main(a:i8, b:i8, c:i8, d:i8, e:i8, en:bool) -> (y:i8) {
+    t0:i8 = reg[1](a, en);
+    t1:i8 = reg[2](b, en);
+    t2:i8 = reg[2, 3](c, en);
+    t3:i8 = add(t0, t1);
+    t4:i8 = mul(t3, t2);
+    t5:i8 = sub(t4, d);
+    y:i8 = add(t5, e);
+}
+"#;
+
+#[test]
+fn retime() -> Result<(), Error> {
+    // moving the input registers past add and mul leaves two operations on
+    // each side, so no register has to be added
+    check(RETIME, &Pipe::Depth(2), 0)?;
+    check(RETIME, &Pipe::Depth(1), 2)?;
+    let prog = Parser::parse_from_str(RETIME)?;
+    let (res, _) = pipe_try_from_prog(&prog, &Pipe::Depth(2))?;
+    let def = res.get("main").unwrap();
+    let reg: Vec<String> = def
+        .body()
+        .iter()
+        .filter(|i| i.is_reg())
+        .map(|i| i.to_string())
+        .collect();
+    assert_eq!(reg, vec!["t4:i8 = reg[6, 9](t4_0, en) @??"]);
+    // registers that already meet the target stay in place
+    let (res, latency) = pipe_try_from_prog(&prog, &Pipe::Depth(4))?;
+    assert_eq!(latency["main"], 0);
+    assert_eq!(res, prog);
+    Ok(())
+}
+
+#[test]
+fn passthrough() -> Result<(), Error> {
+    let src = r#"def main(a:i8, b:i8, c:i8, d:i8, e:i8, en:bool) -> (y:i8, a:i8) {
+        t0:i8 = add(a, b);
+        t1:i8 = mul(t0, c);
+        y:i8 = sub(t1, d);
+    }"#;
+    check(src, &Pipe::Depth(3), 0)?;
+    let prog = Parser::parse_from_str(src)?;
+    let res = pipe_try_from_prog(&prog, &Pipe::Depth(1));
+    assert!(matches!(res, Err(Error::Pipe(e)) if e.contains("output a of def main is an input")));
+    Ok(())
+}
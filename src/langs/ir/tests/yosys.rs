@@ -0,0 +1,236 @@
+use ir::check::type_check_prog;
+use ir::clock::cdc_check_prog;
+use ir::errors::Error;
+use ir::interp::{interp_try_from_prog, Env, Value};
+use ir::yosys::{prog_try_from_file, prog_try_from_str};
+use std::path::Path;
+
+fn env(values: &[(&str, Value)]) -> Env {
+    values
+        .iter()
+        .map(|(id, value)| (id.to_string(), value.clone()))
+        .collect()
+}
+
+fn is_yosys_error(res: Result<ir::ast::Prog, Error>, msg: &str) -> bool {
+    match res {
+        Err(Error::Yosys(err)) => err.contains(msg),
+        _ => false,
+    }
+}
+
+const MEM: &str = r#"{
+  "modules": {
+    "mem": {
+      "ports": {
+        "clk": { "direction": "input", "bits": [ 2 ] },
+        "addr": { "direction": "input", "bits": [ 3, 4 ] },
+        "d": { "direction": "input", "bits": [ 5, 6, 7, 8 ] },
+        "we": { "direction": "input", "bits": [ 9 ] },
+        "y": { "direction": "output", "bits": [ 10, 11, 12, 13 ] }
+      },
+      "cells": {
+        "$mem$mem.v:6$1": {
+          "hide_name": 1,
+          "type": "$mem",
+          "parameters": {
+            "ABITS": "00000000000000000000000000000010",
+            "INIT": "xxxxxxxxxxxxxxxx",
+            "MEMID": "\\ram",
+            "OFFSET": "00000000000000000000000000000000",
+            "RD_CLK_ENABLE": "0",
+            "RD_CLK_POLARITY": "0",
+            "RD_PORTS": "00000000000000000000000000000001",
+            "RD_TRANSPARENT": "0",
+            "SIZE": "00000000000000000000000000000100",
+            "WIDTH": "00000000000000000000000000000100",
+            "WR_CLK_ENABLE": "1",
+            "WR_CLK_POLARITY": "1",
+            "WR_PORTS": "00000000000000000000000000000001"
+          },
+          "port_directions": {
+            "RD_ADDR": "input",
+            "RD_CLK": "input",
+            "RD_DATA": "output",
+            "RD_EN": "input",
+            "WR_ADDR": "input",
+            "WR_CLK": "input",
+            "WR_DATA": "input",
+            "WR_EN": "input"
+          },
+          "connections": {
+            "RD_ADDR": [ 3, 4 ],
+            "RD_CLK": [ "x" ],
+            "RD_DATA": [ 10, 11, 12, 13 ],
+            "RD_EN": [ "1" ],
+            "WR_ADDR": [ 3, 4 ],
+            "WR_CLK": [ 2 ],
+            "WR_DATA": [ 5, 6, 7, 8 ],
+            "WR_EN": [ 9, 9, 9, 9 ]
+          }
+        }
+      }
+    }
+  }
+}"#;
+
+#[test]
+fn alu() -> Result<(), Error> {
+    let prog = prog_try_from_file(Path::new("../../../examples/yosys/alu.json"))?;
+    type_check_prog(&prog)?;
+    let input = vec![
+        env(&[
+            ("a", Value::from(5)),
+            ("b", Value::from(3)),
+            ("sel", Value::from(false)),
+        ]),
+        env(&[
+            ("a", Value::from(5)),
+            ("b", Value::from(5)),
+            ("sel", Value::from(true)),
+        ]),
+    ];
+    let res = interp_try_from_prog(&prog, &input)?;
+    assert_eq!(res[0]["y"], Value::from(8));
+    assert_eq!(res[0]["z"], Value::from(false));
+    assert_eq!(res[1]["y"], Value::from(0));
+    assert_eq!(res[1]["z"], Value::from(true));
+    Ok(())
+}
+
+#[test]
+fn counter() -> Result<(), Error> {
+    let prog = prog_try_from_file(Path::new("../../../examples/yosys/counter.json"))?;
+    type_check_prog(&prog)?;
+    cdc_check_prog(&prog)?;
+    let main = prog.get("main").unwrap();
+    assert!(main.input().get_ty(0)?.is_clock());
+    let input: Vec<Env> = (0..4)
+        .map(|i| env(&[("clk", Value::from(true)), ("en", Value::from(i != 2))]))
+        .collect();
+    let res: Vec<Value> = interp_try_from_prog(&prog, &input)?
+        .iter()
+        .map(|o| o["y"].clone())
+        .collect();
+    let exp: Vec<Value> = vec![0, 1, 2, 2].into_iter().map(Value::from).collect();
+    assert_eq!(res, exp);
+    Ok(())
+}
+
+#[test]
+fn memory() -> Result<(), Error> {
+    let prog = prog_try_from_str(MEM)?;
+    type_check_prog(&prog)?;
+    let input = vec![
+        env(&[
+            ("clk", Value::from(true)),
+            ("addr", Value::from(1)),
+            ("d", Value::from(7)),
+            ("we", Value::from(true)),
+        ]),
+        env(&[
+            ("clk", Value::from(true)),
+            ("addr", Value::from(1)),
+            ("d", Value::from(0)),
+            ("we", Value::from(false)),
+        ]),
+    ];
+    let res = interp_try_from_prog(&prog, &input)?;
+    assert_eq!(res[0]["y"], Value::from(0));
+    assert_eq!(res[1]["y"], Value::from(7));
+    Ok(())
+}
+
+#[test]
+fn unsupported_cell() {
+    let res = prog_try_from_str(&MEM.replace("\"$mem\"", "\"$shiftx\""));
+    assert!(is_yosys_error(res, "unsupported yosys cell $shiftx"));
+}
+
+#[test]
+fn memory_init() {
+    let res = prog_try_from_str(&MEM.replace("xxxxxxxxxxxxxxxx", "0000000000000001"));
+    assert!(is_yosys_error(res, "use --mmap"));
+}
+
+fn bits(start: usize, width: usize) -> String {
+    let bits: Vec<String> = (start..start + width).map(|b| b.to_string()).collect();
+    bits.join(", ")
+}
+
+fn reduce_and(width: usize) -> String {
+    format!(
+        r#"{{ "modules": {{ "top": {{
+          "ports": {{
+            "a": {{ "direction": "input", "bits": [ {} ] }},
+            "y": {{ "direction": "output", "bits": [ {} ] }}
+          }},
+          "cells": {{
+            "and": {{
+              "type": "$reduce_and",
+              "parameters": {{ "A_SIGNED": "0", "A_WIDTH": "{:b}", "Y_WIDTH": "1" }},
+              "port_directions": {{ "A": "input", "Y": "output" }},
+              "connections": {{ "A": [ {} ], "Y": [ {} ] }}
+            }}
+          }}
+        }} }} }}"#,
+        bits(3, width),
+        width + 3,
+        width,
+        bits(3, width),
+        width + 3
+    )
+}
+
+fn sdff(width: usize, value: &str) -> String {
+    format!(
+        r#"{{ "modules": {{ "top": {{
+          "ports": {{
+            "clk": {{ "direction": "input", "bits": [ 2 ] }},
+            "rst": {{ "direction": "input", "bits": [ 3 ] }},
+            "d": {{ "direction": "input", "bits": [ {} ] }},
+            "q": {{ "direction": "output", "bits": [ {} ] }}
+          }},
+          "cells": {{
+            "reg": {{
+              "type": "$sdff",
+              "parameters": {{
+                "CLK_POLARITY": "1",
+                "SRST_POLARITY": "1",
+                "SRST_VALUE": "{}",
+                "WIDTH": "{:b}"
+              }},
+              "port_directions": {{ "CLK": "input", "SRST": "input", "D": "input", "Q": "output" }},
+              "connections": {{ "CLK": [ 2 ], "SRST": [ 3 ], "D": [ {} ], "Q": [ {} ] }}
+            }}
+          }}
+        }} }} }}"#,
+        bits(4, width),
+        bits(4 + width, width),
+        value,
+        width,
+        bits(4, width),
+        bits(4 + width, width)
+    )
+}
+
+#[test]
+fn wide_constants() -> Result<(), Error> {
+    for width in &[64, 65, 128] {
+        let prog = prog_try_from_str(&reduce_and(*width))?;
+        type_check_prog(&prog)?;
+        let max = format!("{}{}", "0".repeat(width - 63), "1".repeat(63));
+        let min = format!("{}{}", "1".repeat(width - 63), "0".repeat(63));
+        for value in &[max, min, "1".repeat(*width)] {
+            let prog = prog_try_from_str(&sdff(*width, value))?;
+            type_check_prog(&prog)?;
+        }
+    }
+    let prog = prog_try_from_str(&sdff(65, &format!("11{}", "0".repeat(63))))?;
+    assert!(prog.to_string().contains("const[-9223372036854775808]"));
+    let res = prog_try_from_str(&sdff(65, &format!("1{}", "0".repeat(64))));
+    assert!(is_yosys_error(res, "does not fit in 64 bits"));
+    let res = prog_try_from_str(&sdff(128, &format!("01{}", "0".repeat(126))));
+    assert!(is_yosys_error(res, "does not fit in 64 bits"));
+    Ok(())
+}
@@ -23,6 +23,7 @@ pub enum Instr {
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash, Default)]
 pub struct Pat {
     pub sig: Sig,
+    pub param: Vec<Id>,
     pub body: Vec<Instr>,
 }
 
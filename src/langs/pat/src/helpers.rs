@@ -60,12 +60,21 @@ impl Pat {
     pub fn body(&self) -> &Vec<Instr> {
         &self.body
     }
+    pub fn param(&self) -> &Vec<Id> {
+        &self.param
+    }
+    pub fn is_param(&self) -> bool {
+        !self.param.is_empty()
+    }
     pub fn set_sig(&mut self, sig: Sig) {
         self.sig = sig;
     }
     pub fn set_body(&mut self, body: Vec<Instr>) {
         self.body = body;
     }
+    pub fn set_param(&mut self, param: Vec<Id>) {
+        self.param = param;
+    }
     pub fn body_mut(&mut self) -> &mut Vec<Instr> {
         &mut self.body
     }
@@ -77,6 +86,9 @@ impl Target {
         let mut buf = BufReader::new(file);
         deserialize_from(&mut buf).expect("Error: cannot deserialize")
     }
+    pub fn deserialize_from_slice(bytes: &[u8]) -> Target {
+        deserialize_from(bytes).expect("Error: cannot deserialize")
+    }
     pub fn pat(&self) -> &HashMap<Id, Pat> {
         &self.pat
     }
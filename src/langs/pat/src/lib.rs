@@ -4,5 +4,6 @@ pub mod errors;
 pub mod from;
 pub mod helpers;
 pub mod infer;
+pub mod mono;
 pub mod parser;
 pub mod pretty_print;
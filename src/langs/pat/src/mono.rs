@@ -0,0 +1,26 @@
+use crate::ast::*;
+use crate::errors::Error;
+use ir::mono::{subst_expr, subst_val, subst_var, Subst};
+
+// pattern with every parameter replaced by its value, under a new name
+pub fn mono_try_from_pat(pat: &Pat, map: &Subst, name: &str) -> Result<Pat, Error> {
+    let mut sig = pat.sig().clone();
+    sig.set_id(name);
+    sig.input = subst_expr(pat.input(), map, subst_var)?;
+    sig.output = subst_expr(pat.output(), map, subst_var)?;
+    let mut body: Vec<Instr> = Vec::new();
+    for instr in pat.body() {
+        let mut instr = instr.clone();
+        instr.set_dst(subst_expr(instr.dst(), map, subst_var)?);
+        instr.set_arg(subst_expr(instr.arg(), map, subst_var)?);
+        match &mut instr {
+            Instr::Prim(prim) => prim.attr = subst_expr(prim.attr(), map, subst_val)?,
+            Instr::Wire(wire) => wire.attr = subst_expr(wire.attr(), map, subst_val)?,
+        }
+        body.push(instr);
+    }
+    let mut res = Pat::default();
+    res.set_sig(sig);
+    res.set_body(body);
+    Ok(res)
+}
@@ -1,15 +1,22 @@
 use crate::ast::*;
 use crate::errors::Error;
 use crate::infer::infer_type_try_from_target;
+use diag::{path_from, Span};
 use io::read_to_string;
 use pest_consume::match_nodes;
 use pest_consume::Error as PestError;
 use pest_consume::Parser as PestParser;
 use std::path::Path;
+use std::rc::Rc;
 use std::str::FromStr;
 
 pub type ParseResult<T> = std::result::Result<T, PestError<Rule>>;
-type Node<'i> = pest_consume::Node<'i, Rule, ()>;
+// nodes carry the path of the file they come from
+type Node<'i> = pest_consume::Node<'i, Rule, Option<Rc<str>>>;
+
+fn span(input: &Node) -> Span {
+    Span::new(input.as_span(), input.user_data().clone())
+}
 
 const _GRAMMAR: &str = include_str!("syntax.pest");
 
@@ -31,7 +38,7 @@ impl Parser {
         let val = i64::from_str_radix(input.as_str(), 2);
         match val {
             Ok(v) => Ok(ExprTerm::Val(v)),
-            Err(_) => panic!("Error: parsing {} as bin i64", input.as_str()),
+            Err(_) => Err(input.error(format!("{} does not fit in i64", input.as_str()))),
         }
     }
 
@@ -39,7 +46,7 @@ impl Parser {
         let val = i64::from_str_radix(input.as_str(), 16);
         match val {
             Ok(v) => Ok(ExprTerm::Val(v)),
-            Err(_) => panic!("Error: parsing {} as hex i64", input.as_str()),
+            Err(_) => Err(input.error(format!("{} does not fit in i64", input.as_str()))),
         }
     }
 
@@ -47,7 +54,7 @@ impl Parser {
         let val = input.as_str().parse::<i64>();
         match val {
             Ok(v) => Ok(ExprTerm::Val(v)),
-            Err(_) => panic!("Error: parsing {} as dec i64", input.as_str()),
+            Err(_) => Err(input.error(format!("{} does not fit in i64", input.as_str()))),
         }
     }
 
@@ -64,7 +71,7 @@ impl Parser {
         let ty = Ty::from_str(input.as_str());
         match ty {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -72,7 +79,7 @@ impl Parser {
         let prim = Prim::from_str(input.as_str());
         match prim {
             Ok(p) => Ok(p),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -110,7 +117,7 @@ impl Parser {
         let op = OpPrim::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -118,11 +125,12 @@ impl Parser {
         let op = OpWire::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
     fn instr(input: Node) -> ParseResult<Instr> {
+        let span = span(&input);
         Ok(match_nodes!(
             input.into_children();
             [io(dst), op_prim(op), io(arg)] => Instr::from(InstrPrim {
@@ -131,6 +139,7 @@ impl Parser {
                 attr: Expr::default(),
                 arg,
                 prim: Prim::Any,
+                span: span.clone(),
             }),
             [io(dst), op_prim(op), tup_val(attr), io(arg)] => Instr::from(InstrPrim {
                 op,
@@ -138,6 +147,7 @@ impl Parser {
                 attr: Expr::from(attr),
                 arg,
                 prim: Prim::Any,
+                span: span.clone(),
             }),
             [io(dst), op_prim(op), io(arg), prim(prim)] => Instr::from(InstrPrim {
                 op,
@@ -145,6 +155,7 @@ impl Parser {
                 attr: Expr::default(),
                 arg,
                 prim,
+                span: span.clone(),
             }),
             [io(dst), op_prim(op), tup_val(attr), io(arg), prim(prim)] => Instr::from(InstrPrim {
                 op,
@@ -152,24 +163,28 @@ impl Parser {
                 attr: Expr::from(attr),
                 arg,
                 prim,
+                span: span.clone(),
             }),
             [io(dst), op_wire(op), tup_val(attr)] => Instr::from(InstrWire {
                 op,
                 dst,
                 attr: Expr::from(attr),
                 arg: Expr::default(),
+                span: span.clone(),
             }),
             [io(dst), op_wire(op), io(arg)] => Instr::from(InstrWire {
                 op,
                 dst,
                 attr: Expr::default(),
                 arg,
+                span: span.clone(),
             }),
             [io(dst), op_wire(op), tup_val(attr), io(arg)] => Instr::from(InstrWire {
                 op,
                 dst,
                 attr: Expr::from(attr),
                 arg,
+                span: span.clone(),
             }),
         ))
     }
@@ -181,37 +196,43 @@ impl Parser {
         ))
     }
 
-    fn sig(input: Node) -> ParseResult<Sig> {
+    fn param(input: Node) -> ParseResult<Vec<Id>> {
         Ok(match_nodes!(
             input.into_children();
-            [id(id)] => Sig {
-                id,
-                input: Expr::default(),
-                output: Expr::default(),
-            },
-            [id(id), io(output)] => Sig {
-                id,
-                input: Expr::default(),
-                output,
-            },
-            [id(id), io(input), io(output)] => Sig {
+            [id(ids)..] => ids.collect(),
+        ))
+    }
+
+    fn sig(input: Node) -> ParseResult<Pat> {
+        let span = span(&input);
+        let (id, param, input, output) = match_nodes!(
+            input.into_children();
+            [id(id)] => (id, Vec::new(), Expr::default(), Expr::default()),
+            [id(id), io(output)] => (id, Vec::new(), Expr::default(), output),
+            [id(id), io(input), io(output)] => (id, Vec::new(), input, output),
+            [id(id), param(param)] => (id, param, Expr::default(), Expr::default()),
+            [id(id), param(param), io(output)] => (id, param, Expr::default(), output),
+            [id(id), param(param), io(input), io(output)] => (id, param, input, output),
+        );
+        Ok(Pat {
+            sig: Sig {
                 id,
                 input,
                 output,
+                span,
             },
-        ))
+            param,
+            body: Vec::new(),
+        })
     }
 
     fn pat(input: Node) -> ParseResult<Pat> {
         Ok(match_nodes!(
             input.into_children();
-            [sig(sig)] => Pat {
-                sig,
-                body: Vec::new(),
-            },
+            [sig(sig)] => sig,
             [sig(sig), body(body)] => Pat {
-                sig,
                 body,
+                ..sig
             },
         ))
     }
@@ -239,14 +260,21 @@ impl Parser {
 }
 
 impl Parser {
-    pub fn parse_from_str(input_str: &str) -> Result<Target, Error> {
-        let inputs = Parser::parse(Rule::file, input_str)?;
+    fn parse_with_path(input_str: &str, path: Option<Rc<str>>) -> Result<Target, Error> {
+        let inputs = Parser::parse_with_userdata(Rule::file, input_str, path)?;
         let input = inputs.single()?;
         let target = Parser::file(input)?;
         Ok(infer_type_try_from_target(&target))
     }
+    pub fn parse_from_str(input_str: &str) -> Result<Target, Error> {
+        Parser::parse_with_path(input_str, None)
+    }
     pub fn parse_from_file<P: AsRef<Path>>(path: P) -> Result<Target, Error> {
-        let content = read_to_string(path);
-        Parser::parse_from_str(&content)
+        let content = read_to_string(&path);
+        let path = path_from(&path);
+        match Parser::parse_with_path(&content, Some(path.clone())) {
+            Err(Error::Parse(e)) => Err(Error::Parse(e.with_path(&path))),
+            res => res,
+        }
     }
 }
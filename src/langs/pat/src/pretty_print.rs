@@ -1,6 +1,6 @@
 use crate::ast::*;
 use itertools::Itertools;
-use prettyprint::{block_with_braces, intersperse, PrettyPrint, RcDoc};
+use prettyprint::{block_with_braces, intersperse, PrettyHelper, PrettyPrint, RcDoc};
 
 impl PrettyPrint for Instr {
     fn to_doc(&self) -> RcDoc<()> {
@@ -13,7 +13,24 @@ impl PrettyPrint for Instr {
 
 impl PrettyPrint for Pat {
     fn to_doc(&self) -> RcDoc<()> {
-        let sig = self.sig().to_doc();
+        let param = if self.is_param() {
+            intersperse(
+                self.param().iter().map(RcDoc::as_string),
+                RcDoc::text(",").append(RcDoc::space()),
+            )
+            .comps()
+        } else {
+            RcDoc::nil()
+        };
+        let sig = RcDoc::text("pat")
+            .append(RcDoc::space())
+            .append(RcDoc::as_string(self.id()))
+            .append(param)
+            .append(self.input().to_doc())
+            .append(RcDoc::space())
+            .append(RcDoc::text("->"))
+            .append(RcDoc::space())
+            .append(self.output().to_doc());
         let body = intersperse(
             self.body()
                 .iter()
@@ -1,4 +1,5 @@
-use pat::ast::{Pat, Sig, Target};
+use pat::ast::{ExprTerm, Pat, Sig, Target, Ty};
+use pat::mono::mono_try_from_pat;
 use pat::parser::Parser;
 
 #[test]
@@ -12,3 +13,20 @@ fn simple_target() {
     exp.insert(&pat.id(), pat);
     assert_eq!(res, exp);
 }
+
+#[test]
+fn generic_pat() {
+    let res = Parser::parse_from_str(
+        "pat ladd<W>(a:i<W>, b:i<W>) -> (y:i<W>) {
+            y:i<W> = add(a, b) @lut;
+        }",
+    )
+    .unwrap();
+    let pat = res.get("ladd").unwrap();
+    assert!(pat.is_param());
+    let map = [("W".to_string(), 12)].iter().cloned().collect();
+    let spec = mono_try_from_pat(pat, &map, "ladd_i12").unwrap();
+    assert!(!spec.is_param());
+    let output: Vec<ExprTerm> = spec.output().clone().into();
+    assert_eq!(output[0].ty(), Some(&Ty::SInt(12)));
+}
@@ -1,3 +1,30 @@
+use std::fmt;
 use vast::v05::ast as vl;
 
 pub use vl::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edge {
+    Posedge,
+    Negedge,
+}
+
+impl fmt::Display for Edge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Edge::Posedge => write!(f, "posedge"),
+            Edge::Negedge => write!(f, "negedge"),
+        }
+    }
+}
+
+// event on the rising edge of clock or on any of the other edges, i.e.
+// always @(posedge clock or negedge reset). vast events hold a single edge,
+// so the other edges are emitted after the clock
+pub fn new_posedge_or(clock: &str, other: &[(Edge, &str)]) -> vl::Sequential {
+    let mut event = clock.to_string();
+    for (edge, name) in other {
+        event.push_str(&format!(" or {} {}", edge, name));
+    }
+    vl::Sequential::new_posedge(&event)
+}
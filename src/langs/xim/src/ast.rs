@@ -1,3 +1,4 @@
+use ir::elab;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use xir::ast as xir;
@@ -20,9 +21,12 @@ pub type BelLum = xir::BelLum;
 pub type BelBlock = xir::BelBlock;
 pub type Bel = xir::Bel;
 pub type Loc = xir::Loc;
+pub type Span = xir::Span;
 pub type InstrMach = xir::InstrMach;
 pub type InstrBasc = xir::InstrBasc;
 pub type Instr = xir::Instr;
+pub type Range = elab::Range;
+pub type GenExpr = elab::GenExpr;
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash)]
 pub struct Sig {
@@ -31,12 +35,28 @@ pub struct Sig {
     pub output: Expr,
     pub area: u64,
     pub perf: u64,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+// instr dst and arg are kept apart, because they can be generated
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash)]
+pub enum GenInstr {
+    Instr(Box<Instr>, GenExpr, GenExpr),
+    For(Range, Vec<GenInstr>),
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash, Default)]
 pub struct Imp {
     pub sig: Sig,
+    pub param: Vec<Id>,
     pub body: Vec<Instr>,
+    // body of parametric implementations, it is elaborated for every
+    // specialization. Other implementations are elaborated when parsed
+    pub gen: Vec<GenInstr>,
+    // area given as an index expression, i.e. {(W+7)/8}, it is evaluated
+    // together with the body
+    pub gen_area: Option<Id>,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Default)]
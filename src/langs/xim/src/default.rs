@@ -8,6 +8,7 @@ impl Default for Sig {
             output: Expr::Tup(ExprTup::default()),
             area: 0,
             perf: 0,
+            span: Span::default(),
         }
     }
 }
@@ -0,0 +1,79 @@
+use crate::ast::*;
+use crate::errors::Error;
+use ir::elab::{each, elab_expr, elab_vals, eval_index, IndexEnv};
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+// loop indices in coordinates become offsets, e.g. @c8(??+k, ??)
+fn elab_coord(coord: &ExprCoord, env: &IndexEnv) -> Result<ExprCoord, Error> {
+    match coord {
+        ExprCoord::Var(id) => match env.get(id) {
+            Some(v) => Ok(ExprCoord::Val(u64::try_from(*v)?)),
+            None => Ok(coord.clone()),
+        },
+        ExprCoord::Bin(op, lhs, rhs) => Ok(ExprCoord::Bin(
+            op.clone(),
+            Rc::new(elab_coord(lhs, env)?),
+            Rc::new(elab_coord(rhs, env)?),
+        )),
+        _ => Ok(coord.clone()),
+    }
+}
+
+fn elab_instrs(gen: &[GenInstr], env: &IndexEnv, res: &mut Vec<Instr>) -> Result<(), Error> {
+    for g in gen {
+        match g {
+            GenInstr::Instr(instr, dst, arg) => {
+                let mut instr = instr.as_ref().clone();
+                instr.set_dst(elab_expr(dst, env)?);
+                instr.set_arg(elab_expr(arg, env)?);
+                match &mut instr {
+                    Instr::Basc(basc) => basc.attr = elab_vals(basc.attr(), env)?,
+                    Instr::Mach(mach) => {
+                        mach.attr = elab_vals(mach.attr(), env)?;
+                        if let Some(loc) = &mut mach.loc {
+                            loc.x = elab_coord(&loc.x, env)?;
+                            loc.y = elab_coord(&loc.y, env)?;
+                        }
+                    }
+                }
+                res.push(instr);
+            }
+            GenInstr::For(range, body) => each(range, env, |env| elab_instrs(body, env, res))?,
+        }
+    }
+    Ok(())
+}
+
+// loops and index expressions are evaluated, parameters are looked up in env
+pub fn body_try_from_gen(gen: &[GenInstr], env: &IndexEnv) -> Result<Vec<Instr>, Error> {
+    let mut body: Vec<Instr> = Vec::new();
+    elab_instrs(gen, env, &mut body)?;
+    Ok(body)
+}
+
+// area index expressions, with the braces, i.e. {(W+7)/8}
+pub fn area_try_from_gen(area: &str, env: &IndexEnv) -> Result<u64, Error> {
+    let text = area.trim_start_matches('{').trim_end_matches('}');
+    Ok(u64::try_from(eval_index(text, env)?)?)
+}
+
+// parametric implementations are kept as they are, until they are specialized
+pub fn elab_try_from_target(target: &Target) -> Result<Target, Error> {
+    let mut res = Target::default();
+    for (name, imp) in target.imp() {
+        let mut imp = imp.clone();
+        if !imp.is_param() {
+            imp.set_body(body_try_from_gen(imp.gen(), &IndexEnv::new())?);
+            imp.set_gen(Vec::new());
+            if let Some(area) = imp.gen_area() {
+                let mut sig = imp.sig().clone();
+                sig.set_area(area_try_from_gen(area, &IndexEnv::new())?);
+                imp.set_sig(sig);
+                imp.set_gen_area(None);
+            }
+        }
+        res.insert(name, imp);
+    }
+    Ok(res)
+}
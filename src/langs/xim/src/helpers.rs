@@ -21,6 +21,9 @@ impl Sig {
     pub fn perf(&self) -> u64 {
         self.perf
     }
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
     pub fn set_id(&mut self, id: &str) {
         self.id = id.to_string();
     }
@@ -54,12 +57,33 @@ impl Imp {
     pub fn body(&self) -> &Vec<Instr> {
         &self.body
     }
+    pub fn param(&self) -> &Vec<Id> {
+        &self.param
+    }
+    pub fn is_param(&self) -> bool {
+        !self.param.is_empty()
+    }
+    pub fn gen(&self) -> &Vec<GenInstr> {
+        &self.gen
+    }
+    pub fn gen_area(&self) -> Option<&Id> {
+        self.gen_area.as_ref()
+    }
     pub fn set_sig(&mut self, sig: Sig) {
         self.sig = sig;
     }
     pub fn set_body(&mut self, body: Vec<Instr>) {
         self.body = body;
     }
+    pub fn set_param(&mut self, param: Vec<Id>) {
+        self.param = param;
+    }
+    pub fn set_gen(&mut self, gen: Vec<GenInstr>) {
+        self.gen = gen;
+    }
+    pub fn set_gen_area(&mut self, area: Option<Id>) {
+        self.gen_area = area;
+    }
     pub fn body_mut(&mut self) -> &mut Vec<Instr> {
         &mut self.body
     }
@@ -71,6 +95,9 @@ impl Target {
         let mut buf = BufReader::new(file);
         deserialize_from(&mut buf).expect("Error: cannot deserialize")
     }
+    pub fn deserialize_from_slice(bytes: &[u8]) -> Target {
+        deserialize_from(bytes).expect("Error: cannot deserialize")
+    }
     pub fn imp(&self) -> &HashMap<Id, Imp> {
         &self.imp
     }
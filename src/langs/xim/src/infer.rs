@@ -26,7 +26,7 @@ fn build_env(imp: &Imp) -> HashMap<String, Ty> {
     env
 }
 
-fn infer_type_try_from_imp(imp: &Imp) -> Imp {
+pub fn infer_type_try_from_imp(imp: &Imp) -> Imp {
     let env = build_env(imp);
     let mut imp = imp.clone();
     // solve instr arg types with environment
@@ -34,11 +34,10 @@ fn infer_type_try_from_imp(imp: &Imp) -> Imp {
         let mut arg = ExprTup::default();
         if let Some(tup) = instr.arg().tup() {
             for e in tup.term() {
-                if let Some(id) = e.id() {
-                    if let Some(ty) = env.get(&id) {
-                        let term = ExprTerm::Var(id.to_string(), ty.clone());
-                        arg.add_term(term);
-                    }
+                match e.id().and_then(|id| env.get(&id).map(|ty| (id, ty))) {
+                    Some((id, ty)) => arg.add_term(ExprTerm::Var(id, ty.clone())),
+                    // undefined variables are kept, so they can be reported later
+                    None => arg.add_term(e.clone()),
                 }
             }
         }
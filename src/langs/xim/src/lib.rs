@@ -1,8 +1,10 @@
 pub mod ast;
 pub mod default;
 pub mod display;
+pub mod elab;
 pub mod errors;
 pub mod helpers;
 pub mod infer;
+pub mod mono;
 pub mod parser;
 pub mod pretty_print;
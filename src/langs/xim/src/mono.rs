@@ -0,0 +1,26 @@
+use crate::ast::*;
+use crate::elab::{area_try_from_gen, body_try_from_gen};
+use crate::errors::Error;
+use crate::infer::infer_type_try_from_imp;
+use ir::mono::{subst_expr, subst_var, Subst};
+
+// implementation with every parameter replaced by its value, under a new name
+pub fn mono_try_from_imp(imp: &Imp, map: &Subst, name: &str) -> Result<Imp, Error> {
+    let mut sig = imp.sig().clone();
+    sig.set_id(name);
+    sig.input = subst_expr(imp.input(), map, subst_var)?;
+    sig.output = subst_expr(imp.output(), map, subst_var)?;
+    if let Some(area) = imp.gen_area() {
+        sig.set_area(area_try_from_gen(area, map)?);
+    }
+    let mut body: Vec<Instr> = Vec::new();
+    for mut instr in body_try_from_gen(imp.gen(), map)? {
+        instr.set_dst(subst_expr(instr.dst(), map, subst_var)?);
+        instr.set_arg(subst_expr(instr.arg(), map, subst_var)?);
+        body.push(instr);
+    }
+    let mut res = Imp::default();
+    res.set_sig(sig);
+    res.set_body(body);
+    Ok(infer_type_try_from_imp(&res))
+}
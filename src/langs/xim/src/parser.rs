@@ -1,7 +1,10 @@
 use crate::ast::*;
+use crate::elab::elab_try_from_target;
 use crate::errors::Error;
 use crate::infer::infer_type_try_from_target;
+use diag::path_from;
 use io::read_to_string;
+use ir::elab::GenTerm;
 use pest_consume::match_nodes;
 use pest_consume::Error as PestError;
 use pest_consume::Parser as PestParser;
@@ -10,7 +13,44 @@ use std::rc::Rc;
 use std::str::FromStr;
 
 pub type ParseResult<T> = std::result::Result<T, PestError<Rule>>;
-type Node<'i> = pest_consume::Node<'i, Rule, ()>;
+// nodes carry the path of the file they come from
+type Node<'i> = pest_consume::Node<'i, Rule, Option<Rc<str>>>;
+
+fn span(input: &Node) -> Span {
+    Span::new(input.as_span(), input.user_data().clone())
+}
+
+// dst and arg are set when the body is elaborated
+fn gen_mach(
+    op: OpMach,
+    attr: Expr,
+    dst: GenExpr,
+    arg: GenExpr,
+    loc: Option<Loc>,
+    span: Span,
+) -> GenInstr {
+    let instr = InstrMach {
+        op,
+        attr,
+        dst: Expr::default(),
+        arg: Expr::default(),
+        loc,
+        mem: None,
+        span,
+    };
+    GenInstr::Instr(Box::new(Instr::from(instr)), dst, arg)
+}
+
+fn gen_basc(op: OpBasc, attr: Expr, dst: GenExpr, arg: GenExpr, span: Span) -> GenInstr {
+    let instr = InstrBasc {
+        op,
+        dst: Expr::default(),
+        attr,
+        arg: Expr::default(),
+        span,
+    };
+    GenInstr::Instr(Box::new(Instr::from(instr)), dst, arg)
+}
 
 const _GRAMMAR: &str = include_str!("syntax.pest");
 
@@ -32,7 +72,7 @@ impl Parser {
         let val = u64::from_str_radix(input.as_str(), 2);
         match val {
             Ok(v) => Ok(ExprTerm::Val(v as i64)),
-            Err(_) => panic!("Error: parsing {} as bin i64", input.as_str()),
+            Err(_) => Err(input.error(format!("{} does not fit in 64 bits", input.as_str()))),
         }
     }
 
@@ -40,7 +80,7 @@ impl Parser {
         let val = u64::from_str_radix(input.as_str(), 16);
         match val {
             Ok(v) => Ok(ExprTerm::Val(v as i64)),
-            Err(_) => panic!("Error: parsing {} as hex i64", input.as_str()),
+            Err(_) => Err(input.error(format!("{} does not fit in 64 bits", input.as_str()))),
         }
     }
 
@@ -48,7 +88,7 @@ impl Parser {
         let val = input.as_str().parse::<i64>();
         match val {
             Ok(v) => Ok(ExprTerm::Val(v)),
-            Err(_) => panic!("Error: parsing {} as dec i64", input.as_str()),
+            Err(_) => Err(input.error(format!("{} does not fit in i64", input.as_str()))),
         }
     }
 
@@ -56,7 +96,7 @@ impl Parser {
         let val = input.as_str().parse::<u64>();
         match val {
             Ok(v) => Ok(v),
-            Err(_) => panic!("Error: parsing {} as dec u64", input.as_str()),
+            Err(_) => Err(input.error(format!("{} does not fit in u64", input.as_str()))),
         }
     }
 
@@ -73,7 +113,7 @@ impl Parser {
         let ty = Ty::from_str(input.as_str());
         match ty {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -81,7 +121,7 @@ impl Parser {
         let op = OpCoord::from_str(input.as_str());
         match op {
             Ok(e) => Ok(e),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -89,15 +129,29 @@ impl Parser {
         let expr = ExprCoord::from_str(input.as_str());
         match expr {
             Ok(e) => Ok(e),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
-    fn expr_coord(input: Node) -> ParseResult<ExprCoord> {
+    fn term_coord(input: Node) -> ParseResult<ExprCoord> {
         Ok(match_nodes!(
             input.into_children();
             [coord(coord)] => coord,
-            [coord(lhs), op_coord(op), coord(rhs)] => ExprCoord::Bin(op, Rc::new(lhs), Rc::new(rhs)),
+            [expr_coord(expr)] => expr,
+        ))
+    }
+
+    fn bin_coord(input: Node) -> ParseResult<(OpCoord, ExprCoord)> {
+        Ok(match_nodes!(
+            input.into_children();
+            [op_coord(op), term_coord(term)] => (op, term),
+        ))
+    }
+
+    fn expr_coord(input: Node) -> ParseResult<ExprCoord> {
+        Ok(match_nodes!(
+            input.into_children();
+            [term_coord(term), bin_coord(bin)..] => ExprCoord::from_terms(term, bin),
         ))
     }
 
@@ -105,7 +159,7 @@ impl Parser {
         let bel = BelBlock::from_str(input.as_str());
         match bel {
             Ok(t) => Ok(t.into()),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -113,7 +167,7 @@ impl Parser {
         let bel = BelDsp::from_str(input.as_str());
         match bel {
             Ok(t) => Ok(t.into()),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -121,7 +175,7 @@ impl Parser {
         let bel = BelReg::from_str(input.as_str());
         match bel {
             Ok(t) => Ok(t.into()),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -129,7 +183,7 @@ impl Parser {
         let bel = BelCarry::from_str(input.as_str());
         match bel {
             Ok(t) => Ok(t.into()),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -137,7 +191,7 @@ impl Parser {
         let bel = BelLut::from_str(input.as_str());
         match bel {
             Ok(t) => Ok(t.into()),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -145,7 +199,7 @@ impl Parser {
         let bel = BelLum::from_str(input.as_str());
         match bel {
             Ok(t) => Ok(t.into()),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -231,10 +285,60 @@ impl Parser {
         ))
     }
 
+    fn index_val(input: Node) -> ParseResult<Id> {
+        Ok(input.as_str().to_string())
+    }
+
+    fn param_val(input: Node) -> ParseResult<ExprTerm> {
+        Ok(match_nodes!(
+            input.into_children();
+            [val(val)] => val,
+            [id(id)] => ExprTerm::Var(id, Ty::Any),
+            [index_val(id)] => ExprTerm::Var(id, Ty::Any),
+        ))
+    }
+
     fn tup_val(input: Node) -> ParseResult<ExprTup> {
         Ok(match_nodes!(
             input.into_children();
-            [val(vals)..] => ExprTup{ term: vals.collect() },
+            [param_val(vals)..] => ExprTup{ term: vals.collect() },
+        ))
+    }
+
+    fn range(input: Node) -> ParseResult<Range> {
+        Ok(match_nodes!(
+            input.into_children();
+            [id(var), param_val(lo), param_val(hi)] => Range { var, lo, hi },
+        ))
+    }
+
+    fn gen_var(input: Node) -> ParseResult<GenTerm> {
+        Ok(match_nodes!(
+            input.into_children();
+            [range(range), tup_gen_var(tup)] => GenTerm::For(range, tup),
+        ))
+    }
+
+    fn var_item(input: Node) -> ParseResult<GenTerm> {
+        Ok(match_nodes!(
+            input.into_children();
+            [var(var)] => GenTerm::Term(var),
+            [gen_var(gen)] => gen,
+        ))
+    }
+
+    fn tup_gen_var(input: Node) -> ParseResult<Vec<GenTerm>> {
+        Ok(match_nodes!(
+            input.into_children();
+            [var_item(items)..] => items.collect(),
+        ))
+    }
+
+    fn gen_io(input: Node) -> ParseResult<GenExpr> {
+        Ok(match_nodes!(
+            input.into_children();
+            [var(var)] => GenExpr::Term(var),
+            [tup_gen_var(tup)] => GenExpr::Tup(tup),
         ))
     }
 
@@ -250,7 +354,7 @@ impl Parser {
         let op = OpMach::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -258,7 +362,7 @@ impl Parser {
         let op = OpMach::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -266,7 +370,7 @@ impl Parser {
         let op = OpMach::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -274,7 +378,7 @@ impl Parser {
         let op = OpMach::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -282,7 +386,7 @@ impl Parser {
         let op = OpMach::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -290,7 +394,7 @@ impl Parser {
         let op = OpMach::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -298,183 +402,77 @@ impl Parser {
         let op = OpBasc::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
-    fn instr_block(input: Node) -> ParseResult<Instr> {
-        Ok(match_nodes!(
-            input.into_children();
-            [io(dst), op_block(op), io(arg)] => Instr::from(InstrMach {
-                op,
-                attr: Expr::default(),
-                dst,
-                arg,
-                loc: None,
-                mem: None,
-            }),
-            [io(dst), op_block(op), io(arg), loc_block(loc)] => Instr::from(InstrMach {
-                op,
-                attr: Expr::default(),
-                dst,
-                arg,
-                loc: Some(loc),
-                mem: None,
-            })
-        ))
-    }
-
-    fn instr_dsp(input: Node) -> ParseResult<Instr> {
-        Ok(match_nodes!(
-            input.into_children();
-            [io(dst), op_dsp(op), io(arg)] => Instr::from(InstrMach {
-                op,
-                attr: Expr::default(),
-                dst,
-                arg,
-                loc: None,
-                mem: None,
-            }),
-            [io(dst), op_dsp(op), io(arg), loc_dsp(loc)] => Instr::from(InstrMach {
-                op,
-                attr: Expr::default(),
-                dst,
-                arg,
-                loc: Some(loc),
-                mem: None,
-            })
-        ))
-    }
-
-    fn instr_reg(input: Node) -> ParseResult<Instr> {
-        Ok(match_nodes!(
-            input.into_children();
-            [io(dst), op_reg(op), io(arg)] => Instr::from(InstrMach {
-                op,
-                attr: Expr::default(),
-                dst,
-                arg,
-                loc: None,
-                mem: None,
-            }),
-            [io(dst), op_reg(op), io(arg), loc_reg(loc)] => Instr::from(InstrMach {
-                op,
-                attr: Expr::default(),
-                dst,
-                arg,
-                loc: Some(loc),
-                mem: None,
-            })
-        ))
-    }
-
-    fn instr_carry(input: Node) -> ParseResult<Instr> {
-        Ok(match_nodes!(
-            input.into_children();
-            [io(dst), op_carry(op), io(arg)] => Instr::from(InstrMach {
-                op,
-                attr: Expr::default(),
-                dst,
-                arg,
-                loc: None,
-                mem: None,
-            }),
-            [io(dst), op_carry(op), io(arg), loc_carry(loc)] => Instr::from(InstrMach {
-                op,
-                attr: Expr::default(),
-                dst,
-                arg,
-                loc: Some(loc),
-                mem: None,
-            })
-        ))
-    }
-
-    fn instr_lut(input: Node) -> ParseResult<Instr> {
-        Ok(match_nodes!(
-            input.into_children();
-            [io(dst), op_lut(op), io(arg)] => Instr::from(InstrMach {
-                op,
-                attr: Expr::default(),
-                dst,
-                arg,
-                loc: None,
-                mem: None,
-            }),
-            [io(dst), op_lut(op), io(arg), loc_lut(loc)] => Instr::from(InstrMach {
-                op,
-                attr: Expr::default(),
-                dst,
-                arg,
-                loc: Some(loc),
-                mem: None,
-            }),
-            [io(dst), op_lut(op), tup_val(attr), io(arg)] => Instr::from(InstrMach {
-                op,
-                attr: Expr::from(attr),
-                dst,
-                arg,
-                loc: None,
-                mem: None,
-            }),
-            [io(dst), op_lut(op), tup_val(attr), io(arg), loc_lut(loc)] => Instr::from(InstrMach {
-                op,
-                attr: Expr::from(attr),
-                dst,
-                arg,
-                loc: Some(loc),
-                mem: None,
-            })
-        ))
-    }
-
-    fn instr_lum(input: Node) -> ParseResult<Instr> {
-        Ok(match_nodes!(
-            input.into_children();
-            [io(dst), op_lum(op), io(arg)] => Instr::from(InstrMach {
-                op,
-                attr: Expr::default(),
-                dst,
-                arg,
-                loc: None,
-                mem: None,
-            }),
-            [io(dst), op_lum(op), io(arg), loc_lum(loc)] => Instr::from(InstrMach {
-                op,
-                attr: Expr::default(),
-                dst,
-                arg,
-                loc: Some(loc),
-                mem: None,
-            })
-        ))
-    }
-
-    fn instr_basc(input: Node) -> ParseResult<Instr> {
-        Ok(match_nodes!(
-            input.into_children();
-            [io(dst), op_basc(op), tup_val(attr)] => Instr::from(InstrBasc {
-                op,
-                dst,
-                attr: Expr::from(attr),
-                arg: Expr::default(),
-            }),
-            [io(dst), op_basc(op), io(arg)] => Instr::from(InstrBasc {
-                op,
-                dst,
-                attr: Expr::default(),
-                arg,
-            }),
-            [io(dst), op_basc(op), tup_val(attr), io(arg)] => Instr::from(InstrBasc {
-                op,
-                dst,
-                attr: Expr::from(attr),
-                arg,
-            })
-        ))
-    }
-
-    fn instr(input: Node) -> ParseResult<Instr> {
+    fn instr_block(input: Node) -> ParseResult<GenInstr> {
+        let span = span(&input);
+        Ok(match_nodes!(
+            input.into_children();
+            [gen_io(dst), op_block(op), gen_io(arg)] => gen_mach(op, Expr::default(), dst, arg, None, span.clone()),
+            [gen_io(dst), op_block(op), gen_io(arg), loc_block(loc)] => gen_mach(op, Expr::default(), dst, arg, Some(loc), span.clone()),
+        ))
+    }
+
+    fn instr_dsp(input: Node) -> ParseResult<GenInstr> {
+        let span = span(&input);
+        Ok(match_nodes!(
+            input.into_children();
+            [gen_io(dst), op_dsp(op), gen_io(arg)] => gen_mach(op, Expr::default(), dst, arg, None, span.clone()),
+            [gen_io(dst), op_dsp(op), gen_io(arg), loc_dsp(loc)] => gen_mach(op, Expr::default(), dst, arg, Some(loc), span.clone()),
+        ))
+    }
+
+    fn instr_reg(input: Node) -> ParseResult<GenInstr> {
+        let span = span(&input);
+        Ok(match_nodes!(
+            input.into_children();
+            [gen_io(dst), op_reg(op), gen_io(arg)] => gen_mach(op, Expr::default(), dst, arg, None, span.clone()),
+            [gen_io(dst), op_reg(op), gen_io(arg), loc_reg(loc)] => gen_mach(op, Expr::default(), dst, arg, Some(loc), span.clone()),
+        ))
+    }
+
+    fn instr_carry(input: Node) -> ParseResult<GenInstr> {
+        let span = span(&input);
+        Ok(match_nodes!(
+            input.into_children();
+            [gen_io(dst), op_carry(op), gen_io(arg)] => gen_mach(op, Expr::default(), dst, arg, None, span.clone()),
+            [gen_io(dst), op_carry(op), gen_io(arg), loc_carry(loc)] => gen_mach(op, Expr::default(), dst, arg, Some(loc), span.clone()),
+        ))
+    }
+
+    fn instr_lut(input: Node) -> ParseResult<GenInstr> {
+        let span = span(&input);
+        Ok(match_nodes!(
+            input.into_children();
+            [gen_io(dst), op_lut(op), gen_io(arg)] => gen_mach(op, Expr::default(), dst, arg, None, span.clone()),
+            [gen_io(dst), op_lut(op), gen_io(arg), loc_lut(loc)] => gen_mach(op, Expr::default(), dst, arg, Some(loc), span.clone()),
+            [gen_io(dst), op_lut(op), tup_val(attr), gen_io(arg)] => gen_mach(op, Expr::from(attr), dst, arg, None, span.clone()),
+            [gen_io(dst), op_lut(op), tup_val(attr), gen_io(arg), loc_lut(loc)] => gen_mach(op, Expr::from(attr), dst, arg, Some(loc), span.clone()),
+        ))
+    }
+
+    fn instr_lum(input: Node) -> ParseResult<GenInstr> {
+        let span = span(&input);
+        Ok(match_nodes!(
+            input.into_children();
+            [gen_io(dst), op_lum(op), gen_io(arg)] => gen_mach(op, Expr::default(), dst, arg, None, span.clone()),
+            [gen_io(dst), op_lum(op), gen_io(arg), loc_lum(loc)] => gen_mach(op, Expr::default(), dst, arg, Some(loc), span.clone()),
+        ))
+    }
+
+    fn instr_basc(input: Node) -> ParseResult<GenInstr> {
+        let span = span(&input);
+        Ok(match_nodes!(
+            input.into_children();
+            [gen_io(dst), op_basc(op), tup_val(attr)] => gen_basc(op, Expr::from(attr), dst, GenExpr::default(), span.clone()),
+            [gen_io(dst), op_basc(op), gen_io(arg)] => gen_basc(op, Expr::default(), dst, arg, span.clone()),
+            [gen_io(dst), op_basc(op), tup_val(attr), gen_io(arg)] => gen_basc(op, Expr::from(attr), dst, arg, span.clone()),
+        ))
+    }
+
+    fn instr(input: Node) -> ParseResult<GenInstr> {
         Ok(match_nodes!(
             input.into_children();
             [instr_basc(instr)] => instr,
@@ -487,50 +485,84 @@ impl Parser {
         ))
     }
 
-    fn body(input: Node) -> ParseResult<Vec<Instr>> {
+    fn gen_instr(input: Node) -> ParseResult<GenInstr> {
         Ok(match_nodes!(
             input.into_children();
-            [instr(instr)..] => instr.collect(),
+            [range(range)] => GenInstr::For(range, Vec::new()),
+            [range(range), body(body)] => GenInstr::For(range, body),
         ))
     }
 
-    fn sig(input: Node) -> ParseResult<Sig> {
+    fn body_item(input: Node) -> ParseResult<GenInstr> {
         Ok(match_nodes!(
             input.into_children();
-            [id(id), cost(area), cost(perf)] => Sig {
-                id,
-                input: Expr::default(),
-                output: Expr::default(),
-                area,
-                perf,
-            },
-            [id(id), cost(area), cost(perf), io(output)] => Sig {
-                id,
-                input: Expr::default(),
-                output,
-                area,
-                perf,
-            },
-            [id(id), cost(area), cost(perf), io(input), io(output)] => Sig {
+            [instr(instr)] => instr,
+            [gen_instr(gen)] => gen,
+        ))
+    }
+
+    fn body(input: Node) -> ParseResult<Vec<GenInstr>> {
+        Ok(match_nodes!(
+            input.into_children();
+            [body_item(items)..] => items.collect(),
+        ))
+    }
+
+    fn param(input: Node) -> ParseResult<Vec<Id>> {
+        Ok(match_nodes!(
+            input.into_children();
+            [id(ids)..] => ids.collect(),
+        ))
+    }
+
+    fn area(input: Node) -> ParseResult<(u64, Option<Id>)> {
+        Ok(match_nodes!(
+            input.into_children();
+            [cost(area)] => (area, None),
+            [index_val(area)] => (0, Some(area)),
+        ))
+    }
+
+    fn sig(input: Node) -> ParseResult<Imp> {
+        let span = span(&input);
+        let (id, param, area, perf, input, output) = match_nodes!(
+            input.into_children();
+            [id(id), area(area), cost(perf)] =>
+                (id, Vec::new(), area, perf, Expr::default(), Expr::default()),
+            [id(id), area(area), cost(perf), io(output)] =>
+                (id, Vec::new(), area, perf, Expr::default(), output),
+            [id(id), area(area), cost(perf), io(input), io(output)] =>
+                (id, Vec::new(), area, perf, input, output),
+            [id(id), param(param), area(area), cost(perf)] =>
+                (id, param, area, perf, Expr::default(), Expr::default()),
+            [id(id), param(param), area(area), cost(perf), io(output)] =>
+                (id, param, area, perf, Expr::default(), output),
+            [id(id), param(param), area(area), cost(perf), io(input), io(output)] =>
+                (id, param, area, perf, input, output),
+        );
+        let (area, gen_area) = area;
+        Ok(Imp {
+            sig: Sig {
                 id,
                 input,
                 output,
                 area,
                 perf,
+                span,
             },
-        ))
+            param,
+            gen_area,
+            ..Imp::default()
+        })
     }
 
     fn imp(input: Node) -> ParseResult<Imp> {
         Ok(match_nodes!(
             input.into_children();
-            [sig(sig)] => Imp {
-                sig,
-                body: Vec::new(),
-            },
-            [sig(sig), body(body)] => Imp {
-                sig,
-                body,
+            [sig(sig)] => sig,
+            [sig(sig), body(gen)] => Imp {
+                gen,
+                ..sig
             },
         ))
     }
@@ -558,14 +590,22 @@ impl Parser {
 }
 
 impl Parser {
-    pub fn parse_from_str(input_str: &str) -> Result<Target, Error> {
-        let inputs = Parser::parse(Rule::file, input_str)?;
+    fn parse_with_path(input_str: &str, path: Option<Rc<str>>) -> Result<Target, Error> {
+        let inputs = Parser::parse_with_userdata(Rule::file, input_str, path)?;
         let input = inputs.single()?;
         let target = Parser::file(input)?;
+        let target = elab_try_from_target(&target)?;
         Ok(infer_type_try_from_target(&target))
     }
+    pub fn parse_from_str(input_str: &str) -> Result<Target, Error> {
+        Parser::parse_with_path(input_str, None)
+    }
     pub fn parse_from_file<P: AsRef<Path>>(path: P) -> Result<Target, Error> {
-        let content = read_to_string(path);
-        Parser::parse_from_str(&content)
+        let content = read_to_string(&path);
+        let path = path_from(&path);
+        match Parser::parse_with_path(&content, Some(path.clone())) {
+            Err(Error::Parse(e)) => Err(Error::Parse(e.with_path(&path))),
+            res => res,
+        }
     }
 }
@@ -1,36 +1,97 @@
 use crate::ast::*;
+use ir::pretty_print::{expr_attrs, gen_expr_names};
 use itertools::Itertools;
 use prettyprint::{block_with_braces, intersperse, PrettyHelper, PrettyPrint, RcDoc};
 
-impl PrettyPrint for Sig {
-    fn to_doc(&self) -> RcDoc<()> {
-        let attr_val = vec![RcDoc::as_string(self.area()), RcDoc::as_string(self.perf())];
-        let attr = intersperse(
-            attr_val.iter().cloned(),
+fn sig_doc<'a>(sig: &'a Sig, param: &'a [Id], area: Option<&'a Id>) -> RcDoc<'a, ()> {
+    let area = match area {
+        Some(area) => RcDoc::as_string(area),
+        None => RcDoc::as_string(sig.area()),
+    };
+    let attr_val = [area, RcDoc::as_string(sig.perf())];
+    let attr = intersperse(
+        attr_val.iter().cloned(),
+        RcDoc::text(",").append(RcDoc::space()),
+    )
+    .brackets();
+    let param = if param.is_empty() {
+        RcDoc::nil()
+    } else {
+        intersperse(
+            param.iter().map(RcDoc::as_string),
             RcDoc::text(",").append(RcDoc::space()),
         )
-        .brackets();
-        RcDoc::text("imp")
-            .append(RcDoc::space())
-            .append(RcDoc::as_string(self.id()))
-            .append(attr)
-            .append(self.input().to_doc())
-            .append(RcDoc::space())
-            .append(RcDoc::text("->"))
-            .append(RcDoc::space())
-            .append(self.output().to_doc())
+        .comps()
+    };
+    RcDoc::text("imp")
+        .append(RcDoc::space())
+        .append(RcDoc::as_string(sig.id()))
+        .append(param)
+        .append(attr)
+        .append(sig.input().to_doc())
+        .append(RcDoc::space())
+        .append(RcDoc::text("->"))
+        .append(RcDoc::space())
+        .append(sig.output().to_doc())
+}
+
+impl PrettyPrint for Sig {
+    fn to_doc(&self) -> RcDoc<()> {
+        sig_doc(self, &[], None)
+    }
+}
+
+impl PrettyPrint for GenInstr {
+    fn to_doc(&self) -> RcDoc<()> {
+        match self {
+            GenInstr::Instr(instr, dst, arg) => {
+                let (op, attr, loc) = match instr.as_ref() {
+                    Instr::Basc(basc) => (basc.op().to_doc(), basc.attr(), None),
+                    Instr::Mach(mach) => (mach.op().to_doc(), mach.attr(), mach.loc()),
+                };
+                let arg = match instr.as_ref() {
+                    Instr::Basc(basc) if matches!(basc.op(), OpBasc::Gnd | OpBasc::Vcc) => {
+                        RcDoc::nil().parens()
+                    }
+                    _ => gen_expr_names(arg),
+                };
+                let loc = if let Some(loc) = loc {
+                    RcDoc::space().append(RcDoc::text("@")).append(loc.to_doc())
+                } else {
+                    RcDoc::nil()
+                };
+                dst.to_doc()
+                    .append(RcDoc::space())
+                    .append(RcDoc::text("="))
+                    .append(RcDoc::space())
+                    .append(op)
+                    .append(expr_attrs(attr))
+                    .append(arg)
+                    .append(loc)
+                    .append(RcDoc::text(";"))
+            }
+            GenInstr::For(range, body) => block_with_braces(
+                range.to_doc(),
+                intersperse(body.iter().map(|i| i.to_doc()), RcDoc::hardline()),
+            ),
+        }
     }
 }
 
 impl PrettyPrint for Imp {
     fn to_doc(&self) -> RcDoc<()> {
-        let sig = self.sig().to_doc();
-        let body = intersperse(
-            self.body()
-                .iter()
-                .map(|i| i.to_doc().append(RcDoc::text(";"))),
-            RcDoc::hardline(),
-        );
+        let sig = sig_doc(self.sig(), self.param(), self.gen_area());
+        // parametric implementations print their body template
+        let body = if self.is_param() {
+            intersperse(self.gen().iter().map(|i| i.to_doc()), RcDoc::hardline())
+        } else {
+            intersperse(
+                self.body()
+                    .iter()
+                    .map(|i| i.to_doc().append(RcDoc::text(";"))),
+                RcDoc::hardline(),
+            )
+        };
         block_with_braces(sig, body)
     }
 }
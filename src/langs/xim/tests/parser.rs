@@ -1,4 +1,5 @@
 use xim::ast::{Imp, Sig, Target};
+use xim::mono::mono_try_from_imp;
 use xim::parser::Parser;
 
 #[test]
@@ -14,3 +15,48 @@ fn simple_target() {
     exp.insert(&imp.id(), imp);
     assert_eq!(res, exp);
 }
+
+#[test]
+fn loop_target() {
+    let res = Parser::parse_from_str(
+        "imp lnot_i2[1, 1](a:i2) -> (y:i2) {
+            for i in 0..2 {
+                a{i}:bool = ext[{i}](a);
+                y{i}:bool = lut1[1](a{i}) @a6(??, ??);
+            }
+            y:i2 = cat(for i in 0..2 { y{i} });
+        }",
+    )
+    .unwrap();
+    let imp = res.get("lnot_i2").unwrap();
+    assert!(imp.gen().is_empty());
+    assert_eq!(imp.body().len(), 5);
+    assert_eq!(imp.body()[4].arg().to_string(), "(y0:bool, y1:bool)");
+}
+
+#[test]
+fn generic_target() {
+    let res = Parser::parse_from_str(
+        "imp lrega<W>[{(W+7)/8}, 2](a:i<W>, en:bool) -> (y:i<W>) {
+            for k in 0..{(W+7)/8} {
+                for i in {8*k}..{8*k+8} {
+                    a{i}:bool = ext[{i}](a);
+                }
+                y{8*k}:bool = fdre(a{8*k}, en) @a(??, ??+k);
+            }
+            y:i<W> = cat(for k in 0..{(W+7)/8} { y{8*k} });
+        }",
+    )
+    .unwrap();
+    let imp = res.get("lrega").unwrap();
+    assert!(imp.is_param());
+    assert!(imp.body().is_empty());
+    let map = [("W".to_string(), 12)].iter().cloned().collect();
+    let spec = mono_try_from_imp(imp, &map, "lrega_i12").unwrap();
+    assert_eq!(spec.body().len(), 19);
+    assert_eq!(spec.area(), 2);
+    assert_eq!(
+        spec.body()[17].to_string(),
+        "y8:bool = fdre(a8, en) @a(??, ??+1)"
+    );
+}
@@ -11,6 +11,7 @@ pub type ExprTup = ir::ExprTup;
 pub type Expr = ir::Expr;
 pub type ExprCoord = asm::ExprCoord;
 pub type Sig = ir::Sig;
+pub type Span = ir::Span;
 pub type Mem = mmap::Mem;
 // pub type Mmap = mmap::Mmap;
 
@@ -111,6 +112,7 @@ pub enum BelBlock {
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash)]
 pub enum BelLum {
     H6,
+    D6,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash)]
@@ -123,6 +125,13 @@ pub enum Bel {
     Lum(BelLum),
 }
 
+// device family, which decides the primitives bels are implemented with
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug, Eq, Hash)]
+pub enum Family {
+    UltraScale,
+    Series7,
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash)]
 pub struct Loc {
     pub bel: Bel,
@@ -136,6 +145,8 @@ pub struct InstrBasc {
     pub attr: Expr,
     pub dst: Expr,
     pub arg: Expr,
+    #[serde(skip)]
+    pub span: Span,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash)]
@@ -146,6 +157,8 @@ pub struct InstrMach {
     pub arg: Expr,
     pub loc: Option<Loc>,
     pub mem: Option<Mem>,
+    #[serde(skip)]
+    pub span: Span,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Eq, Hash)]
@@ -19,6 +19,7 @@ display!(BelReg);
 display!(BelCarry);
 display!(BelDsp);
 display!(Bel);
+display!(Family);
 display!(Loc);
 display!(InstrBasc);
 display!(InstrMach);
@@ -0,0 +1,33 @@
+use crate::ast::*;
+use ir::dot::{dot_cluster, dot_digraph, dot_label, DotNode};
+
+// cells are drawn by the kind of bel they are placed on
+fn bel_attr(loc: Option<&Loc>) -> &'static str {
+    match loc.map(|l| l.bel()) {
+        Some(Bel::Lut(_)) => "fillcolor = lightblue",
+        Some(Bel::Reg(_)) => "fillcolor = khaki, peripheries = 2",
+        Some(Bel::Carry(_)) => "fillcolor = plum",
+        Some(Bel::Dsp(_)) => "fillcolor = orange",
+        Some(Bel::Block(_)) => "fillcolor = mediumseagreen",
+        Some(Bel::Lum(_)) => "fillcolor = palegreen",
+        None => "fillcolor = lightgrey",
+    }
+}
+
+pub fn dot_from_prog(prog: &Prog) -> String {
+    let mut node: Vec<DotNode> = Vec::new();
+    for instr in prog.body() {
+        let dst = instr.dst().to_string();
+        let attr = match instr {
+            Instr::Basc(basc) => dot_label(&[dst, basc.op().to_string()]),
+            Instr::Mach(mach) => {
+                let loc = mach.loc().map_or("??".to_string(), |l| l.to_string());
+                let label = dot_label(&[dst, mach.op().to_string(), format!("@{}", loc)]);
+                format!("{}, {}", label, bel_attr(mach.loc()))
+            }
+        };
+        node.push(DotNode::new(instr.dst(), instr.arg(), &attr));
+    }
+    let sig = prog.sig();
+    dot_digraph(&[dot_cluster(&sig.id(), sig.input(), sig.output(), &node)])
+}
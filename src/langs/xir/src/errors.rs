@@ -1,4 +1,5 @@
 use crate::parser;
+use ir::errors::Error as IrError;
 use pest_consume::Error as PestError;
 use std::fmt;
 use std::num::ParseIntError;
@@ -9,6 +10,8 @@ pub enum Error {
     Parser(PestError<parser::Rule>),
     Conversion(String),
     Type(String),
+    Interp(String),
+    Ir(IrError),
     TryFromInt(TryFromIntError),
     ParseInt(ParseIntError),
 }
@@ -20,6 +23,9 @@ impl Error {
     pub fn new_type_error(msg: &str) -> Self {
         Error::Conversion(msg.to_string())
     }
+    pub fn new_interp_error(msg: &str) -> Self {
+        Error::Interp(msg.to_string())
+    }
 }
 
 impl From<PestError<parser::Rule>> for Error {
@@ -28,6 +34,12 @@ impl From<PestError<parser::Rule>> for Error {
     }
 }
 
+impl From<IrError> for Error {
+    fn from(e: IrError) -> Self {
+        Error::Ir(e)
+    }
+}
+
 impl From<TryFromIntError> for Error {
     fn from(e: TryFromIntError) -> Self {
         Error::TryFromInt(e)
@@ -47,6 +59,8 @@ impl fmt::Display for Error {
             Error::ParseInt(msg) => write!(f, "{}", msg),
             Error::Conversion(msg) => write!(f, "{}", msg),
             Error::Type(msg) => write!(f, "{}", msg),
+            Error::Interp(msg) => write!(f, "{}", msg),
+            Error::Ir(msg) => write!(f, "{}", msg),
             Error::TryFromInt(msg) => write!(f, "{}", msg),
         }
     }
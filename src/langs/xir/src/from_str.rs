@@ -144,6 +144,19 @@ impl FromStr for BelLum {
         let err = format!("Error: {} is not valid lum bel", input);
         match input {
             "h6" => Ok(BelLum::H6),
+            "d6" => Ok(BelLum::D6),
+            _ => Err(Error::new_conv_error(&err)),
+        }
+    }
+}
+
+impl FromStr for Family {
+    type Err = Error;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let err = format!("Error: {} is not valid family", input);
+        match input {
+            "ultrascale" => Ok(Family::UltraScale),
+            "7series" => Ok(Family::Series7),
             _ => Err(Error::new_conv_error(&err)),
         }
     }
@@ -16,6 +16,29 @@ impl OpMach {
                 | OpMach::MulAddRegACio
         )
     }
+    // ops with registers or memories, which take the clock of their domain
+    pub fn is_clocked(&self) -> bool {
+        matches!(
+            self,
+            OpMach::Fdre
+                | OpMach::Fdse
+                | OpMach::VecAddRegA
+                | OpMach::MulAddRegA
+                | OpMach::MulAddRegACi
+                | OpMach::MulAddRegACo
+                | OpMach::MulAddRegACio
+                | OpMach::Lram
+                | OpMach::Bram
+                | OpMach::Lrom
+                | OpMach::Brom
+        )
+    }
+}
+
+impl Default for Family {
+    fn default() -> Self {
+        Family::UltraScale
+    }
 }
 
 impl Loc {
@@ -55,6 +78,9 @@ impl InstrBasc {
     pub fn arg(&self) -> &Expr {
         &self.arg
     }
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
     pub fn set_dst(&mut self, dst: Expr) {
         self.dst = dst;
     }
@@ -82,18 +108,27 @@ impl InstrMach {
     pub fn mem(&self) -> Option<&Mem> {
         self.mem.as_ref()
     }
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
     pub fn set_loc(&mut self, loc: Loc) {
         self.loc = Some(loc);
     }
     pub fn set_dst(&mut self, dst: Expr) {
         self.dst = dst;
     }
+    pub fn set_attr(&mut self, attr: Expr) {
+        self.attr = attr;
+    }
     pub fn set_arg(&mut self, arg: Expr) {
         self.arg = arg;
     }
     pub fn set_mem(&mut self, mem: Mem) {
         self.mem = Some(mem);
     }
+    pub fn set_span(&mut self, span: Span) {
+        self.span = span;
+    }
 }
 
 impl Instr {
@@ -121,6 +156,12 @@ impl Instr {
             Instr::Mach(instr) => instr.arg(),
         }
     }
+    pub fn span(&self) -> &Span {
+        match self {
+            Instr::Basc(instr) => instr.span(),
+            Instr::Mach(instr) => instr.span(),
+        }
+    }
     pub fn set_dst(&mut self, dst: Expr) {
         match self {
             Instr::Basc(instr) => instr.set_dst(dst),
@@ -133,6 +174,12 @@ impl Instr {
             Instr::Mach(instr) => instr.set_arg(arg),
         }
     }
+    pub fn set_span(&mut self, span: Span) {
+        match self {
+            Instr::Basc(instr) => instr.span = span,
+            Instr::Mach(instr) => instr.set_span(span),
+        }
+    }
 }
 
 impl Prog {
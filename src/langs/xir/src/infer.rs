@@ -34,11 +34,10 @@ pub fn infer_type_try_from_prog(prog: &Prog) -> Prog {
         let mut arg = ExprTup::default();
         if let Some(tup) = instr.arg().tup() {
             for e in tup.term() {
-                if let Some(id) = e.id() {
-                    if let Some(ty) = env.get(&id) {
-                        let term = ExprTerm::Var(id.to_string(), ty.clone());
-                        arg.add_term(term);
-                    }
+                match e.id().and_then(|id| env.get(&id).map(|ty| (id, ty))) {
+                    Some((id, ty)) => arg.add_term(ExprTerm::Var(id, ty.clone())),
+                    // undefined variables are kept, so they can be reported later
+                    None => arg.add_term(e.clone()),
                 }
             }
         }
@@ -0,0 +1,531 @@
+use crate::ast::*;
+use crate::errors::Error;
+use ir::interp::{Env, Value};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+
+#[derive(Clone, Debug)]
+pub struct Interp {
+    sig: Sig,
+    body: Vec<Instr>,
+    // registers of sequential instructions, by destination id. The last one
+    // drives the output of the instruction
+    state: HashMap<Id, Vec<Value>>,
+    mem: HashMap<Id, Vec<i128>>,
+    cycle: u64,
+}
+
+fn mask(width: u64) -> u128 {
+    if width >= 128 {
+        u128::MAX
+    } else {
+        (1_u128 << width) - 1
+    }
+}
+
+fn bits(width: u64, value: i128) -> u128 {
+    (value as u128) & mask(width)
+}
+
+fn is_seq(op: &OpMach) -> bool {
+    matches!(
+        op,
+        OpMach::Fdre
+            | OpMach::Fdse
+            | OpMach::VecAddRegA
+            | OpMach::MulAddRegA
+            | OpMach::MulAddRegACi
+            | OpMach::MulAddRegACo
+            | OpMach::MulAddRegACio
+            | OpMach::Bram
+            | OpMach::Brom
+    )
+}
+
+fn is_mem(op: &OpMach) -> bool {
+    matches!(
+        op,
+        OpMach::Lram | OpMach::Bram | OpMach::Lrom | OpMach::Brom
+    )
+}
+
+fn is_seq_instr(instr: &Instr) -> bool {
+    instr.mach().map_or(false, |m| is_seq(m.op()))
+}
+
+fn dst_try_from_instr(instr: &Instr) -> Result<(Id, &Ty), Error> {
+    let dst = instr.dst();
+    let ty = dst.get_ty(0)?;
+    match ty.width() {
+        Some(width) if width <= 128 => Ok((dst.get_id(0)?, ty)),
+        Some(_) => {
+            let err = format!("{} is wider than 128 bits", dst.get_id(0)?);
+            Err(Error::new_interp_error(&err))
+        }
+        None => {
+            let err = format!("{} has unknown type", dst.get_id(0)?);
+            Err(Error::new_interp_error(&err))
+        }
+    }
+}
+
+fn width_try_from_term(term: &ExprTerm) -> Result<u64, Error> {
+    if let Some(width) = term.width() {
+        Ok(width)
+    } else {
+        let err = format!("{} has unknown width", term.get_id()?);
+        Err(Error::new_interp_error(&err))
+    }
+}
+
+fn check_arity(instr: &Instr, arg: &[ExprTerm], num: usize) -> Result<(), Error> {
+    if arg.len() == num {
+        Ok(())
+    } else {
+        let err = format!(
+            "{} expects {} arguments but got {}",
+            instr.dst().get_id(0)?,
+            num,
+            arg.len()
+        );
+        Err(Error::new_interp_error(&err))
+    }
+}
+
+fn value_try_from_term<'a>(env: &'a Env, term: &ExprTerm) -> Result<&'a Value, Error> {
+    let id = term.get_id()?;
+    if let Some(value) = env.get(&id) {
+        Ok(value)
+    } else {
+        let err = format!("{} is not defined", id);
+        Err(Error::new_interp_error(&err))
+    }
+}
+
+fn args_try_from_instr<'a>(
+    instr: &Instr,
+    env: &'a Env,
+    num: usize,
+) -> Result<(Vec<ExprTerm>, Vec<&'a Value>), Error> {
+    let term: Vec<ExprTerm> = instr.arg().clone().into();
+    check_arity(instr, &term, num)?;
+    let mut arg: Vec<&Value> = Vec::new();
+    for t in &term {
+        arg.push(value_try_from_term(env, t)?);
+    }
+    Ok((term, arg))
+}
+
+fn eval_lanes<F>(ty: &Ty, arg: &[&Value], f: F) -> Value
+where
+    F: Fn(&[i128]) -> i128,
+{
+    let length = ty.length().unwrap_or(1) as usize;
+    let lanes: Vec<i128> = (0..length)
+        .map(|i| {
+            let lane: Vec<i128> = arg.iter().map(|a| a.lane(i)).collect();
+            f(&lane)
+        })
+        .collect();
+    Value::new(ty, &lanes)
+}
+
+fn value_try_from_input(ty: &Ty, value: &Value) -> Result<Value, Error> {
+    let length = ty.length().unwrap_or(1) as usize;
+    if ty.is_vector() == value.is_vector() && value.len() == length {
+        Ok(Value::new(ty, &value.lanes()))
+    } else {
+        Err(Error::new_interp_error("input value does not match type"))
+    }
+}
+
+// registers with an enable keep their old value when it is low
+fn hold(en: &Value, new: Value, old: &Value) -> Result<Value, Error> {
+    if en.get_scalar()? != 0 {
+        Ok(new)
+    } else {
+        Ok(old.clone())
+    }
+}
+
+fn eval_basc(instr: &Instr, basc: &InstrBasc, env: &Env) -> Result<Value, Error> {
+    let (_, ty) = dst_try_from_instr(instr)?;
+    match basc.op() {
+        OpBasc::Gnd => Ok(Value::new(ty, &[0])),
+        OpBasc::Vcc => Ok(eval_lanes(ty, &[], |_| -1)),
+        OpBasc::Id => {
+            let (_, arg) = args_try_from_instr(instr, env, 1)?;
+            Ok(eval_lanes(ty, &arg, |a| a[0]))
+        }
+        OpBasc::Ext => {
+            let (term, arg) = args_try_from_instr(instr, env, 1)?;
+            let width = width_try_from_term(&term[0])?;
+            let lo = u64::try_from(basc.attr().get_val(0)?)?;
+            let hi = if let Some(t) = basc.attr().idx(1) {
+                u64::try_from(t.get_val()?)?
+            } else {
+                lo
+            };
+            if lo > hi || hi >= width {
+                let err = format!(
+                    "{} extracts bits {} to {} of {} bits",
+                    instr.dst().get_id(0)?,
+                    lo,
+                    hi,
+                    width
+                );
+                return Err(Error::new_interp_error(&err));
+            }
+            let value = (bits(width, arg[0].get_scalar()?) >> lo) & mask(hi - lo + 1);
+            Ok(Value::new(ty, &[value as i128]))
+        }
+        OpBasc::Cat => {
+            let term: Vec<ExprTerm> = basc.arg().clone().into();
+            let mut value: u128 = 0;
+            let mut shift: u64 = 0;
+            // first argument is the least significant one
+            for t in &term {
+                let width = width_try_from_term(t)?;
+                let v = bits(width, value_try_from_term(env, t)?.get_scalar()?);
+                if shift < 128 {
+                    value |= v << shift;
+                }
+                shift += width;
+            }
+            Ok(Value::new(ty, &[value as i128]))
+        }
+    }
+}
+
+fn eval_lut(instr: &Instr, mach: &InstrMach, env: &Env, num: usize) -> Result<Value, Error> {
+    let (_, ty) = dst_try_from_instr(instr)?;
+    let (_, arg) = args_try_from_instr(instr, env, num)?;
+    let init = mach.attr().get_val(0)? as u64;
+    let mut index: u32 = 0;
+    for (i, a) in arg.iter().enumerate() {
+        index |= (bits(1, a.get_scalar()?) as u32) << i;
+    }
+    Ok(Value::new(ty, &[i128::from((init >> index) & 1)]))
+}
+
+// carry chain, o[i] = s[i] ^ c[i] and c[i+1] = s[i] ? c[i] : di[i]. The
+// carry-in is an optional third argument, tied to zero otherwise, and the
+// carry-out of every bit is an optional second destination
+fn eval_carry(instr: &Instr, env: &Env) -> Result<(Value, Option<Value>), Error> {
+    let (_, ty) = dst_try_from_instr(instr)?;
+    let term: Vec<ExprTerm> = instr.arg().clone().into();
+    let num = if term.len() == 3 { 3 } else { 2 };
+    let (term, arg) = args_try_from_instr(instr, env, num)?;
+    let width = ty.width().unwrap_or(0).min(128);
+    let di = bits(width_try_from_term(&term[0])?, arg[0].get_scalar()?);
+    let s = bits(width_try_from_term(&term[1])?, arg[1].get_scalar()?);
+    let mut carry: u128 = if num == 3 {
+        bits(1, arg[2].get_scalar()?)
+    } else {
+        0
+    };
+    let mut value: u128 = 0;
+    let mut out: u128 = 0;
+    for i in 0..width {
+        let si = (s >> i) & 1;
+        value |= (si ^ carry) << i;
+        if si == 0 {
+            carry = (di >> i) & 1;
+        }
+        out |= carry << i;
+    }
+    let co = match instr.dst().get_ty(1) {
+        Ok(ty) => Some(Value::new(ty, &[out as i128])),
+        Err(_) => None,
+    };
+    Ok((Value::new(ty, &[value as i128]), co))
+}
+
+fn eval_comb_mach(instr: &Instr, mach: &InstrMach, env: &Env) -> Result<Value, Error> {
+    let (_, ty) = dst_try_from_instr(instr)?;
+    match mach.op() {
+        OpMach::Lut1 => eval_lut(instr, mach, env, 1),
+        OpMach::Lut2 => eval_lut(instr, mach, env, 2),
+        OpMach::Lut3 => eval_lut(instr, mach, env, 3),
+        OpMach::Lut4 => eval_lut(instr, mach, env, 4),
+        OpMach::Lut5 => eval_lut(instr, mach, env, 5),
+        OpMach::Lut6 => eval_lut(instr, mach, env, 6),
+        OpMach::VecAdd => {
+            let (_, arg) = args_try_from_instr(instr, env, 2)?;
+            Ok(eval_lanes(ty, &arg, |a| a[0].wrapping_add(a[1])))
+        }
+        OpMach::VecSub => {
+            let (_, arg) = args_try_from_instr(instr, env, 2)?;
+            Ok(eval_lanes(ty, &arg, |a| a[0].wrapping_sub(a[1])))
+        }
+        OpMach::VecMul | OpMach::Mul => {
+            let (_, arg) = args_try_from_instr(instr, env, 2)?;
+            Ok(eval_lanes(ty, &arg, |a| a[0].wrapping_mul(a[1])))
+        }
+        OpMach::MulAdd => {
+            let (_, arg) = args_try_from_instr(instr, env, 3)?;
+            Ok(eval_lanes(ty, &arg, |a| {
+                a[0].wrapping_mul(a[1]).wrapping_add(a[2])
+            }))
+        }
+        _ => {
+            let err = format!(
+                "{} uses {:?}, which is not supported by the interpreter",
+                instr.dst().get_id(0)?,
+                mach.op()
+            );
+            Err(Error::new_interp_error(&err))
+        }
+    }
+}
+
+// order instructions so that arguments are computed before they are used,
+// inputs and the outputs of sequential instructions are known at the start of a cycle
+fn sort_try_from_prog(prog: &Prog) -> Result<Vec<Instr>, Error> {
+    let mut defined: HashSet<Id> = HashSet::new();
+    let input: Vec<ExprTerm> = prog.sig().input.clone().into();
+    for term in input {
+        defined.insert(term.get_id()?);
+    }
+    let mut seq: Vec<Instr> = Vec::new();
+    let mut comb: Vec<Instr> = Vec::new();
+    for instr in prog.body() {
+        if is_seq_instr(instr) {
+            defined.insert(instr.dst().get_id(0)?);
+            seq.push(instr.clone());
+        } else {
+            comb.push(instr.clone());
+        }
+    }
+    let mut body: Vec<Instr> = Vec::new();
+    while !comb.is_empty() {
+        let (ready, rest): (Vec<Instr>, Vec<Instr>) = comb.into_iter().partition(|instr| {
+            let arg: Vec<ExprTerm> = instr.arg().clone().into();
+            arg.iter()
+                .all(|t| t.id().map_or(true, |id| defined.contains(&id)))
+        });
+        if ready.is_empty() {
+            let mut dst: HashSet<Id> = HashSet::new();
+            for instr in &rest {
+                let term: Vec<ExprTerm> = instr.dst().clone().into();
+                for t in term {
+                    dst.insert(t.get_id()?);
+                }
+            }
+            for instr in &rest {
+                let arg: Vec<ExprTerm> = instr.arg().clone().into();
+                for id in arg.iter().filter_map(|t| t.id()) {
+                    if !defined.contains(&id) && !dst.contains(&id) {
+                        let err = format!("{} is not defined", id);
+                        return Err(Error::new_interp_error(&err));
+                    }
+                }
+            }
+            let err = format!(
+                "{} is part of a combinational loop",
+                rest[0].dst().get_id(0)?
+            );
+            return Err(Error::new_interp_error(&err));
+        }
+        for instr in &ready {
+            let dst: Vec<ExprTerm> = instr.dst().clone().into();
+            for term in dst {
+                defined.insert(term.get_id()?);
+            }
+        }
+        body.extend(ready);
+        comb = rest;
+    }
+    body.extend(seq);
+    Ok(body)
+}
+
+impl Interp {
+    pub fn try_from_prog(prog: &Prog) -> Result<Self, Error> {
+        let body = sort_try_from_prog(prog)?;
+        let mut state = HashMap::new();
+        for instr in body.iter().filter(|i| is_seq_instr(i)) {
+            let (id, ty) = dst_try_from_instr(instr)?;
+            let op = instr.mach().map(|m| m.op().clone());
+            let num = match op {
+                Some(OpMach::VecAddRegA) => 3,
+                Some(OpMach::Fdre) | Some(OpMach::Fdse) => 1,
+                Some(OpMach::Bram) | Some(OpMach::Brom) => 1,
+                _ => 4,
+            };
+            // fdre[init, value] starts with init, as expanded by xpand
+            let init = match (&op, instr.mach().and_then(|m| m.attr().idx(0))) {
+                (Some(OpMach::Fdre), Some(term)) | (Some(OpMach::Fdse), Some(term)) => {
+                    i128::from(term.get_val()? & 1)
+                }
+                _ => 0,
+            };
+            state.insert(id, vec![Value::new(ty, &[init]); num]);
+        }
+        Ok(Interp {
+            sig: prog.sig().clone(),
+            body,
+            state,
+            mem: HashMap::new(),
+            cycle: 0,
+        })
+    }
+    pub fn cycle(&self) -> u64 {
+        self.cycle
+    }
+    fn read_mem(&self, id: &str, addr: u128) -> i128 {
+        if let Some(mem) = self.mem.get(id) {
+            usize::try_from(addr)
+                .ok()
+                .and_then(|a| mem.get(a).copied())
+                .unwrap_or(0)
+        } else {
+            0
+        }
+    }
+    fn eval_mem(&self, instr: &Instr, mach: &InstrMach, env: &Env) -> Result<Value, Error> {
+        let (id, ty) = dst_try_from_instr(instr)?;
+        let num = if matches!(mach.op(), OpMach::Lrom | OpMach::Brom) {
+            1
+        } else {
+            3
+        };
+        let (term, arg) = args_try_from_instr(instr, env, num)?;
+        let width = width_try_from_term(&term[0])?;
+        let value = self.read_mem(&id, bits(width, arg[0].get_scalar()?));
+        Ok(Value::new(ty, &[value]))
+    }
+    fn eval(&self, input: &Env) -> Result<Env, Error> {
+        let mut env = Env::new();
+        let inp: Vec<ExprTerm> = self.sig.input.clone().into();
+        for term in inp {
+            let id = term.get_id()?;
+            if let Some(value) = input.get(&id) {
+                let value = value_try_from_input(term.get_ty()?, value)?;
+                env.insert(id, value);
+            } else {
+                let err = format!("missing value for input {}", id);
+                return Err(Error::new_interp_error(&err));
+            }
+        }
+        for (id, state) in &self.state {
+            env.insert(id.to_string(), state[state.len() - 1].clone());
+        }
+        for instr in self.body.iter().filter(|i| !is_seq_instr(i)) {
+            let value = match instr {
+                Instr::Basc(basc) => eval_basc(instr, basc, &env)?,
+                Instr::Mach(mach) if mach.op() == &OpMach::CarryAdd => {
+                    let (value, co) = eval_carry(instr, &env)?;
+                    if let Some(co) = co {
+                        env.insert(instr.dst().get_id(1)?, co);
+                    }
+                    value
+                }
+                Instr::Mach(mach) if is_mem(mach.op()) => self.eval_mem(instr, mach, &env)?,
+                Instr::Mach(mach) => eval_comb_mach(instr, mach, &env)?,
+            };
+            env.insert(instr.dst().get_id(0)?, value);
+        }
+        Ok(env)
+    }
+    // clock edge, every register is updated with the values of the current cycle
+    fn update(&mut self, env: &Env) -> Result<(), Error> {
+        let mut state: Vec<(Id, Vec<Value>)> = Vec::new();
+        let mut mem: Vec<(Id, u128, i128)> = Vec::new();
+        for instr in &self.body {
+            let mach = if let Instr::Mach(mach) = instr {
+                mach
+            } else {
+                continue;
+            };
+            let (id, ty) = dst_try_from_instr(instr)?;
+            match mach.op() {
+                OpMach::Fdre | OpMach::Fdse => {
+                    let (_, arg) = args_try_from_instr(instr, env, 2)?;
+                    let old = &self.state[&id];
+                    let q = hold(arg[1], Value::new(ty, &arg[0].lanes()), &old[0])?;
+                    state.push((id, vec![q]));
+                }
+                OpMach::VecAddRegA => {
+                    let (_, arg) = args_try_from_instr(instr, env, 5)?;
+                    let old = &self.state[&id];
+                    let sum = eval_lanes(ty, &[&old[0], &old[1]], |a| a[0].wrapping_add(a[1]));
+                    let a = hold(arg[2], Value::new(ty, &arg[0].lanes()), &old[0])?;
+                    let b = hold(arg[3], Value::new(ty, &arg[1].lanes()), &old[1])?;
+                    let p = hold(arg[4], sum, &old[2])?;
+                    state.push((id, vec![a, b, p]));
+                }
+                OpMach::MulAddRegA
+                | OpMach::MulAddRegACi
+                | OpMach::MulAddRegACo
+                | OpMach::MulAddRegACio => {
+                    let (_, arg) = args_try_from_instr(instr, env, 7)?;
+                    let old = &self.state[&id];
+                    let prod = eval_lanes(ty, &[&old[0], &old[1]], |a| a[0].wrapping_mul(a[1]));
+                    let sum = eval_lanes(ty, &[&old[2], arg[2]], |a| a[0].wrapping_add(a[1]));
+                    let a = hold(arg[3], Value::new(ty, &arg[0].lanes()), &old[0])?;
+                    let b = hold(arg[4], Value::new(ty, &arg[1].lanes()), &old[1])?;
+                    let m = hold(arg[5], prod, &old[2])?;
+                    let p = hold(arg[6], sum, &old[3])?;
+                    state.push((id, vec![a, b, m, p]));
+                }
+                // block rams read through their output register, which
+                // keeps its value in write cycles (WRITE_MODE NO_CHANGE)
+                OpMach::Brom => {
+                    let q = self.eval_mem(instr, mach, env)?;
+                    state.push((id, vec![q]));
+                }
+                OpMach::Lram | OpMach::Bram => {
+                    let (term, arg) = args_try_from_instr(instr, env, 3)?;
+                    if arg[2].get_scalar()? != 0 {
+                        let width = width_try_from_term(&term[0])?;
+                        let addr = bits(width, arg[0].get_scalar()?);
+                        let data = Value::new(ty, &arg[1].lanes()).get_scalar()?;
+                        mem.push((id, addr, data));
+                    } else if mach.op() == &OpMach::Bram {
+                        let q = self.eval_mem(instr, mach, env)?;
+                        state.push((id, vec![q]));
+                    }
+                }
+                _ => (),
+            }
+        }
+        for (id, value) in state {
+            self.state.insert(id, value);
+        }
+        for (id, addr, data) in mem {
+            let addr = usize::try_from(addr)?;
+            let values = self.mem.entry(id).or_default();
+            if values.len() <= addr {
+                values.resize(addr + 1, 0);
+            }
+            values[addr] = data;
+        }
+        Ok(())
+    }
+    // evaluate one cycle, returns outputs before the clock edge
+    pub fn step(&mut self, input: &Env) -> Result<Env, Error> {
+        let env = self.eval(input)?;
+        let mut output = Env::new();
+        let out: Vec<ExprTerm> = self.sig.output.clone().into();
+        for term in out {
+            let value = value_try_from_term(&env, &term)?;
+            output.insert(term.get_id()?, value.clone());
+        }
+        self.update(&env)?;
+        self.cycle += 1;
+        Ok(output)
+    }
+    pub fn run(&mut self, input: &[Env]) -> Result<Vec<Env>, Error> {
+        let mut output = Vec::new();
+        for i in input {
+            output.push(self.step(i)?);
+        }
+        Ok(output)
+    }
+}
+
+pub fn interp_try_from_prog(prog: &Prog, input: &[Env]) -> Result<Vec<Env>, Error> {
+    let mut interp = Interp::try_from_prog(prog)?;
+    interp.run(input)
+}
@@ -1,9 +1,11 @@
 pub mod ast;
 pub mod display;
+pub mod dot;
 pub mod errors;
 pub mod from;
 pub mod from_str;
 pub mod helpers;
 pub mod infer;
+pub mod interp;
 pub mod parser;
 pub mod pretty_print;
@@ -1,6 +1,7 @@
 use crate::ast::*;
 use crate::errors::Error;
 use crate::infer::infer_type_try_from_prog;
+use diag::path_from;
 use io::read_to_string;
 use pest_consume::match_nodes;
 use pest_consume::Error as PestError;
@@ -10,7 +11,12 @@ use std::rc::Rc;
 use std::str::FromStr;
 
 pub type ParseResult<T> = std::result::Result<T, PestError<Rule>>;
-type Node<'i> = pest_consume::Node<'i, Rule, ()>;
+// nodes carry the path of the file they come from
+type Node<'i> = pest_consume::Node<'i, Rule, Option<Rc<str>>>;
+
+fn span(input: &Node) -> Span {
+    Span::new(input.as_span(), input.user_data().clone())
+}
 
 const _GRAMMAR: &str = include_str!("syntax.pest");
 
@@ -32,7 +38,7 @@ impl Parser {
         let val = u64::from_str_radix(input.as_str(), 2);
         match val {
             Ok(v) => Ok(ExprTerm::Val(v as i64)),
-            Err(_) => panic!("Error: parsing {} as bin i64", input.as_str()),
+            Err(_) => Err(input.error(format!("{} does not fit in 64 bits", input.as_str()))),
         }
     }
 
@@ -40,7 +46,7 @@ impl Parser {
         let val = u64::from_str_radix(input.as_str(), 16);
         match val {
             Ok(v) => Ok(ExprTerm::Val(v as i64)),
-            Err(_) => panic!("Error: parsing {} as hex i64", input.as_str()),
+            Err(_) => Err(input.error(format!("{} does not fit in 64 bits", input.as_str()))),
         }
     }
 
@@ -48,7 +54,7 @@ impl Parser {
         let val = input.as_str().parse::<i64>();
         match val {
             Ok(v) => Ok(ExprTerm::Val(v)),
-            Err(_) => panic!("Error: parsing {} as dec i64", input.as_str()),
+            Err(_) => Err(input.error(format!("{} does not fit in i64", input.as_str()))),
         }
     }
 
@@ -65,7 +71,7 @@ impl Parser {
         let ty = Ty::from_str(input.as_str());
         match ty {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -73,7 +79,7 @@ impl Parser {
         let op = OpCoord::from_str(input.as_str());
         match op {
             Ok(e) => Ok(e),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -81,15 +87,29 @@ impl Parser {
         let expr = ExprCoord::from_str(input.as_str());
         match expr {
             Ok(e) => Ok(e),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
-    fn expr_coord(input: Node) -> ParseResult<ExprCoord> {
+    fn term_coord(input: Node) -> ParseResult<ExprCoord> {
         Ok(match_nodes!(
             input.into_children();
             [coord(coord)] => coord,
-            [coord(lhs), op_coord(op), coord(rhs)] => ExprCoord::Bin(op, Rc::new(lhs), Rc::new(rhs)),
+            [expr_coord(expr)] => expr,
+        ))
+    }
+
+    fn bin_coord(input: Node) -> ParseResult<(OpCoord, ExprCoord)> {
+        Ok(match_nodes!(
+            input.into_children();
+            [op_coord(op), term_coord(term)] => (op, term),
+        ))
+    }
+
+    fn expr_coord(input: Node) -> ParseResult<ExprCoord> {
+        Ok(match_nodes!(
+            input.into_children();
+            [term_coord(term), bin_coord(bin)..] => ExprCoord::from_terms(term, bin),
         ))
     }
 
@@ -97,7 +117,7 @@ impl Parser {
         let bel = BelBlock::from_str(input.as_str());
         match bel {
             Ok(t) => Ok(t.into()),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -105,7 +125,7 @@ impl Parser {
         let bel = BelDsp::from_str(input.as_str());
         match bel {
             Ok(t) => Ok(t.into()),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -113,7 +133,7 @@ impl Parser {
         let bel = BelReg::from_str(input.as_str());
         match bel {
             Ok(t) => Ok(t.into()),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -121,7 +141,7 @@ impl Parser {
         let bel = BelCarry::from_str(input.as_str());
         match bel {
             Ok(t) => Ok(t.into()),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -129,7 +149,7 @@ impl Parser {
         let bel = BelLut::from_str(input.as_str());
         match bel {
             Ok(t) => Ok(t.into()),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -137,7 +157,7 @@ impl Parser {
         let bel = BelLum::from_str(input.as_str());
         match bel {
             Ok(t) => Ok(t.into()),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -242,7 +262,7 @@ impl Parser {
         let op = OpMach::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -250,7 +270,7 @@ impl Parser {
         let op = OpMach::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -258,7 +278,7 @@ impl Parser {
         let op = OpMach::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -266,7 +286,7 @@ impl Parser {
         let op = OpMach::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -274,7 +294,7 @@ impl Parser {
         let op = OpMach::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -282,7 +302,7 @@ impl Parser {
         let op = OpMach::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
@@ -290,11 +310,12 @@ impl Parser {
         let op = OpBasc::from_str(input.as_str());
         match op {
             Ok(t) => Ok(t),
-            Err(m) => panic!("{}", m),
+            Err(m) => Err(input.error(m)),
         }
     }
 
     fn instr_block(input: Node) -> ParseResult<Instr> {
+        let span = span(&input);
         Ok(match_nodes!(
             input.into_children();
             [io(dst), op_block(op), io(arg)] => Instr::from(InstrMach {
@@ -304,6 +325,7 @@ impl Parser {
                 arg,
                 loc: None,
                 mem: None,
+                span: span.clone(),
             }),
             [io(dst), op_block(op), io(arg), loc_block(loc)] => Instr::from(InstrMach {
                 op,
@@ -312,11 +334,13 @@ impl Parser {
                 arg,
                 loc: Some(loc),
                 mem: None,
+                span: span.clone(),
             })
         ))
     }
 
     fn instr_dsp(input: Node) -> ParseResult<Instr> {
+        let span = span(&input);
         Ok(match_nodes!(
             input.into_children();
             [io(dst), op_dsp(op), io(arg)] => Instr::from(InstrMach {
@@ -326,6 +350,7 @@ impl Parser {
                 arg,
                 loc: None,
                 mem: None,
+                span: span.clone(),
             }),
             [io(dst), op_dsp(op), io(arg), loc_dsp(loc)] => Instr::from(InstrMach {
                 op,
@@ -334,11 +359,13 @@ impl Parser {
                 arg,
                 loc: Some(loc),
                 mem: None,
+                span: span.clone(),
             })
         ))
     }
 
     fn instr_reg(input: Node) -> ParseResult<Instr> {
+        let span = span(&input);
         Ok(match_nodes!(
             input.into_children();
             [io(dst), op_reg(op), io(arg)] => Instr::from(InstrMach {
@@ -348,6 +375,7 @@ impl Parser {
                 arg,
                 loc: None,
                 mem: None,
+                span: span.clone(),
             }),
             [io(dst), op_reg(op), io(arg), loc_reg(loc)] => Instr::from(InstrMach {
                 op,
@@ -356,11 +384,31 @@ impl Parser {
                 arg,
                 loc: Some(loc),
                 mem: None,
+                span: span.clone(),
+            }),
+            [io(dst), op_reg(op), tup_val(attr), io(arg)] => Instr::from(InstrMach {
+                op,
+                attr: Expr::from(attr),
+                dst,
+                arg,
+                loc: None,
+                mem: None,
+                span: span.clone(),
+            }),
+            [io(dst), op_reg(op), tup_val(attr), io(arg), loc_reg(loc)] => Instr::from(InstrMach {
+                op,
+                attr: Expr::from(attr),
+                dst,
+                arg,
+                loc: Some(loc),
+                mem: None,
+                span: span.clone(),
             })
         ))
     }
 
     fn instr_carry(input: Node) -> ParseResult<Instr> {
+        let span = span(&input);
         Ok(match_nodes!(
             input.into_children();
             [io(dst), op_carry(op), io(arg)] => Instr::from(InstrMach {
@@ -370,6 +418,7 @@ impl Parser {
                 arg,
                 loc: None,
                 mem: None,
+                span: span.clone(),
             }),
             [io(dst), op_carry(op), io(arg), loc_carry(loc)] => Instr::from(InstrMach {
                 op,
@@ -378,11 +427,13 @@ impl Parser {
                 arg,
                 loc: Some(loc),
                 mem: None,
+                span: span.clone(),
             })
         ))
     }
 
     fn instr_lut(input: Node) -> ParseResult<Instr> {
+        let span = span(&input);
         Ok(match_nodes!(
             input.into_children();
             [io(dst), op_lut(op), io(arg)] => Instr::from(InstrMach {
@@ -392,6 +443,7 @@ impl Parser {
                 arg,
                 loc: None,
                 mem: None,
+                span: span.clone(),
             }),
             [io(dst), op_lut(op), io(arg), loc_lut(loc)] => Instr::from(InstrMach {
                 op,
@@ -400,6 +452,7 @@ impl Parser {
                 arg,
                 loc: Some(loc),
                 mem: None,
+                span: span.clone(),
             }),
             [io(dst), op_lut(op), tup_val(attr), io(arg)] => Instr::from(InstrMach {
                 op,
@@ -408,6 +461,7 @@ impl Parser {
                 arg,
                 loc: None,
                 mem: None,
+                span: span.clone(),
             }),
             [io(dst), op_lut(op), tup_val(attr), io(arg), loc_lut(loc)] => Instr::from(InstrMach {
                 op,
@@ -416,11 +470,13 @@ impl Parser {
                 arg,
                 loc: Some(loc),
                 mem: None,
+                span: span.clone(),
             })
         ))
     }
 
     fn instr_lum(input: Node) -> ParseResult<Instr> {
+        let span = span(&input);
         Ok(match_nodes!(
             input.into_children();
             [io(dst), op_lum(op), io(arg)] => Instr::from(InstrMach {
@@ -430,6 +486,7 @@ impl Parser {
                 arg,
                 loc: None,
                 mem: None,
+                span: span.clone(),
             }),
             [io(dst), op_lum(op), io(arg), loc_lum(loc)] => Instr::from(InstrMach {
                 op,
@@ -438,11 +495,13 @@ impl Parser {
                 arg,
                 loc: Some(loc),
                 mem: None,
+                span: span.clone(),
             })
         ))
     }
 
     fn instr_basc(input: Node) -> ParseResult<Instr> {
+        let span = span(&input);
         Ok(match_nodes!(
             input.into_children();
             [io(dst), op_basc(op), tup_val(attr)] => Instr::from(InstrBasc {
@@ -450,18 +509,21 @@ impl Parser {
                 dst,
                 attr: Expr::from(attr),
                 arg: Expr::default(),
+                span: span.clone(),
             }),
             [io(dst), op_basc(op), io(arg)] => Instr::from(InstrBasc {
                 op,
                 dst,
                 attr: Expr::default(),
                 arg,
+                span: span.clone(),
             }),
             [io(dst), op_basc(op), tup_val(attr), io(arg)] => Instr::from(InstrBasc {
                 op,
                 dst,
                 attr: Expr::from(attr),
                 arg,
+                span: span.clone(),
             })
         ))
     }
@@ -487,22 +549,26 @@ impl Parser {
     }
 
     fn sig(input: Node) -> ParseResult<Sig> {
+        let span = span(&input);
         Ok(match_nodes!(
             input.into_children();
             [id(id)] => Sig {
                 id,
                 input: Expr::default(),
                 output: Expr::default(),
+                span: span.clone(),
             },
             [id(id), io(output)] => Sig {
                 id,
                 input: Expr::default(),
                 output,
+                span: span.clone(),
             },
             [id(id), io(input), io(output)] => Sig {
                 id,
                 input,
                 output,
+                span: span.clone(),
             },
         ))
     }
@@ -530,14 +596,21 @@ impl Parser {
 }
 
 impl Parser {
-    pub fn parse_from_str(input_str: &str) -> Result<Prog, Error> {
-        let inputs = Parser::parse(Rule::file, input_str)?;
+    fn parse_with_path(input_str: &str, path: Option<Rc<str>>) -> Result<Prog, Error> {
+        let inputs = Parser::parse_with_userdata(Rule::file, input_str, path)?;
         let input = inputs.single()?;
         let prog = Parser::file(input)?;
         Ok(infer_type_try_from_prog(&prog))
     }
+    pub fn parse_from_str(input_str: &str) -> Result<Prog, Error> {
+        Parser::parse_with_path(input_str, None)
+    }
     pub fn parse_from_file<P: AsRef<Path>>(path: P) -> Result<Prog, Error> {
-        let content = read_to_string(path);
-        Parser::parse_from_str(&content)
+        let content = read_to_string(&path);
+        let path = path_from(&path);
+        match Parser::parse_with_path(&content, Some(path.clone())) {
+            Err(Error::Parser(e)) => Err(Error::Parser(e.with_path(&path))),
+            res => res,
+        }
     }
 }
@@ -121,6 +121,16 @@ impl PrettyPrint for BelLum {
     fn to_doc(&self) -> RcDoc<()> {
         match self {
             BelLum::H6 => RcDoc::text("h6"),
+            BelLum::D6 => RcDoc::text("d6"),
+        }
+    }
+}
+
+impl PrettyPrint for Family {
+    fn to_doc(&self) -> RcDoc<()> {
+        match self {
+            Family::UltraScale => RcDoc::text("ultrascale"),
+            Family::Series7 => RcDoc::text("7series"),
         }
     }
 }
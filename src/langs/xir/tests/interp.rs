@@ -0,0 +1,165 @@
+use ir::interp::{Env, Value};
+use xir::errors::Error;
+use xir::interp::interp_try_from_prog;
+use xir::parser::Parser;
+
+const ADD: &str = "def main(a:i4, b:i4) -> (y:i4) {
+    t0:bool = ext[0](a);
+    t1:bool = ext[1](a);
+    t2:bool = ext[2](a);
+    t3:bool = ext[3](a);
+    t4:bool = ext[0](b);
+    t5:bool = ext[1](b);
+    t6:bool = ext[2](b);
+    t7:bool = ext[3](b);
+    t8:bool = lut2[6](t0, t4) @a6(??, ??);
+    t9:bool = lut2[6](t1, t5) @b6(??, ??);
+    t10:bool = lut2[6](t2, t6) @c6(??, ??);
+    t11:bool = lut2[6](t3, t7) @d6(??, ??);
+    t12:i4 = cat(t8, t9, t10, t11);
+    y:i4 = carryadd(a, t12) @c8(??, ??);
+}";
+
+fn env(values: &[(&str, i64)]) -> Env {
+    values
+        .iter()
+        .map(|(n, v)| (n.to_string(), Value::from(*v)))
+        .collect()
+}
+
+fn is_interp_error(res: Result<Vec<Env>, Error>, msg: &str) -> bool {
+    match res {
+        Err(Error::Interp(err)) => err.contains(msg),
+        _ => false,
+    }
+}
+
+#[test]
+fn lut_carry_add() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(ADD)?;
+    let mut input = Vec::new();
+    for a in -8..8 {
+        for b in -8..8 {
+            input.push(env(&[("a", a), ("b", b)]));
+        }
+    }
+    let res = interp_try_from_prog(&prog, &input)?;
+    for (i, o) in input.iter().zip(res.iter()) {
+        let sum = i["a"].get_scalar()? + i["b"].get_scalar()?;
+        let exp = ((sum + 8) & 0xf) - 8;
+        assert_eq!(o["y"], Value::Scalar(exp));
+    }
+    Ok(())
+}
+
+#[test]
+fn fdre_init() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        "def main(a:bool, en:bool) -> (y:bool, z:bool) {
+            y:bool = fdre(a, en) @a(??, ??);
+            z:bool = fdre[1](a, en) @b(??, ??);
+        }",
+    )?;
+    let input = [
+        env(&[("a", 0), ("en", 0)]),
+        env(&[("a", 0), ("en", 1)]),
+        env(&[("a", 1), ("en", 1)]),
+        env(&[("a", 0), ("en", 0)]),
+    ];
+    let res = interp_try_from_prog(&prog, &input)?;
+    let y: Vec<i128> = res.iter().map(|o| o["y"].get_scalar().unwrap()).collect();
+    let z: Vec<i128> = res.iter().map(|o| o["z"].get_scalar().unwrap()).collect();
+    assert_eq!(y, vec![0, 0, 0, 1]);
+    assert_eq!(z, vec![1, 1, 0, 1]);
+    Ok(())
+}
+
+#[test]
+fn dsp_pipeline() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        "def main(a:i8, b:i8, c:i8, en:bool) -> (y:i8) {
+            y:i8 = muladdrega(a, b, c, en, en, en, en) @alu(??, ??);
+        }",
+    )?;
+    let input = [
+        env(&[("a", 3), ("b", 5), ("c", 1), ("en", 1)]),
+        env(&[("a", 0), ("b", 0), ("c", 1), ("en", 1)]),
+        env(&[("a", 0), ("b", 0), ("c", 2), ("en", 1)]),
+        env(&[("a", 0), ("b", 0), ("c", 0), ("en", 1)]),
+    ];
+    let res = interp_try_from_prog(&prog, &input)?;
+    assert_eq!(res[3]["y"], Value::from(17));
+    Ok(())
+}
+
+#[test]
+fn undefined() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        "def main(a:bool) -> (y:i2) {
+            t0:bool = gnd();
+            y:i2 = cat(a, gnd);
+        }",
+    )?;
+    let res = interp_try_from_prog(&prog, &[env(&[("a", 1)])]);
+    assert!(is_interp_error(res, "gnd is not defined"));
+    Ok(())
+}
+
+#[test]
+fn carry_chain() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        "def main(a0:i4, p0:i4, a1:i4, p1:i4) -> (y0:i4, y1:i4) {
+            t0:bool = gnd();
+            (y0:i4, t1:i4) = carryadd(a0, p0, t0) @c8(??, ??);
+            t2:bool = ext[3](t1);
+            (y1:i4, t3:i4) = carryadd(a1, p1, t2) @c8(??, ??);
+        }",
+    )?;
+    let mut input = Vec::new();
+    for a in (0..256).step_by(7) {
+        for b in (0..256).step_by(5) {
+            let p = a ^ b;
+            input.push(env(&[
+                ("a0", a & 0xf),
+                ("p0", p & 0xf),
+                ("a1", a >> 4),
+                ("p1", p >> 4),
+            ]));
+        }
+    }
+    let res = interp_try_from_prog(&prog, &input)?;
+    for (i, o) in input.iter().zip(res.iter()) {
+        let a = i["a0"].get_scalar()? | (i["a1"].get_scalar()? << 4);
+        let p = i["p0"].get_scalar()? | (i["p1"].get_scalar()? << 4);
+        let sum = (a + (a ^ p)) & 0xff;
+        let y = (o["y0"].get_scalar()? & 0xf) | ((o["y1"].get_scalar()? & 0xf) << 4);
+        assert_eq!(y, sum);
+    }
+    Ok(())
+}
+
+#[test]
+fn bram_read() -> Result<(), Error> {
+    let prog = Parser::parse_from_str(
+        "def main(addr:i3, data:i8, en:bool) -> (y:i8, z:i8) {
+            t0:i16 = bram(addr, data, en) @l(??, ??);
+            y:i8 = ext[0, 7](t0);
+            z:i8 = lram(addr, data, en) @h6(??, ??);
+        }",
+    )?;
+    let input = [
+        env(&[("addr", 0), ("data", 5), ("en", 1)]),
+        env(&[("addr", 0), ("data", 7), ("en", 0)]),
+        env(&[("addr", 1), ("data", 0), ("en", 0)]),
+        env(&[("addr", 0), ("data", 9), ("en", 1)]),
+        env(&[("addr", 0), ("data", 0), ("en", 0)]),
+        env(&[("addr", 0), ("data", 0), ("en", 0)]),
+    ];
+    let res = interp_try_from_prog(&prog, &input)?;
+    let y: Vec<i128> = res.iter().map(|o| o["y"].get_scalar().unwrap()).collect();
+    let z: Vec<i128> = res.iter().map(|o| o["z"].get_scalar().unwrap()).collect();
+    // the block ram output register holds in write cycles
+    assert_eq!(y, vec![0, 0, 5, 0, 0, 9]);
+    assert_eq!(z, vec![0, 5, 0, 5, 9, 9]);
+    Ok(())
+}
@@ -8,3 +8,15 @@ fn simple_prog() {
     exp.set_id("main");
     assert_eq!(res, exp);
 }
+
+#[test]
+fn reg_attr() {
+    let res = Parser::parse_from_str(
+        "def main (a:bool, en:bool) -> (y:bool) { y:bool = fdre[1, 0](a, en) @a(??, ??); }",
+    )
+    .unwrap();
+    let instr = res.body()[0].mach().unwrap();
+    assert_eq!(instr.attr().get_val(0).unwrap(), 1);
+    assert_eq!(instr.attr().get_val(1).unwrap(), 0);
+    assert!(res.to_string().contains("fdre[1, 0](a, en)"));
+}
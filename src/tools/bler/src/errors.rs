@@ -1,5 +1,6 @@
 use asm::errors::Error as AsmError;
 use ir::errors::Error as IrError;
+use registry::errors::Error as RegistryError;
 use std::fmt;
 use xir::errors::Error as XirError;
 
@@ -7,6 +8,7 @@ use xir::errors::Error as XirError;
 pub enum Error {
     Ir(IrError),
     Asm(AsmError),
+    Registry(RegistryError),
     Xir(XirError),
     Bler(String),
 }
@@ -35,11 +37,18 @@ impl From<IrError> for Error {
     }
 }
 
+impl From<RegistryError> for Error {
+    fn from(e: RegistryError) -> Self {
+        Error::Registry(e)
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Error::Ir(msg) => write!(f, "{}", msg),
             Error::Asm(msg) => write!(f, "{}", msg),
+            Error::Registry(msg) => write!(f, "{}", msg),
             Error::Xir(msg) => write!(f, "{}", msg),
             Error::Bler(msg) => write!(f, "{}", msg),
         }
@@ -3,13 +3,109 @@ pub mod errors;
 use crate::errors::Error;
 use asm::ast as asm;
 use mmap::Mmap;
+use registry::spec::spec_try_from_dsts;
+use registry::Target;
 use std::collections::HashMap;
-use std::path::Path;
 use xim::ast as xim;
 use xir::ast as xir;
 
 type Scope = HashMap<xir::ExprTerm, xir::ExprTerm>;
 
+// asm instructions outside the default clock domain carry their clock last
+fn clock_from_expr(expr: &asm::Expr) -> Option<xir::ExprTerm> {
+    let term: Vec<asm::ExprTerm> = expr.clone().into();
+    term.into_iter()
+        .last()
+        .filter(|t| t.ty().map_or(false, |ty| ty.is_clock()))
+}
+
+// implementation coordinates are relative to the instruction, both ?? and
+// variables stand for its coordinate and constants are offsets from it
+fn coord_from_imp(imp: &xir::ExprCoord, instr: &xir::ExprCoord) -> xir::ExprCoord {
+    match imp {
+        xir::ExprCoord::Any | xir::ExprCoord::Var(_) => instr.clone(),
+        xir::ExprCoord::Val(_) => imp.clone(),
+        xir::ExprCoord::Bin(op, lhs, rhs) => {
+            let lhs = coord_from_imp(lhs, instr);
+            let rhs = coord_from_imp(rhs, instr);
+            match (op, &lhs, &rhs) {
+                (xir::OpCoord::Add, _, xir::ExprCoord::Val(0))
+                | (xir::OpCoord::Sub, _, xir::ExprCoord::Val(0)) => return lhs,
+                (xir::OpCoord::Add, xir::ExprCoord::Val(0), _) => return rhs,
+                _ => (),
+            }
+            let val = match (op, &lhs, &rhs) {
+                (xir::OpCoord::Add, xir::ExprCoord::Val(a), xir::ExprCoord::Val(b)) => {
+                    a.checked_add(*b)
+                }
+                (xir::OpCoord::Sub, xir::ExprCoord::Val(a), xir::ExprCoord::Val(b)) => {
+                    a.checked_sub(*b)
+                }
+                (xir::OpCoord::Mul, xir::ExprCoord::Val(a), xir::ExprCoord::Val(b)) => {
+                    a.checked_mul(*b)
+                }
+                _ => None,
+            };
+            match val {
+                Some(v) => xir::ExprCoord::Val(v),
+                None => xir::ExprCoord::new_bin(op.clone(), lhs, rhs),
+            }
+        }
+    }
+}
+
+// the output bit each term of an implementation drives, following cat and id
+// back from the output, so flops take their values from the bit they hold
+fn bit_from_imp(imp: &xim::Imp) -> HashMap<xir::ExprTerm, u32> {
+    let mut def: HashMap<xir::ExprTerm, &xir::InstrBasc> = HashMap::new();
+    for instr in imp.body() {
+        if let Some(basc) = instr.basc() {
+            if matches!(basc.op(), xir::OpBasc::Cat | xir::OpBasc::Id) {
+                if let Some(term) = basc.dst().term() {
+                    def.insert(term.clone(), basc);
+                }
+            }
+        }
+    }
+    let mut res: HashMap<xir::ExprTerm, u32> = HashMap::new();
+    let mut stack: Vec<(xir::ExprTerm, u32)> = vec![];
+    let mut bit = 0;
+    let output: Vec<xir::ExprTerm> = imp.output().clone().into();
+    for term in output {
+        let width = term.get_ty().ok().and_then(|ty| ty.width()).unwrap_or(1);
+        stack.push((term, bit));
+        bit += width as u32;
+    }
+    while let Some((term, bit)) = stack.pop() {
+        if let Some(basc) = def.get(&term) {
+            let mut offset = bit;
+            let arg: Vec<xir::ExprTerm> = basc.arg().clone().into();
+            for a in arg {
+                let width = a.get_ty().ok().and_then(|ty| ty.width()).unwrap_or(1);
+                stack.push((a, offset));
+                if basc.op() == &xir::OpBasc::Cat {
+                    offset += width as u32;
+                }
+            }
+        }
+        res.entry(term).or_insert(bit);
+    }
+    res
+}
+
+// the init and reset values of a registered instruction are split into one
+// fdre[init, value] per bit
+fn reg_attr_from_asm(attr: &asm::Expr, bit: u32) -> Option<xir::Expr> {
+    let init = i128::from(attr.get_val(0).ok()?);
+    let value = attr.get_val(1).map(i128::from).unwrap_or(init);
+    let bit = bit.min(127);
+    let term = vec![
+        xir::ExprTerm::Val(((init >> bit) & 1) as i64),
+        xir::ExprTerm::Val(((value >> bit) & 1) as i64),
+    ];
+    Some(xir::Expr::from(xir::ExprTup { term }))
+}
+
 pub fn scope_from_expr(left: &xir::Expr, right: &xir::Expr) -> Scope {
     let mut scope = Scope::new();
     let left: Vec<xir::ExprTerm> = left.clone().into();
@@ -150,6 +246,7 @@ impl Assembler {
             attr: xir::Expr::default(),
             dst,
             arg,
+            span: instr.span.clone(),
         };
         self.add_instr(xir::Instr::from(instr));
         Ok(())
@@ -162,6 +259,7 @@ impl Assembler {
             attr: xir::Expr::default(),
             dst,
             arg,
+            span: instr.span.clone(),
         };
         self.add_instr(xir::Instr::from(instr));
         Ok(())
@@ -175,6 +273,7 @@ impl Assembler {
             attr,
             dst,
             arg,
+            span: instr.span.clone(),
         };
         self.add_instr(xir::Instr::from(instr));
         Ok(())
@@ -212,6 +311,7 @@ impl Assembler {
                     attr: xir::Expr::default(),
                     dst,
                     arg: xir::Expr::default(),
+                    span: instr.span.clone(),
                 };
                 self.add_instr(xir::Instr::from(instr_basc));
             }
@@ -230,6 +330,7 @@ impl Assembler {
                     attr: xir::Expr::default(),
                     dst,
                     arg,
+                    span: instr.span.clone(),
                 };
                 self.add_instr(xir::Instr::from(cat));
             }
@@ -239,7 +340,9 @@ impl Assembler {
     pub fn expand_instr_asm(&mut self, input: &asm::InstrAsm) -> Result<(), Error> {
         let op = input.op().to_string();
         let instr = self.rename_instr_asm(input)?;
+        let clock = clock_from_expr(instr.arg());
         if let Some(imp) = self.get_target_imp(&op) {
+            let bit = bit_from_imp(imp);
             let mut scope = scope_from_expr(&imp.output(), instr.dst());
             scope.extend(scope_from_expr(&imp.input(), instr.arg()));
             for i in imp.clone().body() {
@@ -250,6 +353,11 @@ impl Assembler {
                         arg_tup.add_term(term.clone());
                     }
                 }
+                if let (Some(term), xir::Instr::Mach(mach)) = (&clock, &i) {
+                    if mach.op().is_clocked() {
+                        arg_tup.add_term(term.clone());
+                    }
+                }
                 let arg_expr = xir::Expr::from(arg_tup);
                 let dst: Vec<xir::ExprTerm> = i.dst().clone().into();
                 let mut out: Vec<xir::ExprTerm> = Vec::new();
@@ -277,20 +385,29 @@ impl Assembler {
                 match i {
                     xir::Instr::Mach(mach) => {
                         if let Some(loc) = mach.loc() {
-                            if *mach.op() == xir::OpMach::Brom {
+                            if matches!(mach.op(), xir::OpMach::Brom | xir::OpMach::Bram) {
                                 let old = input.dst().get_id(0)?;
                                 let new = dst_expr.get_id(0)?;
                                 self.replace_mem(&old, &new);
                             }
                             let mut loc = loc.clone();
-                            let x = instr.loc().x().clone();
-                            let y = instr.loc().y().clone();
+                            let x = coord_from_imp(loc.x(), instr.loc().x());
+                            let y = coord_from_imp(loc.y(), instr.loc().y());
                             loc.set_x(x);
                             loc.set_y(y);
                             let mut instr_mach = mach.clone();
+                            if mach.op() == &xir::OpMach::Fdre {
+                                let b = mach.dst().term().and_then(|t| bit.get(t));
+                                if let Some(attr) =
+                                    b.and_then(|b| reg_attr_from_asm(input.attr(), *b))
+                                {
+                                    instr_mach.set_attr(attr);
+                                }
+                            }
                             instr_mach.set_loc(loc);
                             instr_mach.set_arg(arg_expr);
                             instr_mach.set_dst(dst_expr);
+                            instr_mach.set_span(input.span().clone());
                             self.add_instr(xir::Instr::from(instr_mach));
                         }
                     }
@@ -298,6 +415,7 @@ impl Assembler {
                         let mut instr_xir = i.clone();
                         instr_xir.set_arg(arg_expr);
                         instr_xir.set_dst(dst_expr);
+                        instr_xir.set_span(input.span().clone());
                         self.add_instr(instr_xir);
                     }
                 }
@@ -307,28 +425,15 @@ impl Assembler {
     }
 }
 
-pub fn deserialize_target_from_file(prim: &str) -> xim::Target {
-    let filename = format!("{}_xim.bin", prim);
-    let path = Path::new(env!("OUT_DIR")).join(filename);
-    xim::Target::deserialize_from_file(path)
-}
-
-pub fn deserialize_target() -> xim::Target {
-    let mut tar = deserialize_target_from_file("lut");
-    let dsp = deserialize_target_from_file("dsp");
-    let mem = deserialize_target_from_file("mem");
-    tar.extend(dsp);
-    tar.extend(mem);
-    tar
-}
-
-pub fn try_from_asm_prog(
+pub fn try_from_asm_prog_with_target(
     input: &asm::Prog,
     mem: Option<&Mmap>,
+    target: &Target,
 ) -> Result<(xir::Prog, Option<Mmap>), Error> {
+    // generic implementations are specialized as they were during selection
+    let target = spec_try_from_dsts(target, input.body().iter().map(|i| i.dst()))?;
     let mut assembler = Assembler::new(input.sig().clone());
-    let target = deserialize_target();
-    assembler.set_target(target);
+    assembler.set_target(target.imp_target());
     if let Some(m) = mem {
         assembler.set_mem(m.clone());
     }
@@ -348,10 +453,8 @@ pub fn try_from_asm_prog(
             }
             asm::Instr::Asm(instr) => assembler.expand_instr_asm(instr)?,
             _ => {
-                return Err(Error::new_bler_error(&format!(
-                    "{} instruction not supported",
-                    instr
-                )))
+                let err = format!("{} instruction not supported", instr);
+                return Err(Error::new_bler_error(&instr.span().render(&err)));
             }
         }
     }
@@ -364,3 +467,10 @@ pub fn try_from_asm_prog(
         Ok((prog, Some(assembler.mem().clone())))
     }
 }
+
+pub fn try_from_asm_prog(
+    input: &asm::Prog,
+    mem: Option<&Mmap>,
+) -> Result<(xir::Prog, Option<Mmap>), Error> {
+    try_from_asm_prog_with_target(input, mem, &Target::default())
+}
@@ -1,7 +1,11 @@
 use asm::parser::Parser as AsmParser;
 use bler::errors::Error;
-use bler::try_from_asm_prog;
+use bler::{try_from_asm_prog, try_from_asm_prog_with_target};
+use ir::interp::{Env, Value};
+use ir::parser::Parser as IrParser;
+use registry::Target;
 use std::path::Path;
+use xir::ast::OpMach;
 use xir::parser::Parser as XirParser;
 
 fn test(name: &str) -> Result<(), Error> {
@@ -21,11 +25,21 @@ fn add() -> Result<(), Error> {
     test("add")
 }
 
+#[test]
+fn add_i12() -> Result<(), Error> {
+    test("add_i12")
+}
+
 #[test]
 fn addreduce_placed() -> Result<(), Error> {
     test("addreduce_placed")
 }
 
+#[test]
+fn cdc_sync() -> Result<(), Error> {
+    test("cdc_sync")
+}
+
 #[test]
 fn fsm_3() -> Result<(), Error> {
     test("fsm_3")
@@ -100,3 +114,64 @@ fn tdot_5_36() -> Result<(), Error> {
 fn mux128() -> Result<(), Error> {
     test("mux128")
 }
+
+#[test]
+fn lram_8x8() -> Result<(), Error> {
+    test("lram_8x8")
+}
+
+#[test]
+fn bram_8x256() -> Result<(), Error> {
+    test("bram_8x256")
+}
+
+#[test]
+fn bram_read() -> Result<(), Error> {
+    // reads of the assembled block ram take as many cycles as the ir ones
+    let prog = IrParser::parse_from_file("../../../examples/ir/bram_8x256.ir")?;
+    let parsed = AsmParser::parse_from_file("../../../examples/asm/bram_8x256.asm")?;
+    let (xir, _) = try_from_asm_prog(&parsed, None)?;
+    let input: Vec<Env> = (0..16)
+        .map(|i| {
+            let mut env = Env::new();
+            env.insert("addr".to_string(), Value::from(i % 4));
+            env.insert("data".to_string(), Value::from(i * 3));
+            env.insert("en".to_string(), Value::from(i % 3 == 0));
+            env
+        })
+        .collect();
+    let exp = ir::interp::interp_try_from_prog(&prog, &input)?;
+    let res = xir::interp::interp_try_from_prog(&xir, &input)?;
+    let exp: Vec<&Value> = exp.iter().map(|o| &o["y"]).collect();
+    let res: Vec<&Value> = res.iter().map(|o| &o["y"]).collect();
+    assert_eq!(res, exp);
+    Ok(())
+}
+
+#[test]
+fn reg_value_bits() -> Result<(), Error> {
+    // flops listed from the highest bit still take the value of the bit they hold
+    let imp = r#"imp lrev_i2[1, 2](a:i2, en:bool) -> (y:i2) {
+        t0:bool = ext[0](a);
+        t1:bool = ext[1](a);
+        t3:bool = fdre(t1, en) @b(??, ??);
+        t2:bool = fdre(t0, en) @a(??, ??);
+        y:i2 = cat(t2, t3);
+    }"#;
+    let xim = [include_str!("../../../../examples/xim/lut.xim"), imp].concat();
+    let target = Target::from_strs(&[("lut.xim", &xim)])?;
+    let parsed = AsmParser::parse_from_str(
+        r#"def main(a:i2, en:bool) -> (y:i2) {
+            y:i2 = lrev_i2[0, 1](a, en) @lut(0, 0);
+        }"#,
+    )?;
+    let (res, _) = try_from_asm_prog_with_target(&parsed, None, &target)?;
+    let mut value: Vec<i64> = Vec::new();
+    for instr in res.body() {
+        if let Some(mach) = instr.mach().filter(|m| m.op() == &OpMach::Fdre) {
+            value.push(mach.attr().get_val(1)?);
+        }
+    }
+    assert_eq!(value, vec![0, 1]);
+    Ok(())
+}
@@ -1,8 +1,13 @@
 pub mod errors;
+pub mod reset;
 
 use crate::errors::Error;
+use crate::reset::Reset;
+use ::ir::hier::def_order_try_from_def;
+use ::ir::inline::inline_try_from_def;
 use ir::ast as ir;
 use itertools::izip;
+use mmap::{Mem, Mmap};
 use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::convert::TryInto;
@@ -125,10 +130,25 @@ pub fn reg_try_from_expr(expr: &ir::Expr) -> Result<Vec<vl::Decl>, Error> {
     }
 }
 
-pub fn input_try_from_sig(sig: &ir::Sig) -> Result<Vec<vl::Port>, Error> {
+// defs with several clock domains can declare the default clock as an input
+fn has_clock_input(sig: &ir::Sig) -> bool {
+    let input: Vec<ir::ExprTerm> = sig.input().clone().into();
+    input.iter().any(|t| t.id().map_or(false, |id| id == CLOCK))
+}
+
+// registers and memories use the default clock unless they name one
+fn clock_id(instr: &ir::InstrPrim) -> vl::Id {
+    instr.clock().unwrap_or_else(|| CLOCK.to_string())
+}
+
+pub fn input_try_from_sig(sig: &ir::Sig, reset: &Reset) -> Result<Vec<vl::Port>, Error> {
     let mut port: Vec<vl::Port> = Vec::new();
-    port.push(vl::Port::Input(vl::Decl::new_wire(CLOCK, 1)));
-    port.push(vl::Port::Input(vl::Decl::new_wire(RESET, 1)));
+    if !has_clock_input(sig) {
+        port.push(vl::Port::Input(vl::Decl::new_wire(CLOCK, 1)));
+    }
+    if reset.has_port() {
+        port.push(vl::Port::Input(vl::Decl::new_wire(RESET, 1)));
+    }
     let input: Vec<vl::Decl> = wire_try_from_expr(sig.input())?;
     for decl in input {
         port.push(vl::Port::Input(decl.clone()));
@@ -143,6 +163,7 @@ pub fn vec_decl_try_from_instr_wire(instr: &ir::InstrWire) -> Result<Vec<vl::Dec
 pub fn vec_decl_try_from_instr_prim(instr: &ir::InstrPrim) -> Result<Vec<vl::Decl>, Error> {
     match instr.op() {
         ir::OpPrim::Reg => Ok(reg_try_from_expr(instr.dst())?),
+        _ if instr.is_sync_read() => Ok(reg_try_from_expr(instr.dst())?),
         _ => Ok(wire_try_from_expr(instr.dst())?),
     }
 }
@@ -151,50 +172,229 @@ pub fn vec_decl_try_from_instr(instr: &ir::Instr) -> Result<Vec<vl::Decl>, Error
     match instr {
         ir::Instr::Wire(instr) => Ok(vec_decl_try_from_instr_wire(instr)?),
         ir::Instr::Prim(instr) => Ok(vec_decl_try_from_instr_prim(instr)?),
-        _ => Err(Error::new_bline_error("call not implemented yet")),
+        ir::Instr::Call(instr) => Ok(wire_try_from_expr(instr.dst())?),
     }
 }
 
-fn vec_stmt_try_from_instr_prim(instr: &ir::InstrPrim) -> Result<Vec<vl::Stmt>, Error> {
-    match instr.op() {
-        ir::OpPrim::Reg => {
-            let attr: Vec<i32> = instr.attr().clone().try_into()?;
-            if let Some(d0) = instr.dst().idx(0) {
-                if let Some(a0) = instr.arg().idx(0) {
-                    if let Some(en) = instr.arg().idx(1) {
-                        let v0: i32 = if let Some(v) = attr.get(0) { *v } else { 0 };
-                        let event = vl::Sequential::new_posedge(CLOCK);
-                        let rst_expr = vl::Expr::new_ref(RESET);
-                        let ena_id: vl::Id = en.clone().try_into()?;
-                        let ena_expr = vl::Expr::new_ref(&ena_id);
-                        let dst: Vec<vl::Expr> = vec_expr_try_from_term(d0)?;
-                        let arg: Vec<vl::Expr> = vec_expr_try_from_term(a0)?;
-                        let val_expr = vl::Expr::new_int(v0);
-                        let mut stmt: Vec<vl::Stmt> = Vec::new();
-                        for (d, a) in dst.iter().zip(arg.iter()) {
-                            let mut always = vl::ParallelProcess::new_always();
-                            let s0 = vl::Sequential::new_nonblk_assign(d.clone(), val_expr.clone());
-                            let s1 = vl::Sequential::new_nonblk_assign(d.clone(), a.clone());
-                            let mut i0 = vl::SequentialIfElse::new(rst_expr.clone());
-                            let mut i1 = vl::SequentialIfElse::new(ena_expr.clone());
-                            i0.add_seq(s0);
-                            i1.add_seq(s1);
-                            i0.set_else(i1.into());
-                            always.set_event(event.clone());
-                            always.add_seq(i0.into());
-                            stmt.push(vl::Stmt::from(always));
-                        }
-                        Ok(stmt)
-                    } else {
-                        Err(Error::new_bline_error("reg instr must have en arg"))
-                    }
-                } else {
-                    Err(Error::new_bline_error("reg instr must have two args"))
+pub fn inst_name_try_from_instr_call(instr: &ir::InstrCall) -> Result<vl::Id, Error> {
+    let dst = instr.dst().get_id(0)?;
+    Ok(format!("__{}", dst))
+}
+
+pub fn instance_try_from_instr_call(
+    instr: &ir::InstrCall,
+    sig: &ir::Sig,
+    reset: &Reset,
+) -> Result<vl::Instance, Error> {
+    let name = inst_name_try_from_instr_call(instr)?;
+    let mut inst = vl::Instance::new(&name, &sig.id());
+    if !has_clock_input(sig) {
+        inst.connect(CLOCK, vl::Expr::new_ref(CLOCK));
+    }
+    if reset.has_port() {
+        inst.connect(RESET, vl::Expr::new_ref(RESET));
+    }
+    let input: Vec<vl::Expr> = vec_expr_try_from_expr(sig.input())?;
+    let output: Vec<vl::Expr> = vec_expr_try_from_expr(sig.output())?;
+    let arg: Vec<vl::Expr> = vec_expr_try_from_expr(instr.arg())?;
+    let dst: Vec<vl::Expr> = vec_expr_try_from_expr(instr.dst())?;
+    if input.len() != arg.len() || output.len() != dst.len() {
+        let err = format!("call does not match signature of def {}", sig.id());
+        return Err(Error::new_bline_error(&err));
+    }
+    for (port, expr) in input.iter().zip(arg) {
+        inst.connect(&port.id(), expr);
+    }
+    for (port, expr) in output.iter().zip(dst) {
+        inst.connect(&port.id(), expr);
+    }
+    Ok(inst)
+}
+
+// memories are modeled with a register per address
+fn mem_name(dst: &str, index: u64) -> vl::Id {
+    format!("{}_mem_{}", dst, index)
+}
+
+fn mem_depth_try_from_instr(instr: &ir::InstrPrim) -> Result<u64, Error> {
+    let addr = instr.arg().get_term(0)?;
+    match addr.width() {
+        Some(width) if width < 16 => Ok(1 << width),
+        Some(_) => Err(Error::new_bline_error("memory address is too wide")),
+        None => Err(Error::new_bline_error("memory address does not have width")),
+    }
+}
+
+pub fn vec_mem_decl_try_from_instr(instr: &ir::Instr) -> Result<Vec<vl::Decl>, Error> {
+    let mut decl: Vec<vl::Decl> = Vec::new();
+    if let ir::Instr::Prim(prim) = instr {
+        if matches!(prim.op(), ir::OpPrim::Rom | ir::OpPrim::Ram) {
+            let dst = prim.dst().get_term(0)?;
+            if dst.is_vector() {
+                return Err(Error::new_bline_error(
+                    "memory vector type not supported yet",
+                ));
+            }
+            let id = dst.get_id()?;
+            if let Some(width) = dst.width() {
+                for i in 0..mem_depth_try_from_instr(prim)? {
+                    decl.push(vl::Decl::new_reg(&mem_name(&id, i), width));
                 }
-            } else {
-                Err(Error::new_bline_error("reg instr must have one dst"))
             }
         }
+    }
+    Ok(decl)
+}
+
+// writes happen on the clock edge and reads are combinational, except for block
+// rams, which read through an output register that holds in write cycles
+// (WRITE_MODE NO_CHANGE) and clears on a synchronous reset. Memories are set to
+// their initial values, given by mmap or zero, at power-on only, like block and
+// lut rams, which keep their contents on reset
+fn vec_stmt_try_from_instr_mem(
+    instr: &ir::InstrPrim,
+    mmap: Option<&Mmap>,
+    reset: &Reset,
+) -> Result<Vec<vl::Stmt>, Error> {
+    let dst = instr.dst().get_term(0)?;
+    let addr = instr.arg().get_term(0)?;
+    let id = dst.get_id()?;
+    let addr_id = addr.get_id()?;
+    let addr_width = if let Some(width) = addr.width() {
+        u32::try_from(width)?
+    } else {
+        return Err(Error::new_bline_error("memory address does not have width"));
+    };
+    let depth = mem_depth_try_from_instr(instr)?;
+    let mem: Option<&Mem> = mmap.and_then(|m| m.get(&id));
+    let addr_eq = |i: u64| {
+        vl::Expr::new_eq(
+            vl::Expr::new_ref(&addr_id),
+            vl::Expr::new_ulit_dec(addr_width, &i.to_string()),
+        )
+    };
+    let mut read = vl::Expr::new_ref(&mem_name(&id, depth - 1));
+    for i in (0..depth - 1).rev() {
+        read = vl::Expr::new_mux(addr_eq(i), vl::Expr::new_ref(&mem_name(&id, i)), read);
+    }
+    let is_ram = instr.op() == &ir::OpPrim::Ram;
+    let mut always = vl::ParallelProcess::new_always();
+    always.set_event(vl::Sequential::new_posedge(&clock_id(instr)));
+    let mut initial = vl::ParallelProcess::new_initial();
+    for i in 0..depth {
+        let name = vl::Expr::new_ref(&mem_name(&id, i));
+        let init = mem
+            .and_then(|m| m.values().get(i as usize))
+            .map_or(0, |v| i32::from(*v));
+        initial.add_seq(vl::Sequential::new_nonblk_assign(
+            name.clone(),
+            vl::Expr::new_int(init),
+        ));
+        if is_ram {
+            let data_id = instr.arg().get_id(1)?;
+            let en_id = instr.arg().get_id(2)?;
+            let wen = vl::Expr::new_bit_and(vl::Expr::new_ref(&en_id), addr_eq(i));
+            let s1 = vl::Sequential::new_nonblk_assign(name, vl::Expr::new_ref(&data_id));
+            let mut i1 = vl::SequentialIfElse::new(wen);
+            i1.add_seq(s1);
+            always.add_seq(i1.into());
+        }
+    }
+    if instr.is_sync_read() {
+        if reset.is_async() {
+            return Err(Error::new_bline_error(
+                "bram registers do not support asynchronous reset",
+            ));
+        }
+        let dst = vl::Expr::new_ref(&id);
+        initial.add_seq(vl::Sequential::new_nonblk_assign(
+            dst.clone(),
+            vl::Expr::new_int(0),
+        ));
+        let s1 = vl::Sequential::new_nonblk_assign(dst.clone(), read);
+        let s1 = if is_ram {
+            let en_id = instr.arg().get_id(2)?;
+            let ren = vl::Expr::new_eq(vl::Expr::new_ref(&en_id), vl::Expr::new_ulit_bin(1, "0"));
+            let mut i1 = vl::SequentialIfElse::new(ren);
+            i1.add_seq(s1);
+            i1.into()
+        } else {
+            s1
+        };
+        if let Some(cond) = reset.cond(RESET) {
+            let s0 = vl::Sequential::new_nonblk_assign(dst, vl::Expr::new_int(0));
+            let mut i0 = vl::SequentialIfElse::new(cond);
+            i0.add_seq(s0);
+            i0.set_else(s1);
+            always.add_seq(i0.into());
+        } else {
+            always.add_seq(s1);
+        }
+        return Ok(vec![vl::Stmt::from(initial), vl::Stmt::from(always)]);
+    }
+    let mut stmt: Vec<vl::Stmt> = vec![vl::Stmt::from(initial)];
+    if is_ram {
+        stmt.push(vl::Stmt::from(always));
+    }
+    stmt.push(vl::Stmt::from(vl::Parallel::Assign(
+        vl::Expr::new_ref(&id),
+        read,
+    )));
+    Ok(stmt)
+}
+
+// the first attr of a register is its power-on value and the second one, if
+// any, the value it takes on reset
+fn vec_stmt_try_from_instr_reg(
+    instr: &ir::InstrPrim,
+    reset: &Reset,
+) -> Result<Vec<vl::Stmt>, Error> {
+    let attr: Vec<i32> = instr.attr().clone().try_into()?;
+    let d0 = instr.dst().get_term(0)?;
+    let a0 = instr.arg().get_term(0)?;
+    let en = instr.arg().get_term(1)?;
+    let init = attr.get(0).cloned().unwrap_or(0);
+    let value = attr.get(1).cloned().unwrap_or(init);
+    let ena_id: vl::Id = en.clone().try_into()?;
+    let dst: Vec<vl::Expr> = vec_expr_try_from_term(d0)?;
+    let arg: Vec<vl::Expr> = vec_expr_try_from_term(a0)?;
+    let mut stmt: Vec<vl::Stmt> = Vec::new();
+    for (d, a) in dst.iter().zip(arg.iter()) {
+        if reset.is_none() || init != value {
+            let mut initial = vl::ParallelProcess::new_initial();
+            initial.add_seq(vl::Sequential::new_nonblk_assign(
+                d.clone(),
+                vl::Expr::new_int(init),
+            ));
+            stmt.push(vl::Stmt::from(initial));
+        }
+        let mut always = vl::ParallelProcess::new_always();
+        let s1 = vl::Sequential::new_nonblk_assign(d.clone(), a.clone());
+        let mut i1 = vl::SequentialIfElse::new(vl::Expr::new_ref(&ena_id));
+        i1.add_seq(s1);
+        always.set_event(reset.event(&clock_id(instr), RESET));
+        if let Some(cond) = reset.cond(RESET) {
+            let s0 = vl::Sequential::new_nonblk_assign(d.clone(), vl::Expr::new_int(value));
+            let mut i0 = vl::SequentialIfElse::new(cond);
+            i0.add_seq(s0);
+            i0.set_else(i1.into());
+            always.add_seq(i0.into());
+        } else {
+            always.add_seq(i1.into());
+        }
+        stmt.push(vl::Stmt::from(always));
+    }
+    Ok(stmt)
+}
+
+fn vec_stmt_try_from_instr_prim(
+    instr: &ir::InstrPrim,
+    mmap: Option<&Mmap>,
+    reset: &Reset,
+) -> Result<Vec<vl::Stmt>, Error> {
+    match instr.op() {
+        ir::OpPrim::Rom | ir::OpPrim::Ram => vec_stmt_try_from_instr_mem(instr, mmap, reset),
+        ir::OpPrim::Reg => vec_stmt_try_from_instr_reg(instr, reset),
         ir::OpPrim::Add => {
             if let Some(d0) = instr.dst().idx(0) {
                 if let Some(a0) = instr.arg().idx(0) {
@@ -332,37 +532,72 @@ fn vec_stmt_try_from_instr_wire(instr: &ir::InstrWire) -> Result<Vec<vl::Stmt>,
     }
 }
 
-fn vec_stmt_try_from_instr(instr: &ir::Instr) -> Result<Vec<vl::Stmt>, Error> {
+fn vec_stmt_try_from_instr_call(
+    instr: &ir::InstrCall,
+    prog: &ir::Prog,
+    reset: &Reset,
+) -> Result<Vec<vl::Stmt>, Error> {
+    if let Some(callee) = prog.get(&instr.op().op()) {
+        let inst = instance_try_from_instr_call(instr, callee.sig(), reset)?;
+        Ok(vec![vl::Stmt::from(inst)])
+    } else {
+        let err = format!("def {} not found", instr.op().op());
+        Err(Error::new_bline_error(&err))
+    }
+}
+
+fn vec_stmt_try_from_instr(
+    instr: &ir::Instr,
+    prog: &ir::Prog,
+    mmap: Option<&Mmap>,
+    reset: &Reset,
+) -> Result<Vec<vl::Stmt>, Error> {
     match instr {
-        ir::Instr::Prim(instr) => Ok(vec_stmt_try_from_instr_prim(instr)?),
+        ir::Instr::Prim(instr) => Ok(vec_stmt_try_from_instr_prim(instr, mmap, reset)?),
         ir::Instr::Wire(instr) => Ok(vec_stmt_try_from_instr_wire(instr)?),
-        ir::Instr::Call(_) => Err(Error::new_bline_error("call instr not implemented yet")),
+        ir::Instr::Call(instr) => Ok(vec_stmt_try_from_instr_call(instr, prog, reset)?),
     }
 }
 
-pub fn behav_try_from_ir_def(def: &ir::Def) -> Result<vl::Module, Error> {
+// calls become instances of the modules generated for their defs in prog
+fn module_try_from_ir_def(
+    def: &ir::Def,
+    prog: &ir::Prog,
+    mmap: Option<&Mmap>,
+    reset: &Reset,
+) -> Result<vl::Module, Error> {
     let id = def.sig().id();
     let mut module = vl::Module::new(&id);
-    let input = input_try_from_sig(def.sig())?;
+    let input = input_try_from_sig(def.sig(), reset)?;
     for i in input {
         module.add_port(i.clone());
     }
     let output: Vec<ir::ExprTerm> = def.sig().output().clone().into();
     let output_set: HashSet<ir::ExprTerm> = output.into_iter().collect();
     let mut stmt: Vec<vl::Stmt> = Vec::new();
+    let mut def = def.clone();
+    def.sort_body()?;
     for instr in def.body() {
         let dst: Vec<ir::ExprTerm> = instr.dst().clone().into();
-        let decl = vec_decl_try_from_instr(instr)?;
         for e in dst {
-            for d in &decl {
+            let sync_read = matches!(instr, ir::Instr::Prim(prim) if prim.is_sync_read());
+            let decl = if instr.is_reg() || sync_read {
+                reg_try_from_term(&e)?
+            } else {
+                wire_try_from_term(&e)?
+            };
+            for d in decl {
                 if output_set.contains(&e) {
-                    module.add_port(vl::Port::Output(d.clone()));
+                    module.add_port(vl::Port::Output(d));
                 } else {
-                    module.add_decl(d.clone())
+                    module.add_decl(d)
                 }
             }
         }
-        let s: Vec<vl::Stmt> = vec_stmt_try_from_instr(instr)?;
+        for d in vec_mem_decl_try_from_instr(instr)? {
+            module.add_decl(d);
+        }
+        let s: Vec<vl::Stmt> = vec_stmt_try_from_instr(instr, prog, mmap, reset)?;
         stmt.extend(s);
     }
     for s in stmt {
@@ -371,10 +606,40 @@ pub fn behav_try_from_ir_def(def: &ir::Def) -> Result<vl::Module, Error> {
     Ok(module)
 }
 
-pub fn try_from_ir_prog(prog: &ir::Prog) -> Result<vl::Module, Error> {
-    if let Some(def) = prog.get("main") {
-        Ok(behav_try_from_ir_def(def)?)
+pub fn behav_try_from_ir_def(def: &ir::Def) -> Result<vl::Module, Error> {
+    module_try_from_ir_def(def, &ir::Prog::default(), None, &Reset::default())
+}
+
+// main with every call inlined
+pub fn try_from_ir_prog(
+    prog: &ir::Prog,
+    mmap: Option<&Mmap>,
+    reset: &Reset,
+) -> Result<vl::Module, Error> {
+    if prog.get("main").is_some() {
+        let main = inline_try_from_def(prog, "main")?;
+        Ok(module_try_from_ir_def(
+            &main,
+            &ir::Prog::default(),
+            mmap,
+            reset,
+        )?)
     } else {
         Err(Error::new_bline_error("main not found"))
     }
 }
+
+// one module per def reachable from main, callees come first
+pub fn hier_try_from_ir_prog(
+    prog: &ir::Prog,
+    mmap: Option<&Mmap>,
+    reset: &Reset,
+) -> Result<Vec<vl::Module>, Error> {
+    let mut module: Vec<vl::Module> = Vec::new();
+    for name in def_order_try_from_def(prog, "main")? {
+        if let Some(def) = prog.get(&name) {
+            module.push(module_try_from_ir_def(def, prog, mmap, reset)?);
+        }
+    }
+    Ok(module)
+}
@@ -0,0 +1,108 @@
+use crate::errors::Error;
+use std::fmt;
+use std::str::FromStr;
+use verilog::ast as vl;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResetMode {
+    Sync,
+    Async,
+    None,
+}
+
+// how registers are reset in a design, default is synchronous and active-high
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Reset {
+    pub mode: ResetMode,
+    pub active_low: bool,
+}
+
+impl Default for Reset {
+    fn default() -> Self {
+        Reset {
+            mode: ResetMode::Sync,
+            active_low: false,
+        }
+    }
+}
+
+impl Reset {
+    pub fn new(mode: ResetMode, active_low: bool) -> Self {
+        Reset { mode, active_low }
+    }
+    pub fn mode(&self) -> &ResetMode {
+        &self.mode
+    }
+    pub fn is_active_low(&self) -> bool {
+        self.active_low
+    }
+    pub fn is_sync(&self) -> bool {
+        self.mode == ResetMode::Sync
+    }
+    pub fn is_async(&self) -> bool {
+        self.mode == ResetMode::Async
+    }
+    pub fn is_none(&self) -> bool {
+        self.mode == ResetMode::None
+    }
+    // true when modules need a reset port
+    pub fn has_port(&self) -> bool {
+        !self.is_none()
+    }
+    // condition that holds while reset is asserted
+    pub fn cond(&self, reset: &str) -> Option<vl::Expr> {
+        match self.mode {
+            ResetMode::None => None,
+            _ if self.active_low => Some(vl::Expr::new_eq(
+                vl::Expr::new_ref(reset),
+                vl::Expr::new_ulit_bin(1, "0"),
+            )),
+            _ => Some(vl::Expr::new_ref(reset)),
+        }
+    }
+    // asynchronous resets add their edge to the clock event
+    pub fn event(&self, clock: &str, reset: &str) -> vl::Sequential {
+        if self.is_async() {
+            let edge = if self.active_low {
+                vl::Edge::Negedge
+            } else {
+                vl::Edge::Posedge
+            };
+            vl::new_posedge_or(clock, &[(edge, reset)])
+        } else {
+            vl::Sequential::new_posedge(clock)
+        }
+    }
+}
+
+impl fmt::Display for Reset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = match self.mode {
+            ResetMode::Sync => "sync",
+            ResetMode::Async => "async",
+            ResetMode::None => return write!(f, "none"),
+        };
+        if self.active_low {
+            write!(f, "{}-low", mode)
+        } else {
+            write!(f, "{}", mode)
+        }
+    }
+}
+
+impl FromStr for Reset {
+    type Err = Error;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "sync" | "sync-high" => Ok(Reset::new(ResetMode::Sync, false)),
+            "sync-low" => Ok(Reset::new(ResetMode::Sync, true)),
+            "async" | "async-high" => Ok(Reset::new(ResetMode::Async, false)),
+            "async-low" => Ok(Reset::new(ResetMode::Async, true)),
+            "none" => Ok(Reset::new(ResetMode::None, false)),
+            _ => {
+                let err = format!("{} is not sync, async or none (with -low or -high)", input);
+                Err(Error::new_bline_error(&err))
+            }
+        }
+    }
+}
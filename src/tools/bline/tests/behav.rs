@@ -1,4 +1,5 @@
-use bline::try_from_ir_prog;
+use bline::reset::{Reset, ResetMode};
+use bline::{hier_try_from_ir_prog, try_from_ir_prog};
 use io::read_to_string;
 use ir::parser::Parser as IrParser;
 
@@ -6,6 +7,102 @@ use ir::parser::Parser as IrParser;
 fn add() {
     let prog = IrParser::parse_from_file("../../../examples/ir/add.ir").unwrap();
     let exp = read_to_string("../../../examples/behav/add.v");
-    let res = try_from_ir_prog(&prog).unwrap().to_string();
+    let res = try_from_ir_prog(&prog, None, &Reset::default())
+        .unwrap()
+        .to_string();
     assert_eq!(res, exp);
 }
+
+#[test]
+fn hier() {
+    let prog = IrParser::parse_from_str(
+        r#"def acc(a:i8, en:bool) -> (y:i8) {
+            t0:i8 = add(a, y);
+            y:i8 = reg[0](t0, en);
+        }
+        def main(a:i8, en:bool) -> (y:i8) {
+            t0:i8 = acc(a, en);
+            y:i8 = acc(t0, en);
+        }"#,
+    )
+    .unwrap();
+    let res = hier_try_from_ir_prog(&prog, None, &Reset::default()).unwrap();
+    assert_eq!(res.len(), 2);
+    assert!(res[0].to_string().starts_with("module acc"));
+    let main = res[1].to_string();
+    assert!(main.starts_with("module main"));
+    assert!(main.contains("__t0"));
+    assert!(main.contains("__y"));
+}
+
+#[test]
+fn ram() {
+    let prog = IrParser::parse_from_file("../../../examples/ir/lram_8x8.ir").unwrap();
+    let mmap = mmap::Mmap::from_file("../../../examples/mmap/lram_8x8.json");
+    let res = try_from_ir_prog(&prog, Some(&mmap), &Reset::default())
+        .unwrap()
+        .to_string();
+    assert!(res.contains("y_mem_0"));
+    assert!(res.contains("y_mem_7"));
+    assert!(!res.contains("y_mem_8"));
+    assert!(res.contains("170"));
+    // memory contents are kept on reset
+    let reset: Reset = "async-low".parse().unwrap();
+    let res = try_from_ir_prog(&prog, Some(&mmap), &reset)
+        .unwrap()
+        .to_string();
+    assert!(res.contains("initial"));
+    assert!(!res.contains("negedge reset"));
+    assert!(!res.contains("reset == 1'b0"));
+}
+
+#[test]
+fn bram() {
+    let prog = IrParser::parse_from_file("../../../examples/ir/bram_8x256.ir").unwrap();
+    let res = try_from_ir_prog(&prog, None, &Reset::default())
+        .unwrap()
+        .to_string();
+    // block ram reads go through an output register
+    assert!(res.contains("output reg [7:0] y"));
+    assert!(!res.contains("assign y"));
+    let reset: Reset = "async-low".parse().unwrap();
+    assert!(try_from_ir_prog(&prog, None, &reset).is_err());
+}
+
+#[test]
+fn reset() {
+    let prog = IrParser::parse_from_str(
+        r#"def main(a:i8, en:bool) -> (y:i8) {
+            y:i8 = reg[1, 2](a, en);
+        }"#,
+    )
+    .unwrap();
+    let reset: Reset = "async-low".parse().unwrap();
+    assert_eq!(reset, Reset::new(ResetMode::Async, true));
+    let res = try_from_ir_prog(&prog, None, &reset).unwrap().to_string();
+    assert!(res.contains("posedge clock or negedge reset"));
+    assert!(res.contains("reset == 1'b0"));
+    assert!(res.contains("initial"));
+    let reset: Reset = "none".parse().unwrap();
+    let res = try_from_ir_prog(&prog, None, &reset).unwrap().to_string();
+    assert!(!res.contains("reset"));
+    assert!(res.contains("initial"));
+}
+
+#[test]
+fn clock_domains() {
+    let prog = IrParser::parse_from_str(
+        r#"def main(a:i8, en:bool, slow:clock) -> (y:i8, z:i8) {
+            y:i8 = reg[0](a, en);
+            z:i8 = reg[0](a, en, slow);
+        }"#,
+    )
+    .unwrap();
+    let res = try_from_ir_prog(&prog, None, &Reset::default())
+        .unwrap()
+        .to_string();
+    assert!(res.contains("input wire clock"));
+    assert!(res.contains("input wire slow"));
+    assert!(res.contains("posedge clock"));
+    assert!(res.contains("posedge slow"));
+}
@@ -0,0 +1,97 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::rc::Rc;
+
+// source location of an ast node, line and col start at one and a line of
+// zero means the node was not parsed from source
+#[derive(Clone, Debug, Default)]
+pub struct Span {
+    path: Option<Rc<str>>,
+    line: usize,
+    col: usize,
+    len: usize,
+    text: Rc<str>,
+}
+
+// spans never make two nodes different, so programs built by passes compare
+// equal to parsed ones
+impl PartialEq for Span {
+    fn eq(&self, _: &Span) -> bool {
+        true
+    }
+}
+
+impl Eq for Span {}
+
+impl Hash for Span {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+impl Span {
+    pub fn new(span: pest::Span, path: Option<Rc<str>>) -> Self {
+        let start = span.start_pos();
+        let (line, col) = start.line_col();
+        let text = start.line_of().trim_end_matches(&['\r', '\n'][..]);
+        let len = span
+            .as_str()
+            .lines()
+            .next()
+            .map_or(1, |s| s.chars().count().max(1));
+        Span {
+            path,
+            line,
+            col,
+            len,
+            text: Rc::from(text),
+        }
+    }
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_ref().map(|p| p.as_ref())
+    }
+    pub fn line(&self) -> usize {
+        self.line
+    }
+    pub fn col(&self) -> usize {
+        self.col
+    }
+    pub fn is_known(&self) -> bool {
+        self.line > 0
+    }
+    // error message with the location and an excerpt of the source line
+    pub fn render(&self, msg: &str) -> String {
+        if self.is_known() {
+            let num = self.line.to_string();
+            let pad = " ".repeat(num.len());
+            let lead = " ".repeat(self.col - 1);
+            format!(
+                "{}\n{}--> {}\n{} |\n{} | {}\n{} | {}{}",
+                msg,
+                pad,
+                self,
+                pad,
+                num,
+                self.text,
+                pad,
+                lead,
+                "^".repeat(self.len)
+            )
+        } else {
+            msg.to_string()
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(path) = self.path() {
+            write!(f, "{}:{}:{}", path, self.line, self.col)
+        } else {
+            write!(f, "{}:{}", self.line, self.col)
+        }
+    }
+}
+
+pub fn path_from<P: AsRef<Path>>(path: P) -> Rc<str> {
+    Rc::from(path.as_ref().to_string_lossy().as_ref())
+}
@@ -0,0 +1,171 @@
+use crate::errors::Error;
+use asm::ast as asm;
+use registry::spec::spec_try_from_dsts;
+use registry::{Target, PRIMS};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use xim::ast as xim;
+
+// what instruction selection minimizes, a weighted mix is
+// latency * perf + area * area, e.g. weighted:1,4
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Objective {
+    Latency,
+    Area,
+    Weighted(u64, u64),
+}
+
+// cost of an implementation under an objective, where the area of every
+// resource is scaled by its weight (dsp=4 makes a dsp worth four luts)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CostModel {
+    pub objective: Objective,
+    pub weight: HashMap<String, u64>,
+    pub order: Vec<String>,
+}
+
+impl Default for Objective {
+    fn default() -> Self {
+        Objective::Latency
+    }
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        CostModel {
+            objective: Objective::default(),
+            weight: HashMap::new(),
+            order: vec!["dsp".to_string(), "lut".to_string(), "mem".to_string()],
+        }
+    }
+}
+
+fn is_prim(prim: &str) -> bool {
+    PRIMS.contains(&prim)
+}
+
+impl CostModel {
+    pub fn new(objective: Objective) -> Self {
+        CostModel {
+            objective,
+            ..CostModel::default()
+        }
+    }
+    pub fn objective(&self) -> &Objective {
+        &self.objective
+    }
+    // resources without a weight count once
+    pub fn weight(&self, prim: &str) -> u64 {
+        self.weight.get(prim).cloned().unwrap_or(1)
+    }
+    // primitives in the order patterns are tried, ties go to the first one
+    pub fn order(&self) -> &Vec<String> {
+        &self.order
+    }
+    // u64::MAX marks nodes without a pattern, so costs stop right below it
+    // and a large weight makes an implementation expensive, not unselectable
+    pub fn cost(&self, prim: &str, imp: &xim::Imp) -> u64 {
+        let area = imp.area().saturating_mul(self.weight(prim));
+        let cost = match self.objective {
+            Objective::Latency => imp.perf(),
+            Objective::Area => area,
+            Objective::Weighted(latency, scale) => latency
+                .saturating_mul(imp.perf())
+                .saturating_add(scale.saturating_mul(area)),
+        };
+        cost.min(u64::MAX - 1)
+    }
+    pub fn set_objective(&mut self, objective: Objective) {
+        self.objective = objective;
+    }
+    pub fn set_weight(&mut self, prim: &str, weight: u64) -> Result<(), Error> {
+        if is_prim(prim) {
+            self.weight.insert(prim.to_string(), weight);
+            Ok(())
+        } else {
+            let err = format!("{} is not a resource, expected lut, dsp or mem", prim);
+            Err(Error::new_isel_error(&err))
+        }
+    }
+    // resource weight written as prim=weight, i.e. dsp=4
+    pub fn set_weight_from_str(&mut self, input: &str) -> Result<(), Error> {
+        let mut split = input.splitn(2, '=');
+        match (split.next(), split.next().map(|w| w.parse::<u64>())) {
+            (Some(prim), Some(Ok(weight))) => self.set_weight(prim.trim(), weight),
+            _ => {
+                let err = format!("{} is not a weight, expected prim=weight", input);
+                Err(Error::new_isel_error(&err))
+            }
+        }
+    }
+    // every primitive has to be in the order exactly once
+    pub fn set_order(&mut self, order: &[String]) -> Result<(), Error> {
+        let valid =
+            order.len() == PRIMS.len() && PRIMS.iter().all(|p| order.iter().any(|o| o == p));
+        if valid {
+            self.order = order.to_vec();
+            Ok(())
+        } else {
+            let err = format!("{} is not an order of lut, dsp and mem", order.join(","));
+            Err(Error::new_isel_error(&err))
+        }
+    }
+}
+
+impl fmt::Display for Objective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Objective::Latency => write!(f, "latency"),
+            Objective::Area => write!(f, "area"),
+            Objective::Weighted(latency, area) => write!(f, "weighted:{},{}", latency, area),
+        }
+    }
+}
+
+impl FromStr for Objective {
+    type Err = Error;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let weights: Vec<Result<u64, _>> = match input.strip_prefix("weighted:") {
+            Some(w) => w.split(',').map(|v| v.trim().parse::<u64>()).collect(),
+            None => Vec::new(),
+        };
+        match (input, weights.as_slice()) {
+            ("latency", _) => Ok(Objective::Latency),
+            ("area", _) => Ok(Objective::Area),
+            ("weighted", _) => Ok(Objective::Weighted(1, 1)),
+            (_, [Ok(latency), Ok(area)]) => Ok(Objective::Weighted(*latency, *area)),
+            _ => {
+                let err = format!("{} is not latency, area or weighted:latency,area", input);
+                Err(Error::new_isel_error(&err))
+            }
+        }
+    }
+}
+
+// total cost of the instructions selected for a program, generic
+// implementations are specialized as they were during selection
+pub fn cost_try_from_asm_prog(
+    prog: &asm::Prog,
+    target: &Target,
+    model: &CostModel,
+) -> Result<u64, Error> {
+    let target = spec_try_from_dsts(target, prog.body().iter().map(|i| i.dst()))?;
+    let mut cost: u64 = 0;
+    for instr in prog.body() {
+        if let asm::Instr::Asm(asm) = instr {
+            let op = asm.op().to_string();
+            let imp = PRIMS
+                .iter()
+                .find_map(|prim| target.imp(prim).get(&op).map(|imp| (prim, imp)));
+            match imp {
+                Some((prim, imp)) => cost = cost.saturating_add(model.cost(prim, imp)),
+                None => {
+                    let err = format!("{} does not have an implementation", op);
+                    return Err(Error::new_isel_error(&asm.span().render(&err)));
+                }
+            }
+        }
+    }
+    Ok(cost)
+}
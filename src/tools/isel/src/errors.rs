@@ -1,11 +1,13 @@
 use asm::errors::Error as AsmError;
 use ir::errors::Error as IrError;
+use registry::errors::Error as RegistryError;
 use std::fmt;
 
 #[derive(Debug)]
 pub enum Error {
     Ir(IrError),
     Asm(AsmError),
+    Registry(RegistryError),
     ISel(String),
 }
 
@@ -27,11 +29,18 @@ impl From<AsmError> for Error {
     }
 }
 
+impl From<RegistryError> for Error {
+    fn from(e: RegistryError) -> Self {
+        Error::Registry(e)
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Error::Ir(msg) => write!(f, "{}", msg),
             Error::Asm(msg) => write!(f, "{}", msg),
+            Error::Registry(msg) => write!(f, "{}", msg),
             Error::ISel(msg) => write!(f, "{}", msg),
         }
     }
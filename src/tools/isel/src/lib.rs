@@ -1,30 +1,22 @@
+pub mod cost;
 pub mod errors;
+pub mod sim;
 pub mod tree;
 
+use crate::cost::CostModel;
 use crate::errors::Error;
 use crate::tree::helpers::{
-    tree_codegen, tree_commit, tree_select, treelist_try_from_prog, treemap_try_from_target_pair,
+    tree_codegen, tree_commit, tree_select, treelist_try_from_def, treemap_try_from_target_pair,
 };
-use crate::tree::TreeMap;
+use crate::tree::{Tree, TreeMap};
+use ::ir::clock::{domain_map_from_def, strip_clocks_from_def};
 use asm::ast as asm;
 use ir::ast as ir;
-use pat::ast as pat;
-use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use registry::spec::spec_try_from_dsts;
+use registry::Target;
+use std::collections::HashSet;
 use xim::ast as xim;
 
-pub fn deserialize_pat_from_file(prim: &str) -> pat::Target {
-    let filename = format!("{}_pat.bin", prim);
-    let path = Path::new(env!("OUT_DIR")).join(filename);
-    pat::Target::deserialize_from_file(path)
-}
-
-pub fn deserialize_imp_from_file(prim: &str) -> xim::Target {
-    let filename = format!("{}_xim.bin", prim);
-    let path = Path::new(env!("OUT_DIR")).join(filename);
-    xim::Target::deserialize_from_file(path)
-}
-
 pub fn instrmap_from_prog(prog: &ir::Prog) -> Result<ir::InstrMap, Error> {
     if let Some(main) = prog.get("main") {
         Ok(ir::InstrMap::from(main.clone()))
@@ -33,43 +25,105 @@ pub fn instrmap_from_prog(prog: &ir::Prog) -> Result<ir::InstrMap, Error> {
     }
 }
 
-// TODO: impl try_from after refactoring done
-pub fn try_from_ir_prog(prog: &ir::Prog) -> Result<asm::Prog, Error> {
+fn select_try_from_def(def: &ir::Def, map: &[TreeMap]) -> Result<Vec<Tree>, Error> {
+    let mut blks = treelist_try_from_def(def)?;
+    for m in map {
+        blks = tree_select(&blks, m)?;
+    }
+    tree_commit(&blks)
+}
+
+// pattern trees of every primitive, in the order they are tried
+fn treemaps_try_from_target(target: &Target, model: &CostModel) -> Result<Vec<TreeMap>, Error> {
+    let mut map: Vec<TreeMap> = Vec::new();
+    for prim in model.order() {
+        let cost = |imp: &xim::Imp| model.cost(prim, imp);
+        map.push(treemap_try_from_target_pair(
+            target.pat(prim),
+            target.imp(prim),
+            cost,
+        )?);
+    }
+    Ok(map)
+}
+
+// trees of a def after selection, before code generation
+pub fn tree_try_from_ir_def_with_model(
+    def: &ir::Def,
+    target: &Target,
+    model: &CostModel,
+) -> Result<Vec<Tree>, Error> {
+    let def = strip_clocks_from_def(def)?;
+    let target = spec_try_from_dsts(target, def.body().iter().map(|i| i.dst()))?;
+    let map = treemaps_try_from_target(&target, model)?;
+    select_try_from_def(&def, &map)
+}
+
+pub fn tree_try_from_ir_def_with_target(
+    def: &ir::Def,
+    target: &Target,
+) -> Result<Vec<Tree>, Error> {
+    tree_try_from_ir_def_with_model(def, target, &CostModel::default())
+}
+
+pub fn tree_try_from_ir_def(def: &ir::Def) -> Result<Vec<Tree>, Error> {
+    tree_try_from_ir_def_with_target(def, &Target::default())
+}
+
+pub fn try_from_ir_def_with_model(
+    def: &ir::Def,
+    target: &Target,
+    model: &CostModel,
+) -> Result<asm::Prog, Error> {
+    // generic patterns are specialized for the types in the def
+    let target = &spec_try_from_dsts(target, def.body().iter().map(|i| i.dst()))?;
+    let map = treemaps_try_from_target(target, model)?;
+    // patterns do not know about clocks, domains are added back after selection
+    let cmap = domain_map_from_def(def)?;
+    let def = strip_clocks_from_def(def)?;
+    let imap = ir::InstrMap::from(def.clone());
+    let blks = select_try_from_def(&def, &map)?;
+    let mut body: Vec<asm::Instr> = Vec::new();
+    let mut iset: HashSet<ir::Id> = HashSet::new();
+    let tree_map: TreeMap = map.into_iter().flatten().collect();
+    let pat_map = target.pat_map();
+    for blk in blks {
+        body.extend(tree_codegen(
+            &mut iset, &imap, &blk, &tree_map, &pat_map, &cmap,
+        )?);
+    }
+    let mut res = asm::Prog::default();
+    res.set_sig(def.sig().clone());
+    res.set_body(body);
+    // tile operations that can be arranged in multiple slices
+    Ok(tile::tile_from_prog_with_target(&res, target))
+}
+
+pub fn try_from_ir_def_with_target(def: &ir::Def, target: &Target) -> Result<asm::Prog, Error> {
+    try_from_ir_def_with_model(def, target, &CostModel::default())
+}
+
+pub fn try_from_ir_def(def: &ir::Def) -> Result<asm::Prog, Error> {
+    try_from_ir_def_with_target(def, &Target::default())
+}
+
+pub fn try_from_ir_prog_with_model(
+    prog: &ir::Prog,
+    target: &Target,
+    model: &CostModel,
+) -> Result<asm::Prog, Error> {
     if let Some(main) = prog.get("main") {
-        let lpat = deserialize_pat_from_file("lut");
-        let dpat = deserialize_pat_from_file("dsp");
-        let mpat = deserialize_pat_from_file("mem");
-        let limp = deserialize_imp_from_file("lut");
-        let dimp = deserialize_imp_from_file("dsp");
-        let mimp = deserialize_imp_from_file("mem");
-        let lmap = treemap_try_from_target_pair(&lpat, &limp)?;
-        let dmap = treemap_try_from_target_pair(&dpat, &dimp)?;
-        let mmap = treemap_try_from_target_pair(&mpat, &mimp)?;
-        let imap = instrmap_from_prog(prog)?;
-        let blks = treelist_try_from_prog(prog)?;
-        let blks = tree_select(&blks, &dmap)?;
-        let blks = tree_select(&blks, &lmap)?;
-        let blks = tree_select(&blks, &mmap)?;
-        let blks = tree_commit(&blks)?;
-        let mut body: Vec<asm::Instr> = Vec::new();
-        let mut iset: HashSet<ir::Id> = HashSet::new();
-        let tree_map: TreeMap = lmap.into_iter().chain(dmap).chain(mmap).collect();
-        let pat_map: HashMap<String, pat::Pat> = lpat
-            .pat()
-            .clone()
-            .into_iter()
-            .chain(dpat.pat().clone())
-            .chain(mpat.pat().clone())
-            .collect();
-        for blk in blks {
-            body.extend(tree_codegen(&mut iset, &imap, &blk, &tree_map, &pat_map)?);
-        }
-        let mut res = asm::Prog::default();
-        res.set_sig(main.sig().clone());
-        res.set_body(body);
-        // tile operations that can be arranged in multiple slices
-        Ok(tile::tile_from_prog(&res))
+        try_from_ir_def_with_model(main, target, model)
     } else {
         Err(Error::new_isel_error("main is not present"))
     }
 }
+
+pub fn try_from_ir_prog_with_target(prog: &ir::Prog, target: &Target) -> Result<asm::Prog, Error> {
+    try_from_ir_prog_with_model(prog, target, &CostModel::default())
+}
+
+// TODO: impl try_from after refactoring done
+pub fn try_from_ir_prog(prog: &ir::Prog) -> Result<asm::Prog, Error> {
+    try_from_ir_prog_with_target(prog, &Target::default())
+}
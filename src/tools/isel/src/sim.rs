@@ -0,0 +1,178 @@
+use crate::errors::Error;
+use ::ir::interp::{Env, Interp};
+use asm::ast as asm;
+use ir::ast as ir;
+use pat::ast as pat;
+use registry::spec::spec_try_from_dsts;
+use registry::{pat_from_op, Target};
+use std::collections::{HashMap, HashSet};
+
+fn pat_try_from_op<'a>(
+    map: &'a HashMap<String, pat::Pat>,
+    op: &str,
+) -> Result<&'a pat::Pat, Error> {
+    if let Some(pat) = pat_from_op(map, op) {
+        Ok(pat)
+    } else {
+        let err = format!("pattern for {} not found", op);
+        Err(Error::new_isel_error(&err))
+    }
+}
+
+fn is_clocked(instr: &pat::Instr) -> bool {
+    match instr {
+        pat::Instr::Prim(prim) => matches!(prim.op(), ir::OpPrim::Reg | ir::OpPrim::Ram),
+        _ => false,
+    }
+}
+
+fn rename_expr(expr: &ir::Expr, map: &HashMap<ir::Id, ir::ExprTerm>) -> ir::Expr {
+    let rename = |term: &ir::ExprTerm| match term.id().and_then(|id| map.get(&id)) {
+        Some(t) => t.clone(),
+        None => term.clone(),
+    };
+    match expr {
+        ir::Expr::Term(term) => ir::Expr::from(rename(term)),
+        ir::Expr::Tup(tup) => ir::Expr::from(ir::ExprTup::from(
+            tup.term().iter().map(rename).collect::<Vec<_>>(),
+        )),
+    }
+}
+
+struct Expander {
+    pat: HashMap<String, pat::Pat>,
+    names: HashSet<ir::Id>,
+}
+
+impl Expander {
+    fn new(prog: &asm::Prog, target: &Target) -> Result<Self, Error> {
+        let mut names: HashSet<ir::Id> = HashSet::new();
+        let input: Vec<ir::ExprTerm> = prog.sig().input.clone().into();
+        for term in input {
+            names.insert(term.get_id()?);
+        }
+        for instr in prog.body() {
+            let dst: Vec<ir::ExprTerm> = instr.dst().clone().into();
+            for term in dst {
+                names.insert(term.get_id()?);
+            }
+        }
+        let target = spec_try_from_dsts(target, prog.body().iter().map(|i| i.dst()))?;
+        Ok(Expander {
+            pat: target.pat_map(),
+            names,
+        })
+    }
+    fn fresh(&mut self, prefix: &str, id: &str) -> ir::Id {
+        let mut name = format!("{}_{}", prefix, id);
+        let mut cnt = 0;
+        while self.names.contains(&name) {
+            name = format!("{}_{}{}", prefix, id, cnt);
+            cnt += 1;
+        }
+        self.names.insert(name.to_string());
+        name
+    }
+    // bind pattern inputs and outputs to the terms of the instruction, values
+    // internal to the pattern get names derived from the destination
+    fn expand(&mut self, instr: &asm::InstrAsm) -> Result<Vec<ir::Instr>, Error> {
+        let pat = pat_try_from_op(&self.pat, &instr.op().name())?.clone();
+        let input: Vec<ir::ExprTerm> = pat.input().clone().into();
+        let output: Vec<ir::ExprTerm> = pat.output().clone().into();
+        let mut arg: Vec<ir::ExprTerm> = instr.arg().clone().into();
+        let dst: Vec<ir::ExprTerm> = instr.dst().clone().into();
+        // clocks are not part of patterns and go to every register and memory
+        let clock = if arg.len() == input.len() + 1 {
+            arg.pop()
+        } else {
+            None
+        };
+        if arg.len() != input.len() || dst.len() != output.len() {
+            let err = format!("{} does not match the signature of its pattern", instr);
+            return Err(Error::new_isel_error(&err));
+        }
+        let mut map: HashMap<ir::Id, ir::ExprTerm> = HashMap::new();
+        for (p, t) in input
+            .iter()
+            .chain(output.iter())
+            .zip(arg.iter().chain(dst.iter()))
+        {
+            let term = match t.ty() {
+                Some(ty) if ty != &ir::Ty::Any => t.clone(),
+                _ => ir::ExprTerm::Var(t.get_id()?, p.get_ty()?.clone()),
+            };
+            map.insert(p.get_id()?, term);
+        }
+        let prefix = dst[0].get_id()?;
+        let mut body: Vec<ir::Instr> = Vec::new();
+        for p in pat.body() {
+            let mut instr_ir = match p {
+                pat::Instr::Wire(wire) => ir::Instr::from(wire.clone()),
+                // generic registers take the init and reset values of the instruction
+                pat::Instr::Prim(prim) if prim.is_reg() && prim.attr().idx(0).is_none() => {
+                    let mut prim = prim.clone();
+                    prim.set_attr(instr.attr().clone());
+                    ir::Instr::from(prim)
+                }
+                pat::Instr::Prim(prim) => ir::Instr::from(prim.clone()),
+            };
+            let pdst: Vec<ir::ExprTerm> = p.dst().clone().into();
+            for term in pdst {
+                let id = term.get_id()?;
+                let ty = term.get_ty()?.clone();
+                map.entry(id.to_string())
+                    .or_insert_with(|| ir::ExprTerm::Var(self.fresh(&prefix, &id), ty));
+            }
+            instr_ir.set_dst(rename_expr(p.dst(), &map));
+            let mut parg: Vec<ir::ExprTerm> = rename_expr(p.arg(), &map).into();
+            if let Some(clock) = clock.as_ref().filter(|_| is_clocked(p)) {
+                parg.push(clock.clone());
+            }
+            instr_ir.set_arg(ir::Expr::from(ir::ExprTup::from(parg)));
+            instr_ir.set_span(instr.span().clone());
+            body.push(instr_ir);
+        }
+        Ok(body)
+    }
+}
+
+// ir program with the same behavior as an asm program, every asm instruction
+// is replaced by the body of its pattern
+pub fn ir_try_from_asm_prog_with_target(
+    prog: &asm::Prog,
+    target: &Target,
+) -> Result<ir::Prog, Error> {
+    let mut exp = Expander::new(prog, target)?;
+    let mut body: Vec<ir::Instr> = Vec::new();
+    for instr in prog.body() {
+        match instr {
+            asm::Instr::Wire(wire) => body.push(ir::Instr::from(wire.clone())),
+            asm::Instr::Asm(instr) => body.extend(exp.expand(instr)?),
+        }
+    }
+    let mut def = ir::Def::default();
+    def.set_sig(prog.sig().clone());
+    def.set_body(body);
+    let mut res = ir::Prog::default();
+    res.insert("main", def);
+    Ok(res)
+}
+
+pub fn ir_try_from_asm_prog(prog: &asm::Prog) -> Result<ir::Prog, Error> {
+    ir_try_from_asm_prog_with_target(prog, &Target::default())
+}
+
+// simulate an asm program cycle by cycle, outputs are sampled before the clock edge
+pub fn interp_try_from_asm_prog_with_target(
+    prog: &asm::Prog,
+    input: &[Env],
+    target: &Target,
+) -> Result<Vec<Env>, Error> {
+    let prog = ir_try_from_asm_prog_with_target(prog, target)?;
+    let mut interp = Interp::try_from_prog(&prog)?;
+    Ok(interp.run(input)?)
+}
+
+pub fn interp_try_from_asm_prog(prog: &asm::Prog, input: &[Env]) -> Result<Vec<Env>, Error> {
+    interp_try_from_asm_prog_with_target(prog, input, &Target::default())
+}
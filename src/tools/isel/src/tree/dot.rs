@@ -0,0 +1,52 @@
+use crate::tree::*;
+use ::ir::dot::{dot_color, dot_digraph, dot_label};
+use std::collections::HashMap;
+
+// every tree is a cluster with a bold root, nodes that root a pattern show
+// its name and prim, covered nodes are dashed and inputs link to the tree
+// that defines them
+pub fn dot_from_trees(trees: &[Tree]) -> String {
+    let mut root: HashMap<Id, String> = HashMap::new();
+    for (t, tree) in trees.iter().enumerate() {
+        if let Some(node) = tree.node(0) {
+            root.insert(node.id(), format!("\"{}/0\"", t));
+        }
+    }
+    let mut cluster: Vec<String> = Vec::new();
+    let mut link = String::new();
+    for (t, tree) in trees.iter().enumerate() {
+        let name = tree.node(0).map_or(t.to_string(), |n| n.id());
+        let mut dot = format!("subgraph \"cluster_{}\" {{\nlabel = \"{}\"\n", t, name);
+        for i in 0..tree.index() {
+            if let Some(node) = tree.node(i) {
+                let key = format!("\"{}/{}\"", t, i);
+                let mut line = vec![node.id(), format!("{}", node.op())];
+                let mut attr = String::new();
+                if let Some(pat) = node.pat().filter(|_| node.is_committed()) {
+                    line.push(format!("{} @{}", pat, node.pat_prim()));
+                    attr.push_str(&format!(", fillcolor = {}", dot_color(node.pat_prim())));
+                } else if node.is_inp_op() {
+                    attr.push_str(", shape = ellipse");
+                    if let Some(from) = root.get(&node.id()) {
+                        link.push_str(&format!("{} -> {} [ style = dashed ]\n", from, key));
+                    }
+                } else if node.is_staged() {
+                    attr.push_str(", style = \"filled,dashed\"");
+                }
+                if i == 0 {
+                    attr.push_str(", penwidth = 3");
+                }
+                dot.push_str(&format!("{} [ {}{} ]\n", key, dot_label(&line), attr));
+            }
+            if let Some(edge) = tree.edge(i) {
+                for e in edge {
+                    dot.push_str(&format!("\"{}/{}\" -> \"{}/{}\" [ ]\n", t, e, t, i));
+                }
+            }
+        }
+        dot.push_str("}\n");
+        cluster.push(dot);
+    }
+    cluster.push(link);
+    dot_digraph(&cluster)
+}
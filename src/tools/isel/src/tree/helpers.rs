@@ -1,8 +1,8 @@
 use crate::errors::Error;
 use crate::tree::*;
+use ::ir::clock::DEFAULT_CLOCK;
 use asm::ast as asm;
 use pat::ast as pat;
-use std::collections::BTreeMap;
 use std::collections::VecDeque;
 use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
@@ -224,8 +224,8 @@ impl Tree {
     }
 }
 
-pub fn tree_roots_from_def(def: &Def) -> Vec<Id> {
-    let mut count: BTreeMap<Id, u64> = BTreeMap::new();
+pub fn tree_roots_from_def(def: &Def) -> Result<Vec<Id>, Error> {
+    let mut count: HashMap<Id, u64> = HashMap::new();
     // store compute instructions
     for instr in def.body() {
         if instr.is_prim() {
@@ -248,10 +248,14 @@ pub fn tree_roots_from_def(def: &Def) -> Vec<Id> {
         }
     }
     let mut root: Vec<Id> = Vec::new();
-    // a node is a root if it is used more than once
-    for (k, v) in count {
-        if v > 1 {
-            root.push(k);
+    let mut sorted = def.clone();
+    sorted.sort_body()?;
+    // a node is a root if it is used more than once, in topological order
+    for instr in sorted.body() {
+        if let Ok(id) = instr.dst().get_id(0) {
+            if count.get(&id).map_or(false, |v| *v > 1) {
+                root.push(id);
+            }
         }
     }
     // add outputs as roots
@@ -261,7 +265,7 @@ pub fn tree_roots_from_def(def: &Def) -> Vec<Id> {
             root.push(id);
         }
     }
-    root
+    Ok(root)
 }
 
 pub fn tree_try_from_map(
@@ -364,7 +368,7 @@ pub fn create_tree(
 pub fn treelist_try_from_def(def: &Def) -> Result<Vec<Tree>, Error> {
     let instr_map = InstrMap::from(def.clone());
     let mut res: Vec<Tree> = Vec::new();
-    let roots = tree_roots_from_def(def);
+    let roots = tree_roots_from_def(def)?;
     let mut visited: HashSet<Id> = HashSet::new();
     // inputs cannot be child nodes
     let input_map: TermMap = def.input().clone().into();
@@ -382,14 +386,19 @@ pub fn treelist_try_from_def(def: &Def) -> Result<Vec<Tree>, Error> {
     Ok(res)
 }
 
-pub fn treemap_try_from_target_pair(
+// the cost of a pattern is the cost of its implementation
+pub fn treemap_try_from_target_pair<F>(
     target_pat: &pat::Target,
     target_imp: &xim::Target,
-) -> Result<TreeMap, Error> {
+    cost: F,
+) -> Result<TreeMap, Error>
+where
+    F: Fn(&xim::Imp) -> u64,
+{
     let mut tree_map = TreeMap::new();
     for (n, p) in target_pat.pat() {
         if let Some(imp) = target_imp.get(n) {
-            let cost = imp.perf();
+            let cost = cost(imp);
             let instr_map = InstrMap::from(p.clone());
             let mut visited: HashSet<Id> = HashSet::new();
             let tree = tree_try_from_map(
@@ -417,6 +426,48 @@ pub fn treelist_try_from_prog(prog: &Prog) -> Result<Vec<Tree>, Error> {
     }
 }
 
+// reg[init, value] where value defaults to init and both default to zero
+fn reg_values(attr: &Expr) -> (i64, i64) {
+    let init = attr.get_val(0).unwrap_or(0);
+    let value = attr.get_val(1).unwrap_or(init);
+    (init, value)
+}
+
+// a pattern register without values matches registers with any init and reset value,
+// the values are then carried by the selected instruction
+fn is_generic_reg(node: &Node) -> bool {
+    node.op() == &NodeOp::Prim(OpPrim::Reg) && node.attr().idx(0).is_none()
+}
+
+// block ram patterns read through a register, so only memories placed
+// with @bram can use them
+fn is_prim_match(pnode: &Node, bnode: &Node) -> bool {
+    let is_mem = matches!(
+        pnode.op(),
+        NodeOp::Prim(OpPrim::Rom) | NodeOp::Prim(OpPrim::Ram)
+    );
+    pnode.prim() == bnode.prim()
+        || (bnode.prim().is_any() && !(is_mem && pnode.prim() == &Prim::Bram))
+}
+
+// memories without placement are read combinationally, which block rams can not do
+fn is_comb_mem(node: &Node) -> bool {
+    let is_mem = matches!(
+        node.op(),
+        NodeOp::Prim(OpPrim::Rom) | NodeOp::Prim(OpPrim::Ram)
+    );
+    is_mem && node.prim().is_any()
+}
+
+fn is_attr_match(pnode: &Node, bnode: &Node) -> bool {
+    match pnode.op() {
+        NodeOp::Prim(OpPrim::Reg) => {
+            is_generic_reg(pnode) || reg_values(pnode.attr()) == reg_values(bnode.attr())
+        }
+        _ => pnode.attr() == bnode.attr(),
+    }
+}
+
 pub fn is_valid_change(block: &Tree, pat: &Tree, start: u64) -> (bool, u64) {
     let mut p_stack = pat.bfs(0);
     p_stack.reverse();
@@ -433,10 +484,8 @@ pub fn is_valid_change(block: &Tree, pat: &Tree, start: u64) -> (bool, u64) {
                     if let Some(pnode) = pat.node(pindex) {
                         if pnode.ty() != bnode.ty()
                             || (!pnode.is_inp_op() && pnode.op() != bnode.op())
-                            || (!pnode.is_inp_op()
-                                && !bnode.prim().is_any()
-                                && pnode.prim() != bnode.prim())
-                            || (!pnode.is_inp_op() && pnode.attr() != bnode.attr())
+                            || (!pnode.is_inp_op() && !is_prim_match(pnode, bnode))
+                            || (!pnode.is_inp_op() && !is_attr_match(pnode, bnode))
                             || (!pnode.is_inp_op() && bnode.is_committed())
                         {
                             next = None;
@@ -445,7 +494,7 @@ pub fn is_valid_change(block: &Tree, pat: &Tree, start: u64) -> (bool, u64) {
                             if bnode.cost() == u64::MAX {
                                 bcost = bnode.cost();
                             } else if bcost != u64::MAX {
-                                bcost += bnode.cost();
+                                bcost = bcost.saturating_add(bnode.cost());
                             }
                             if let Some(edge) = block.edge(bindex) {
                                 for e in edge {
@@ -526,6 +575,82 @@ pub fn input_map(block: &Tree, pat: &Tree, target: u64) -> HashMap<String, Strin
     map
 }
 
+// block nodes implemented by a pattern, inputs excluded
+pub fn cover_map(block: &Tree, pat: &Tree, target: u64) -> Vec<String> {
+    let mut cover: Vec<String> = Vec::new();
+    let mut p_stack = pat.bfs(0);
+    p_stack.reverse();
+    let mut b_stack: VecDeque<u64> = VecDeque::new();
+    b_stack.push_back(target);
+    while let Some(bindex) = b_stack.pop_front() {
+        if let Some(pindex) = p_stack.pop() {
+            if let Some(pnode) = pat.node(pindex) {
+                if !pnode.is_inp_op() {
+                    if let Some(bnode) = block.node(bindex) {
+                        cover.push(bnode.id());
+                    }
+                    if let Some(edge) = block.edge(bindex) {
+                        for e in edge {
+                            b_stack.push_back(*e);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    cover
+}
+
+// the init and reset values of the block register covered by a generic one,
+// registers that start and reset at zero need none
+pub fn reg_attr_map(block: &Tree, pat: &Tree, target: u64) -> Expr {
+    let mut p_stack = pat.bfs(0);
+    p_stack.reverse();
+    let mut b_stack: VecDeque<u64> = VecDeque::new();
+    b_stack.push_back(target);
+    while let Some(bindex) = b_stack.pop_front() {
+        if let Some(pindex) = p_stack.pop() {
+            if let Some(pnode) = pat.node(pindex) {
+                if !pnode.is_inp_op() {
+                    if let Some(bnode) = block.node(bindex) {
+                        if is_generic_reg(pnode) && reg_values(bnode.attr()) != (0, 0) {
+                            return bnode.attr().clone();
+                        }
+                    }
+                    if let Some(edge) = block.edge(bindex) {
+                        for e in edge {
+                            b_stack.push_back(*e);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Expr::default()
+}
+
+// registers in a pattern must share a clock, which becomes the last argument
+// of the instruction unless it is the default one
+fn clock_try_from_cover(
+    cover: &[String],
+    cmap: &HashMap<Id, Id>,
+    name: &str,
+    span: &asm::Span,
+) -> Result<Option<Id>, Error> {
+    let mut clock: Option<&Id> = None;
+    for id in cover {
+        match (clock, cmap.get(id)) {
+            (Some(a), Some(b)) if a != b => {
+                let msg = format!("{} mixes clock domains {} and {}", name, a, b);
+                return Err(Error::new_isel_error(&span.render(&msg)));
+            }
+            (None, Some(b)) => clock = Some(b),
+            _ => (),
+        }
+    }
+    Ok(clock.filter(|c| c.as_str() != DEFAULT_CLOCK).cloned())
+}
+
 pub fn output_map(block: &Tree, pat: &Tree, target: u64) -> HashMap<String, String> {
     let mut map: HashMap<String, String> = HashMap::new();
     if let Some(pnode) = pat.node(0) {
@@ -601,12 +726,15 @@ pub fn tree_codegen(
     block: &Tree,
     tmap: &TreeMap,
     pmap: &HashMap<String, pat::Pat>,
+    cmap: &HashMap<Id, Id>,
 ) -> Result<Vec<asm::Instr>, Error> {
     let mut body: Vec<asm::Instr> = Vec::new();
     let mut indices = block.bfs(0);
     indices.reverse();
     let mut next = indices.pop();
     let mut uncover = String::new();
+    let mut uncover_span = asm::Span::default();
+    let mut uncover_mem = false;
     // bottom-up code generation
     while let Some(index) = next {
         if let Some(node) = block.node(index) {
@@ -617,14 +745,33 @@ pub fn tree_codegen(
                             let input = input_map(block, tree, index);
                             let output = output_map(block, tree, index);
                             let dst = rename_dst(&output, pat.output())?;
-                            let arg = rename_arg(&input, pat.input())?;
+                            let mut arg = rename_arg(&input, pat.input())?;
                             let op = asm::OpAsm::from(name.clone());
                             let loc = asm::Loc {
                                 prim: node.pat_prim().clone(),
                                 x: asm::ExprCoord::Any,
                                 y: asm::ExprCoord::Any,
                             };
-                            let asm = asm::InstrAsm { op, dst, arg, loc };
+                            // the selected instruction points at the root of the tree
+                            let span = imap
+                                .get(&node.id())
+                                .map(|instr| instr.span().clone())
+                                .unwrap_or_default();
+                            let cover = cover_map(block, tree, index);
+                            if let Some(clock) = clock_try_from_cover(&cover, cmap, name, &span)? {
+                                let mut term: Vec<asm::ExprTerm> = arg.into();
+                                term.push(asm::ExprTerm::Var(clock, asm::Ty::Clock));
+                                arg = asm::Expr::from(asm::ExprTup::from(term));
+                            }
+                            let attr = reg_attr_map(block, tree, index);
+                            let asm = asm::InstrAsm {
+                                op,
+                                dst,
+                                attr,
+                                arg,
+                                loc,
+                                span,
+                            };
                             body.push(asm::Instr::from(asm));
                         }
                     }
@@ -645,6 +792,10 @@ pub fn tree_codegen(
                 } else {
                     next = None;
                     uncover = node.to_string();
+                    uncover_mem = is_comb_mem(node);
+                    if let Some(instr) = imap.get(&node.id()) {
+                        uncover_span = instr.span().clone();
+                    }
                 }
             } else {
                 next = indices.pop();
@@ -653,8 +804,14 @@ pub fn tree_codegen(
     }
     if indices.is_empty() {
         Ok(body)
+    } else if uncover_mem {
+        let msg = format!(
+            "missing node: {}, memories without placement are read combinationally and can not use block rams, place it with @bram to read it through the block ram output register",
+            uncover
+        );
+        Err(Error::new_isel_error(&uncover_span.render(&msg)))
     } else {
         let msg = format!("missing node: {}", uncover);
-        Err(Error::new_isel_error(&msg))
+        Err(Error::new_isel_error(&uncover_span.render(&msg)))
     }
 }
@@ -1,5 +1,6 @@
 pub mod default;
 pub mod display;
+pub mod dot;
 pub mod from;
 pub mod helpers;
 pub mod try_from;
@@ -0,0 +1,92 @@
+use ir::parser::Parser as IrParser;
+use isel::cost::{cost_try_from_asm_prog, CostModel, Objective};
+use isel::errors::Error;
+use isel::{try_from_ir_prog, try_from_ir_prog_with_model};
+use registry::Target;
+use std::path::Path;
+
+const DSP_PAT: &str = "pat dadd_i8(a:i8, b:i8) -> (y:i8) {
+    y:i8 = add(a, b) @dsp;
+}";
+
+const DSP_XIM: &str = "imp dadd_i8[4, 1](a:i8, b:i8) -> (y:i8) {}";
+
+const ADD: &str = "def main(a:i8, b:i8) -> (y:i8) {
+    y:i8 = add(a, b);
+}";
+
+// a dsp adder that is faster but larger than the lut one
+fn dsp_target() -> Result<Target, Error> {
+    Ok(Target::from_strs(&[
+        ("dsp.pat", DSP_PAT),
+        ("dsp.xim", DSP_XIM),
+    ])?)
+}
+
+fn select(target: &Target, model: &CostModel) -> Result<String, Error> {
+    let prog = IrParser::parse_from_str(ADD)?;
+    let asm = try_from_ir_prog_with_model(&prog, target, model)?;
+    Ok(asm.body()[0].to_string())
+}
+
+fn is_isel_error<T>(res: Result<T, Error>, msg: &str) -> bool {
+    match res {
+        Err(Error::ISel(err)) => err.contains(msg),
+        _ => false,
+    }
+}
+
+#[test]
+fn objective() -> Result<(), Error> {
+    let target = dsp_target()?;
+    let latency = select(&target, &CostModel::new(Objective::Latency))?;
+    assert!(latency.contains("dadd_i8"));
+    let area = select(&target, &CostModel::new(Objective::Area))?;
+    assert!(area.contains("ladd_i8"));
+    let mut model = CostModel::new(Objective::Area);
+    model.set_weight("lut", 8)?;
+    assert!(select(&target, &model)?.contains("dadd_i8"));
+    // the largest weight still leaves the only adder selectable
+    model.set_weight("lut", u64::MAX)?;
+    assert!(select(&Target::default(), &model)?.contains("ladd_i8"));
+    Ok(())
+}
+
+#[test]
+fn total() -> Result<(), Error> {
+    let prog = IrParser::parse_from_file(Path::new("../../../examples/ir/add.ir"))?;
+    let asm = try_from_ir_prog(&prog)?;
+    let target = Target::default();
+    let latency = CostModel::new(Objective::Latency);
+    let weighted = CostModel::new(Objective::Weighted(1, 3));
+    assert_eq!(cost_try_from_asm_prog(&asm, &target, &latency)?, 2);
+    assert_eq!(cost_try_from_asm_prog(&asm, &target, &weighted)?, 5);
+    Ok(())
+}
+
+#[test]
+fn options() -> Result<(), Error> {
+    assert_eq!("area".parse::<Objective>()?, Objective::Area);
+    assert_eq!(
+        "weighted:2,1".parse::<Objective>()?,
+        Objective::Weighted(2, 1)
+    );
+    assert!(is_isel_error(
+        "fast".parse::<Objective>(),
+        "fast is not latency"
+    ));
+    let mut model = CostModel::default();
+    model.set_weight_from_str("dsp=4")?;
+    assert_eq!(model.weight("dsp"), 4);
+    assert_eq!(model.weight("lut"), 1);
+    assert!(is_isel_error(
+        model.set_weight_from_str("alu=2"),
+        "alu is not a resource"
+    ));
+    let order: Vec<String> = vec!["lut".to_string(), "dsp".to_string()];
+    assert!(is_isel_error(
+        model.set_order(&order),
+        "lut,dsp is not an order"
+    ));
+    Ok(())
+}
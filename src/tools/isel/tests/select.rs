@@ -1,7 +1,13 @@
+use asm::ast::Instr;
 use asm::parser::Parser as AsmParser;
+use ir::cse::cse_try_from_def;
+use ir::hier::split_calls_from_def;
+use ir::narrow::narrow_try_from_prog;
 use ir::parser::Parser as IrParser;
 use isel::errors::Error;
-use isel::try_from_ir_prog;
+use isel::tree::dot::dot_from_trees;
+use isel::tree::helpers::tree_roots_from_def;
+use isel::{tree_try_from_ir_def, try_from_ir_def, try_from_ir_prog};
 use std::path::Path;
 
 fn test(name: &str) -> Result<(), Error> {
@@ -21,6 +27,11 @@ fn add() -> Result<(), Error> {
     test("add")
 }
 
+#[test]
+fn add_i12() -> Result<(), Error> {
+    test("add_i12")
+}
+
 #[test]
 fn fsm_3() -> Result<(), Error> {
     test("fsm_3")
@@ -95,3 +106,120 @@ fn tdot_5_36() -> Result<(), Error> {
 fn mux128() -> Result<(), Error> {
     test("mux128")
 }
+
+#[test]
+fn lram_8x8() -> Result<(), Error> {
+    test("lram_8x8")
+}
+
+#[test]
+fn bram_8x256() -> Result<(), Error> {
+    test("bram_8x256")
+}
+
+#[test]
+fn cdc_sync() -> Result<(), Error> {
+    test("cdc_sync")
+}
+
+#[test]
+fn hier() -> Result<(), Error> {
+    let parsed = IrParser::parse_from_str(
+        r#"def acc(a:i8, en:bool) -> (y:i8) {
+            t0:i8 = add(a, y);
+            y:i8 = reg[0](t0, en);
+        }
+        def main(a:i8, b:i8, en:bool) -> (y:i8) {
+            t0:i8 = add(a, b);
+            y:i8 = acc(t0, en);
+        }"#,
+    )?;
+    let main = parsed.get("main").unwrap();
+    let (def, _) = split_calls_from_def(main)?;
+    let res = try_from_ir_def(&def)?;
+    assert_eq!(res.sig(), def.sig());
+    assert_eq!(res.body().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn reg_init_value() -> Result<(), Error> {
+    let parsed = IrParser::parse_from_str(
+        r#"def main(a:i8, b:i8, en:bool) -> (y:i8, z:i8) {
+            y:i8 = reg[0, 3](a, en);
+            t0:i8 = add(a, b);
+            z:i8 = reg[5](t0, en);
+        }"#,
+    )?;
+    let exp = AsmParser::parse_from_str(
+        r#"def main(a:i8, b:i8, en:bool) -> (y:i8, z:i8) {
+            y:i8 = lrega_i8[0, 3](a, en) @lut(??, ??);
+            z:i8 = laddrega_i8[5](a, b, en) @lut(??, ??);
+        }"#,
+    )?;
+    let res = try_from_ir_prog(&parsed)?;
+    assert_eq!(res, exp);
+    Ok(())
+}
+
+#[test]
+fn cse() -> Result<(), Error> {
+    let parsed = IrParser::parse_from_str(
+        r#"def main(a:i8, b:i8, c:i8, d:i8) -> (y:i8, z:i8) {
+            t0:i8 = add(a, b);
+            t1:i8 = add(b, a);
+            y:i8 = add(t0, c);
+            z:i8 = add(t1, d);
+        }"#,
+    )?;
+    let main = parsed.get("main").unwrap();
+    let (def, removed) = cse_try_from_def(main)?;
+    assert_eq!(removed, 1);
+    assert!(tree_roots_from_def(main)?.iter().all(|r| r != "t0"));
+    assert!(tree_roots_from_def(&def)?.iter().any(|r| r == "t0"));
+    let res = try_from_ir_def(&def)?;
+    assert_eq!(res.body().len(), 3);
+    Ok(())
+}
+
+#[test]
+fn narrow() -> Result<(), Error> {
+    let parsed = IrParser::parse_from_file("../../../examples/ir/fsm_3.ir")?;
+    let res = try_from_ir_prog(&narrow_try_from_prog(&parsed)?)?;
+    let mut op: Vec<String> = Vec::new();
+    for instr in res.body() {
+        if let Instr::Asm(asm) = instr {
+            op.push(asm.op().name());
+        }
+    }
+    assert!(op.iter().any(|o| o == "lmux_i2"));
+    assert!(op.iter().all(|o| !o.ends_with("_i4")));
+    Ok(())
+}
+
+#[test]
+fn dot() -> Result<(), Error> {
+    let prog = IrParser::parse_from_file(Path::new("../../../examples/ir/tadd.ir"))?;
+    let trees = tree_try_from_ir_def(prog.get("main").unwrap())?;
+    let dot = dot_from_trees(&trees);
+    assert_eq!(dot.matches("subgraph").count(), trees.len());
+    assert!(dot.contains("penwidth = 3"));
+    assert!(dot.contains("@dsp"));
+    Ok(())
+}
+
+#[test]
+fn bram_placement() -> Result<(), Error> {
+    // block ram reads are registered, so memories have to ask for them
+    let prog = IrParser::parse_from_str(
+        r#"def main(a:i8) -> (y:i8) {
+            y:i8 = rom(a) @bram;
+        }"#,
+    )?;
+    let res = try_from_ir_prog(&prog)?.to_string();
+    assert!(res.contains("brom_i8i8"));
+    let prog = IrParser::parse_from_file("../../../examples/ir/rom_8x256.ir")?;
+    let err = try_from_ir_prog(&prog).unwrap_err().to_string();
+    assert!(err.contains("place it with @bram"));
+    Ok(())
+}
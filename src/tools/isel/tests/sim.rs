@@ -0,0 +1,123 @@
+use asm::parser::Parser as AsmParser;
+use ir::ast::{ExprTerm, Sig, Ty};
+use ir::interp::{interp_try_from_prog, Env, Value};
+use ir::parser::Parser as IrParser;
+use isel::errors::Error;
+use isel::sim::interp_try_from_asm_prog;
+use isel::try_from_ir_prog;
+use std::path::Path;
+
+// pseudo-random inputs, the same on every run, clocks tick every cycle
+fn input_from_sig(sig: &Sig, cycles: usize) -> Result<Vec<Env>, Error> {
+    let input: Vec<ExprTerm> = sig.input.clone().into();
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut res: Vec<Env> = Vec::new();
+    for _ in 0..cycles {
+        let mut env = Env::new();
+        for term in &input {
+            let ty = term.get_ty()?;
+            let value = if ty == &Ty::Clock {
+                Value::from(true)
+            } else {
+                let lanes: Vec<i128> = (0..ty.length().unwrap_or(1))
+                    .map(|_| {
+                        seed = seed
+                            .wrapping_mul(6_364_136_223_846_793_005)
+                            .wrapping_add(1_442_695_040_888_963_407);
+                        i128::from(seed >> 1)
+                    })
+                    .collect();
+                Value::new(ty, &lanes)
+            };
+            env.insert(term.get_id()?, value);
+        }
+        res.push(env);
+    }
+    Ok(res)
+}
+
+fn test(name: &str) -> Result<(), Error> {
+    let mut path = Path::new("../../../examples/ir").join(name);
+    path.set_extension("ir");
+    let prog = IrParser::parse_from_file(path)?;
+    let input = input_from_sig(prog.get("main").unwrap().sig(), 16)?;
+    let exp = interp_try_from_prog(&prog, &input)?;
+    let asm = try_from_ir_prog(&prog)?;
+    let res = interp_try_from_asm_prog(&asm, &input)?;
+    assert_eq!(res, exp);
+    Ok(())
+}
+
+#[test]
+fn add() -> Result<(), Error> {
+    test("add")
+}
+
+#[test]
+fn add_i12() -> Result<(), Error> {
+    test("add_i12")
+}
+
+#[test]
+fn fsm_5() -> Result<(), Error> {
+    test("fsm_5")
+}
+
+#[test]
+fn tadd_64() -> Result<(), Error> {
+    test("tadd_64")
+}
+
+#[test]
+fn tdot() -> Result<(), Error> {
+    test("tdot")
+}
+
+#[test]
+fn mux128() -> Result<(), Error> {
+    test("mux128")
+}
+
+#[test]
+fn lram_8x8() -> Result<(), Error> {
+    test("lram_8x8")
+}
+
+#[test]
+fn bram_8x256() -> Result<(), Error> {
+    test("bram_8x256")
+}
+
+#[test]
+fn cdc_sync() -> Result<(), Error> {
+    test("cdc_sync")
+}
+
+#[test]
+fn cascade() -> Result<(), Error> {
+    let prog = AsmParser::parse_from_file("../../../examples/asm/tdot_5_3.asm")?;
+    let opt = AsmParser::parse_from_file("../../../examples/asm/tdot_5_3_opt.asm")?;
+    let input = input_from_sig(prog.sig(), 16)?;
+    let exp = interp_try_from_asm_prog(&prog, &input)?;
+    let res = interp_try_from_asm_prog(&opt, &input)?;
+    assert_eq!(res, exp);
+    Ok(())
+}
+
+#[test]
+fn reg_init_value() -> Result<(), Error> {
+    let prog = IrParser::parse_from_str(
+        r#"def main(a:i8, b:i8, en:bool) -> (y:i8, z:i8) {
+            y:i8 = reg[5](a, en);
+            t0:i8 = add(a, b);
+            z:i8 = reg[-3, 0](t0, en);
+        }"#,
+    )?;
+    let input = input_from_sig(prog.get("main").unwrap().sig(), 4)?;
+    let exp = interp_try_from_prog(&prog, &input)?;
+    assert_eq!(exp[0]["y"].get_scalar()?, 5);
+    let asm = try_from_ir_prog(&prog)?;
+    let res = interp_try_from_asm_prog(&asm, &input)?;
+    assert_eq!(res, exp);
+    Ok(())
+}
@@ -1,4 +1,5 @@
 mod prim;
+pub mod series7;
 pub mod ultrascale;
 
 pub use crate::prim::{Error, Param, ParamSet, Port, PortSet, Prim, ToPrim};
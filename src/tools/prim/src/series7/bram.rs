@@ -0,0 +1,194 @@
+use crate::{Param, ParamSet, Port, PortSet, Prim, ToPrim};
+use derive_more::{Deref, DerefMut, Display, From};
+use std::fmt;
+
+pub use crate::ultrascale::bram::{CollisionCheck, FilePath, RstRegPriority, WriteMode};
+
+#[derive(Clone, Debug, PartialEq, Eq, Display)]
+pub enum RamMode {
+    #[display(fmt = "TDP")]
+    Tdp,
+    #[display(fmt = "SDP")]
+    Sdp,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Display)]
+pub enum CollisionConfig {
+    #[display(fmt = "DELAYED_WRITE")]
+    DelayedWrite,
+    #[display(fmt = "PERFORMANCE")]
+    Performance,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Display)]
+pub enum SimDevice {
+    #[display(fmt = "7SERIES")]
+    Series7,
+}
+
+#[derive(Clone, Debug, From, Eq)]
+pub enum ParamValue {
+    CollisionCheck(CollisionCheck),
+    CollisionConfig(CollisionConfig),
+    Bool(bool),
+    #[from(ignore)]
+    BoolNum(bool),
+    Bytes(u32, Vec<u8>),
+    Num(u32),
+    FilePath(FilePath),
+    RamMode(RamMode),
+    RstRegPriority(RstRegPriority),
+    SimDevice(SimDevice),
+    WriteMode(WriteMode),
+}
+
+#[derive(Clone, Debug, Deref, DerefMut)]
+pub struct Bram(Prim<ParamValue>);
+
+#[derive(Clone, Debug, Default)]
+struct BramPrim;
+
+impl PartialEq for ParamValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ParamValue::CollisionCheck(_), ParamValue::CollisionCheck(_))
+            | (ParamValue::CollisionConfig(_), ParamValue::CollisionConfig(_))
+            | (ParamValue::Bool(_), ParamValue::Bool(_))
+            | (ParamValue::BoolNum(_), ParamValue::BoolNum(_))
+            | (ParamValue::Bytes(_, _), ParamValue::Bytes(_, _))
+            | (ParamValue::Num(_), ParamValue::Num(_))
+            | (ParamValue::FilePath(_), ParamValue::FilePath(_))
+            | (ParamValue::RamMode(_), ParamValue::RamMode(_))
+            | (ParamValue::RstRegPriority(_), ParamValue::RstRegPriority(_))
+            | (ParamValue::SimDevice(_), ParamValue::SimDevice(_))
+            | (ParamValue::WriteMode(_), ParamValue::WriteMode(_)) => true,
+            (_, _) => false,
+        }
+    }
+}
+
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamValue::CollisionCheck(v) => write!(f, "{}", v),
+            ParamValue::CollisionConfig(v) => write!(f, "{}", v),
+            ParamValue::Bool(v) | ParamValue::BoolNum(v) => write!(f, "{}", v),
+            ParamValue::Bytes(w, v) => write!(f, "width:{} values:{:?}", w, v),
+            ParamValue::Num(v) => write!(f, "{}", v),
+            ParamValue::FilePath(v) => write!(f, "{}", v),
+            ParamValue::RamMode(v) => write!(f, "{}", v),
+            ParamValue::RstRegPriority(v) => write!(f, "{}", v),
+            ParamValue::SimDevice(v) => write!(f, "{}", v),
+            ParamValue::WriteMode(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+const PARAM: [(&str, ParamValue); 27] = [
+    ("DOA_REG", ParamValue::BoolNum(false)),
+    ("DOB_REG", ParamValue::BoolNum(false)),
+    ("INIT_A", ParamValue::Bytes(18, vec![])),
+    ("INIT_B", ParamValue::Bytes(18, vec![])),
+    ("INIT_FILE", ParamValue::FilePath(FilePath::None)),
+    ("IS_CLKARDCLK_INVERTED", ParamValue::Bool(false)),
+    ("IS_CLKBWRCLK_INVERTED", ParamValue::Bool(false)),
+    ("IS_ENARDEN_INVERTED", ParamValue::Bool(false)),
+    ("IS_ENBWREN_INVERTED", ParamValue::Bool(false)),
+    ("IS_RSTRAMARSTRAM_INVERTED", ParamValue::Bool(false)),
+    ("IS_RSTRAMB_INVERTED", ParamValue::Bool(false)),
+    ("IS_RSTREGARSTREG_INVERTED", ParamValue::Bool(false)),
+    ("IS_RSTREGB_INVERTED", ParamValue::Bool(false)),
+    ("RAM_MODE", ParamValue::RamMode(RamMode::Tdp)),
+    (
+        "RDADDR_COLLISION_HWCONFIG",
+        ParamValue::CollisionConfig(CollisionConfig::DelayedWrite),
+    ),
+    ("READ_WIDTH_A", ParamValue::Num(0)),
+    ("READ_WIDTH_B", ParamValue::Num(0)),
+    ("WRITE_WIDTH_A", ParamValue::Num(0)),
+    ("WRITE_WIDTH_B", ParamValue::Num(0)),
+    (
+        "RSTREG_PRIORITY_A",
+        ParamValue::RstRegPriority(RstRegPriority::RstReg),
+    ),
+    (
+        "RSTREG_PRIORITY_B",
+        ParamValue::RstRegPriority(RstRegPriority::RstReg),
+    ),
+    (
+        "SIM_COLLISION_CHECK",
+        ParamValue::CollisionCheck(CollisionCheck::All),
+    ),
+    ("SIM_DEVICE", ParamValue::SimDevice(SimDevice::Series7)),
+    ("SRVAL_A", ParamValue::Num(0)),
+    ("SRVAL_B", ParamValue::Num(0)),
+    ("WRITE_MODE_A", ParamValue::WriteMode(WriteMode::NoChange)),
+    ("WRITE_MODE_B", ParamValue::WriteMode(WriteMode::NoChange)),
+];
+
+impl ToPrim<ParamValue> for BramPrim {
+    fn to_name(&self) -> String {
+        String::from("RAMB18E1")
+    }
+    fn to_param(&self) -> ParamSet<ParamValue> {
+        let mut param = ParamSet::new();
+        for p in &PARAM {
+            param.insert(Param {
+                name: p.0.into(),
+                value: p.1.clone(),
+            });
+        }
+        for i in 0..8 {
+            let name = format!("INITP_{:02X}", i);
+            param.insert(Param {
+                name,
+                value: (256, vec![0; 32]).into(),
+            });
+        }
+        for i in 0..64 {
+            let name = format!("INIT_{:02X}", i);
+            param.insert(Param {
+                name,
+                value: (256, vec![0; 32]).into(),
+            });
+        }
+        param
+    }
+    fn to_input(&self) -> PortSet {
+        let mut port = PortSet::new();
+        port.insert(Port::new("ADDRARDADDR", 14));
+        port.insert(Port::new("CLKARDCLK", 1));
+        port.insert(Port::new("ENARDEN", 1));
+        port.insert(Port::new("REGCEAREGCE", 1));
+        port.insert(Port::new("RSTRAMARSTRAM", 1));
+        port.insert(Port::new("RSTREGARSTREG", 1));
+        port.insert(Port::new("WEA", 2));
+        port.insert(Port::new("DIADI", 16));
+        port.insert(Port::new("DIPADIP", 2));
+        port.insert(Port::new("ADDRBWRADDR", 14));
+        port.insert(Port::new("CLKBWRCLK", 1));
+        port.insert(Port::new("ENBWREN", 1));
+        port.insert(Port::new("REGCEB", 1));
+        port.insert(Port::new("RSTRAMB", 1));
+        port.insert(Port::new("RSTREGB", 1));
+        port.insert(Port::new("WEBWE", 4));
+        port.insert(Port::new("DIBDI", 16));
+        port.insert(Port::new("DIPBDIP", 2));
+        port
+    }
+    fn to_output(&self) -> PortSet {
+        let mut port = PortSet::new();
+        port.insert(Port::new("DOADO", 16));
+        port.insert(Port::new("DOPADOP", 2));
+        port.insert(Port::new("DOBDO", 16));
+        port.insert(Port::new("DOPBDOP", 2));
+        port
+    }
+}
+
+impl Default for Bram {
+    fn default() -> Self {
+        let ram = BramPrim;
+        Bram(ram.to_prim())
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{ParamSet, Port, PortSet, Prim, ToPrim};
+use derive_more::{Deref, DerefMut};
+use std::fmt;
+
+// CARRY4 does not have parameters
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParamValue;
+
+#[derive(Clone, Debug, Deref, DerefMut)]
+pub struct Carry(Prim<ParamValue>);
+
+#[derive(Clone, Debug, Default)]
+struct CarryPrim;
+
+impl fmt::Display for ParamValue {
+    fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Ok(())
+    }
+}
+
+impl ToPrim<ParamValue> for CarryPrim {
+    fn to_name(&self) -> String {
+        String::from("CARRY4")
+    }
+    fn to_param(&self) -> ParamSet<ParamValue> {
+        ParamSet::new()
+    }
+    fn to_input(&self) -> PortSet {
+        let mut port = PortSet::new();
+        port.insert(Port::new("DI", 4));
+        port.insert(Port::new("S", 4));
+        port.insert(Port::new("CI", 1));
+        port.insert(Port::new("CYINIT", 1));
+        port
+    }
+    fn to_output(&self) -> PortSet {
+        let mut port = PortSet::new();
+        port.insert(Port::new("O", 4));
+        port.insert(Port::new("CO", 4));
+        port
+    }
+}
+
+impl Default for Carry {
+    fn default() -> Carry {
+        let carry = CarryPrim;
+        Carry(carry.to_prim())
+    }
+}
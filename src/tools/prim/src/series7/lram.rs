@@ -0,0 +1,61 @@
+use crate::{Param, ParamSet, Port, PortSet, Prim, ToPrim};
+use derive_more::{Deref, DerefMut};
+
+// same parameter values as the UltraScale RAM64M8, with half the luts
+pub use crate::ultrascale::lram::ParamValue;
+
+#[derive(Clone, Debug, Deref, DerefMut)]
+pub struct Lram(Prim<ParamValue>);
+
+#[derive(Clone, Debug, Default)]
+struct LramPrim;
+
+const LUT: [&str; 4] = ["A", "B", "C", "D"];
+
+impl ToPrim<ParamValue> for LramPrim {
+    fn to_name(&self) -> String {
+        String::from("RAM64M")
+    }
+    fn to_param(&self) -> ParamSet<ParamValue> {
+        let mut param = ParamSet::new();
+        for l in &LUT {
+            let name = format!("INIT_{}", l);
+            param.insert(Param {
+                name,
+                value: (64, vec![0; 8]).into(),
+            });
+        }
+        param.insert(Param {
+            name: "IS_WCLK_INVERTED".into(),
+            value: false.into(),
+        });
+        param
+    }
+    fn to_input(&self) -> PortSet {
+        let mut port = PortSet::new();
+        for l in &LUT {
+            let din = format!("DI{}", l);
+            let addr = format!("ADDR{}", l);
+            port.insert(Port::new(&din, 1));
+            port.insert(Port::new(&addr, 6));
+        }
+        port.insert(Port::new("WE", 1));
+        port.insert(Port::new("WCLK", 1));
+        port
+    }
+    fn to_output(&self) -> PortSet {
+        let mut port = PortSet::new();
+        for l in &LUT {
+            let dout = format!("DO{}", l);
+            port.insert(Port::new(&dout, 1));
+        }
+        port
+    }
+}
+
+impl Default for Lram {
+    fn default() -> Self {
+        let ram = LramPrim;
+        Lram(ram.to_prim())
+    }
+}
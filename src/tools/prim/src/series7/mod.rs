@@ -0,0 +1,4 @@
+pub mod bram;
+pub mod carry;
+pub mod dsp;
+pub mod lram;
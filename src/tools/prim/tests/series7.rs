@@ -0,0 +1,155 @@
+use prim::{Param, ParamSet, PortSet, Prim};
+use std::fmt;
+
+fn test_name<T: Eq + fmt::Debug + fmt::Display>(prim: &Prim<T>, exp: &str) {
+    let res = prim.name();
+    assert_eq!(res, exp);
+}
+
+fn test_param<T: Eq + fmt::Debug + fmt::Display>(prim: &Prim<T>, exp: &ParamSet<T>) {
+    let res = prim.param();
+    let inter = res.symmetric_difference(exp);
+    assert_eq!(inter.count(), 0);
+}
+
+fn test_input<T: Eq + fmt::Debug + fmt::Display>(prim: &Prim<T>, exp: &[(&str, u32)]) {
+    let res = prim.input();
+    let exp = PortSet::from(exp);
+    assert_eq!(*res, exp);
+}
+
+fn test_output<T: Eq + fmt::Debug + fmt::Display>(prim: &Prim<T>, exp: &[(&str, u32)]) {
+    let res = prim.output();
+    let exp = PortSet::from(exp);
+    assert_eq!(*res, exp);
+}
+
+mod test_carry {
+    use super::*;
+    use prim::series7::carry::{Carry, ParamValue};
+
+    #[test]
+    fn name() {
+        let prim = Carry::default();
+        test_name(&prim, "CARRY4");
+    }
+
+    #[test]
+    fn param() {
+        let prim = Carry::default();
+        test_param(&prim, &ParamSet::<ParamValue>::new());
+    }
+
+    #[test]
+    fn input() {
+        let prim = Carry::default();
+        let input = [("DI", 4), ("S", 4), ("CI", 1), ("CYINIT", 1)];
+        test_input(&prim, &input);
+    }
+
+    #[test]
+    fn output() {
+        let prim = Carry::default();
+        let output = [("O", 4), ("CO", 4)];
+        test_output(&prim, &output);
+    }
+}
+
+mod test_bram {
+    use super::*;
+    use prim::series7::bram::*;
+
+    #[test]
+    fn name() {
+        let prim = Bram::default();
+        test_name(&prim, "RAMB18E1");
+    }
+
+    #[test]
+    fn param() {
+        let prim = Bram::default();
+        let res = prim.param();
+        assert_eq!(res.len(), 27 + 8 + 64);
+        let mut param = ParamSet::<ParamValue>::new();
+        param.insert(Param {
+            name: "SIM_DEVICE".into(),
+            value: SimDevice::Series7.into(),
+        });
+        param.insert(Param {
+            name: "RAM_MODE".into(),
+            value: RamMode::Tdp.into(),
+        });
+        assert!(param.is_subset(res));
+        assert!(!res.iter().any(|p| p.name().starts_with("CASCADE")));
+    }
+
+    const INPUT: [(&str, u32); 18] = [
+        ("ADDRARDADDR", 14),
+        ("CLKARDCLK", 1),
+        ("ENARDEN", 1),
+        ("REGCEAREGCE", 1),
+        ("RSTRAMARSTRAM", 1),
+        ("RSTREGARSTREG", 1),
+        ("WEA", 2),
+        ("DIADI", 16),
+        ("DIPADIP", 2),
+        ("ADDRBWRADDR", 14),
+        ("CLKBWRCLK", 1),
+        ("ENBWREN", 1),
+        ("REGCEB", 1),
+        ("RSTRAMB", 1),
+        ("RSTREGB", 1),
+        ("WEBWE", 4),
+        ("DIBDI", 16),
+        ("DIPBDIP", 2),
+    ];
+
+    #[test]
+    fn input() {
+        let prim = Bram::default();
+        test_input(&prim, &INPUT);
+    }
+
+    #[test]
+    fn output() {
+        let prim = Bram::default();
+        let output = [("DOADO", 16), ("DOPADOP", 2), ("DOBDO", 16), ("DOPBDOP", 2)];
+        test_output(&prim, &output);
+    }
+}
+
+mod test_lram {
+    use super::*;
+    use prim::series7::lram::*;
+
+    #[test]
+    fn name() {
+        let prim = Lram::default();
+        test_name(&prim, "RAM64M");
+    }
+
+    #[test]
+    fn input() {
+        let prim = Lram::default();
+        let input = [
+            ("DIA", 1),
+            ("DIB", 1),
+            ("DIC", 1),
+            ("DID", 1),
+            ("ADDRA", 6),
+            ("ADDRB", 6),
+            ("ADDRC", 6),
+            ("ADDRD", 6),
+            ("WE", 1),
+            ("WCLK", 1),
+        ];
+        test_input(&prim, &input);
+    }
+
+    #[test]
+    fn output() {
+        let prim = Lram::default();
+        let output = [("DOA", 1), ("DOB", 1), ("DOC", 1), ("DOD", 1)];
+        test_output(&prim, &output);
+    }
+}
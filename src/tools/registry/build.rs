@@ -11,11 +11,20 @@ fn build_pattern(prim: &str) {
     pat.serialize_to_file(bin_path);
 }
 
-fn build_implementation(prim: &str) {
+// families other than ultrascale have their implementations in a subdirectory
+fn build_implementation(family: Option<&str>, prim: &str) {
     use xim::parser::Parser;
     let out_dir = env::var("OUT_DIR").unwrap();
-    let imp_path = format!("../../../examples/xim/{}.xim", prim);
-    let bin_name = format!("{}_xim.bin", prim);
+    let (imp_path, bin_name) = match family {
+        Some(family) => (
+            format!("../../../examples/xim/{}/{}.xim", family, prim),
+            format!("{}_{}_xim.bin", family, prim),
+        ),
+        None => (
+            format!("../../../examples/xim/{}.xim", prim),
+            format!("{}_xim.bin", prim),
+        ),
+    };
     let imp = Parser::parse_from_file(imp_path).unwrap();
     let bin_path = Path::new(&out_dir).join(bin_name);
     imp.serialize_to_file(bin_path);
@@ -24,8 +33,13 @@ fn build_implementation(prim: &str) {
 fn build(prim: &str) {
     println!("cargo:rerun-if-changed=../../../examples/pat/{}.pat", prim);
     println!("cargo:rerun-if-changed=../../../examples/xim/{}.xim", prim);
+    println!(
+        "cargo:rerun-if-changed=../../../examples/xim/7series/{}.xim",
+        prim
+    );
     build_pattern(prim);
-    build_implementation(prim);
+    build_implementation(None, prim);
+    build_implementation(Some("7series"), prim);
 }
 
 fn main() {
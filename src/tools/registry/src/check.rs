@@ -0,0 +1,281 @@
+use crate::{pat_from_op, Target};
+use ::ir::interp::{Env, Interp as IrInterp, Value};
+use ::ir::mono::Subst;
+use ::pat::mono::mono_try_from_pat;
+use ::xim::mono::mono_try_from_imp;
+use ::xir::interp::Interp as XirInterp;
+use ir::ast as ir;
+use pat::ast as pat;
+use std::collections::BTreeSet;
+use xim::ast as xim;
+use xir::ast as xir;
+
+// inputs up to this many bits are simulated exhaustively
+const EXHAUSTIVE_BITS: u64 = 12;
+
+// number of random input vectors for wider inputs
+const RANDOM_CYCLES: usize = 256;
+
+// generic patterns are checked at these widths, which cover a single bit,
+// partial slices and chains of slices
+const SPEC_WIDTHS: [i64; 4] = [1, 8, 12, 20];
+
+// signature of a pattern or implementation, as a list of typed terms
+fn terms(expr: &ir::Expr) -> Vec<ir::ExprTerm> {
+    expr.clone().into()
+}
+
+fn check_terms(name: &str, kind: &str, pat: &ir::Expr, imp: &ir::Expr) -> Vec<String> {
+    let pat = terms(pat);
+    let imp = terms(imp);
+    if pat.len() != imp.len() {
+        return vec![format!(
+            "{}: pattern has {} {}s but implementation has {}",
+            name,
+            pat.len(),
+            kind,
+            imp.len()
+        )];
+    }
+    pat.iter()
+        .zip(imp.iter())
+        .filter(|(p, i)| p.ty() != i.ty())
+        .map(|(p, i)| {
+            format!(
+                "{}: {} {} in the pattern does not match {} in the implementation",
+                name, kind, p, i
+            )
+        })
+        .collect()
+}
+
+// outputs must be produced with the type given in the signature
+fn check_body(name: &str, imp: &xim::Imp) -> Vec<String> {
+    let mut res = Vec::new();
+    for out in terms(imp.output()) {
+        for instr in imp.body() {
+            for dst in terms(instr.dst()) {
+                if dst.id() == out.id() && dst.ty() != out.ty() {
+                    res.push(format!(
+                        "{}: output {} is produced as {} in the implementation body",
+                        name, out, dst
+                    ));
+                }
+            }
+        }
+    }
+    res
+}
+
+fn ir_prog_from_pat(pat: &pat::Pat) -> ir::Prog {
+    let body: Vec<ir::Instr> = pat
+        .body()
+        .iter()
+        .map(|instr| match instr {
+            pat::Instr::Wire(wire) => ir::Instr::from(wire.clone()),
+            pat::Instr::Prim(prim) => ir::Instr::from(prim.clone()),
+        })
+        .collect();
+    let mut def = ir::Def::default();
+    def.set_sig(pat.sig().clone());
+    def.set_body(body);
+    let mut prog = ir::Prog::default();
+    prog.insert("main", def);
+    prog
+}
+
+fn xir_prog_from_imp(imp: &xim::Imp) -> xir::Prog {
+    let sig = ir::Sig {
+        id: imp.id(),
+        input: imp.input().clone(),
+        output: imp.output().clone(),
+        span: imp.sig().span().clone(),
+    };
+    let mut prog = xir::Prog::default();
+    prog.set_sig(sig);
+    prog.set_body(imp.body().clone());
+    prog
+}
+
+fn mask(width: u64) -> u128 {
+    if width >= 128 {
+        u128::MAX
+    } else {
+        (1 << width) - 1
+    }
+}
+
+// every lane of every input gets the value returned for its width
+fn env_from_lanes<F: FnMut(u64) -> u128>(input: &[ir::ExprTerm], mut lane: F) -> Env {
+    let mut env = Env::new();
+    for term in input {
+        if let (Some(id), Some(ty), Some(width)) = (term.id(), term.ty(), term.width()) {
+            let lanes: Vec<i128> = (0..term.length().unwrap_or(1))
+                .map(|_| lane(width) as i128)
+                .collect();
+            env.insert(id, Value::new(ty, &lanes));
+        }
+    }
+    env
+}
+
+// exhaustive for narrow inputs, otherwise a fixed pseudo-random sequence
+fn input_from_terms(input: &[ir::ExprTerm]) -> Vec<Env> {
+    let total: u64 = input
+        .iter()
+        .map(|t| t.width().unwrap_or(0) * t.length().unwrap_or(1))
+        .sum();
+    if total <= EXHAUSTIVE_BITS {
+        (0..1_u128 << total)
+            .map(|mut bits| {
+                env_from_lanes(input, |width| {
+                    let lane = bits & mask(width);
+                    bits >>= width;
+                    lane
+                })
+            })
+            .collect()
+    } else {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            state
+        };
+        (0..RANDOM_CYCLES)
+            .map(|_| {
+                env_from_lanes(input, |width| {
+                    let bits = (u128::from(next()) << 64) | u128::from(next());
+                    bits & mask(width)
+                })
+            })
+            .collect()
+    }
+}
+
+fn show_env(term: &[ir::ExprTerm], env: &Env) -> String {
+    term.iter()
+        .filter_map(|t| t.id())
+        .map(|id| match env.get(&id) {
+            Some(Value::Scalar(v)) => format!("{}={}", id, v),
+            Some(Value::Vector(v)) => format!("{}={:?}", id, v),
+            None => format!("{}=?", id),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// simulate both sides with the same inputs, which are matched by position
+fn check_sim(pat: &pat::Pat, imp: &xim::Imp) -> Result<(), String> {
+    let pat_input = terms(pat.input());
+    let pat_output = terms(pat.output());
+    let imp_input = terms(imp.input());
+    let imp_output = terms(imp.output());
+    // patterns with parameters or values wider than 128 bits are only checked by signature
+    let simple = |t: &ir::ExprTerm| matches!(t.width(), Some(w) if w <= 128);
+    if !pat_input.iter().chain(pat_output.iter()).all(simple) {
+        return Ok(());
+    }
+    let pat_err = |err: ::ir::errors::Error| format!("pattern cannot be simulated, {}", err);
+    let imp_err =
+        |err: ::xir::errors::Error| format!("implementation cannot be simulated, {}", err);
+    let mut ir_interp = IrInterp::try_from_prog(&ir_prog_from_pat(pat)).map_err(pat_err)?;
+    let mut xir_interp = XirInterp::try_from_prog(&xir_prog_from_imp(imp)).map_err(imp_err)?;
+    for (cycle, input) in input_from_terms(&pat_input).iter().enumerate() {
+        let mut imp_env = Env::new();
+        for (p, i) in pat_input.iter().zip(imp_input.iter()) {
+            if let (Some(value), Some(id)) = (p.id().and_then(|id| input.get(&id)), i.id()) {
+                imp_env.insert(id, value.clone());
+            }
+        }
+        let exp = ir_interp.step(input).map_err(pat_err)?;
+        let res = xir_interp.step(&imp_env).map_err(imp_err)?;
+        for (p, i) in pat_output.iter().zip(imp_output.iter()) {
+            let ty = p.ty().unwrap();
+            let lanes = |env: &Env, t: &ir::ExprTerm| {
+                let value = t.id().and_then(|id| env.get(&id).cloned());
+                value.map(|v| Value::new(ty, &v.lanes()))
+            };
+            if lanes(&exp, p) != lanes(&res, i) {
+                return Err(format!(
+                    "cycle {} with {} gives {} in the pattern but {} in the implementation",
+                    cycle,
+                    show_env(&pat_input, input),
+                    show_env(std::slice::from_ref(p), &exp),
+                    show_env(std::slice::from_ref(i), &res)
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_pair(name: &str, pat: &pat::Pat, imp: &xim::Imp) -> Vec<String> {
+    let mut res = check_terms(name, "input", pat.input(), imp.input());
+    res.extend(check_terms(name, "output", pat.output(), imp.output()));
+    res.extend(check_body(name, imp));
+    // empty implementations are placeholders that are tiled from narrower ones
+    if res.is_empty() && !imp.body().is_empty() {
+        if let Err(err) = check_sim(pat, imp) {
+            res.push(format!("{}: {}", name, err));
+        }
+    }
+    res
+}
+
+// every parameter takes the same value, checking stops at the first width with problems
+fn check_spec(name: &str, pat: &pat::Pat, imp: &xim::Imp) -> Vec<String> {
+    if pat.param() != imp.param() {
+        return vec![format!(
+            "{}: pattern parameters {} do not match implementation parameters {}",
+            name,
+            pat.param().join(", "),
+            imp.param().join(", ")
+        )];
+    }
+    for width in SPEC_WIDTHS.iter() {
+        let map: Subst = pat
+            .param()
+            .iter()
+            .map(|id| (id.to_string(), *width))
+            .collect();
+        let spec = format!("{}<{}>", name, width);
+        let res = match (
+            mono_try_from_pat(pat, &map, &spec),
+            mono_try_from_imp(imp, &map, &spec),
+        ) {
+            (Ok(p), Ok(i)) => check_pair(&spec, &p, &i),
+            (Err(err), _) => vec![format!("{}: pattern cannot be specialized, {}", spec, err)],
+            (_, Err(err)) => vec![format!(
+                "{}: implementation cannot be specialized, {}",
+                spec, err
+            )],
+        };
+        if !res.is_empty() {
+            return res;
+        }
+    }
+    Vec::new()
+}
+
+// every pattern must have an implementation with the same signature that
+// computes the same function, problems are returned sorted by name
+pub fn check_target(target: &Target) -> Vec<String> {
+    let pat = target.pat_map();
+    let imp = target.imp_target();
+    let names: BTreeSet<&String> = pat.keys().chain(imp.imp().keys()).collect();
+    let mut res = Vec::new();
+    for name in names {
+        match (pat_from_op(&pat, name), imp.get(name)) {
+            (Some(p), Some(i)) if p.is_param() || i.is_param() => {
+                res.extend(check_spec(name, p, i))
+            }
+            (Some(p), Some(i)) => res.extend(check_pair(name, p, i)),
+            (Some(_), None) => res.push(format!("{}: pattern has no implementation", name)),
+            (None, Some(_)) => res.push(format!("{}: implementation has no pattern", name)),
+            (None, None) => (),
+        }
+    }
+    res
+}
@@ -0,0 +1,58 @@
+use ir::errors::Error as IrError;
+use pat::errors::Error as PatError;
+use std::fmt;
+use std::io::Error as IoError;
+use xim::errors::Error as XimError;
+
+// pattern and implementation errors carry their parse errors, they are boxed
+// so results returning this error stay small
+#[derive(Debug)]
+pub enum Error {
+    Pat(Box<PatError>),
+    Xim(Box<XimError>),
+    Ir(IrError),
+    Io(IoError),
+    Registry(String),
+}
+
+impl Error {
+    pub fn new_registry_error(msg: &str) -> Self {
+        Error::Registry(msg.to_string())
+    }
+}
+
+impl From<PatError> for Error {
+    fn from(e: PatError) -> Self {
+        Error::Pat(Box::new(e))
+    }
+}
+
+impl From<XimError> for Error {
+    fn from(e: XimError) -> Self {
+        Error::Xim(Box::new(e))
+    }
+}
+
+impl From<IrError> for Error {
+    fn from(e: IrError) -> Self {
+        Error::Ir(e)
+    }
+}
+
+impl From<IoError> for Error {
+    fn from(e: IoError) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Pat(msg) => write!(f, "{}", msg),
+            Error::Xim(msg) => write!(f, "{}", msg),
+            Error::Ir(msg) => write!(f, "{}", msg),
+            Error::Io(msg) => write!(f, "{}", msg),
+            Error::Registry(msg) => write!(f, "{}", msg),
+        }
+    }
+}
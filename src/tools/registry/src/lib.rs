@@ -0,0 +1,176 @@
+pub mod check;
+pub mod errors;
+pub mod spec;
+
+use crate::errors::Error;
+use ::pat::parser::Parser as PatParser;
+use ::xim::parser::Parser as XimParser;
+use pat::ast as pat;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+use xim::ast as xim;
+use xir::ast::Family;
+
+// every target has a pattern file and an implementation file per primitive
+pub const PRIMS: [&str; 3] = ["lut", "dsp", "mem"];
+
+// cascaded ops only change how values are routed between primitives, they
+// compute the same function as the op they were derived from
+const CASCADE: [&str; 3] = ["cio", "ci", "co"];
+
+// compiled into the binary, so it does not depend on the directory it was built in
+const BUILTIN_PAT: [&[u8]; 3] = [
+    include_bytes!(concat!(env!("OUT_DIR"), "/lut_pat.bin")),
+    include_bytes!(concat!(env!("OUT_DIR"), "/dsp_pat.bin")),
+    include_bytes!(concat!(env!("OUT_DIR"), "/mem_pat.bin")),
+];
+
+const BUILTIN_IMP: [&[u8]; 3] = [
+    include_bytes!(concat!(env!("OUT_DIR"), "/lut_xim.bin")),
+    include_bytes!(concat!(env!("OUT_DIR"), "/dsp_xim.bin")),
+    include_bytes!(concat!(env!("OUT_DIR"), "/mem_xim.bin")),
+];
+
+// 7-series implementations share the patterns above
+const BUILTIN_IMP_7SERIES: [&[u8]; 3] = [
+    include_bytes!(concat!(env!("OUT_DIR"), "/7series_lut_xim.bin")),
+    include_bytes!(concat!(env!("OUT_DIR"), "/7series_dsp_xim.bin")),
+    include_bytes!(concat!(env!("OUT_DIR"), "/7series_mem_xim.bin")),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Target {
+    pat: HashMap<String, pat::Target>,
+    imp: HashMap<String, xim::Target>,
+}
+
+// built-in target, from examples/pat and examples/xim
+impl Default for Target {
+    fn default() -> Self {
+        Target::from_family(Family::default())
+    }
+}
+
+// primitive a target file is for and whether it has patterns or
+// implementations, files that are neither are skipped
+fn prim_from_path(path: &Path) -> Result<Option<(String, bool)>, Error> {
+    let ext = path.extension().and_then(OsStr::to_str);
+    if !matches!(ext, Some("pat") | Some("xim")) {
+        return Ok(None);
+    }
+    let prim = path.file_stem().and_then(OsStr::to_str).unwrap_or_default();
+    if PRIMS.contains(&prim) {
+        Ok(Some((prim.to_string(), ext == Some("pat"))))
+    } else {
+        let err = format!(
+            "{} does not name a primitive, expected one of {}",
+            path.display(),
+            PRIMS.join(", ")
+        );
+        Err(Error::new_registry_error(&err))
+    }
+}
+
+impl Target {
+    // built-in target of a device family
+    pub fn from_family(family: Family) -> Self {
+        let builtin_imp = match family {
+            Family::UltraScale => BUILTIN_IMP,
+            Family::Series7 => BUILTIN_IMP_7SERIES,
+        };
+        let mut target = Target {
+            pat: HashMap::new(),
+            imp: HashMap::new(),
+        };
+        for (i, prim) in PRIMS.iter().enumerate() {
+            let pat = pat::Target::deserialize_from_slice(BUILTIN_PAT[i]);
+            let imp = xim::Target::deserialize_from_slice(builtin_imp[i]);
+            target.pat.insert(prim.to_string(), pat);
+            target.imp.insert(prim.to_string(), imp);
+        }
+        target
+    }
+    // lut.pat, dsp.xim, ... in the directory replace the built-in files
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self, Error> {
+        Target::from_dir_with_family(dir, Family::default())
+    }
+    pub fn from_dir_with_family<P: AsRef<Path>>(dir: P, family: Family) -> Result<Self, Error> {
+        let dir = dir.as_ref();
+        if !dir.is_dir() {
+            let err = format!("target directory {} does not exist", dir.display());
+            return Err(Error::new_registry_error(&err));
+        }
+        let mut target = Target::from_family(family);
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            match prim_from_path(&path)? {
+                Some((prim, true)) => {
+                    let pat = PatParser::parse_from_file(&path)?;
+                    target.pat.insert(prim, pat);
+                }
+                Some((prim, false)) => {
+                    let imp = XimParser::parse_from_file(&path)?;
+                    target.imp.insert(prim, imp);
+                }
+                None => (),
+            }
+        }
+        Ok(target)
+    }
+    // same as from_dir with the files given by name and contents, i.e. ("lut.pat", "pat ...")
+    pub fn from_strs(files: &[(&str, &str)]) -> Result<Self, Error> {
+        let mut target = Target::default();
+        for (name, contents) in files {
+            match prim_from_path(Path::new(name))? {
+                Some((prim, true)) => {
+                    let pat = PatParser::parse_from_str(contents)?;
+                    target.pat.insert(prim, pat);
+                }
+                Some((prim, false)) => {
+                    let imp = XimParser::parse_from_str(contents)?;
+                    target.imp.insert(prim, imp);
+                }
+                None => (),
+            }
+        }
+        Ok(target)
+    }
+    // prim must be one of PRIMS
+    pub fn pat(&self, prim: &str) -> &pat::Target {
+        &self.pat[prim]
+    }
+    pub fn imp(&self, prim: &str) -> &xim::Target {
+        &self.imp[prim]
+    }
+    // patterns of every primitive, by name
+    pub fn pat_map(&self) -> HashMap<String, pat::Pat> {
+        let mut map: HashMap<String, pat::Pat> = HashMap::new();
+        for prim in PRIMS.iter() {
+            map.extend(self.pat(prim).pat().clone());
+        }
+        map
+    }
+    // implementations of every primitive in a single target
+    pub fn imp_target(&self) -> xim::Target {
+        let mut target = xim::Target::default();
+        for prim in PRIMS.iter() {
+            target.extend(self.imp(prim).clone());
+        }
+        target
+    }
+}
+
+// pattern of an op, cascaded ops use the pattern of the op they were derived from
+pub fn pat_from_op<'a>(map: &'a HashMap<String, pat::Pat>, op: &str) -> Option<&'a pat::Pat> {
+    if let Some(pat) = map.get(op) {
+        return Some(pat);
+    }
+    let idx = op.find('_')?;
+    let (name, ty) = op.split_at(idx);
+    CASCADE.iter().find_map(|suffix| {
+        let base = name.strip_suffix(suffix)?;
+        map.get(&format!("{}{}", base, ty))
+    })
+}
@@ -0,0 +1,84 @@
+use crate::errors::Error;
+use crate::{Target, PRIMS};
+use ::ir::mono::{unify, Subst};
+use ::pat::mono::mono_try_from_pat;
+use ::xim::mono::mono_try_from_imp;
+use ir::ast as ir;
+use pat::ast as pat;
+use xim::ast as xim;
+
+// specializations are named after the type they produce, as hand-written
+// patterns are, i.e. ladd_i12 or vadd_i8v4
+pub fn spec_name(name: &str, ty: &ir::Ty) -> String {
+    let ty = ty.to_string().replace('<', "v").replace('>', "");
+    format!("{}_{}", name, ty)
+}
+
+// parameters are inferred from the type of the first output
+fn subst_from_ty(pat: &pat::Pat, ty: &ir::Ty) -> Result<Option<Subst>, Error> {
+    let output: Vec<ir::ExprTerm> = pat.output().clone().into();
+    let mut map = Subst::new();
+    if let Some(param) = output.first().and_then(|t| t.ty()) {
+        unify(param, ty, &mut map, &pat.id())?;
+    }
+    if pat.param().iter().all(|id| map.contains_key(id)) {
+        Ok(Some(map))
+    } else {
+        Ok(None)
+    }
+}
+
+// generic patterns and implementations are specialized for every type in dst.
+// Patterns that are written for a type take precedence over specializations,
+// and generic ones are left out, because they can not be selected
+pub fn spec_try_from_dsts<'a, I>(target: &Target, dst: I) -> Result<Target, Error>
+where
+    I: IntoIterator<Item = &'a ir::Expr>,
+{
+    let mut ty: Vec<ir::Ty> = Vec::new();
+    for expr in dst {
+        let term: Vec<ir::ExprTerm> = expr.clone().into();
+        for t in term.iter().filter_map(|t| t.ty()) {
+            if !ty.contains(t) {
+                ty.push(t.clone());
+            }
+        }
+    }
+    let written = target.pat_map();
+    let mut res = target.clone();
+    for prim in PRIMS.iter() {
+        let mut pat = pat::Target::default();
+        let mut imp = xim::Target::default();
+        for (name, p) in target.pat(prim).pat().iter().filter(|(_, p)| !p.is_param()) {
+            pat.insert(name, p.clone());
+        }
+        for (name, i) in target.imp(prim).imp().iter().filter(|(_, i)| !i.is_param()) {
+            imp.insert(name, i.clone());
+        }
+        for (name, p) in target.pat(prim).pat().iter().filter(|(_, p)| p.is_param()) {
+            let i = match target.imp(prim).get(name) {
+                Some(i) if i.param() == p.param() => i,
+                _ => {
+                    let err = format!(
+                        "generic pattern {} has no implementation with the same parameters",
+                        name
+                    );
+                    return Err(Error::new_registry_error(&err));
+                }
+            };
+            for t in &ty {
+                let spec = spec_name(name, t);
+                if written.contains_key(&spec) {
+                    continue;
+                }
+                if let Some(map) = subst_from_ty(p, t)? {
+                    pat.insert(&spec, mono_try_from_pat(p, &map, &spec)?);
+                    imp.insert(&spec, mono_try_from_imp(i, &map, &spec)?);
+                }
+            }
+        }
+        res.pat.insert(prim.to_string(), pat);
+        res.imp.insert(prim.to_string(), imp);
+    }
+    Ok(res)
+}
@@ -0,0 +1,77 @@
+use registry::check::check_target;
+use registry::errors::Error;
+use registry::Target;
+use xir::ast::Family;
+
+const LUT_PAT: &str = "pat leq_bool(a:bool, b:bool) -> (y:bool) {
+    y:bool = eq(a, b) @lut;
+}
+
+pat lnot_bool(a:bool) -> (y:bool) {
+    y:bool = not(a) @lut;
+}
+
+pat lnot<W>(a:i<W>) -> (y:i<W>) {
+    y:i<W> = not(a) @lut;
+}
+
+pat lrega_i2(a:i2, en:bool) -> (y:i2) {
+    y:i2 = reg[0](a, en) @lut;
+}
+
+pat lxor_i2(a:i2, b:i2) -> (y:i2) {
+    y:i2 = xor(a, b) @lut;
+}";
+
+const LUT_XIM: &str = "imp leq_bool[1, 2](a:bool, b:bool) -> (y:bool) {
+    y:bool = lut2[8](a, b) @a6(??, ??);
+}
+
+imp lrega_i2[1, 2](a:i2, en:bool) -> (y:i2) {
+    t0:bool = ext[0](a);
+    t1:bool = ext[1](a);
+    t2:bool = fdre(t0, en) @a(??, ??);
+    t3:bool = fdre(t1, en) @b(??, ??);
+    y:i2 = cat(t2, t3);
+}
+
+imp lnot<W>[1, 2](a:i<W>) -> (y:i<W>) {
+    for i in 0..W {
+        a{i}:bool = ext[{i}](a);
+        y{i}:bool = lut1[2](a{i}) @a6(??, ??);
+    }
+    y:i<W> = cat(for i in 0..W { y{i} });
+}
+
+imp lxor_i2[1, 2](a:i1, b:i2) -> (y:i2) {}
+
+imp land_bool[1, 2](a:bool, b:bool) -> (y:bool) {
+    y:bool = lut2[8](a, b) @a6(??, ??);
+}";
+
+#[test]
+fn builtin() {
+    let res = check_target(&Target::default());
+    assert!(res.is_empty(), "{}", res.join("\n"));
+}
+
+#[test]
+fn builtin_7series() {
+    let res = check_target(&Target::from_family(Family::Series7));
+    assert!(res.is_empty(), "{}", res.join("\n"));
+}
+
+#[test]
+fn broken() -> Result<(), Error> {
+    let target = Target::from_strs(&[("lut.pat", LUT_PAT), ("lut.xim", LUT_XIM)])?;
+    let res = check_target(&target);
+    let exp = [
+        "land_bool: implementation has no pattern",
+        "leq_bool: cycle 0 with a=0, b=0 gives y=1 in the pattern but y=0 in the implementation",
+        "lnot<1>: cycle 0 with a=0 gives y=-1 in the pattern but y=0 in the implementation",
+        "lnot_bool: pattern has no implementation",
+        "lxor_i2: input a:i2 in the pattern does not match a:i1 in the implementation",
+    ];
+    assert_eq!(res, exp);
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use registry::errors::Error;
+use registry::spec::spec_try_from_dsts;
+use registry::Target;
+use xir::ast::Family;
+
+fn dst(text: &str) -> ir::ast::Expr {
+    let prog = ir::parser::Parser::parse_from_str(text).unwrap();
+    prog.get("main").unwrap().output().clone()
+}
+
+#[test]
+fn spec_widths() -> Result<(), Error> {
+    let output = dst("def main(a:i12, b:i8) -> (y:i12, z:i8) {
+        y:i12 = id(a);
+        z:i8 = id(b);
+    }");
+    let target = spec_try_from_dsts(&Target::default(), vec![&output])?;
+    let pat = target.pat_map();
+    // i8 has hand-written patterns, so only i12 is specialized
+    assert!(pat.contains_key("ladd_i12"));
+    assert!(pat.contains_key("lrega_i12"));
+    assert!(pat.contains_key("ladd_i8"));
+    assert!(!pat.contains_key("ladd"));
+    let imp = target.imp_target();
+    let ladd = imp.get("ladd_i12").unwrap();
+    assert!(!ladd.is_param());
+    let carry = ladd
+        .body()
+        .iter()
+        .filter(|i| i.to_string().contains("carryadd"));
+    assert_eq!(carry.count(), 2);
+    // the area grows with the width, a slice holds 8 bits or 4 on 7-series
+    assert_eq!(ladd.area(), 2);
+    let series7 = Target::from_family(Family::Series7);
+    let spec = spec_try_from_dsts(&series7, vec![&output])?.imp_target();
+    assert_eq!(spec.get("ladd_i12").map(|i| i.area()), Some(3));
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use registry::errors::Error;
+use registry::Target;
+use xir::ast::Family;
+
+const LUT_PAT: &str = "pat ladd_i8(a:i8, b:i8) -> (y:i8) {
+    y:i8 = add(a, b) @lut;
+}";
+
+fn is_registry_error(res: Result<Target, Error>, msg: &str) -> bool {
+    match res {
+        Err(Error::Registry(err)) => err.contains(msg),
+        _ => false,
+    }
+}
+
+#[test]
+fn builtin() {
+    let target = Target::default();
+    assert!(target.pat("lut").get("lxor_i8").is_some());
+    assert!(target.imp("dsp").get("daddrega_i8v4").is_some());
+    assert!(target.pat_map().contains_key("lxor_i8"));
+    assert!(target.imp_target().get("daddrega_i8v4").is_some());
+}
+
+#[test]
+fn family() {
+    let ultrascale = Target::from_family(Family::UltraScale);
+    let series7 = Target::from_family(Family::Series7);
+    assert_eq!(ultrascale, Target::default());
+    assert_eq!(series7.pat_map(), ultrascale.pat_map());
+    assert_eq!(series7.imp("lut").get("ladd_i8").map(|i| i.area()), Some(2));
+    assert_eq!(
+        ultrascale.imp("lut").get("ladd_i8").map(|i| i.area()),
+        Some(1)
+    );
+}
+
+#[test]
+fn from_dir() -> Result<(), Error> {
+    let target = Target::from_dir("../../../examples/pat")?;
+    assert_eq!(target, Target::default());
+    Ok(())
+}
+
+#[test]
+fn from_strs() -> Result<(), Error> {
+    let target = Target::from_strs(&[("lut.pat", LUT_PAT), ("README", "notes")])?;
+    let builtin = Target::default();
+    assert_eq!(target.pat("lut").pat().len(), 1);
+    assert_eq!(target.imp("lut"), builtin.imp("lut"));
+    assert_eq!(target.pat("dsp"), builtin.pat("dsp"));
+    Ok(())
+}
+
+#[test]
+fn unknown_prim() {
+    let res = Target::from_strs(&[("slice.pat", LUT_PAT)]);
+    assert!(is_registry_error(res, "does not name a primitive"));
+}
+
+#[test]
+fn missing_dir() {
+    let res = Target::from_dir("../../../examples/missing");
+    assert!(is_registry_error(res, "does not exist"));
+}
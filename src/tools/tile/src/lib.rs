@@ -1,6 +1,9 @@
-use asm::ast::{Expr, ExprTerm, ExprTup, Instr, InstrAsm, InstrWire, Loc, OpAsm, OpWire, Prog, Ty};
+use asm::ast::{
+    Expr, ExprTerm, ExprTup, Instr, InstrAsm, InstrWire, Loc, OpAsm, OpWire, Prog, Span, Ty,
+};
 use asm::parser::Parser;
 use regex::Regex;
+use registry::Target;
 use std::path::Path;
 
 #[derive(Clone, Debug)]
@@ -103,10 +106,11 @@ fn emit_ext(instr: &InstrAsm, dst: Expr, arg: usize, start: i64, end: i64) -> In
         dst,
         attr,
         arg,
+        span: instr.span().clone(),
     })
 }
 
-fn emit_cat(dst: Expr, arg: &[String]) -> Instr {
+fn emit_cat(dst: Expr, arg: &[String], span: &Span) -> Instr {
     let mut arg_term: Vec<ExprTerm> = Vec::new();
     for a in arg {
         arg_term.push(emit_term(a, 8));
@@ -116,10 +120,11 @@ fn emit_cat(dst: Expr, arg: &[String]) -> Instr {
         dst,
         attr: Expr::default(),
         arg: Expr::from(ExprTup::from(arg_term)),
+        span: span.clone(),
     })
 }
 
-fn emit_op(op: &str, dst: &str, arg: &[String], loc: &Loc) -> Instr {
+fn emit_op(op: &str, dst: &str, arg: &[String], loc: &Loc, span: &Span) -> Instr {
     let op_asm: OpAsm = op.to_string().into();
     let dst = emit_expr(dst, 8);
     let mut arg_term: Vec<ExprTerm> = Vec::new();
@@ -129,12 +134,14 @@ fn emit_op(op: &str, dst: &str, arg: &[String], loc: &Loc) -> Instr {
     Instr::from(InstrAsm {
         op: op_asm,
         dst,
+        attr: Expr::default(),
         arg: Expr::from(ExprTup::from(arg_term)),
         loc: loc.clone(),
+        span: span.clone(),
     })
 }
 
-fn emit_op_mux(op: &str, dst: &str, arg: &[String], loc: &Loc) -> Instr {
+fn emit_op_mux(op: &str, dst: &str, arg: &[String], loc: &Loc, span: &Span) -> Instr {
     let op_asm: OpAsm = op.to_string().into();
     let dst = emit_expr(dst, 8);
     let mut arg_term: Vec<ExprTerm> = Vec::new();
@@ -148,12 +155,62 @@ fn emit_op_mux(op: &str, dst: &str, arg: &[String], loc: &Loc) -> Instr {
     Instr::from(InstrAsm {
         op: op_asm,
         dst,
+        attr: Expr::default(),
         arg: Expr::from(ExprTup::from(arg_term)),
         loc: loc.clone(),
+        span: span.clone(),
     })
 }
 
-pub fn tile_from_prog(input: &Prog) -> Prog {
+// registered ops in a clock domain other than the default one carry its clock last
+fn clock_term(instr: &InstrAsm) -> Option<ExprTerm> {
+    let arg: Vec<ExprTerm> = instr.arg().clone().into();
+    arg.into_iter()
+        .last()
+        .filter(|t| t.ty().map_or(false, |ty| ty.is_clock()))
+}
+
+fn add_clock(instr: Instr, clock: &Option<ExprTerm>) -> Instr {
+    match (instr, clock) {
+        (Instr::Asm(mut asm), Some(term)) => {
+            let mut arg: Vec<ExprTerm> = asm.arg.into();
+            arg.push(term.clone());
+            asm.arg = Expr::from(ExprTup::from(arg));
+            Instr::from(asm)
+        }
+        (instr, _) => instr,
+    }
+}
+
+// the init and reset values of a registered op are sliced with its data, 8 bits per slice
+fn slice_attr(instr: Instr, attr: &Expr, slice: i64) -> Instr {
+    match instr {
+        Instr::Asm(mut asm) => {
+            let term: Vec<ExprTerm> = attr.clone().into();
+            let term: Vec<ExprTerm> = term
+                .iter()
+                .map(|t| match t {
+                    ExprTerm::Val(v) => {
+                        ExprTerm::Val(((i128::from(*v) >> (8 * slice)) & 0xff) as i64)
+                    }
+                    _ => t.clone(),
+                })
+                .collect();
+            asm.attr = Expr::from(ExprTup::from(term));
+            Instr::from(asm)
+        }
+        instr => instr,
+    }
+}
+
+// wide ops without an implementation of their own are built out of slices
+fn is_tiled(target: &Target, op: &str, slice: &str) -> bool {
+    let imp = target.imp("lut");
+    let wide = imp.get(op).map_or(true, |i| i.body().is_empty());
+    wide && imp.get(slice).is_some()
+}
+
+pub fn tile_from_prog_with_target(input: &Prog, target: &Target) -> Prog {
     let mut body: Vec<Instr> = Vec::new();
     let max = get_max(input.body());
     let init = if max < 0 { 0_u64 } else { (max + 1) as u64 };
@@ -162,8 +219,9 @@ pub fn tile_from_prog(input: &Prog) -> Prog {
         match instr {
             Instr::Wire(_) => body.push(instr.clone()),
             Instr::Asm(asm) => {
-                match asm.op().to_string().as_str() {
-                    "lxor_i128" | "lxor_i32" => {
+                let op = asm.op().to_string();
+                match op.as_str() {
+                    "lxor_i128" | "lxor_i32" if is_tiled(target, &op, "lxor_i8") => {
                         let num = (asm.dst().get_ty(0).unwrap().width().unwrap() / 8) as i64;
                         let mut cat: Vec<String> = Vec::new();
                         for i in 0..num {
@@ -177,13 +235,13 @@ pub fn tile_from_prog(input: &Prog) -> Prog {
                             }
                             let name = namer.next_name();
                             cat.push(name.clone());
-                            let new = emit_op("lxor_i8", &name, &arg, asm.loc());
+                            let new = emit_op("lxor_i8", &name, &arg, asm.loc(), asm.span());
                             body.push(new);
                         }
-                        let new = emit_cat(asm.dst().clone(), &cat);
+                        let new = emit_cat(asm.dst().clone(), &cat, asm.span());
                         body.push(new);
                     }
-                    "lmux_i128" => {
+                    "lmux_i128" if is_tiled(target, &op, "lmux_i8") => {
                         let num = (asm.dst().get_ty(0).unwrap().width().unwrap() / 8) as i64;
                         let mut cat: Vec<String> = Vec::new();
                         for i in 0..num {
@@ -199,13 +257,14 @@ pub fn tile_from_prog(input: &Prog) -> Prog {
                             }
                             let name = namer.next_name();
                             cat.push(name.clone());
-                            let new = emit_op_mux("lmux_i8", &name, &arg, asm.loc());
+                            let new = emit_op_mux("lmux_i8", &name, &arg, asm.loc(), asm.span());
                             body.push(new);
                         }
-                        let new = emit_cat(asm.dst().clone(), &cat);
+                        let new = emit_cat(asm.dst().clone(), &cat, asm.span());
                         body.push(new);
                     }
-                    "lmuxrega_i128" => {
+                    "lmuxrega_i128" if is_tiled(target, &op, "lmuxrega_i8") => {
+                        let clock = clock_term(asm);
                         let num = (asm.dst().get_ty(0).unwrap().width().unwrap() / 8) as i64;
                         let mut cat: Vec<String> = Vec::new();
                         for i in 0..num {
@@ -223,10 +282,12 @@ pub fn tile_from_prog(input: &Prog) -> Prog {
                             arg.push(e);
                             let name = namer.next_name();
                             cat.push(name.clone());
-                            let new = emit_op_mux("lmuxrega_i8", &name, &arg, asm.loc());
-                            body.push(new);
+                            let new =
+                                emit_op_mux("lmuxrega_i8", &name, &arg, asm.loc(), asm.span());
+                            let new = slice_attr(new, asm.attr(), i);
+                            body.push(add_clock(new, &clock));
                         }
-                        let new = emit_cat(asm.dst().clone(), &cat);
+                        let new = emit_cat(asm.dst().clone(), &cat, asm.span());
                         body.push(new);
                     }
                     _ => body.push(instr.clone()),
@@ -240,6 +301,10 @@ pub fn tile_from_prog(input: &Prog) -> Prog {
     prog
 }
 
+pub fn tile_from_prog(input: &Prog) -> Prog {
+    tile_from_prog_with_target(input, &Target::default())
+}
+
 pub fn tile_from_file<P: AsRef<Path>>(path: P) -> Prog {
     let parsed = Parser::parse_from_file(path).unwrap();
     tile_from_prog(&parsed)
@@ -1,15 +1,38 @@
 use crate::errors::Error;
-use crate::opt::{Lang, Opt};
+use crate::opt::{CheckOpt, Command, Lang, Opt, Stage};
+use asm::dot::dot_from_prog as asm_to_dot;
 use asm::parser::Parser as AsmParser;
-use bler::try_from_asm_prog as asm_try_into_xir;
+use bler::try_from_asm_prog_with_target as asm_try_into_xir;
+use bline::hier_try_from_ir_prog as ir_try_into_hier_behav;
+use bline::reset::Reset;
 use bline::try_from_ir_prog as ir_try_into_behav;
 use io::write_to_file;
+use ir::check::type_check_prog;
+use ir::clock::cdc_check_prog;
+use ir::cse::cse_try_from_prog;
+use ir::dce::dce_try_from_prog;
+use ir::dot::dot_from_prog as ir_to_dot;
+use ir::fold::fold_try_from_prog;
+use ir::hier::{def_order_try_from_def, split_calls_from_def};
+use ir::inline::inline_try_from_prog;
+use ir::mono::mono_try_from_prog;
+use ir::narrow::narrow_try_from_prog;
 use ir::parser::Parser as IrParser;
-use isel::try_from_ir_prog as ir_try_into_asm;
-use std::path::PathBuf;
+use ir::pipe::pipe_try_from_prog;
+use ir::yosys::prog_try_from_file as yosys_try_into_ir;
+use isel::cost::{cost_try_from_asm_prog, CostModel};
+use isel::tree::dot::dot_from_trees as tree_to_dot;
+use isel::tree_try_from_ir_def_with_model as ir_def_try_into_tree;
+use isel::try_from_ir_def_with_model as ir_def_try_into_asm;
+use registry::check::check_target;
+use registry::Target;
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
+use xir::ast::Family;
+use xir::dot::dot_from_prog as xir_to_dot;
 use xir::parser::Parser as XirParser;
-use xpand::try_from_xir_prog as xir_try_into_struct;
+use xpand::hier_try_from_xir_prog_with_family as xir_try_into_hier_struct;
+use xpand::try_from_xir_prog_with_family as xir_try_into_struct;
 
 #[derive(Clone, Debug)]
 pub struct Driver {
@@ -32,6 +55,175 @@ fn write_output(path: Option<&PathBuf>, contents: &str) {
     }
 }
 
+// yosys netlists are imported as ir
+fn lang_from(opts: &Opt) -> &Lang {
+    if opts.is_yosys() {
+        &Lang::Ir
+    } else {
+        opts.from()
+    }
+}
+
+fn parse_ir_from_file(opts: &Opt, inline: bool) -> Result<ir::ast::Prog, Error> {
+    let prog = if opts.is_yosys() {
+        yosys_try_into_ir(opts.input())?
+    } else {
+        IrParser::parse_from_file(opts.input())?
+    };
+    let prog = mono_try_from_prog(&prog)?;
+    type_check_prog(&prog)?;
+    cdc_check_prog(&prog)?;
+    let prog = if inline {
+        inline_try_from_prog(&prog)?
+    } else {
+        prog
+    };
+    let prog = fold_try_from_prog(&prog)?;
+    let (prog, removed) = cse_try_from_prog(&prog)?;
+    if opts.stats() {
+        eprintln!("instructions removed by cse: {}", removed);
+    }
+    let prog = if let Some(pipe) = opts.pipe() {
+        let (prog, latency) = pipe_try_from_prog(&prog, &pipe)?;
+        let mut name: Vec<&String> = latency.keys().collect();
+        name.sort();
+        for n in name {
+            eprintln!("latency of {}: {}", n, latency[n]);
+        }
+        // inserted registers must keep types and clock domains consistent
+        type_check_prog(&prog)?;
+        cdc_check_prog(&prog)?;
+        prog
+    } else {
+        prog
+    };
+    let prog = if opts.narrow() {
+        narrow_try_from_prog(&prog)?
+    } else {
+        prog
+    };
+    Ok(dce_try_from_prog(&prog)?)
+}
+
+// only behav and struct output and ir graphs can keep a def per module, others are inlined
+fn is_hier_supported(opts: &Opt) -> bool {
+    matches!(
+        (lang_from(opts), opts.to(), opts.stage()),
+        (Lang::Ir, Lang::Behav, _) | (Lang::Ir, Lang::Struct, _) | (Lang::Ir, Lang::Dot, Stage::Ir)
+    )
+}
+
+// built-in patterns and implementations of the family unless a target directory is given
+fn target_from(dir: Option<&PathBuf>, family: Family) -> Result<Target, Error> {
+    if let Some(dir) = dir {
+        Ok(Target::from_dir_with_family(dir, family)?)
+    } else {
+        Ok(Target::from_family(family))
+    }
+}
+
+// with stats, selection is followed by the estimated cost of the instructions
+fn ir_def_try_into_asm_with_cost(
+    name: &str,
+    def: &ir::ast::Def,
+    target: &Target,
+    model: &CostModel,
+    stats: bool,
+) -> Result<asm::ast::Prog, Error> {
+    let asm = ir_def_try_into_asm(def, target, model)?;
+    if stats {
+        let cost = cost_try_from_asm_prog(&asm, target, model)?;
+        eprintln!("{} cost of {}: {}", model.objective(), name, cost);
+    }
+    Ok(asm)
+}
+
+fn ir_try_into_asm(
+    prog: &ir::ast::Prog,
+    target: &Target,
+    model: &CostModel,
+    stats: bool,
+) -> Result<asm::ast::Prog, Error> {
+    if let Some(main) = prog.get("main") {
+        ir_def_try_into_asm_with_cost("main", main, target, model, stats)
+    } else {
+        Err(Error::new_driver_error("main is not present"))
+    }
+}
+
+// every def is compiled on its own and calls become module instances
+fn ir_try_into_hier_struct(
+    prog: &ir::ast::Prog,
+    mmap: Option<&mmap::Mmap>,
+    reset: &Reset,
+    family: Family,
+    target: &Target,
+    model: &CostModel,
+    stats: bool,
+) -> Result<Vec<String>, Error> {
+    let mut module: Vec<String> = Vec::new();
+    for name in def_order_try_from_def(prog, "main")? {
+        if let Some(def) = prog.get(&name) {
+            let (body, call) = split_calls_from_def(def)?;
+            let mut callee: Vec<(ir::ast::InstrCall, ir::ast::Sig)> = Vec::new();
+            for instr in call {
+                if let Some(d) = prog.get(&instr.op().op()) {
+                    let sig = d.sig().clone();
+                    callee.push((instr, sig));
+                }
+            }
+            let asm = ir_def_try_into_asm_with_cost(&name, &body, target, model, stats)?;
+            let (xir, mem) = asm_try_into_xir(&asm, mmap, target)?;
+            let sct =
+                xir_try_into_hier_struct(&xir, def.sig(), &callee, mem.as_ref(), reset, family)?;
+            module.push(sct.to_string());
+        }
+    }
+    Ok(module)
+}
+
+// dataflow graph of a program at the stage selected in the options
+fn dot_from_file(opts: &Opt, target: &Target, model: &CostModel) -> Result<String, Error> {
+    let input = opts.input();
+    match (lang_from(opts), opts.stage()) {
+        (Lang::Ir, Stage::Ir) => Ok(ir_to_dot(&parse_ir_from_file(opts, !opts.hier())?)),
+        (Lang::Ir, Stage::Tree) => {
+            let ir = parse_ir_from_file(opts, true)?;
+            if let Some(main) = ir.get("main") {
+                Ok(tree_to_dot(&ir_def_try_into_tree(main, target, model)?))
+            } else {
+                Err(Error::new_driver_error("main is not present"))
+            }
+        }
+        (Lang::Ir, Stage::Asm) => {
+            let ir = parse_ir_from_file(opts, true)?;
+            Ok(asm_to_dot(&ir_try_into_asm(
+                &ir,
+                target,
+                model,
+                opts.stats(),
+            )?))
+        }
+        (Lang::Ir, Stage::Xir) => {
+            let ir = parse_ir_from_file(opts, true)?;
+            let asm = ir_try_into_asm(&ir, target, model, opts.stats())?;
+            let (xir, _) = asm_try_into_xir(&asm, None, target)?;
+            Ok(xir_to_dot(&xir))
+        }
+        (Lang::Asm, Stage::Asm) => Ok(asm_to_dot(&AsmParser::parse_from_file(input)?)),
+        (Lang::Asm, Stage::Xir) => {
+            let prog = AsmParser::parse_from_file(input)?;
+            let (xir, _) = asm_try_into_xir(&prog, None, target)?;
+            Ok(xir_to_dot(&xir))
+        }
+        (Lang::Xir, Stage::Xir) => Ok(xir_to_dot(&XirParser::parse_from_file(input)?)),
+        (from, stage) => {
+            let err = format!("Unsupported dot stage {} from {}", stage, from);
+            Err(Error::new_driver_error(&err))
+        }
+    }
+}
+
 impl Driver {
     pub fn new(opts: Opt) -> Driver {
         Driver { opts }
@@ -40,68 +232,115 @@ impl Driver {
         &self.opts
     }
     pub fn run(&self) -> Result<(), Error> {
+        if let Some(Command::CheckTarget(opts)) = self.opts().cmd() {
+            return Checker::new(opts.clone()).run();
+        }
         let input = self.opts().input();
         let output = self.opts().output();
-        match (self.opts().from(), self.opts().to(), self.opts().mmap()) {
+        let hier = self.opts().hier();
+        if hier && !is_hier_supported(self.opts()) {
+            let err = format!(
+                "--hier is not supported from {} to {}",
+                lang_from(self.opts()),
+                self.opts().to()
+            );
+            return Err(Error::new_driver_error(&err));
+        }
+        let reset = self.opts().reset();
+        let family = self.opts().family();
+        let target = &target_from(self.opts().target_dir(), family)?;
+        let model = &self.opts().cost_model()?;
+        let stats = self.opts().stats();
+        match (lang_from(self.opts()), self.opts().to(), self.opts().mmap()) {
+            (_, Lang::Dot, _) => {
+                write_output(output, &dot_from_file(self.opts(), target, model)?);
+                Ok(())
+            }
+            (Lang::Ir, Lang::Behav, path) if hier => {
+                let ir = parse_ir_from_file(self.opts(), false)?;
+                let mmap = path.map(mmap::Mmap::from_file);
+                let module: Vec<String> = ir_try_into_hier_behav(&ir, mmap.as_ref(), &reset)?
+                    .iter()
+                    .map(|m| m.to_string())
+                    .collect();
+                write_output(output, &module.join("\n"));
+                Ok(())
+            }
+            (Lang::Ir, Lang::Struct, path) if hier => {
+                let ir = parse_ir_from_file(self.opts(), false)?;
+                let mmap = path.map(mmap::Mmap::from_file);
+                let module = ir_try_into_hier_struct(
+                    &ir,
+                    mmap.as_ref(),
+                    &reset,
+                    family,
+                    target,
+                    model,
+                    stats,
+                )?;
+                write_output(output, &module.join("\n"));
+                Ok(())
+            }
             (Lang::Ir, Lang::Asm, _) => {
-                let ir = IrParser::parse_from_file(input)?;
-                let asm = ir_try_into_asm(&ir)?;
+                let ir = parse_ir_from_file(self.opts(), true)?;
+                let asm = ir_try_into_asm(&ir, target, model, stats)?;
                 write_output(output, &asm.to_string());
                 Ok(())
             }
             (Lang::Ir, Lang::Xir, _) => {
-                let ir = IrParser::parse_from_file(input)?;
-                let asm = ir_try_into_asm(&ir)?;
-                let (xir, _) = asm_try_into_xir(&asm, None)?;
+                let ir = parse_ir_from_file(self.opts(), true)?;
+                let asm = ir_try_into_asm(&ir, target, model, stats)?;
+                let (xir, _) = asm_try_into_xir(&asm, None, target)?;
                 write_output(output, &xir.to_string());
                 Ok(())
             }
-            (Lang::Ir, Lang::Behav, _) => {
-                let ir = IrParser::parse_from_file(input)?;
-                let behav_prog = ir_try_into_behav(&ir)?;
+            (Lang::Ir, Lang::Behav, path) => {
+                let ir = parse_ir_from_file(self.opts(), true)?;
+                let mmap = path.map(mmap::Mmap::from_file);
+                let behav_prog = ir_try_into_behav(&ir, mmap.as_ref(), &reset)?;
                 write_output(output, &behav_prog.to_string());
                 Ok(())
             }
             (Lang::Asm, Lang::Xir, _) => {
                 let prog = AsmParser::parse_from_file(input)?;
-                let (xir, _) = asm_try_into_xir(&prog, None)?;
+                let (xir, _) = asm_try_into_xir(&prog, None, target)?;
                 write_output(output, &xir.to_string());
                 Ok(())
             }
             (Lang::Asm, Lang::Struct, _) => {
                 let prog = AsmParser::parse_from_file(input)?;
-                let (xir, _) = asm_try_into_xir(&prog, None)?;
-                let sct = xir_try_into_struct(&xir, None)?;
+                let (xir, _) = asm_try_into_xir(&prog, None, target)?;
+                let sct = xir_try_into_struct(&xir, None, &reset, family)?;
                 write_output(output, &sct.to_string());
                 Ok(())
             }
             (Lang::Ir, Lang::Struct, None) => {
-                let ir = IrParser::parse_from_file(input)?;
-                let asm = ir_try_into_asm(&ir)?;
-                let (xir, _) = asm_try_into_xir(&asm, None)?;
-                let sct = xir_try_into_struct(&xir, None)?;
+                let ir = parse_ir_from_file(self.opts(), true)?;
+                let asm = ir_try_into_asm(&ir, target, model, stats)?;
+                let (xir, _) = asm_try_into_xir(&asm, None, target)?;
+                let sct = xir_try_into_struct(&xir, None, &reset, family)?;
                 write_output(output, &sct.to_string());
                 Ok(())
             }
             (Lang::Xir, Lang::Struct, None) => {
                 let prog = XirParser::parse_from_file(input)?;
-                let sct = xir_try_into_struct(&prog, None)?;
+                let sct = xir_try_into_struct(&prog, None, &reset, family)?;
                 write_output(output, &sct.to_string());
                 Ok(())
             }
             (Lang::Ir, Lang::Struct, Some(path)) => {
-                let ir = IrParser::parse_from_file(input)?;
-                let asm = ir_try_into_asm(&ir)?;
+                let ir = parse_ir_from_file(self.opts(), true)?;
+                let asm = ir_try_into_asm(&ir, target, model, stats)?;
                 let mmap = Some(mmap::Mmap::from_file(path));
-                let (xir, mmap) = asm_try_into_xir(&asm, mmap.as_ref())?;
-                let sct = xir_try_into_struct(&xir, mmap.as_ref())?;
+                let (xir, mmap) = asm_try_into_xir(&asm, mmap.as_ref(), target)?;
+                let sct = xir_try_into_struct(&xir, mmap.as_ref(), &reset, family)?;
                 write_output(output, &sct.to_string());
                 Ok(())
             }
             (Lang::Xir, Lang::Struct, Some(path)) => {
                 let prog = XirParser::parse_from_file(input)?;
                 let mmap = Some(mmap::Mmap::from_file(path));
-                let sct = xir_try_into_struct(&prog, mmap.as_ref())?;
+                let sct = xir_try_into_struct(&prog, mmap.as_ref(), &reset, family)?;
                 write_output(output, &sct.to_string());
                 Ok(())
             }
@@ -109,3 +348,31 @@ impl Driver {
         }
     }
 }
+
+#[derive(Clone, Debug)]
+pub struct Checker {
+    pub opts: CheckOpt,
+}
+
+impl Checker {
+    pub fn new(opts: CheckOpt) -> Checker {
+        Checker { opts }
+    }
+    pub fn opts(&self) -> &CheckOpt {
+        &self.opts
+    }
+    // print every mismatch between patterns and implementations
+    pub fn run(&self) -> Result<(), Error> {
+        let target = target_from(self.opts().target_dir(), self.opts().family())?;
+        let problems = check_target(&target);
+        for problem in &problems {
+            println!("{}", problem);
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            let err = format!("target check found {} problems", problems.len());
+            Err(Error::new_driver_error(&err))
+        }
+    }
+}
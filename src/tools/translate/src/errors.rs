@@ -3,6 +3,7 @@ use bler::errors::Error as BlerError;
 use bline::errors::Error as BlineError;
 use ir::errors::Error as IrError;
 use isel::errors::Error as ISelError;
+use registry::errors::Error as RegistryError;
 use std::fmt;
 use xir::errors::Error as XirError;
 use xpand::errors::Error as XpandError;
@@ -18,6 +19,7 @@ pub enum Error {
     Bler(BlerError),
     Bline(BlineError),
     Xpand(XpandError),
+    Registry(RegistryError),
 }
 
 impl Error {
@@ -71,6 +73,12 @@ impl From<XpandError> for Error {
     }
 }
 
+impl From<RegistryError> for Error {
+    fn from(e: RegistryError) -> Self {
+        Error::Registry(e)
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -83,6 +91,7 @@ impl fmt::Display for Error {
             Error::Opt(msg) => write!(f, "{}", msg),
             Error::Driver(msg) => write!(f, "{}", msg),
             Error::Xpand(msg) => write!(f, "{}", msg),
+            Error::Registry(msg) => write!(f, "{}", msg),
         }
     }
 }
@@ -1,19 +1,29 @@
 use crate::errors::Error;
+use bline::reset::Reset;
+use ir::pipe::Pipe;
+use isel::cost::{CostModel, Objective};
 use std::fmt;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use structopt::clap::{AppSettings, ArgSettings};
 use structopt::StructOpt;
+use xir::ast::Family;
 
 #[derive(Clone, Debug, StructOpt)]
 #[structopt(
     name = env!("CARGO_PKG_NAME"),
     version = env!("CARGO_PKG_VERSION"),
-    author = env!("CARGO_PKG_AUTHORS")
+    author = env!("CARGO_PKG_AUTHORS"),
+    settings = &[AppSettings::SubcommandsNegateReqs, AppSettings::ArgsNegateSubcommands]
 )]
 pub struct Opt {
-    // Input file
-    #[structopt(parse(from_os_str))]
-    pub input: PathBuf,
+    // Mode other than translating an input file
+    #[structopt(subcommand)]
+    pub cmd: Option<Command>,
+
+    // Input file, required unless a mode is given
+    #[structopt(parse(from_os_str), set = ArgSettings::Required)]
+    pub input: Option<PathBuf>,
 
     // Mmap file
     #[structopt(long = "mmap", parse(from_os_str))]
@@ -30,11 +40,63 @@ pub struct Opt {
     // To language
     #[structopt(long = "to", default_value = "asm")]
     pub to: Lang,
+
+    // Keep every def as its own module instead of inlining calls
+    #[structopt(long = "hier")]
+    pub hier: bool,
+
+    // Narrow values to the bits they need before instruction selection
+    #[structopt(long = "narrow")]
+    pub narrow: bool,
+
+    // Print statistics of the ir passes, such as instructions removed by cse and the selection cost
+    #[structopt(long = "stats")]
+    pub stats: bool,
+
+    // Pipeline to a max number of operations per stage
+    #[structopt(long = "pipe-depth", conflicts_with = "pipe-stages")]
+    pub pipe_depth: Option<u64>,
+
+    // Pipeline the critical path into a number of register stages
+    #[structopt(long = "pipe-stages")]
+    pub pipe_stages: Option<u64>,
+
+    // Stage rendered by --to dot, defaults to the input language
+    #[structopt(long = "stage")]
+    pub stage: Option<Stage>,
+
+    // Directory with lut, dsp and mem .pat and .xim files replacing the built-in ones
+    #[structopt(long = "target-dir", parse(from_os_str))]
+    pub target_dir: Option<PathBuf>,
+
+    // Device family the target is implemented with: ultrascale or 7series
+    #[structopt(long = "family", default_value = "ultrascale")]
+    pub family: Family,
+
+    // Register reset: sync, async or none, with an optional -low suffix
+    #[structopt(long = "reset", default_value = "sync")]
+    pub reset: Reset,
+
+    // Instruction selection minimizes latency, area or weighted:latency,area
+    #[structopt(long = "cost", default_value = "latency")]
+    pub cost: Objective,
+
+    // Area weight of a resource, e.g. dsp=4,lut=1
+    #[structopt(long = "weight", use_delimiter = true)]
+    pub weight: Vec<String>,
+
+    // Order in which lut, dsp and mem patterns are tried
+    #[structopt(long = "order", use_delimiter = true, default_value = "dsp,lut,mem")]
+    pub order: Vec<String>,
 }
 
 impl Opt {
+    pub fn cmd(&self) -> Option<&Command> {
+        self.cmd.as_ref()
+    }
+    // parsing only leaves the input out when a mode is given
     pub fn input(&self) -> &Path {
-        &self.input
+        self.input.as_deref().unwrap_or_else(|| Path::new(""))
     }
     pub fn mmap(&self) -> Option<&PathBuf> {
         self.mmap.as_ref()
@@ -42,31 +104,114 @@ impl Opt {
     pub fn output(&self) -> Option<&PathBuf> {
         self.output.as_ref()
     }
+    pub fn target_dir(&self) -> Option<&PathBuf> {
+        self.target_dir.as_ref()
+    }
     pub fn from(&self) -> &Lang {
         &self.from
     }
     pub fn to(&self) -> &Lang {
         &self.to
     }
+    pub fn is_yosys(&self) -> bool {
+        matches!(self.from, Lang::Yosys)
+    }
+    pub fn hier(&self) -> bool {
+        self.hier
+    }
+    pub fn narrow(&self) -> bool {
+        self.narrow
+    }
+    pub fn stats(&self) -> bool {
+        self.stats
+    }
+    pub fn family(&self) -> Family {
+        self.family
+    }
+    pub fn reset(&self) -> Reset {
+        self.reset
+    }
+    pub fn stage(&self) -> Stage {
+        match (&self.stage, &self.from) {
+            (Some(stage), _) => stage.clone(),
+            (None, Lang::Asm) => Stage::Asm,
+            (None, Lang::Xir) => Stage::Xir,
+            (None, _) => Stage::Ir,
+        }
+    }
+    pub fn cost_model(&self) -> Result<CostModel, Error> {
+        let mut model = CostModel::new(self.cost);
+        for weight in &self.weight {
+            model.set_weight_from_str(weight)?;
+        }
+        model.set_order(&self.order)?;
+        Ok(model)
+    }
+    pub fn pipe(&self) -> Option<Pipe> {
+        match (self.pipe_depth, self.pipe_stages) {
+            (Some(depth), _) => Some(Pipe::Depth(depth)),
+            (_, Some(stages)) => Some(Pipe::Stages(stages)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub enum Command {
+    // Compare the patterns of a target with their implementations
+    #[structopt(name = "check-target")]
+    CheckTarget(CheckOpt),
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct CheckOpt {
+    // Directory with lut, dsp and mem .pat and .xim files replacing the built-in ones
+    #[structopt(long = "target-dir", parse(from_os_str))]
+    pub target_dir: Option<PathBuf>,
+
+    // Device family the target is implemented with: ultrascale or 7series
+    #[structopt(long = "family", default_value = "ultrascale")]
+    pub family: Family,
+}
+
+impl CheckOpt {
+    pub fn target_dir(&self) -> Option<&PathBuf> {
+        self.target_dir.as_ref()
+    }
+    pub fn family(&self) -> Family {
+        self.family
+    }
 }
 
 #[derive(Clone, Debug)]
 pub enum Lang {
     Ir,
+    Yosys,
     Asm,
     Xir,
     Behav,
     Struct,
+    Dot,
+}
+
+#[derive(Clone, Debug)]
+pub enum Stage {
+    Ir,
+    Tree,
+    Asm,
+    Xir,
 }
 
 impl fmt::Display for Lang {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let backend = match self {
             Lang::Ir => "ir",
+            Lang::Yosys => "yosys-json",
             Lang::Asm => "asm",
             Lang::Xir => "xir",
             Lang::Behav => "behav",
             Lang::Struct => "struct",
+            Lang::Dot => "dot",
         };
         write!(f, "{}", backend)
     }
@@ -77,11 +222,38 @@ impl FromStr for Lang {
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         match input {
             "ir" => Ok(Lang::Ir),
+            "yosys-json" => Ok(Lang::Yosys),
             "asm" => Ok(Lang::Asm),
             "xir" => Ok(Lang::Xir),
             "behav" => Ok(Lang::Behav),
             "struct" => Ok(Lang::Struct),
+            "dot" => Ok(Lang::Dot),
             _ => Err(Error::new_opt_error("Unsupported language")),
         }
     }
 }
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stage = match self {
+            Stage::Ir => "ir",
+            Stage::Tree => "tree",
+            Stage::Asm => "asm",
+            Stage::Xir => "xir",
+        };
+        write!(f, "{}", stage)
+    }
+}
+
+impl FromStr for Stage {
+    type Err = Error;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "ir" => Ok(Stage::Ir),
+            "tree" => Ok(Stage::Tree),
+            "asm" => Ok(Stage::Asm),
+            "xir" => Ok(Stage::Xir),
+            _ => Err(Error::new_opt_error("Unsupported stage")),
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use bler::try_from_asm_prog;
+use bline::reset::Reset;
+use ir::parser::Parser as IrParser;
+use isel::try_from_ir_prog;
+use translate::errors::Error;
+use xir::ast::OpMach;
+use xpand::try_from_xir_prog;
+
+#[test]
+fn reg_init_value() -> Result<(), Error> {
+    let parsed = IrParser::parse_from_str(
+        r#"def main(a:i8, en:bool) -> (y:i8) {
+            y:i8 = reg[0, 3](a, en);
+        }"#,
+    )?;
+    let asm = try_from_ir_prog(&parsed)?;
+    let (xir, _) = try_from_asm_prog(&asm, None)?;
+    let mut init: Vec<i64> = Vec::new();
+    let mut value: Vec<i64> = Vec::new();
+    for instr in xir.body() {
+        if let Some(mach) = instr.mach().filter(|m| m.op() == &OpMach::Fdre) {
+            init.push(mach.attr().get_val(0)?);
+            value.push(mach.attr().get_val(1)?);
+        }
+    }
+    assert_eq!(init, vec![0; 8]);
+    assert_eq!(value, vec![1, 1, 0, 0, 0, 0, 0, 0]);
+    // bits that reset to one are set instead of reset
+    let res = try_from_xir_prog(&xir, None, &Reset::default())?.to_string();
+    assert_eq!(res.matches("FDSE").count(), 2);
+    assert_eq!(res.matches("FDRE").count(), 6);
+    Ok(())
+}
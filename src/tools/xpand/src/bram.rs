@@ -1,10 +1,11 @@
+use crate::clock_expr;
 use crate::create_literal;
 use crate::errors::Error;
 use crate::inst_name_try_from_instr;
 use crate::loc::Loc;
 use crate::to_verilog::{ToVerilogExpr, ToVerilogInstance, VerilogExprMap};
+use bline::reset::Reset;
 use prim::ultrascale::bram::{Bram, ParamValue};
-use prim::ultrascale::clock::CLOCK;
 use prim::ultrascale::gnd::GND;
 use prim::ultrascale::reset::RESET;
 use prim::ultrascale::vcc::VCC;
@@ -62,13 +63,15 @@ impl ToVerilogInstance<ParamValue> for Bram {
 struct Rom {
     pub prim: Bram,
     pub instr: InstrMach,
+    pub reset: Reset,
 }
 
 impl Rom {
-    pub fn new(instr: InstrMach) -> Self {
+    pub fn new(instr: InstrMach, reset: Reset) -> Self {
         Rom {
             prim: Bram::default(),
             instr,
+            reset,
         }
     }
     pub fn instr(&self) -> &InstrMach {
@@ -76,7 +79,27 @@ impl Rom {
     }
 }
 
-fn init_mem(values: &[u8]) -> VerilogExprMap {
+#[derive(Clone, Debug)]
+struct Ram {
+    pub prim: Bram,
+    pub instr: InstrMach,
+    pub reset: Reset,
+}
+
+impl Ram {
+    pub fn new(instr: InstrMach, reset: Reset) -> Self {
+        Ram {
+            prim: Bram::default(),
+            instr,
+            reset,
+        }
+    }
+    pub fn instr(&self) -> &InstrMach {
+        &self.instr
+    }
+}
+
+pub(crate) fn init_mem(values: &[u8]) -> VerilogExprMap {
     let width = 32;
     let depth = 64;
     let bits = (width * 8) as u32;
@@ -101,6 +124,93 @@ fn init_mem(values: &[u8]) -> VerilogExprMap {
     map
 }
 
+// bram registers only have synchronous resets
+pub(crate) fn check_reset(reset: &Reset) -> Result<(), Error> {
+    if reset.is_async() {
+        Err(Error::new_xpand_error(
+            "bram registers do not support asynchronous reset",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+// output resets are tied to ground when the design has no reset
+pub(crate) fn reset_expr(reset: &Reset) -> vl::Expr {
+    if reset.has_port() {
+        vl::Expr::new_ref(RESET)
+    } else {
+        vl::Expr::new_ref(GND)
+    }
+}
+
+// set memory width to 9 (8 data bits and 1 parity bit) for the ports in width
+fn param_map(
+    instr: &InstrMach,
+    param: &ParamSet<ParamValue>,
+    width: &[&str],
+    reset: &Reset,
+) -> VerilogExprMap {
+    let mut map = VerilogExprMap::new();
+    let init_map = if let Some(mem) = instr.mem() {
+        init_mem(mem.values())
+    } else {
+        VerilogExprMap::new()
+    };
+    for p in param.iter() {
+        if let Some(value) = init_map.get(&p.name()) {
+            map.insert(p.name(), value.clone());
+        } else if width.contains(&p.name().as_str()) {
+            let param = ParamValue::Num(9);
+            map.insert(p.name(), param.to_expr());
+        } else if reset.has_port()
+            && reset.is_active_low()
+            && matches!(
+                p.name().as_str(),
+                "IS_RSTRAMARSTRAM_INVERTED" | "IS_RSTREGARSTREG_INVERTED"
+            )
+        {
+            map.insert(p.name(), ParamValue::Bool(true).to_expr());
+        } else {
+            map.insert(p.name(), p.value().to_expr());
+        }
+    }
+    map
+}
+
+pub(crate) fn addr_expr(instr: &InstrMach) -> Option<vl::Expr> {
+    let id = instr.arg().get_id(0).unwrap();
+    let ty = instr.arg().get_ty(0).unwrap();
+    if let Some(width) = ty.width() {
+        let tail_pad = (width as f32).log(2.0) as u32;
+        let mut concat = vl::ExprConcat::default();
+        for _ in 0..tail_pad {
+            concat.add_expr(vl::Expr::new_ref(GND));
+        }
+        concat.add_expr(vl::Expr::new_ref(&id));
+        let head_pad = 14 - width as u32 - tail_pad;
+        for _ in 0..head_pad {
+            concat.add_expr(vl::Expr::new_ref(GND));
+        }
+        Some(vl::Expr::from(concat))
+    } else {
+        None
+    }
+}
+
+fn output_map(instr: &InstrMach, output: &PortSet) -> VerilogExprMap {
+    let mut map = VerilogExprMap::new();
+    let id = instr.dst().get_id(0).unwrap();
+    for p in output.iter() {
+        let name = p.name();
+        match name.as_str() {
+            "DOUTADOUT" => map.insert(name, vl::Expr::new_ref(&id)),
+            _ => map.insert(name, vl::Expr::new_ref("")),
+        };
+    }
+    map
+}
+
 impl ToVerilogInstance<ParamValue> for Rom {
     fn to_name(&self) -> String {
         inst_name_try_from_instr(&self.instr).unwrap()
@@ -121,55 +231,87 @@ impl ToVerilogInstance<ParamValue> for Rom {
         self.instr.loc()
     }
     fn to_param_map(&self) -> VerilogExprMap {
+        param_map(
+            self.instr(),
+            self.to_param_set(),
+            &["READ_WIDTH_A"],
+            &self.reset,
+        )
+    }
+    fn to_input_map(&self) -> VerilogExprMap {
         let mut map = VerilogExprMap::new();
-        if let Some(mem) = self.instr().mem() {
-            let init_map = init_mem(mem.values());
-            for p in self.to_param_set().iter() {
-                if let Some(value) = init_map.get(&p.name()) {
-                    map.insert(p.name(), value.clone());
-                } else if p.name().as_str() == "READ_WIDTH_A" {
-                    let param = ParamValue::Num(9);
-                    map.insert(p.name(), param.to_expr());
-                } else {
-                    map.insert(p.name(), p.value().to_expr());
-                }
-            }
-        } else {
-            for p in self.to_param_set().iter() {
-                if p.name().as_str() == "READ_WIDTH_A" {
-                    let param = ParamValue::Num(9);
-                    map.insert(p.name(), param.to_expr());
-                } else {
-                    map.insert(p.name(), p.value().to_expr());
-                }
+        if let Some(expr) = addr_expr(self.instr()) {
+            for p in self.prim.input().iter() {
+                let name = p.name();
+                match name.as_str() {
+                    "ADDRARDADDR" => map.insert(name, expr.clone()),
+                    "CLKARDCLK" => map.insert(name, clock_expr(self.instr())),
+                    "RSTRAMARSTRAM" => map.insert(name, reset_expr(&self.reset)),
+                    "RSTREGARSTREG" => map.insert(name, reset_expr(&self.reset)),
+                    "ENARDEN" => map.insert(name, vl::Expr::new_ref(VCC)),
+                    _ => map.insert(name, create_literal(p.width() as u64, 0)),
+                };
             }
         }
         map
     }
+    fn to_output_map(&self) -> VerilogExprMap {
+        output_map(self.instr(), self.to_output_set())
+    }
+}
+
+// port A is used for both reading and writing, write data goes into the
+// lower byte of DINADIN and the write-enable drives the lower byte enable
+impl ToVerilogInstance<ParamValue> for Ram {
+    fn to_name(&self) -> String {
+        inst_name_try_from_instr(&self.instr).unwrap()
+    }
+    fn to_prim(&self) -> String {
+        self.prim.name()
+    }
+    fn to_param_set(&self) -> &ParamSet<ParamValue> {
+        self.prim.param()
+    }
+    fn to_input_set(&self) -> &PortSet {
+        self.prim.input()
+    }
+    fn to_output_set(&self) -> &PortSet {
+        self.prim.output()
+    }
+    fn to_loc(&self) -> Option<&Loc> {
+        self.instr.loc()
+    }
+    fn to_param_map(&self) -> VerilogExprMap {
+        param_map(
+            self.instr(),
+            self.to_param_set(),
+            &["READ_WIDTH_A", "WRITE_WIDTH_A"],
+            &self.reset,
+        )
+    }
     fn to_input_map(&self) -> VerilogExprMap {
         let mut map = VerilogExprMap::new();
-        let id = self.instr().arg().get_id(0).unwrap();
-        let ty = self.instr().arg().get_ty(0).unwrap();
-        if let Some(width) = ty.width() {
-            let tail_pad = (width as f32).log(2.0) as u32;
-            let mut concat = vl::ExprConcat::default();
-            for _ in 0..tail_pad {
-                concat.add_expr(vl::Expr::new_ref(GND));
+        let data = self.instr().arg().get_id(1).unwrap();
+        let en = self.instr().arg().get_id(2).unwrap();
+        if let Some(expr) = addr_expr(self.instr()) {
+            let mut din = vl::ExprConcat::default();
+            din.add_expr(vl::Expr::new_ref(&data));
+            for _ in 0..8 {
+                din.add_expr(vl::Expr::new_ref(GND));
             }
-            concat.add_expr(vl::Expr::new_ref(&id));
-            let head_pad = 14 - width as u32 - tail_pad;
-            for _ in 0..head_pad {
-                concat.add_expr(vl::Expr::new_ref(GND));
-            }
-            let expr = vl::Expr::from(concat);
+            let mut wea = vl::ExprConcat::default();
+            wea.add_expr(vl::Expr::new_ref(&en));
+            wea.add_expr(vl::Expr::new_ref(GND));
             for p in self.prim.input().iter() {
                 let name = p.name();
                 match name.as_str() {
                     "ADDRARDADDR" => map.insert(name, expr.clone()),
-                    "CLKARDCLK" => map.insert(name, vl::Expr::new_ref(CLOCK)),
-                    "RSTRAMARSTRAM" => map.insert(name, vl::Expr::new_ref(RESET)),
-                    "RSTREGARSTREG" => map.insert(name, vl::Expr::new_ref(RESET)),
+                    "CLKARDCLK" => map.insert(name, clock_expr(self.instr())),
+                    "RSTRAMARSTRAM" => map.insert(name, reset_expr(&self.reset)),
+                    "RSTREGARSTREG" => map.insert(name, reset_expr(&self.reset)),
                     "ENARDEN" => map.insert(name, vl::Expr::new_ref(VCC)),
+                    "DINADIN" => map.insert(name, vl::Expr::from(din.clone())),
+                    "WEA" => map.insert(name, vl::Expr::from(wea.clone())),
                     _ => map.insert(name, create_literal(p.width() as u64, 0)),
                 };
             }
@@ -177,21 +319,20 @@ impl ToVerilogInstance<ParamValue> for Rom {
         map
     }
     fn to_output_map(&self) -> VerilogExprMap {
-        let mut map = VerilogExprMap::new();
-        let id = self.instr().dst().get_id(0).unwrap();
-        for p in self.to_output_set().iter() {
-            let name = p.name();
-            match name.as_str() {
-                "DOUTADOUT" => map.insert(name, vl::Expr::new_ref(&id)),
-                _ => map.insert(name, vl::Expr::new_ref("")),
-            };
-        }
-        map
+        output_map(self.instr(), self.to_output_set())
     }
 }
 
 // TODO: check for valid memory shapes, support only 8x256 now (data:i8, addr:i8)
-pub fn rom_from_mach(instr: &InstrMach) -> Result<Vec<vl::Stmt>, Error> {
-    let rom = Rom::new(instr.clone());
+pub fn rom_from_mach(instr: &InstrMach, reset: &Reset) -> Result<Vec<vl::Stmt>, Error> {
+    check_reset(reset)?;
+    let rom = Rom::new(instr.clone(), *reset);
     Ok(rom.to_block())
 }
+
+// TODO: check for valid memory shapes, support only 8x256 now (data:i8, addr:i8)
+pub fn ram_from_mach(instr: &InstrMach, reset: &Reset) -> Result<Vec<vl::Stmt>, Error> {
+    check_reset(reset)?;
+    let ram = Ram::new(instr.clone(), *reset);
+    Ok(ram.to_block())
+}
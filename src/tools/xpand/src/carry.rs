@@ -78,7 +78,10 @@ impl ToVerilogInstance<ParamValue> for CarryAdd {
         let arg: Vec<vl::Expr> = vec_expr_try_from_expr(self.instr.arg()).unwrap();
         for p in self.prim.input().iter() {
             let name = p.name();
-            if name.as_str() == "CI" || name.as_str() == "CI_TOP" {
+            // carry-in comes from the previous carry in the chain, when there is one
+            if name.as_str() == "CI" && arg.len() > 2 {
+                map.insert(name, arg[2].clone());
+            } else if name.as_str() == "CI" || name.as_str() == "CI_TOP" {
                 map.insert(name, vl::Expr::new_ulit_bin(1, "0"));
             } else if name.as_str() == "DI" {
                 map.insert(name, arg[0].clone());
@@ -95,6 +98,8 @@ impl ToVerilogInstance<ParamValue> for CarryAdd {
             let name = p.name();
             if name.as_str() == "O" {
                 map.insert(name, dst[0].clone());
+            } else if name.as_str() == "CO" && dst.len() > 1 {
+                map.insert(name, dst[1].clone());
             } else {
                 map.insert(name, vl::Expr::new_ref(""));
             }
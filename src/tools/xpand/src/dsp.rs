@@ -1,21 +1,49 @@
 use crate::errors::Error;
 use crate::expr::ToExpr;
 use crate::instance::ToInstance;
-use crate::loc::attr_from_loc;
-use crate::loc::{Bel, BelDsp, ExprCoord, Loc};
+use crate::loc::attr_from_loc_with_family;
+use crate::loc::{Bel, BelDsp, ExprCoord, Family, Loc};
 use crate::param::Param;
 use crate::port::{ConnectionMap, DefaultPort, Port, WidthMap};
 use crate::{
-    create_literal, inst_name_try_from_instr, tmp_name_try_from_term, vec_expr_try_from_expr,
-    vec_expr_try_from_term,
+    clock_expr, create_literal, inst_name_try_from_instr, tmp_name_try_from_term,
+    vec_expr_try_from_expr, vec_expr_try_from_term,
 };
 use crate::{CLOCK, RESET};
+use bline::reset::Reset;
 use prim::ultrascale::gnd::GND;
 use std::collections::HashSet;
 use std::convert::TryFrom;
 use verilog::ast as vl;
 use xir::ast as xir;
 
+const RESET_PORT: [&str; 10] = [
+    "RSTA",
+    "RSTALLCARRYIN",
+    "RSTALUMODE",
+    "RSTB",
+    "RSTC",
+    "RSTCTRL",
+    "RSTD",
+    "RSTINMODE",
+    "RSTM",
+    "RSTP",
+];
+
+// DSP48E2 parameters that DSP48E1 does not have
+const E2_PARAM: [&str; 7] = [
+    "AMULTSEL",
+    "BMULTSEL",
+    "PREADDINSEL",
+    "RND",
+    "USE_WIDEXOR",
+    "XORSIMD",
+    "AUTORESET_PRIORITY",
+];
+
+// DSP48E1 inputs that are narrower than in DSP48E2
+const E1_WIDTH: [(&str, u32); 2] = [("D", 25), ("OPMODE", 7)];
+
 #[derive(Clone, Debug)]
 pub enum InputTy {
     Direct,
@@ -115,6 +143,7 @@ pub enum ParamValue {
     UseMult(UseMult),
     UseSimd(UseSimd),
     UseWideXor(bool),
+    UseDPort(bool),
     XorSimd(XorSimd),
     AutoResetPatDet(AutoResetPatDet),
     AutoResetPriority(AutoResetPriority),
@@ -135,9 +164,36 @@ pub struct Dsp {
     pub param: Param<ParamValue>,
     pub input: Port,
     pub output: Port,
+    pub family: Family,
 }
 
 impl Dsp {
+    // 7-series dsps are DSP48E1, with a narrower opmode and pre-adder input
+    pub fn new(family: Family) -> Self {
+        let mut dsp = Dsp::default();
+        if family == Family::Series7 {
+            dsp.prim = "DSP48E1".to_string();
+            dsp.family = family;
+            for name in E2_PARAM.iter() {
+                dsp.param.remove(*name);
+            }
+            for port in RESET_PORT.iter() {
+                dsp.param.remove(&format!("IS_{}_INVERTED", port));
+            }
+            dsp.param
+                .insert("USE_DPORT".to_string(), ParamValue::UseDPort(false));
+            dsp.param
+                .insert("IS_OPMODE_INVERTED".to_string(), ParamValue::Val(7, 0));
+            for (port, width) in E1_WIDTH.iter() {
+                let expr = create_literal(u64::from(*width), 0);
+                dsp.input.width.insert(port.to_string(), *width);
+                dsp.input.connection.insert(port.to_string(), expr);
+            }
+            dsp.output.width.remove("XOROUT");
+            dsp.output.connection.remove("XOROUT");
+        }
+        dsp
+    }
     pub fn get_input_width(&self, input: &str) -> Option<&u32> {
         self.input.get_width(input)
     }
@@ -154,6 +210,27 @@ impl Dsp {
         self.param.set_param(name, value.into())?;
         Ok(())
     }
+    // dsp registers only have synchronous resets
+    pub fn set_reset(&mut self, reset: &Reset) -> Result<(), Error> {
+        if reset.is_async() {
+            return Err(Error::new_xpand_error(
+                "dsp registers do not support asynchronous reset",
+            ));
+        }
+        for port in RESET_PORT.iter() {
+            if reset.is_none() {
+                self.set_input(port, create_literal(1, 0))?;
+            } else if reset.is_active_low() && self.family == Family::Series7 {
+                // DSP48E1 cannot invert its resets
+                if let Some(cond) = reset.cond(RESET) {
+                    self.set_input(port, cond)?;
+                }
+            } else if reset.is_active_low() {
+                self.set_param(&format!("IS_{}_INVERTED", port), true)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Default for InputTy {
@@ -242,17 +319,7 @@ impl Default for NumRegAB {
 
 impl DefaultPort for Dsp {
     fn default_input_port() -> Port {
-        let mut reset: HashSet<String> = HashSet::new();
-        reset.insert("RSTA".to_string());
-        reset.insert("RSTALLCARRYIN".to_string());
-        reset.insert("RSTALUMODE".to_string());
-        reset.insert("RSTB".to_string());
-        reset.insert("RSTC".to_string());
-        reset.insert("RSTCTRL".to_string());
-        reset.insert("RSTD".to_string());
-        reset.insert("RSTINMODE".to_string());
-        reset.insert("RSTM".to_string());
-        reset.insert("RSTP".to_string());
+        let reset: HashSet<String> = RESET_PORT.iter().map(|r| r.to_string()).collect();
         let mut width = WidthMap::new();
         width.insert("ACIN".to_string(), 30);
         width.insert("BCIN".to_string(), 18);
@@ -330,6 +397,7 @@ impl PartialEq for ParamValue {
             (ParamValue::UseMult(_), ParamValue::UseMult(_)) => true,
             (ParamValue::UseSimd(_), ParamValue::UseSimd(_)) => true,
             (ParamValue::UseWideXor(_), ParamValue::UseWideXor(_)) => true,
+            (ParamValue::UseDPort(_), ParamValue::UseDPort(_)) => true,
             (ParamValue::XorSimd(_), ParamValue::XorSimd(_)) => true,
             (ParamValue::AutoResetPatDet(_), ParamValue::AutoResetPatDet(_)) => true,
             (ParamValue::AutoResetPriority(_), ParamValue::AutoResetPriority(_)) => true,
@@ -444,7 +512,7 @@ impl ToExpr for ParamValue {
             ParamValue::PreAddInSel(v) => v.to_expr(),
             ParamValue::UseMult(v) => v.to_expr(),
             ParamValue::UseSimd(v) => v.to_expr(),
-            ParamValue::UseWideXor(v) => {
+            ParamValue::UseWideXor(v) | ParamValue::UseDPort(v) => {
                 let s = format!("{}", v).to_uppercase();
                 vl::Expr::new_str(&s)
             }
@@ -576,6 +644,7 @@ impl Default for Dsp {
             param: Param::<ParamValue>::default(),
             input: Dsp::default_input_port(),
             output: Dsp::default_output_port(),
+            family: Family::default(),
         }
     }
 }
@@ -729,7 +798,7 @@ impl ToInstance<ParamValue> for Dsp {
             inst.connect(&k, v.clone());
         }
         if self.loc.is_placed() {
-            let attr = attr_from_loc(&self.loc);
+            let attr = attr_from_loc_with_family(&self.loc, self.family);
             inst.set_attr(attr);
         }
         inst
@@ -819,11 +888,16 @@ fn vl_expr_try_from_term(
     }
 }
 
-pub fn vaddrega_from_mach(instr: &xir::InstrMach) -> Result<Vec<vl::Stmt>, Error> {
-    let mut prim = Dsp::default();
+pub fn vaddrega_from_mach(
+    instr: &xir::InstrMach,
+    reset: &Reset,
+    family: Family,
+) -> Result<Vec<vl::Stmt>, Error> {
+    let mut prim = Dsp::new(family);
     let mut stmt: Vec<vl::Stmt> = Vec::new();
     let name = inst_name_try_from_instr(instr)?;
     prim.set_name(&name);
+    prim.set_reset(reset)?;
     // loc
     if let Some(loc) = instr.loc() {
         prim.set_loc(loc.clone());
@@ -840,7 +914,9 @@ pub fn vaddrega_from_mach(instr: &xir::InstrMach) -> Result<Vec<vl::Stmt>, Error
     prim.set_param("BCASCREG", ParamValue::from(NumRegAB::One))?;
     prim.set_param("PREG", ParamValue::from(NumReg::One))?;
     // opcode
-    prim.set_input("OPMODE", create_literal(9, 51))?;
+    let opmode = u64::from(*prim.get_input_width("OPMODE").unwrap());
+    prim.set_input("OPMODE", create_literal(opmode, 51))?;
+    prim.set_input("CLK", clock_expr(instr))?;
     // input
     let left_term = instr.arg().get_term(0)?;
     let c_msb = prim.get_input_width("C").unwrap() - 1;
@@ -893,11 +969,16 @@ pub fn vaddrega_from_mach(instr: &xir::InstrMach) -> Result<Vec<vl::Stmt>, Error
     Ok(stmt)
 }
 
-pub fn muladdrega_from_mach(instr: &xir::InstrMach) -> Result<Vec<vl::Stmt>, Error> {
-    let mut prim = Dsp::default();
+pub fn muladdrega_from_mach(
+    instr: &xir::InstrMach,
+    reset: &Reset,
+    family: Family,
+) -> Result<Vec<vl::Stmt>, Error> {
+    let mut prim = Dsp::new(family);
     let mut stmt: Vec<vl::Stmt> = Vec::new();
     let name = inst_name_try_from_instr(instr)?;
     prim.set_name(&name);
+    prim.set_reset(reset)?;
     // loc
     if let Some(loc) = instr.loc() {
         prim.set_loc(loc.clone());
@@ -912,7 +993,9 @@ pub fn muladdrega_from_mach(instr: &xir::InstrMach) -> Result<Vec<vl::Stmt>, Err
     prim.set_param("MREG", ParamValue::from(NumReg::One))?;
     prim.set_param("PREG", ParamValue::from(NumReg::One))?;
     // opcode
-    prim.set_input("OPMODE", create_literal(9, 53))?;
+    let opmode = u64::from(*prim.get_input_width("OPMODE").unwrap());
+    prim.set_input("OPMODE", create_literal(opmode, 53))?;
+    prim.set_input("CLK", clock_expr(instr))?;
     // input
     let a_term = instr.arg().get_term(0)?;
     let a_width = *prim.get_input_width("A").unwrap();
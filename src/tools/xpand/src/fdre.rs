@@ -1,12 +1,14 @@
 use crate::errors::Error;
 use crate::expr::ToExpr;
+use crate::fdse::fdse_from_mach;
 use crate::instance::ToInstance;
 use crate::loc::attr_from_loc;
 use crate::loc::{Bel, BelReg, ExprCoord, Loc};
 use crate::param::Param;
 use crate::port::{ConnectionMap, DefaultPort, Port, WidthMap};
-use crate::{create_literal, inst_name_try_from_instr, vec_expr_try_from_expr};
+use crate::{clock_expr, create_literal, inst_name_try_from_instr, vec_expr_try_from_expr};
 use crate::{CLOCK, RESET};
+use bline::reset::Reset;
 use verilog::ast as vl;
 use xir::ast as xir;
 
@@ -113,6 +115,30 @@ impl Fdre {
     pub fn set_loc(&mut self, loc: Loc) {
         self.loc = loc;
     }
+    pub fn set_init(&mut self, init: bool) -> Result<(), Error> {
+        self.param.set_param("INIT", init)
+    }
+    // async reset uses FDCE, the same register with an asynchronous CLR port
+    pub fn set_reset(&mut self, reset: &Reset) -> Result<(), Error> {
+        if reset.is_none() {
+            self.set_input("R", create_literal(1, 0))?;
+        } else if reset.is_active_low() {
+            self.param.set_param("IS_R_INVERTED", true)?;
+        }
+        if reset.is_async() {
+            self.prim = "FDCE".to_string();
+            if let Some(expr) = self.input.connection.remove("R") {
+                self.input.connection.insert("CLR".to_string(), expr);
+            }
+            if let Some(width) = self.input.width.remove("R") {
+                self.input.width.insert("CLR".to_string(), width);
+            }
+            if let Some(value) = self.param.remove("IS_R_INVERTED") {
+                self.param.insert("IS_CLR_INVERTED".to_string(), value);
+            }
+        }
+        Ok(())
+    }
 }
 
 impl ToInstance<ParamValue> for Fdre {
@@ -163,13 +189,36 @@ impl ToInstance<ParamValue> for Fdre {
     }
 }
 
-pub fn fdre_from_mach(instr: &xir::InstrMach) -> Result<Vec<vl::Stmt>, Error> {
+// fdre[init, value] gives the power-on and reset bit, value defaults to init
+pub fn reg_attr_try_from_instr(instr: &xir::InstrMach) -> Result<(bool, bool), Error> {
+    let init = if let Some(term) = instr.attr().idx(0) {
+        term.get_val()? & 1 == 1
+    } else {
+        false
+    };
+    let value = if let Some(term) = instr.attr().idx(1) {
+        term.get_val()? & 1 == 1
+    } else {
+        init
+    };
+    Ok((init, value))
+}
+
+// registers that reset to one are expanded to FDSE
+pub fn fdre_from_mach(instr: &xir::InstrMach, reset: &Reset) -> Result<Vec<vl::Stmt>, Error> {
+    if let (_, true) = reg_attr_try_from_instr(instr)? {
+        return fdse_from_mach(instr, reset);
+    }
     let mut fdre = Fdre::default();
     let name = inst_name_try_from_instr(instr)?;
     fdre.set_name(&name);
     if let Some(loc) = instr.loc() {
         fdre.set_loc(loc.clone());
     }
+    let (init, _) = reg_attr_try_from_instr(instr)?;
+    fdre.set_init(init)?;
+    fdre.set_reset(reset)?;
+    fdre.set_input("C", clock_expr(instr))?;
     let input = ["D", "CE"];
     let arg: Vec<vl::Expr> = vec_expr_try_from_expr(instr.arg())?;
     for (i, e) in input.iter().zip(arg) {
@@ -1,12 +1,14 @@
 use crate::errors::Error;
 use crate::expr::ToExpr;
+use crate::fdre::reg_attr_try_from_instr;
 use crate::instance::ToInstance;
 use crate::loc::attr_from_loc;
 use crate::loc::{Bel, BelReg, ExprCoord, Loc};
 use crate::param::Param;
 use crate::port::{ConnectionMap, DefaultPort, Port, WidthMap};
-use crate::{create_literal, inst_name_try_from_instr, vec_expr_try_from_expr};
+use crate::{clock_expr, create_literal, inst_name_try_from_instr, vec_expr_try_from_expr};
 use crate::{CLOCK, RESET};
+use bline::reset::Reset;
 use verilog::ast as vl;
 use xir::ast as xir;
 
@@ -105,6 +107,30 @@ impl Fdse {
     pub fn set_loc(&mut self, loc: Loc) {
         self.loc = loc;
     }
+    pub fn set_init(&mut self, init: bool) -> Result<(), Error> {
+        self.param.set_param("INIT", init)
+    }
+    // async reset uses FDPE, the same register with an asynchronous PRE port
+    pub fn set_reset(&mut self, reset: &Reset) -> Result<(), Error> {
+        if reset.is_none() {
+            self.set_input("S", create_literal(1, 0))?;
+        } else if reset.is_active_low() {
+            self.param.set_param("IS_S_INVERTED", true)?;
+        }
+        if reset.is_async() {
+            self.prim = "FDPE".to_string();
+            if let Some(expr) = self.input.connection.remove("S") {
+                self.input.connection.insert("PRE".to_string(), expr);
+            }
+            if let Some(width) = self.input.width.remove("S") {
+                self.input.width.insert("PRE".to_string(), width);
+            }
+            if let Some(value) = self.param.remove("IS_S_INVERTED") {
+                self.param.insert("IS_PRE_INVERTED".to_string(), value);
+            }
+        }
+        Ok(())
+    }
 }
 
 impl ToInstance<ParamValue> for Fdse {
@@ -155,13 +181,17 @@ impl ToInstance<ParamValue> for Fdse {
     }
 }
 
-pub fn fdre_from_mach(instr: &xir::InstrMach) -> Result<Vec<vl::Stmt>, Error> {
+pub fn fdse_from_mach(instr: &xir::InstrMach, reset: &Reset) -> Result<Vec<vl::Stmt>, Error> {
     let mut fdse = Fdse::default();
     let name = inst_name_try_from_instr(instr)?;
     fdse.set_name(&name);
     if let Some(loc) = instr.loc() {
         fdse.set_loc(loc.clone());
     }
+    let (init, _) = reg_attr_try_from_instr(instr)?;
+    fdse.set_init(init)?;
+    fdse.set_reset(reset)?;
+    fdse.set_input("C", clock_expr(instr))?;
     let input = ["D", "CE"];
     let arg: Vec<vl::Expr> = vec_expr_try_from_expr(instr.arg())?;
     for (i, e) in input.iter().zip(arg) {
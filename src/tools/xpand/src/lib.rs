@@ -17,17 +17,21 @@ pub mod lram;
 pub mod lut;
 pub mod param;
 pub mod port;
+pub mod series7;
 pub mod to_verilog;
 pub mod vcc;
 
 use crate::decl::ToDecl;
 use crate::errors::Error;
 use crate::instance::ToInstance;
+use crate::loc::Family;
 use crate::port::DefaultPort;
 use crate::to_verilog::{ToVerilogDecl, ToVerilogInstance};
 use crate::vcc::Vcc;
+use bline::reset::Reset;
 use bline::{
-    input_try_from_sig, vec_expr_try_from_expr, vec_expr_try_from_term, wire_try_from_expr,
+    input_try_from_sig, instance_try_from_instr_call, vec_expr_try_from_expr,
+    vec_expr_try_from_term, wire_try_from_expr,
 };
 use prim::ultrascale::clock::CLOCK;
 use prim::ultrascale::gnd::Gnd;
@@ -83,6 +87,15 @@ pub fn create_literal(width: u64, value: i64) -> vl::Expr {
     }
 }
 
+// clocked instructions outside the default domain carry their clock last
+pub fn clock_expr(instr: &xir::InstrMach) -> vl::Expr {
+    let arg: Vec<xir::ExprTerm> = instr.arg().clone().into();
+    match arg.last() {
+        Some(xir::ExprTerm::Var(id, ty)) if ty.is_clock() => vl::Expr::new_ref(id),
+        _ => vl::Expr::new_ref(CLOCK),
+    }
+}
+
 fn vec_decl_try_from_instr_basc(instr: &xir::InstrBasc) -> Result<Vec<vl::Decl>, Error> {
     Ok(wire_try_from_expr(instr.dst())?)
 }
@@ -108,23 +121,83 @@ fn inst_name_try_from_instr(instr: &xir::InstrMach) -> Result<vl::Id, Error> {
     Ok(format!("__{}", dst[0]))
 }
 
-fn stmt_from_mach(instr: &xir::InstrMach) -> Result<Vec<vl::Stmt>, Error> {
-    match instr.op() {
-        xir::OpMach::Lut1 => lut::lut1_from_mach(instr),
-        xir::OpMach::Lut2 => lut::lut2_from_mach(instr),
-        xir::OpMach::Lut3 => lut::lut3_from_mach(instr),
-        xir::OpMach::Lut4 => lut::lut4_from_mach(instr),
-        xir::OpMach::Lut5 => lut::lut5_from_mach(instr),
-        xir::OpMach::Lut6 => lut::lut6_from_mach(instr),
-        xir::OpMach::Fdre => fdre::fdre_from_mach(instr),
-        xir::OpMach::CarryAdd => carry::carryadd_from_mach(instr),
-        xir::OpMach::VecAddRegA => dsp::vaddrega_from_mach(instr),
-        xir::OpMach::MulAddRegA => dsp::muladdrega_from_mach(instr),
-        xir::OpMach::Lrom => lram::rom_from_mach(instr),
-        xir::OpMach::Brom => bram::rom_from_mach(instr),
+// 7-series slices have four luts (a to d), eight flops and a 4-bit carry chain
+fn check_family(instr: &xir::InstrMach, family: Family) -> Result<(), Error> {
+    if family != Family::Series7 {
+        return Ok(());
+    }
+    let bel = match instr.loc().map(|loc| loc.bel()) {
+        Some(xir::Bel::Lut(bel)) => matches!(
+            bel,
+            xir::BelLut::A5
+                | xir::BelLut::B5
+                | xir::BelLut::C5
+                | xir::BelLut::D5
+                | xir::BelLut::A6
+                | xir::BelLut::B6
+                | xir::BelLut::C6
+                | xir::BelLut::D6
+        ),
+        Some(xir::Bel::Reg(bel)) => matches!(
+            bel,
+            xir::BelReg::A
+                | xir::BelReg::B
+                | xir::BelReg::C
+                | xir::BelReg::D
+                | xir::BelReg::A2
+                | xir::BelReg::B2
+                | xir::BelReg::C2
+                | xir::BelReg::D2
+        ),
+        Some(xir::Bel::Carry(bel)) => bel == &xir::BelCarry::Carry4,
+        Some(xir::Bel::Lum(bel)) => bel == &xir::BelLum::D6,
+        _ => true,
+    };
+    if !bel {
+        let err = format!("{} is not placed on a 7-series bel", instr);
+        return Err(Error::new_xpand_error(&instr.span().render(&err)));
+    }
+    if instr.op() == &xir::OpMach::CarryAdd {
+        let dst: Vec<xir::ExprTerm> = instr.dst().clone().into();
+        for term in dst {
+            if term.get_ty()?.width() != Some(4) {
+                let err = format!("{} is wider than a 7-series carry chain of 4", instr);
+                return Err(Error::new_xpand_error(&instr.span().render(&err)));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn stmt_from_mach(
+    instr: &xir::InstrMach,
+    reset: &Reset,
+    family: Family,
+) -> Result<Vec<vl::Stmt>, Error> {
+    check_family(instr, family)?;
+    match (instr.op(), family) {
+        (xir::OpMach::Lut1, _) => lut::lut1_from_mach(instr),
+        (xir::OpMach::Lut2, _) => lut::lut2_from_mach(instr),
+        (xir::OpMach::Lut3, _) => lut::lut3_from_mach(instr),
+        (xir::OpMach::Lut4, _) => lut::lut4_from_mach(instr),
+        (xir::OpMach::Lut5, _) => lut::lut5_from_mach(instr),
+        (xir::OpMach::Lut6, _) => lut::lut6_from_mach(instr),
+        (xir::OpMach::Fdre, _) => fdre::fdre_from_mach(instr, reset),
+        (xir::OpMach::CarryAdd, Family::Series7) => series7::carry::carryadd_from_mach(instr),
+        (xir::OpMach::CarryAdd, _) => carry::carryadd_from_mach(instr),
+        (xir::OpMach::VecAddRegA, _) => dsp::vaddrega_from_mach(instr, reset, family),
+        (xir::OpMach::MulAddRegA, _) => dsp::muladdrega_from_mach(instr, reset, family),
+        (xir::OpMach::Lrom, Family::Series7) => series7::lram::rom_from_mach(instr),
+        (xir::OpMach::Lrom, _) => lram::rom_from_mach(instr),
+        (xir::OpMach::Brom, Family::Series7) => series7::bram::rom_from_mach(instr, reset),
+        (xir::OpMach::Brom, _) => bram::rom_from_mach(instr, reset),
+        (xir::OpMach::Lram, Family::Series7) => series7::lram::ram_from_mach(instr),
+        (xir::OpMach::Lram, _) => lram::ram_from_mach(instr),
+        (xir::OpMach::Bram, Family::Series7) => series7::bram::ram_from_mach(instr, reset),
+        (xir::OpMach::Bram, _) => bram::ram_from_mach(instr, reset),
         _ => {
             let err = format!("{} unsupported instruction", instr);
-            Err(Error::new_xpand_error(&err))
+            Err(Error::new_xpand_error(&instr.span().render(&err)))
         }
     }
 }
@@ -139,14 +212,22 @@ fn stmt_from_basc(instr: &xir::InstrBasc) -> Result<Vec<vl::Stmt>, Error> {
     }
 }
 
-pub fn try_from_xir_prog(prog: &xir::Prog, mmap: Option<&mmap::Mmap>) -> Result<vl::Module, Error> {
-    let id = prog.sig().id();
+fn module_try_from_xir_prog(
+    prog: &xir::Prog,
+    sig: &xir::Sig,
+    inst: Vec<vl::Instance>,
+    inst_decl: Vec<vl::Decl>,
+    mmap: Option<&mmap::Mmap>,
+    reset: &Reset,
+    family: Family,
+) -> Result<vl::Module, Error> {
+    let id = sig.id();
     let mut module = vl::Module::new(&id);
-    let input = input_try_from_sig(prog.sig())?;
+    let input = input_try_from_sig(sig, reset)?;
     for i in input {
         module.add_port(i.clone());
     }
-    let mut decl: Vec<vl::Decl> = Vec::new();
+    let mut decl: Vec<vl::Decl> = inst_decl;
     let dsp_outputs = dsp::Dsp::default_output_port();
     for i in prog.body() {
         let d: Vec<vl::Decl> = vec_decl_try_from_instr(i)?;
@@ -161,7 +242,7 @@ pub fn try_from_xir_prog(prog: &xir::Prog, mmap: Option<&mmap::Mmap>) -> Result<
             }
         }
     }
-    let output: Vec<vl::Decl> = wire_try_from_expr(prog.sig().output())?;
+    let output: Vec<vl::Decl> = wire_try_from_expr(sig.output())?;
     for o in output.iter() {
         module.add_port(vl::Port::Output(o.clone()));
     }
@@ -188,12 +269,12 @@ pub fn try_from_xir_prog(prog: &xir::Prog, mmap: Option<&mmap::Mmap>) -> Result<
                         if let Some(mem) = m.get(&id) {
                             let mut instr_mach = mach.clone();
                             instr_mach.set_mem(mem.clone());
-                            stmt_from_mach(&instr_mach)?
+                            stmt_from_mach(&instr_mach, reset, family)?
                         } else {
-                            stmt_from_mach(mach)?
+                            stmt_from_mach(mach, reset, family)?
                         }
                     }
-                    _ => stmt_from_mach(mach)?,
+                    _ => stmt_from_mach(mach, reset, family)?,
                 }
             }
         };
@@ -201,5 +282,62 @@ pub fn try_from_xir_prog(prog: &xir::Prog, mmap: Option<&mmap::Mmap>) -> Result<
             module.add_stmt(s);
         }
     }
+    for i in inst {
+        module.add_stmt(vl::Stmt::from(i));
+    }
     Ok(module)
 }
+
+pub fn try_from_xir_prog(
+    prog: &xir::Prog,
+    mmap: Option<&mmap::Mmap>,
+    reset: &Reset,
+) -> Result<vl::Module, Error> {
+    try_from_xir_prog_with_family(prog, mmap, reset, Family::default())
+}
+
+pub fn try_from_xir_prog_with_family(
+    prog: &xir::Prog,
+    mmap: Option<&mmap::Mmap>,
+    reset: &Reset,
+    family: Family,
+) -> Result<vl::Module, Error> {
+    module_try_from_xir_prog(
+        prog,
+        prog.sig(),
+        Vec::new(),
+        Vec::new(),
+        mmap,
+        reset,
+        family,
+    )
+}
+
+// prog is a def compiled without its calls, see ir::hier::split_calls_from_def. The
+// module gets the original def signature and an instance for every call
+pub fn hier_try_from_xir_prog(
+    prog: &xir::Prog,
+    sig: &xir::Sig,
+    call: &[(ir::ast::InstrCall, ir::ast::Sig)],
+    mmap: Option<&mmap::Mmap>,
+    reset: &Reset,
+) -> Result<vl::Module, Error> {
+    hier_try_from_xir_prog_with_family(prog, sig, call, mmap, reset, Family::default())
+}
+
+pub fn hier_try_from_xir_prog_with_family(
+    prog: &xir::Prog,
+    sig: &xir::Sig,
+    call: &[(ir::ast::InstrCall, ir::ast::Sig)],
+    mmap: Option<&mmap::Mmap>,
+    reset: &Reset,
+    family: Family,
+) -> Result<vl::Module, Error> {
+    let mut inst: Vec<vl::Instance> = Vec::new();
+    let mut decl: Vec<vl::Decl> = Vec::new();
+    for (instr, callee) in call {
+        inst.push(instance_try_from_instr_call(instr, callee, reset)?);
+        decl.extend(wire_try_from_expr(instr.dst())?);
+    }
+    module_try_from_xir_prog(prog, sig, inst, decl, mmap, reset, family)
+}
@@ -9,6 +9,7 @@ pub type BelBlock = xir::BelBlock;
 pub type BelLum = xir::BelLum;
 pub type Bel = xir::Bel;
 pub type ExprCoord = xir::ExprCoord;
+pub type Family = xir::Family;
 pub type Loc = xir::Loc;
 
 fn string_from_bel_reg(bel: &BelReg) -> String {
@@ -56,6 +57,7 @@ fn string_from_bel_lut(bel: &BelLut) -> String {
 fn string_from_bel_lum(bel: &BelLum) -> String {
     match bel {
         BelLum::H6 => "H6LUT".to_string(),
+        BelLum::D6 => "D6LUT".to_string(),
     }
 }
 
@@ -66,48 +68,57 @@ fn string_from_bel_carry(bel: &BelCarry) -> String {
     }
 }
 
-fn string_from_bel_dsp(bel: &BelDsp) -> String {
-    match bel {
-        BelDsp::Alu => "DSP_ALU".to_string(),
+fn string_from_bel_dsp(bel: &BelDsp, family: Family) -> String {
+    match (bel, family) {
+        (BelDsp::Alu, Family::UltraScale) => "DSP_ALU".to_string(),
+        (BelDsp::Alu, Family::Series7) => "DSP48E1".to_string(),
     }
 }
 
-fn string_from_bel_block(bel: &BelBlock) -> String {
-    match bel {
-        BelBlock::L => "RAMB18E2_L".to_string(),
-        BelBlock::U => "RAMB18E2_U".to_string(),
+fn string_from_bel_block(bel: &BelBlock, family: Family) -> String {
+    match (bel, family) {
+        (BelBlock::L, Family::UltraScale) => "RAMB18E2_L".to_string(),
+        (BelBlock::U, Family::UltraScale) => "RAMB18E2_U".to_string(),
+        (_, Family::Series7) => "RAMB18E1".to_string(),
     }
 }
 
-fn string_from_bel(bel: &Bel) -> String {
+fn string_from_bel(bel: &Bel, family: Family) -> String {
     match bel {
         Bel::Lut(b) => string_from_bel_lut(b),
         Bel::Reg(b) => string_from_bel_reg(b),
         Bel::Carry(b) => string_from_bel_carry(b),
-        Bel::Dsp(b) => string_from_bel_dsp(b),
-        Bel::Block(b) => string_from_bel_block(b),
+        Bel::Dsp(b) => string_from_bel_dsp(b, family),
+        Bel::Block(b) => string_from_bel_block(b, family),
         Bel::Lum(b) => string_from_bel_lum(b),
     }
 }
 
-fn attr_stmt_from_bel(bel: &Bel) -> vl::AttributeTy {
-    let val = string_from_bel(bel);
+fn attr_stmt_from_bel(bel: &Bel, family: Family) -> vl::AttributeTy {
+    let val = string_from_bel(bel, family);
     vl::AttributeTy::new_stmt("BEL", &val)
 }
 
-fn attr_stmt_from_loc(loc: &Loc) -> vl::AttributeTy {
-    let val = match loc.bel() {
-        Bel::Dsp(_) => format!("DSP48E2_X{}Y{}", loc.x(), loc.y()),
+// 7-series dsp and block ram sites are not named after the primitive
+fn attr_stmt_from_loc(loc: &Loc, family: Family) -> vl::AttributeTy {
+    let val = match (loc.bel(), family) {
+        (Bel::Dsp(_), Family::UltraScale) => format!("DSP48E2_X{}Y{}", loc.x(), loc.y()),
+        (Bel::Dsp(_), Family::Series7) => format!("DSP48_X{}Y{}", loc.x(), loc.y()),
+        (Bel::Block(_), Family::Series7) => format!("RAMB18_X{}Y{}", loc.x(), loc.y()),
         _ => format!("SLICE_X{}Y{}", loc.x(), loc.y()),
     };
     vl::AttributeTy::new_stmt("LOC", &val)
 }
 
-pub fn attr_from_loc(loc: &Loc) -> vl::Attribute {
+pub fn attr_from_loc_with_family(loc: &Loc, family: Family) -> vl::Attribute {
     let mut attr = vl::Attribute::default();
-    let site = attr_stmt_from_bel(loc.bel());
-    let bel = attr_stmt_from_loc(loc);
+    let site = attr_stmt_from_bel(loc.bel(), family);
+    let bel = attr_stmt_from_loc(loc, family);
     attr.add_attr(site);
     attr.add_attr(bel);
     attr
 }
+
+pub fn attr_from_loc(loc: &Loc) -> vl::Attribute {
+    attr_from_loc_with_family(loc, Family::UltraScale)
+}
@@ -1,8 +1,8 @@
+use crate::clock_expr;
 use crate::errors::Error;
 use crate::inst_name_try_from_instr;
 use crate::loc::Loc;
 use crate::to_verilog::{ToVerilogExpr, ToVerilogInstance, VerilogExprMap};
-use prim::ultrascale::clock::CLOCK;
 use prim::ultrascale::gnd::GND;
 use prim::ultrascale::lram::{Lram, ParamValue};
 use prim::{ParamSet, PortSet};
@@ -64,6 +64,100 @@ impl Rom {
     }
 }
 
+#[derive(Clone, Debug)]
+struct Ram {
+    pub prim: Lram,
+    pub instr: InstrMach,
+}
+
+impl Ram {
+    pub fn new(instr: InstrMach) -> Self {
+        Ram {
+            prim: Lram::default(),
+            instr,
+        }
+    }
+    pub fn instr(&self) -> &InstrMach {
+        &self.instr
+    }
+}
+
+const LUT: [&str; 8] = ["A", "B", "C", "D", "E", "F", "G", "H"];
+
+// init value of the LUT holding data bit, the address drives the upper
+// address pins (see addr_expr), so memory index i lives at bit i << (6 - width)
+pub(crate) fn init_lut(instr: &InstrMach, values: &[u8], bit: u64) -> Vec<u8> {
+    let width = instr
+        .arg()
+        .get_ty(0)
+        .ok()
+        .and_then(|ty| ty.width())
+        .unwrap_or(6);
+    let shift = 6_u64.saturating_sub(width);
+    let mut init: u64 = 0;
+    for (index, value) in values.iter().enumerate().take(1 << width.min(6)) {
+        if (value >> bit) & 1 == 1 {
+            init |= 1 << ((index as u64) << shift);
+        }
+    }
+    init.to_le_bytes().to_vec()
+}
+
+// every LUT holds one bit of the 8-bit data, i.e. INIT_A holds bit 0
+fn param_map(instr: &InstrMach, param: &ParamSet<ParamValue>) -> VerilogExprMap {
+    let mut map = VerilogExprMap::new();
+    for p in param.iter() {
+        let name = p.name();
+        let lut = LUT.iter().position(|l| name == format!("INIT_{}", l));
+        match (instr.mem(), lut) {
+            (Some(mem), Some(lut)) => {
+                let value = init_lut(instr, mem.values(), lut as u64);
+                map.insert(name, ParamValue::Bytes(64, value).to_expr());
+            }
+            _ => {
+                map.insert(name, p.value().to_expr());
+            }
+        }
+    }
+    map
+}
+
+pub(crate) fn addr_expr(instr: &InstrMach) -> Option<vl::Expr> {
+    let id = instr.arg().get_id(0).unwrap();
+    let ty = instr.arg().get_ty(0).unwrap();
+    if let Some(width) = ty.width() {
+        let mut concat = vl::ExprConcat::default();
+        for _ in 0..width {
+            concat.add_expr(vl::Expr::new_ref(GND));
+        }
+        for i in 0..(6_i32 - width as i32) {
+            concat.add_expr(vl::Expr::new_index_bit(&id, i));
+        }
+        Some(vl::Expr::from(concat))
+    } else {
+        None
+    }
+}
+
+fn output_map(instr: &InstrMach, output: &PortSet) -> VerilogExprMap {
+    let mut map = VerilogExprMap::new();
+    let dst = instr.dst().get_id(0).unwrap();
+    for p in output.iter() {
+        let name = p.name();
+        match name.as_str() {
+            "DOA" => map.insert(name, vl::Expr::new_index_bit(&dst, 0)),
+            "DOB" => map.insert(name, vl::Expr::new_index_bit(&dst, 1)),
+            "DOC" => map.insert(name, vl::Expr::new_index_bit(&dst, 2)),
+            "DOD" => map.insert(name, vl::Expr::new_index_bit(&dst, 3)),
+            "DOE" => map.insert(name, vl::Expr::new_index_bit(&dst, 4)),
+            "DOF" => map.insert(name, vl::Expr::new_index_bit(&dst, 5)),
+            "DOG" => map.insert(name, vl::Expr::new_index_bit(&dst, 6)),
+            _ => map.insert(name, vl::Expr::new_index_bit(&dst, 7)),
+        };
+    }
+    map
+}
+
 impl ToVerilogInstance<ParamValue> for Rom {
     fn to_name(&self) -> String {
         inst_name_try_from_instr(&self.instr).unwrap()
@@ -81,60 +175,71 @@ impl ToVerilogInstance<ParamValue> for Rom {
         self.prim.output()
     }
     fn to_param_map(&self) -> VerilogExprMap {
+        param_map(self.instr(), self.to_param_set())
+    }
+    fn to_input_map(&self) -> VerilogExprMap {
         let mut map = VerilogExprMap::new();
-        if let Some(mem) = self.instr().mem() {
-            for p in self.to_param_set().iter() {
-                let mut value: Vec<u8> = Vec::new();
-                for v in mem.values().iter() {
-                    match p.name().as_str() {
-                        "INIT_A" => value.push(v & 1),
-                        "INIT_B" => value.push((v >> 1) & 1),
-                        "INIT_C" => value.push((v >> 2) & 1),
-                        "INIT_D" => value.push((v >> 3) & 1),
-                        "INIT_E" => value.push((v >> 4) & 1),
-                        "INIT_F" => value.push((v >> 5) & 1),
-                        "INIT_G" => value.push((v >> 6) & 1),
-                        "INIT_H" => value.push((v >> 7) & 1),
-                        _ => (),
-                    };
-                }
-                match p.name().as_str() {
-                    "INIT_A" | "INIT_B" | "INIT_C" | "INIT_D" | "INIT_E" | "INIT_F" | "INIT_G"
-                    | "INIT_H" => {
-                        let param = ParamValue::Bytes(64, value);
-                        map.insert(p.name(), param.to_expr());
-                    }
-                    _ => {
-                        map.insert(p.name(), p.value().to_expr());
-                    }
+        if let Some(expr) = addr_expr(self.instr()) {
+            for p in self.prim.input().iter() {
+                let name = p.name();
+                match name.as_str() {
+                    "ADDRA" | "ADDRB" | "ADDRC" | "ADDRD" | "ADDRE" | "ADDRF" | "ADDRG"
+                    | "ADDRH" => map.insert(name, expr.clone()),
+                    "WCLK" => map.insert(name, clock_expr(self.instr())),
+                    _ => map.insert(name, vl::Expr::new_ref(GND)),
                 };
             }
-        } else {
-            for p in self.to_param_set().iter() {
-                map.insert(p.name(), p.value().to_expr());
-            }
         }
         map
     }
+    fn to_output_map(&self) -> VerilogExprMap {
+        output_map(self.instr(), self.to_output_set())
+    }
+    fn to_loc(&self) -> Option<&Loc> {
+        self.instr.loc()
+    }
+}
+
+// all eight LUTs share the address, ADDRH is also the write address
+impl ToVerilogInstance<ParamValue> for Ram {
+    fn to_name(&self) -> String {
+        inst_name_try_from_instr(&self.instr).unwrap()
+    }
+    fn to_prim(&self) -> String {
+        self.prim.name()
+    }
+    fn to_param_set(&self) -> &ParamSet<ParamValue> {
+        self.prim.param()
+    }
+    fn to_input_set(&self) -> &PortSet {
+        self.prim.input()
+    }
+    fn to_output_set(&self) -> &PortSet {
+        self.prim.output()
+    }
+    fn to_param_map(&self) -> VerilogExprMap {
+        param_map(self.instr(), self.to_param_set())
+    }
     fn to_input_map(&self) -> VerilogExprMap {
         let mut map = VerilogExprMap::new();
-        let id = self.instr().arg().get_id(0).unwrap();
-        let ty = self.instr().arg().get_ty(0).unwrap();
-        if let Some(width) = ty.width() {
-            let mut concat = vl::ExprConcat::default();
-            for _ in 0..width {
-                concat.add_expr(vl::Expr::new_ref(GND));
-            }
-            for i in 0..(6_i32 - width as i32) {
-                concat.add_expr(vl::Expr::new_index_bit(&id, i));
-            }
-            let expr = vl::Expr::from(concat);
+        let data = self.instr().arg().get_id(1).unwrap();
+        let en = self.instr().arg().get_id(2).unwrap();
+        if let Some(expr) = addr_expr(self.instr()) {
             for p in self.prim.input().iter() {
                 let name = p.name();
                 match name.as_str() {
                     "ADDRA" | "ADDRB" | "ADDRC" | "ADDRD" | "ADDRE" | "ADDRF" | "ADDRG"
                     | "ADDRH" => map.insert(name, expr.clone()),
-                    "WCLK" => map.insert(name, vl::Expr::new_ref(CLOCK)),
+                    "DIA" => map.insert(name, vl::Expr::new_index_bit(&data, 0)),
+                    "DIB" => map.insert(name, vl::Expr::new_index_bit(&data, 1)),
+                    "DIC" => map.insert(name, vl::Expr::new_index_bit(&data, 2)),
+                    "DID" => map.insert(name, vl::Expr::new_index_bit(&data, 3)),
+                    "DIE" => map.insert(name, vl::Expr::new_index_bit(&data, 4)),
+                    "DIF" => map.insert(name, vl::Expr::new_index_bit(&data, 5)),
+                    "DIG" => map.insert(name, vl::Expr::new_index_bit(&data, 6)),
+                    "DIH" => map.insert(name, vl::Expr::new_index_bit(&data, 7)),
+                    "WE" => map.insert(name, vl::Expr::new_ref(&en)),
+                    "WCLK" => map.insert(name, clock_expr(self.instr())),
                     _ => map.insert(name, vl::Expr::new_ref(GND)),
                 };
             }
@@ -142,22 +247,7 @@ impl ToVerilogInstance<ParamValue> for Rom {
         map
     }
     fn to_output_map(&self) -> VerilogExprMap {
-        let mut map = VerilogExprMap::new();
-        let dst = self.instr().dst().get_id(0).unwrap();
-        for p in self.prim.output().iter() {
-            let name = p.name();
-            match name.as_str() {
-                "DOA" => map.insert(name, vl::Expr::new_index_bit(&dst, 0)),
-                "DOB" => map.insert(name, vl::Expr::new_index_bit(&dst, 1)),
-                "DOC" => map.insert(name, vl::Expr::new_index_bit(&dst, 2)),
-                "DOD" => map.insert(name, vl::Expr::new_index_bit(&dst, 3)),
-                "DOE" => map.insert(name, vl::Expr::new_index_bit(&dst, 4)),
-                "DOF" => map.insert(name, vl::Expr::new_index_bit(&dst, 5)),
-                "DOG" => map.insert(name, vl::Expr::new_index_bit(&dst, 6)),
-                _ => map.insert(name, vl::Expr::new_index_bit(&dst, 7)),
-            };
-        }
-        map
+        output_map(self.instr(), self.to_output_set())
     }
     fn to_loc(&self) -> Option<&Loc> {
         self.instr.loc()
@@ -169,3 +259,9 @@ pub fn rom_from_mach(instr: &InstrMach) -> Result<Vec<vl::Stmt>, Error> {
     let rom = Rom::new(instr.clone());
     Ok(rom.to_block())
 }
+
+// TODO: check for valid memory shapes, support only 8x8 now (data:i8, addr:i3)
+pub fn ram_from_mach(instr: &InstrMach) -> Result<Vec<vl::Stmt>, Error> {
+    let ram = Ram::new(instr.clone());
+    Ok(ram.to_block())
+}
@@ -0,0 +1,177 @@
+use crate::bram::{addr_expr, check_reset, init_mem, reset_expr};
+use crate::errors::Error;
+use crate::loc::{Family, Loc};
+use crate::to_verilog::{ToVerilogExpr, ToVerilogInstance, VerilogExprMap};
+use crate::{clock_expr, create_literal, inst_name_try_from_instr};
+use bline::reset::Reset;
+use prim::series7::bram::{Bram, ParamValue};
+use prim::ultrascale::gnd::GND;
+use prim::ultrascale::vcc::VCC;
+use prim::{ParamSet, PortSet};
+use verilog::ast as vl;
+use xir::ast::{InstrMach, OpMach};
+
+impl ToVerilogExpr for ParamValue {
+    fn to_expr(&self) -> vl::Expr {
+        match self {
+            ParamValue::CollisionCheck(v) => vl::Expr::new_str(&v.to_string()),
+            ParamValue::CollisionConfig(v) => vl::Expr::new_str(&v.to_string()),
+            ParamValue::FilePath(v) => vl::Expr::new_str(&v.to_string()),
+            ParamValue::RamMode(v) => vl::Expr::new_str(&v.to_string()),
+            ParamValue::RstRegPriority(v) => vl::Expr::new_str(&v.to_string()),
+            ParamValue::SimDevice(v) => vl::Expr::new_str(&v.to_string()),
+            ParamValue::WriteMode(v) => vl::Expr::new_str(&v.to_string()),
+            ParamValue::Bool(v) => vl::Expr::new_ulit_bin(1, &format!("{}", *v as i32)),
+            ParamValue::BoolNum(v) => vl::Expr::new_int(*v as i32),
+            ParamValue::Bytes(width, values) if values.is_empty() => {
+                vl::Expr::new_ulit_hex(*width, "0")
+            }
+            ParamValue::Bytes(width, values) => {
+                let mut num = String::new();
+                for v in values.iter().rev() {
+                    let val = format!("{:02X}", v);
+                    num.push_str(&val);
+                }
+                vl::Expr::new_ulit_hex(*width, &num)
+            }
+            ParamValue::Num(v) => vl::Expr::new_int(*v as i32),
+        }
+    }
+}
+
+// RAMB18E1 in true dual-port mode, where port A reads and writes like the
+// UltraScale RAMB18E2
+#[derive(Clone, Debug)]
+struct Mem {
+    pub prim: Bram,
+    pub instr: InstrMach,
+    pub reset: Reset,
+}
+
+impl Mem {
+    pub fn new(instr: InstrMach, reset: Reset) -> Self {
+        Mem {
+            prim: Bram::default(),
+            instr,
+            reset,
+        }
+    }
+    pub fn instr(&self) -> &InstrMach {
+        &self.instr
+    }
+    // roms only have an address, rams also take data and a write-enable
+    pub fn is_ram(&self) -> bool {
+        self.instr.op() == &OpMach::Bram
+    }
+}
+
+impl ToVerilogInstance<ParamValue> for Mem {
+    fn to_name(&self) -> String {
+        inst_name_try_from_instr(&self.instr).unwrap()
+    }
+    fn to_prim(&self) -> String {
+        self.prim.name()
+    }
+    fn to_param_set(&self) -> &ParamSet<ParamValue> {
+        self.prim.param()
+    }
+    fn to_input_set(&self) -> &PortSet {
+        self.prim.input()
+    }
+    fn to_output_set(&self) -> &PortSet {
+        self.prim.output()
+    }
+    fn to_loc(&self) -> Option<&Loc> {
+        self.instr.loc()
+    }
+    fn to_family(&self) -> Family {
+        Family::Series7
+    }
+    // memory width is 9 (8 data bits and 1 parity bit) for the ports in use
+    fn to_param_map(&self) -> VerilogExprMap {
+        let mut map = VerilogExprMap::new();
+        let init_map = if let Some(mem) = self.instr().mem() {
+            init_mem(mem.values())
+        } else {
+            VerilogExprMap::new()
+        };
+        let inverted = self.reset.has_port() && self.reset.is_active_low();
+        for p in self.to_param_set().iter() {
+            let name = p.name();
+            if let Some(value) = init_map.get(&name) {
+                map.insert(name, value.clone());
+            } else if name == "READ_WIDTH_A" || (name == "WRITE_WIDTH_A" && self.is_ram()) {
+                map.insert(name, ParamValue::Num(9).to_expr());
+            } else if inverted
+                && matches!(
+                    name.as_str(),
+                    "IS_RSTRAMARSTRAM_INVERTED" | "IS_RSTREGARSTREG_INVERTED"
+                )
+            {
+                map.insert(name, ParamValue::Bool(true).to_expr());
+            } else {
+                map.insert(name, p.value().to_expr());
+            }
+        }
+        map
+    }
+    fn to_input_map(&self) -> VerilogExprMap {
+        let mut map = VerilogExprMap::new();
+        if let Some(expr) = addr_expr(self.instr()) {
+            for p in self.prim.input().iter() {
+                let name = p.name();
+                match name.as_str() {
+                    "ADDRARDADDR" => map.insert(name, expr.clone()),
+                    "CLKARDCLK" => map.insert(name, clock_expr(self.instr())),
+                    "RSTRAMARSTRAM" => map.insert(name, reset_expr(&self.reset)),
+                    "RSTREGARSTREG" => map.insert(name, reset_expr(&self.reset)),
+                    "ENARDEN" => map.insert(name, vl::Expr::new_ref(VCC)),
+                    _ => map.insert(name, create_literal(p.width() as u64, 0)),
+                };
+            }
+        }
+        // write data goes into the lower byte of DIADI and the write-enable
+        // drives the lower byte enable
+        if self.is_ram() {
+            let data = self.instr().arg().get_id(1).unwrap();
+            let en = self.instr().arg().get_id(2).unwrap();
+            let mut din = vl::ExprConcat::default();
+            din.add_expr(vl::Expr::new_ref(&data));
+            for _ in 0..8 {
+                din.add_expr(vl::Expr::new_ref(GND));
+            }
+            let mut wea = vl::ExprConcat::default();
+            wea.add_expr(vl::Expr::new_ref(&en));
+            wea.add_expr(vl::Expr::new_ref(GND));
+            map.insert("DIADI".to_string(), vl::Expr::from(din));
+            map.insert("WEA".to_string(), vl::Expr::from(wea));
+        }
+        map
+    }
+    fn to_output_map(&self) -> VerilogExprMap {
+        let mut map = VerilogExprMap::new();
+        let id = self.instr().dst().get_id(0).unwrap();
+        for p in self.prim.output().iter() {
+            let name = p.name();
+            match name.as_str() {
+                "DOADO" => map.insert(name, vl::Expr::new_ref(&id)),
+                _ => map.insert(name, vl::Expr::new_ref("")),
+            };
+        }
+        map
+    }
+}
+
+// TODO: check for valid memory shapes, support only 8x256 now (data:i8, addr:i8)
+pub fn rom_from_mach(instr: &InstrMach, reset: &Reset) -> Result<Vec<vl::Stmt>, Error> {
+    check_reset(reset)?;
+    let rom = Mem::new(instr.clone(), *reset);
+    Ok(rom.to_block())
+}
+
+// TODO: check for valid memory shapes, support only 8x256 now (data:i8, addr:i8)
+pub fn ram_from_mach(instr: &InstrMach, reset: &Reset) -> Result<Vec<vl::Stmt>, Error> {
+    check_reset(reset)?;
+    let ram = Mem::new(instr.clone(), *reset);
+    Ok(ram.to_block())
+}
@@ -0,0 +1,88 @@
+use crate::errors::Error;
+use crate::loc::{Family, Loc};
+use crate::to_verilog::{ToVerilogExpr, ToVerilogInstance, VerilogExprMap};
+use crate::{inst_name_try_from_instr, vec_expr_try_from_expr};
+use prim::series7::carry::{Carry, ParamValue};
+use prim::{ParamSet, PortSet};
+use verilog::ast as vl;
+use xir::ast as xir;
+
+impl ToVerilogExpr for ParamValue {}
+
+#[derive(Clone, Debug)]
+struct CarryAdd {
+    pub prim: Carry,
+    pub instr: xir::InstrMach,
+}
+
+impl CarryAdd {
+    pub fn new(instr: xir::InstrMach) -> Self {
+        CarryAdd {
+            prim: Carry::default(),
+            instr,
+        }
+    }
+}
+
+impl ToVerilogInstance<ParamValue> for CarryAdd {
+    fn to_name(&self) -> String {
+        inst_name_try_from_instr(&self.instr).unwrap()
+    }
+    fn to_prim(&self) -> String {
+        self.prim.name()
+    }
+    fn to_param_set(&self) -> &ParamSet<ParamValue> {
+        self.prim.param()
+    }
+    fn to_input_set(&self) -> &PortSet {
+        self.prim.input()
+    }
+    fn to_output_set(&self) -> &PortSet {
+        self.prim.output()
+    }
+    fn to_loc(&self) -> Option<&Loc> {
+        self.instr.loc()
+    }
+    fn to_family(&self) -> Family {
+        Family::Series7
+    }
+    // CARRY4 takes the carry-in from the previous carry in the chain on CI,
+    // CYINIT is only used to start a chain with a constant from the fabric
+    fn to_input_map(&self) -> VerilogExprMap {
+        let mut map = VerilogExprMap::new();
+        let arg: Vec<vl::Expr> = vec_expr_try_from_expr(self.instr.arg()).unwrap();
+        for p in self.prim.input().iter() {
+            let name = p.name();
+            if name.as_str() == "CI" && arg.len() > 2 {
+                map.insert(name, arg[2].clone());
+            } else if name.as_str() == "CI" || name.as_str() == "CYINIT" {
+                map.insert(name, vl::Expr::new_ulit_bin(1, "0"));
+            } else if name.as_str() == "DI" {
+                map.insert(name, arg[0].clone());
+            } else {
+                map.insert(name, arg[1].clone());
+            }
+        }
+        map
+    }
+    fn to_output_map(&self) -> VerilogExprMap {
+        let mut map = VerilogExprMap::new();
+        let dst: Vec<vl::Expr> = vec_expr_try_from_expr(self.instr.dst()).unwrap();
+        for p in self.prim.output().iter() {
+            let name = p.name();
+            if name.as_str() == "O" {
+                map.insert(name, dst[0].clone());
+            } else if name.as_str() == "CO" && dst.len() > 1 {
+                map.insert(name, dst[1].clone());
+            } else {
+                map.insert(name, vl::Expr::new_ref(""));
+            }
+        }
+        map
+    }
+}
+
+pub fn carryadd_from_mach(instr: &xir::InstrMach) -> Result<Vec<vl::Stmt>, Error> {
+    let carry = CarryAdd::new(instr.clone());
+    Ok(carry.to_block())
+}
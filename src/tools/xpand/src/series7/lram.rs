@@ -0,0 +1,150 @@
+use crate::clock_expr;
+use crate::errors::Error;
+use crate::inst_name_try_from_instr;
+use crate::loc::{ExprCoord, Family, Loc};
+use crate::lram::{addr_expr, init_lut};
+use crate::to_verilog::{ToVerilogExpr, ToVerilogInstance, VerilogExprMap};
+use prim::series7::lram::{Lram, ParamValue};
+use prim::ultrascale::gnd::GND;
+use prim::{ParamSet, PortSet};
+use verilog::ast as vl;
+use xir::ast::{InstrMach, OpMach};
+
+// a 7-series slice only has four luts, so 8-bit memories take two RAM64M,
+// the first one holds bits 0 to 3 and the second one, in the slice above,
+// holds bits 4 to 7
+const HALF: [u64; 2] = [0, 1];
+
+const LUT: [&str; 4] = ["A", "B", "C", "D"];
+
+#[derive(Clone, Debug)]
+struct Mem {
+    pub prim: Lram,
+    pub instr: InstrMach,
+    pub half: u64,
+    pub loc: Option<Loc>,
+}
+
+impl Mem {
+    pub fn new(instr: InstrMach, half: u64) -> Self {
+        let loc = instr.loc().map(|loc| {
+            let mut loc = loc.clone();
+            if let ExprCoord::Val(y) = loc.y() {
+                loc.set_y(ExprCoord::Val(y + half));
+            }
+            loc
+        });
+        Mem {
+            prim: Lram::default(),
+            instr,
+            half,
+            loc,
+        }
+    }
+    pub fn instr(&self) -> &InstrMach {
+        &self.instr
+    }
+    // data bit held by a lut, i.e. DOB of the second half is bit 5
+    pub fn bit(&self, lut: usize) -> u64 {
+        self.half * LUT.len() as u64 + lut as u64
+    }
+}
+
+impl ToVerilogInstance<ParamValue> for Mem {
+    fn to_name(&self) -> String {
+        let name = inst_name_try_from_instr(&self.instr).unwrap();
+        format!("{}_{}", name, self.half)
+    }
+    fn to_prim(&self) -> String {
+        self.prim.name()
+    }
+    fn to_param_set(&self) -> &ParamSet<ParamValue> {
+        self.prim.param()
+    }
+    fn to_input_set(&self) -> &PortSet {
+        self.prim.input()
+    }
+    fn to_output_set(&self) -> &PortSet {
+        self.prim.output()
+    }
+    fn to_loc(&self) -> Option<&Loc> {
+        self.loc.as_ref()
+    }
+    fn to_family(&self) -> Family {
+        Family::Series7
+    }
+    fn to_param_map(&self) -> VerilogExprMap {
+        let mut map = VerilogExprMap::new();
+        for p in self.to_param_set().iter() {
+            let name = p.name();
+            let lut = LUT.iter().position(|l| name == format!("INIT_{}", l));
+            match (self.instr().mem(), lut) {
+                (Some(mem), Some(lut)) => {
+                    let bit = self.bit(lut);
+                    let value = init_lut(self.instr(), mem.values(), bit);
+                    map.insert(name, ParamValue::Bytes(64, value).to_expr());
+                }
+                _ => {
+                    map.insert(name, p.value().to_expr());
+                }
+            }
+        }
+        map
+    }
+    // all four luts share the address, ADDRD is also the write address
+    fn to_input_map(&self) -> VerilogExprMap {
+        let mut map = VerilogExprMap::new();
+        let is_ram = self.instr().op() == &OpMach::Lram;
+        if let Some(expr) = addr_expr(self.instr()) {
+            for p in self.prim.input().iter() {
+                let name = p.name();
+                let lut = LUT.iter().position(|l| name == format!("DI{}", l));
+                match (name.as_str(), lut) {
+                    ("ADDRA", _) | ("ADDRB", _) | ("ADDRC", _) | ("ADDRD", _) => {
+                        map.insert(name, expr.clone())
+                    }
+                    ("WCLK", _) => map.insert(name, clock_expr(self.instr())),
+                    ("WE", _) if is_ram => {
+                        let en = self.instr().arg().get_id(2).unwrap();
+                        map.insert(name, vl::Expr::new_ref(&en))
+                    }
+                    (_, Some(lut)) if is_ram => {
+                        let data = self.instr().arg().get_id(1).unwrap();
+                        let bit = vl::Expr::new_index_bit(&data, self.bit(lut) as i32);
+                        map.insert(name, bit)
+                    }
+                    _ => map.insert(name, vl::Expr::new_ref(GND)),
+                };
+            }
+        }
+        map
+    }
+    fn to_output_map(&self) -> VerilogExprMap {
+        let mut map = VerilogExprMap::new();
+        let dst = self.instr().dst().get_id(0).unwrap();
+        for p in self.prim.output().iter() {
+            let name = p.name();
+            if let Some(lut) = LUT.iter().position(|l| name == format!("DO{}", l)) {
+                let bit = vl::Expr::new_index_bit(&dst, self.bit(lut) as i32);
+                map.insert(name, bit);
+            }
+        }
+        map
+    }
+}
+
+fn mem_from_mach(instr: &InstrMach) -> Vec<vl::Stmt> {
+    HALF.iter()
+        .flat_map(|half| Mem::new(instr.clone(), *half).to_block())
+        .collect()
+}
+
+// TODO: check for valid memory shapes, support only 8x8 now (data:i8, addr:i3)
+pub fn rom_from_mach(instr: &InstrMach) -> Result<Vec<vl::Stmt>, Error> {
+    Ok(mem_from_mach(instr))
+}
+
+// TODO: check for valid memory shapes, support only 8x8 now (data:i8, addr:i3)
+pub fn ram_from_mach(instr: &InstrMach) -> Result<Vec<vl::Stmt>, Error> {
+    Ok(mem_from_mach(instr))
+}
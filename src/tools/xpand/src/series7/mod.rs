@@ -0,0 +1,3 @@
+pub mod bram;
+pub mod carry;
+pub mod lram;
@@ -1,5 +1,5 @@
 use crate::create_literal;
-use crate::loc::{attr_from_loc, Loc};
+use crate::loc::{attr_from_loc_with_family, Family, Loc};
 use prim::{ParamSet, PortSet};
 use std::collections::HashMap;
 use verilog::ast as vl;
@@ -41,6 +41,10 @@ pub trait ToVerilogInstance<T: ToVerilogExpr> {
     fn to_loc(&self) -> Option<&Loc> {
         None
     }
+    /// device family, which names the location sites
+    fn to_family(&self) -> Family {
+        Family::default()
+    }
     /// parameter map
     fn to_param_map(&self) -> VerilogExprMap {
         let mut map = VerilogExprMap::new();
@@ -82,7 +86,7 @@ pub trait ToVerilogInstance<T: ToVerilogExpr> {
         }
         if let Some(loc) = self.to_loc() {
             if loc.is_placed() {
-                let attr = attr_from_loc(&loc);
+                let attr = attr_from_loc_with_family(&loc, self.to_family());
                 inst.set_attr(attr);
             }
         }
@@ -1,9 +1,11 @@
+use bline::reset::Reset;
 use io::read_to_string;
 use pretty_assertions::assert_eq;
 use std::path::Path;
 use xir::parser::Parser;
 use xpand::errors::Error;
-use xpand::try_from_xir_prog;
+use xpand::loc::Family;
+use xpand::{try_from_xir_prog, try_from_xir_prog_with_family};
 
 fn test(name: &str) -> Result<(), Error> {
     let mut input = Path::new("../../../examples/xir").join(name);
@@ -12,7 +14,7 @@ fn test(name: &str) -> Result<(), Error> {
     output.set_extension("v");
     let parsed = Parser::parse_from_file(input)?;
     let exp = read_to_string(output);
-    let res = try_from_xir_prog(&parsed, None)?;
+    let res = try_from_xir_prog(&parsed, None, &Reset::default())?;
     assert_eq!(res.to_string(), exp);
     Ok(())
 }
@@ -28,7 +30,7 @@ fn test_with_mmap(name: &str) -> Result<(), Error> {
     let parsed = Parser::parse_from_file(input)?;
     let mmap = mmap::Mmap::from_file(mem);
     let exp = read_to_string(output);
-    let res = try_from_xir_prog(&parsed, Some(&mmap))?;
+    let res = try_from_xir_prog(&parsed, Some(&mmap), &Reset::default())?;
     assert_eq!(res.to_string(), exp);
     Ok(())
 }
@@ -123,6 +125,16 @@ fn brom_8x256() -> Result<(), Error> {
     test("brom_8x256")
 }
 
+#[test]
+fn lram_8x8() -> Result<(), Error> {
+    test("lram_8x8")
+}
+
+#[test]
+fn bram_8x256() -> Result<(), Error> {
+    test("bram_8x256")
+}
+
 #[test]
 fn mux128() -> Result<(), Error> {
     test("mux128")
@@ -137,3 +149,102 @@ fn lrom_8x8_mmap() -> Result<(), Error> {
 fn brom_8x256_mmap() -> Result<(), Error> {
     test_with_mmap("brom_8x256")
 }
+
+#[test]
+fn lram_8x8_mmap() -> Result<(), Error> {
+    test_with_mmap("lram_8x8")
+}
+
+#[test]
+fn bram_8x256_mmap() -> Result<(), Error> {
+    test_with_mmap("bram_8x256")
+}
+
+#[test]
+fn reg_reset() -> Result<(), Error> {
+    let parsed = Parser::parse_from_str(
+        r#"def main(a:bool, b:bool, en:bool) -> (y:bool, z:bool) {
+            y:bool = fdre[1, 0](a, en) @a(??, ??);
+            z:bool = fdre[0, 1](b, en) @b(??, ??);
+        }"#,
+    )?;
+    let res = try_from_xir_prog(&parsed, None, &"async-low".parse()?)?.to_string();
+    assert!(res.contains("FDCE"));
+    assert!(res.contains(".CLR(reset)"));
+    assert!(res.contains(".IS_CLR_INVERTED(1'b1)"));
+    assert!(res.contains("FDPE"));
+    assert!(res.contains(".PRE(reset)"));
+    let res = try_from_xir_prog(&parsed, None, &"none".parse()?)?.to_string();
+    assert!(res.contains(".R(gnd)"));
+    assert!(res.contains(".S(gnd)"));
+    assert!(!res.contains("input wire reset"));
+    Ok(())
+}
+
+#[test]
+fn bram_async_reset() -> Result<(), Error> {
+    // block ram output registers only reset synchronously, like dsp registers
+    let parsed = Parser::parse_from_file("../../../examples/xir/bram_8x256.xir")?;
+    let reset: Reset = "async".parse()?;
+    assert!(try_from_xir_prog(&parsed, None, &reset).is_err());
+    assert!(try_from_xir_prog_with_family(&parsed, None, &reset, Family::Series7).is_err());
+    Ok(())
+}
+
+#[test]
+fn clock_domains() -> Result<(), Error> {
+    let parsed = Parser::parse_from_str(
+        r#"def main(a:bool, en:bool, slow:clock) -> (y:bool, z:bool) {
+            y:bool = fdre(a, en) @a(??, ??);
+            z:bool = fdre(a, en, slow) @b(??, ??);
+        }"#,
+    )?;
+    let res = try_from_xir_prog(&parsed, None, &Reset::default())?.to_string();
+    assert!(res.contains(".C(clock)"));
+    assert!(res.contains(".C(slow)"));
+    Ok(())
+}
+
+#[test]
+fn family_7series() -> Result<(), Error> {
+    let parsed = Parser::parse_from_str(
+        r#"def main(a:i4, b:i4, c:i8, addr:i3, en:bool) -> (y:i4, z:i8, w:i8) {
+            y:i4 = carryadd(a, b) @c4(0, 1);
+            z:i8 = muladdrega(c, c, c, en, en, en, en) @alu(0, 2);
+            w:i8 = lram(addr, c, en) @d6(1, 4);
+        }"#,
+    )?;
+    let family = Family::Series7;
+    let res = try_from_xir_prog_with_family(&parsed, None, &Reset::default(), family)?;
+    let res = res.to_string();
+    assert!(res.contains("CARRY4"));
+    assert!(res.contains("LOC = \"SLICE_X0Y1\""));
+    assert!(res.contains("DSP48E1"));
+    assert!(res.contains("LOC = \"DSP48_X0Y2\""));
+    assert!(!res.contains("XOROUT"));
+    assert!(res.contains("RAM64M"));
+    assert!(res.contains("LOC = \"SLICE_X1Y4\""));
+    assert!(res.contains("LOC = \"SLICE_X1Y5\""));
+    Ok(())
+}
+
+#[test]
+fn family_7series_bels() -> Result<(), Error> {
+    // 7-series slices have no e to h luts and flops, and carry 4 bits
+    for instr in &[
+        "y:bool = lut2[6](a, b) @e6(0, 0);",
+        "y:bool = fdre(a, b) @h(0, 0);",
+        "y:bool = id(a); z:i4 = carryadd(c, c) @c8(0, 0);",
+        "y:bool = id(a); z:i8 = carryadd(d, d) @c4(0, 0);",
+    ] {
+        let src = format!(
+            "def main(a:bool, b:bool, c:i4, d:i8) -> (y:bool) {{ {} }}",
+            instr
+        );
+        let parsed = Parser::parse_from_str(&src)?;
+        let family = Family::Series7;
+        let res = try_from_xir_prog_with_family(&parsed, None, &Reset::default(), family);
+        assert!(matches!(res, Err(Error::Xpand(_))));
+    }
+    Ok(())
+}